@@ -66,6 +66,8 @@ impl<M: CompletionModel, E: EmbeddingModel> Agent<M, E> {
             .preamble(&self.character.preamble)
             .context(&character_context)
             .context(&style_context)
+            .cache_preamble()
+            .cache_context()
             .dynamic_context(2, self.knowledge.clone().document_index());
 
         builder
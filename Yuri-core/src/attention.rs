@@ -1,4 +1,7 @@
-use rig::completion::{CompletionModel, ModelChoice};
+use rig::{
+    classifier::ClassifierBuilder,
+    completion::{CompletionModel, ModelChoice},
+};
 use tracing::debug;
 
 use crate::knowledge::{ChannelType, Source};
@@ -149,60 +152,54 @@ impl<M: CompletionModel> Attention<M> {
     }
 
     pub async fn should_like(&self, tweet_content: &str) -> bool {
-        let prompt = format!(
-            "You are deciding whether to like a tweet. Consider if the content is positive, interesting, or relevant.\n\n\
-            Tweet: {}\n\n\
-            Respond with only 'true' or 'false':",
-            tweet_content
-        );
-
-        let builder = self.completion_model.completion_request(&prompt);
-
-        match self.completion_model.completion(builder.build()).await {
-            Ok(response) => match response.choice {
-                ModelChoice::Message(text) => text.trim().to_lowercase() == "true",
-                ModelChoice::ToolCall(_, _) => false,
-            },
-            Err(_) => false,
-        }
+        self.decide(
+            "You are deciding whether to like a tweet. Consider if the content is positive, interesting, or relevant.\n\
+            Label the tweet `true` if you should like it and `false` otherwise.",
+            tweet_content,
+        )
+        .await
     }
 
     pub async fn should_retweet(&self, tweet_content: &str) -> bool {
-        let prompt = format!(
-            "You are deciding whether to retweet. Only retweet if the content is highly valuable, interesting, or aligns with your values.\n\n\
-            Tweet: {}\n\n\
-            Respond with only 'true' or 'false':",
-            tweet_content
-        );
-
-        let builder = self.completion_model.completion_request(&prompt);
-
-        match self.completion_model.completion(builder.build()).await {
-            Ok(response) => match response.choice {
-                ModelChoice::Message(text) => text.trim().to_lowercase() == "true",
-                ModelChoice::ToolCall(_, _) => false,
-            },
-            Err(_) => false,
-        }
+        self.decide(
+            "You are deciding whether to retweet. Only retweet if the content is highly valuable, interesting, or aligns with your values.\n\
+            Label the tweet `true` if you should retweet it and `false` otherwise.",
+            tweet_content,
+        )
+        .await
     }
 
     pub async fn should_quote(&self, tweet_content: &str) -> bool {
-        let prompt = format!(
+        self.decide(
             "You are deciding whether to quote tweet. Quote tweet if the content deserves commentary, \
-            could benefit from additional context, or warrants a thoughtful response.\n\n\
-            Tweet: {}\n\n\
-            Respond with only 'true' or 'false':",
-            tweet_content
-        );
-
-        let builder = self.completion_model.completion_request(&prompt);
+            could benefit from additional context, or warrants a thoughtful response.\n\
+            Label the tweet `true` if you should quote it and `false` otherwise.",
+            tweet_content,
+        )
+        .await
+    }
 
-        match self.completion_model.completion(builder.build()).await {
-            Ok(response) => match response.choice {
-                ModelChoice::Message(text) => text.trim().to_lowercase() == "true",
-                ModelChoice::ToolCall(_, _) => false,
-            },
-            Err(_) => false,
+    /// Classify a tweet as `true`/`false` according to `instructions`
+    async fn decide(&self, instructions: &str, tweet_content: &str) -> bool {
+        let classifier = ClassifierBuilder::<bool, M>::new(self.completion_model.clone())
+            .preamble(instructions)
+            .retries(1)
+            .build();
+
+        match classifier.classify(tweet_content).await {
+            Ok(decision) => {
+                debug!(
+                    decision = decision.label,
+                    confidence = decision.confidence,
+                    rationale = decision.rationale,
+                    "Classified tweet"
+                );
+                decision.label
+            }
+            Err(err) => {
+                debug!(error = %err, "Failed to classify tweet");
+                false
+            }
         }
     }
 }
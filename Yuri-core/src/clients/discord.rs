@@ -21,13 +21,17 @@ const MAX_MESSAGE_LENGTH: usize = 1500;
 const MAX_HISTORY_MESSAGES: i64 = 99999;
 
 #[derive(Clone)]
-pub struct DiscordClient<M: CompletionModel, E: EmbeddingModel + 'static> {
+pub struct DiscordClient<M: CompletionModel, E: EmbeddingModel + 'static, A: CompletionModel = M>
+{
     agent: Agent<M, E>,
-    attention: Attention<M>,
+    /// Decides whether to respond, possibly with a cheaper model than the agent's
+    attention: Attention<A>,
 }
 
-impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> DiscordClient<M, E> {
-    pub fn new(agent: Agent<M, E>, attention: Attention<M>) -> Self {
+impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static, A: CompletionModel + 'static>
+    DiscordClient<M, E, A>
+{
+    pub fn new(agent: Agent<M, E>, attention: Attention<A>) -> Self {
         Self { agent, attention }
     }
 
@@ -70,8 +74,8 @@ impl From<Message> for knowledge::Message {
 }
 
 #[async_trait]
-impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> EventHandler
-    for DiscordClient<M, E>
+impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static, A: CompletionModel + 'static>
+    EventHandler for DiscordClient<M, E, A>
 {
     async fn message(&self, ctx: Context, msg: Message) {
         if msg.author.bot {
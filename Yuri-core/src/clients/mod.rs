@@ -1,5 +1,4 @@
 pub mod discord;
 pub mod telegram;
 pub mod twitter;
-pub mod heuris;
 pub mod direct;
\ No newline at end of file
@@ -3,29 +3,62 @@ use crate::{
     attention::{Attention, AttentionCommand, AttentionContext},
     knowledge::{self, ChannelType, Source},
 };
-use rig::{completion::{CompletionModel, Prompt}, embeddings::EmbeddingModel};
+use rig::{
+    completion::{CompletionModel, Prompt},
+    embeddings::EmbeddingModel,
+    providers::openai,
+    speech::{AudioFormat, SpeechModel},
+    transcription::TranscriptionModel,
+};
 use std::collections::HashSet;
 use teloxide::{
+    net::Download,
     prelude::*,
-    types::MessageKind,
+    types::{InputFile, MessageKind},
 };
 use tracing::{debug, error, info};
 
 const MAX_HISTORY_MESSAGES: i64 = 99999;
 
-pub struct TelegramClient<M: CompletionModel, E: EmbeddingModel + 'static> {
+/// Telegram client. Voice notes are transcribed with `transcription_model` (and ignored without
+/// one), and replies to voice notes are spoken with `speech_model` when it is set.
+pub struct TelegramClient<
+    M: CompletionModel,
+    E: EmbeddingModel + 'static,
+    A: CompletionModel = M,
+    T: TranscriptionModel = openai::TranscriptionModel,
+    S: SpeechModel = openai::SpeechModel,
+> {
     agent: Agent<M, E>,
-    attention: Attention<M>,
+    /// Decides whether to respond, possibly with a cheaper model than the agent's
+    attention: Attention<A>,
     bot: Bot,
+    transcription_model: Option<T>,
+    speech_model: Option<S>,
 }
 
-impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TelegramClient<M, E> {
-    pub fn new(agent: Agent<M, E>, attention: Attention<M>, token: String) -> Self {
+impl<
+        M: CompletionModel + 'static,
+        E: EmbeddingModel + 'static,
+        A: CompletionModel + 'static,
+        T: TranscriptionModel + 'static,
+        S: SpeechModel + 'static,
+    > TelegramClient<M, E, A, T, S>
+{
+    pub fn new(
+        agent: Agent<M, E>,
+        attention: Attention<A>,
+        token: String,
+        transcription_model: Option<T>,
+        speech_model: Option<S>,
+    ) -> Self {
         let bot = Bot::new(token);
         Self {
             agent,
             attention,
             bot,
+            transcription_model,
+            speech_model,
         }
     }
 
@@ -52,14 +85,17 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TelegramClient<M
             return Ok(());
         }
 
+        let is_voice = msg.text().is_none() && msg.voice().is_some();
         let text = if let Some(text) = msg.text() {
             text.to_string()
+        } else if let Some(text) = self.transcribe_voice(&msg).await? {
+            text
         } else {
             return Ok(());
         };
 
         let knowledge = self.agent.knowledge();
-        let knowledge_msg = self.convert_to_knowledge_message(msg.clone());
+        let knowledge_msg = self.convert_to_knowledge_message(msg.clone(), &text);
 
         if let Err(err) = knowledge.clone().create_message(knowledge_msg.clone()).await {
             error!(?err, "Failed to store message");
@@ -133,6 +169,20 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TelegramClient<M
 
         debug!(response = %response, "Generated response");
 
+        if is_voice {
+            if let Some(audio) = self.speak(&response).await {
+                match self
+                    .bot
+                    .send_voice(msg.chat.id, InputFile::memory(audio))
+                    .send()
+                    .await
+                {
+                    Ok(_) => return Ok(()),
+                    Err(why) => error!(?why, "Failed to send voice message"),
+                }
+            }
+        }
+
         if let Err(why) = self.bot.send_message(msg.chat.id, response).send().await {
             error!(?why, "Failed to send message");
         }
@@ -140,7 +190,61 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TelegramClient<M
         Ok(())
     }
 
-    fn convert_to_knowledge_message(&self, msg: teloxide::types::Message) -> knowledge::Message {
+    /// Downloads and transcribes the voice note of `msg`, if any. Returns `None` when there is
+    /// no voice note, no transcription model or the transcription failed.
+    async fn transcribe_voice(
+        &self,
+        msg: &teloxide::types::Message,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let (Some(voice), Some(transcription_model)) = (msg.voice(), &self.transcription_model)
+        else {
+            return Ok(None);
+        };
+
+        let file = self.bot.get_file(voice.file.id.clone()).send().await?;
+        let mut audio = Vec::new();
+        self.bot.download_file(&file.path, &mut audio).await?;
+
+        // Telegram voice notes are Opus in an Ogg container
+        match transcription_model
+            .transcription_request(audio, "voice.ogg")
+            .send()
+            .await
+        {
+            Ok(response) if !response.text.trim().is_empty() => {
+                debug!(text = %response.text, "Transcribed voice message");
+                Ok(Some(response.text))
+            }
+            Ok(_) => Ok(None),
+            Err(err) => {
+                error!(?err, "Failed to transcribe voice message");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Speaks `text` as an Ogg/Opus voice note, or returns `None` to fall back to a text reply.
+    async fn speak(&self, text: &str) -> Option<Vec<u8>> {
+        let speech_model = self.speech_model.as_ref()?;
+        match speech_model
+            .speech_request(text)
+            .format(AudioFormat::Opus)
+            .send()
+            .await
+        {
+            Ok(response) => Some(response.audio),
+            Err(err) => {
+                error!(?err, "Failed to generate voice reply");
+                None
+            }
+        }
+    }
+
+    fn convert_to_knowledge_message(
+        &self,
+        msg: teloxide::types::Message,
+        content: &str,
+    ) -> knowledge::Message {
         knowledge::Message {
             id: msg.id.to_string(),
             source: Source::Telegram,
@@ -153,7 +257,7 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TelegramClient<M
             channel_id: msg.chat.id.to_string(),
             account_id: msg.from().map_or_else(String::new, |user| user.id.to_string()),
             role: "user".to_string(),
-            content: msg.text().unwrap_or_default().to_string(),
+            content: content.to_string(),
             created_at: msg.date.into(),
         }
     }
@@ -166,12 +270,21 @@ fn extract_mentions(text: &str) -> HashSet<String> {
         .collect()
 }
 
-impl<M: CompletionModel, E: EmbeddingModel> Clone for TelegramClient<M, E> {
+impl<
+        M: CompletionModel,
+        E: EmbeddingModel,
+        A: CompletionModel,
+        T: TranscriptionModel,
+        S: SpeechModel,
+    > Clone for TelegramClient<M, E, A, T, S>
+{
     fn clone(&self) -> Self {
         Self {
             agent: self.agent.clone(),
             attention: self.attention.clone(),
             bot: self.bot.clone(),
+            transcription_model: self.transcription_model.clone(),
+            speech_model: self.speech_model.clone(),
         }
     }
 }
@@ -8,23 +8,36 @@ use rand::Rng;
 use rig::{
     completion::{CompletionModel, Prompt},
     embeddings::EmbeddingModel,
+    guardrail::Guardrails,
+    image_generation::ImageGenerationModel,
+    providers::heurist,
 };
 use agent_twitter_client::scraper::Scraper;
 use std::collections::HashSet;
 use std::sync::Arc;
 use tracing::{debug, error, info};
-use crate::clients::heuris::HeurisClient;
 use base64::{engine::general_purpose::STANDARD, Engine};
 use rina_solana::transfer::TransferTool;
 const MAX_TWEET_LENGTH: usize = 280;
 const MAX_HISTORY_TWEETS: i64 = 10;
-
-pub struct TwitterClient<M: CompletionModel, E: EmbeddingModel + 'static> {
+const IMAGE_PROMPT: &str = "realistic, photorealistic, ultra detailed, masterpiece, 8K illustration, extremely detailed CG unity 8K wallpaper, best quality, absurdres, official art, detailed skin texture, detailed cloth texture, beautiful detailed face, intricate details, best lighting, ultra high res, 8K UHD, film grain, dramatic lighting, delicate,1 girl, Ninym Ralei, blush, beautiful detailed face, skinny, beautiful detailed eyes, medium breasts, shirt, ahoge, straight long hair, red eyes, white shirt, sleeveless, bare shoulders, bangs, skirt, sleeveless shirt, white hair, indoors, upper body, collared shirt, high-waist skirt, lips, blue skirt, gold hair ornament, black ribbon, big pupil, Russian, pointy nose,dynamic angle, uncensored, perfect anatomy, forest, floating hair";
+const IMAGE_NEGATIVE_PROMPT: &str =
+    "worst quality, bad quality, umbrella, blurry face, anime, illustration";
+
+pub struct TwitterClient<
+    M: CompletionModel,
+    E: EmbeddingModel + 'static,
+    A: CompletionModel = M,
+    I: ImageGenerationModel = heurist::ImageGenerationModel,
+> {
     agent: Agent<M, E>,
-    attention: Attention<M>,
+    /// Decides whether to respond, possibly with a cheaper model than the agent's
+    attention: Attention<A>,
     scraper: Scraper,
     username: String,
-    heurist_api_key: Option<String>,
+    image_model: Option<I>,
+    /// Guardrails run on new tweets before they are posted
+    guardrails: Guardrails,
 }
 
 impl From<agent_twitter_client::models::Tweet> for Message {
@@ -45,16 +58,23 @@ impl From<agent_twitter_client::models::Tweet> for Message {
     }
 }
 
-impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TwitterClient<M, E> {
+impl<
+        M: CompletionModel + 'static,
+        E: EmbeddingModel + 'static,
+        A: CompletionModel + 'static,
+        I: ImageGenerationModel,
+    > TwitterClient<M, E, A, I>
+{
     pub async fn new(
         agent: Agent<M, E>,
-        attention: Attention<M>,
+        attention: Attention<A>,
         username: String,
         password: String,
         email: Option<String>,
         two_factor_auth: Option<String>,
         cookie_string: Option<String>,
-        heurist_api_key: Option<String>,
+        image_model: Option<I>,
+        guardrails: Guardrails,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut scraper = Scraper::new().await?;
 
@@ -76,7 +96,8 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TwitterClient<M,
             attention,
             scraper,
             username: username.clone(),
-            heurist_api_key,
+            image_model,
+            guardrails,
         })
     }
 
@@ -163,22 +184,27 @@ impl<M: CompletionModel + 'static, E: EmbeddingModel + 'static> TwitterClient<M,
             .context("Please keep your responses concise and under 280 characters.")
             .build();
         let tweet_prompt = "Share a single brief thought or observation in one short sentence. Be direct and concise. No questions, hashtags, or emojis.";
-        let response = match agent.prompt(&tweet_prompt).await {
+        let response = match self.guardrails.prompt(&agent, tweet_prompt).await {
             Ok(response) => response,
             Err(err) => {
-                error!(?err, "Failed to generate response for tweet");
+                error!(?err, "Failed to generate a tweet passing the guardrails");
                 return Ok(());
             }
         };
         debug!(response = %response, "Generated response for tweet");
 
-        if let Some(heurist_api_key) = self.heurist_api_key.clone() {
-            let heurist = HeurisClient::new(heurist_api_key);
+        if let Some(image_model) = &self.image_model {
             debug!("Generating image");
-            match heurist.generate_image("realistic, photorealistic, ultra detailed, masterpiece, 8K illustration, extremely detailed CG unity 8K wallpaper, best quality, absurdres, official art, detailed skin texture, detailed cloth texture, beautiful detailed face, intricate details, best lighting, ultra high res, 8K UHD, film grain, dramatic lighting, delicate,1 girl, Ninym Ralei, blush, beautiful detailed face, skinny, beautiful detailed eyes, medium breasts, shirt, ahoge, straight long hair, red eyes, white shirt, sleeveless, bare shoulders, bangs, skirt, sleeveless shirt, white hair, indoors, upper body, collared shirt, high-waist skirt, lips, blue skirt, gold hair ornament, black ribbon, big pupil, Russian, pointy nose,dynamic angle, uncensored, perfect anatomy, forest, floating hair".to_string()).await {
-                Ok(image_data) => {
+            match image_model
+                .image_generation_request(IMAGE_PROMPT)
+                .negative_prompt(IMAGE_NEGATIVE_PROMPT)
+                .size(512, 768)
+                .send()
+                .await
+            {
+                Ok(image) => {
                     debug!("Image generated");
-                    let image = vec![(image_data, "image/png".to_string())];
+                    let image = vec![(image.image, image.mime_type)];
                     self.scraper.send_tweet(&response, None, Some(image)).await?;
                 }
                 Err(err) => {
@@ -0,0 +1,70 @@
+//! Chat with the character from the terminal, with the same preamble, context and tools as the
+//! clients, e.g. to try out changes to the character TOML.
+//!
+//! cargo run --example chat -- --character rina/src/characters/rina.toml
+use clap::Parser;
+use rig::cli_chatbot::CliChatbot;
+use rig::providers::openai;
+use rina_core::agent::Agent;
+use rina_core::character;
+use rina_core::init_logging;
+use rina_core::knowledge::KnowledgeBase;
+use sqlite_vec::sqlite3_vec_init;
+use tokio_rusqlite::ffi::sqlite3_auto_extension;
+use tokio_rusqlite::Connection;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Path to character profile TOML file
+    #[arg(long, default_value = "rina/src/characters/rina.toml")]
+    character: String,
+
+    /// Path to database
+    #[arg(long, default_value = "rina.db")]
+    db_path: String,
+
+    /// Completion model
+    #[arg(long, default_value = openai::GPT_4O)]
+    model: String,
+
+    /// File where the input history is kept across sessions
+    #[arg(long, default_value = ".chat_history")]
+    history_file: String,
+
+    /// OpenAI API token (can also be set via OPENAI_API_KEY env var)
+    #[arg(long, env = "OPENAI_API_KEY", default_value = "")]
+    openai_api_key: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    init_logging();
+    dotenv::dotenv().ok();
+
+    let args = Args::parse();
+
+    let character_content =
+        std::fs::read_to_string(&args.character).expect("Failed to read character file");
+    let character: character::Character = toml::from_str(&character_content)
+        .map_err(|e| format!("Failed to parse character TOML: {}", e))?;
+
+    let oai = openai::Client::new(&args.openai_api_key);
+    let embedding_model = oai.embedding_model(openai::TEXT_EMBEDDING_3_LARGE);
+    let completion_model = oai.completion_model(&args.model);
+
+    unsafe {
+        sqlite3_auto_extension(Some(std::mem::transmute(sqlite3_vec_init as *const ())));
+    }
+
+    let conn = Connection::open(args.db_path).await?;
+    let knowledge = KnowledgeBase::new(conn.clone(), embedding_model).await?;
+    let agent = Agent::new(character, completion_model, knowledge);
+
+    CliChatbot::new(agent.builder().build())
+        .history_file(args.history_file)
+        .run()
+        .await?;
+
+    Ok(())
+}
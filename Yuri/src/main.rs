@@ -1,5 +1,9 @@
 use clap::{command, Parser};
-use rig::providers::{self, openai};
+use rig::guardrail::{Action, Blocklist, Format, Guardrails, LlmModeration, Pii};
+use rig::providers::{
+    self, openai,
+    openai_compatible::{self, Profile},
+};
 use rina_core::attention::{Attention, AttentionConfig};
 use rina_core::character;
 use rina_core::init_logging;
@@ -32,6 +36,11 @@ struct Args {
     #[arg(long, env = "OPENAI_API_KEY", default_value = "")]
     openai_api_key: String,
 
+    /// Groq API key, used to decide whether to respond with a fast model (optional, OpenAI's
+    /// GPT-4o is used otherwise)
+    #[arg(long, env = "GROQ_API_KEY")]
+    groq_api_key: Option<String>,
+
     /// Twitter username
     #[arg(long, env = "TWITTER_USERNAME")]
     twitter_username: String,
@@ -86,7 +95,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let oai = providers::openai::Client::new(&args.openai_api_key);
     let embedding_model = oai.embedding_model(openai::TEXT_EMBEDDING_3_LARGE);
     let completion_model = oai.completion_model(openai::GPT_4O);
-    let should_respond_completion_model = oai.completion_model(openai::GPT_4O);
+    let should_respond_completion_model = match &args.groq_api_key {
+        Some(api_key) => openai_compatible::Client::new(Profile::groq(), api_key)
+            .completion_model(openai_compatible::GROQ_LLAMA_3_1_8B_INSTANT),
+        None => openai_compatible::Client::new(
+            Profile::new("https://api.openai.com/v1").name("OpenAI"),
+            &args.openai_api_key,
+        )
+        .completion_model(openai::GPT_4O),
+    };
 
     // Initialize the `sqlite-vec`extension
     // See: https://alexgarcia.xyz/sqlite-vec/rust.html
@@ -109,7 +126,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut handles = vec![];
 
     if clients.contains(&"telegram") {
-        let telegram = TelegramClient::new(agent.clone(), attention.clone(), args.telegram_bot_token);
+        let telegram = TelegramClient::new(
+            agent.clone(),
+            attention.clone(),
+            args.telegram_bot_token,
+            Some(oai.transcription_model(openai::WHISPER_1)),
+            Some(oai.speech_model(openai::TTS_1)),
+        );
         handles.push(tokio::spawn(async move { telegram.start().await }));
     }
     if clients.contains(&"discord") {
@@ -125,7 +148,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             args.twitter_email,
             args.twitter_2fa_code,
             args.twitter_cookie_string,
-            args.heurist_api_key,
+            args.heurist_api_key.map(|api_key| {
+                providers::heurist::Client::new(&api_key)
+                    .image_generation_model(providers::heurist::BLUE_PENCIL_REALISTIC)
+            }),
+            Guardrails::new()
+                .guardrail(Blocklist::new(Action::Regenerate).words([
+                    "seed phrase",
+                    "private key",
+                    "guaranteed returns",
+                    "financial advice",
+                    "giveaway",
+                    "airdrop",
+                ]))
+                .guardrail(Pii::new(Action::Redact))
+                .guardrail(Format::new().min_chars(1).max_chars(280).truncate())
+                .guardrail(oai.moderation_model(openai::OMNI_MODERATION_LATEST))
+                .guardrail(LlmModeration::new(oai.completion_model(openai::GPT_4O)))
+                .max_regenerations(2),
         ).await?;
         handles.push(tokio::spawn(async move { twitter.start().await }));
     }
@@ -0,0 +1,81 @@
+//! Benchmarks of the similarity of a query to many embeddings, one pair at a time with
+//! [VectorDistance] and batched with [EmbeddingMatrix].
+//!
+//! Run with `cargo bench --bench similarity`, and with `--features rayon` to compare the
+//! parallel batched similarities.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rig::embeddings::{
+    distance::{EmbeddingMatrix, VectorDistance},
+    Embedding,
+};
+
+const NDIMS: usize = 1536;
+
+/// Deterministic pseudo-random embeddings
+fn embeddings(n: usize, seed: usize) -> Vec<Embedding> {
+    (0..n)
+        .map(|i| Embedding {
+            document: format!("doc{i}"),
+            vec: (0..NDIMS)
+                .map(|j| ((seed + i * NDIMS + j) as f64 * 0.618).sin())
+                .collect(),
+        })
+        .collect()
+}
+
+fn one_vs_many(c: &mut Criterion) {
+    let query = embeddings(1, 42).remove(0);
+    let mut group = c.benchmark_group("one_vs_many");
+
+    for n in [1_000, 10_000] {
+        let embeddings = embeddings(n, 0);
+        let matrix = EmbeddingMatrix::from_embeddings(&embeddings);
+
+        group.bench_with_input(
+            BenchmarkId::new("pairwise", n),
+            &embeddings,
+            |b, embeddings| {
+                b.iter(|| {
+                    embeddings
+                        .iter()
+                        .map(|embedding| embedding.cosine_similarity(black_box(&query), false))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("batched", n), &matrix, |b, matrix| {
+            b.iter(|| matrix.cosine_similarities(black_box(&query)))
+        });
+    }
+    group.finish();
+}
+
+fn many_vs_many(c: &mut Criterion) {
+    let queries = embeddings(100, 42);
+    let embeddings = embeddings(1_000, 0);
+    let mut group = c.benchmark_group("many_vs_many");
+
+    group.bench_function("pairwise", |b| {
+        b.iter(|| {
+            queries
+                .iter()
+                .map(|query| {
+                    embeddings
+                        .iter()
+                        .map(|embedding| embedding.cosine_similarity(black_box(query), false))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let queries = EmbeddingMatrix::from_embeddings(&queries);
+    let embeddings = EmbeddingMatrix::from_embeddings(&embeddings);
+    group.bench_function("batched", |b| {
+        b.iter(|| black_box(&queries).pairwise_cosine_similarities(&embeddings))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, one_vs_many, many_vs_many);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use rig::{
-    cli_chatbot::cli_chatbot,
+    cli_chatbot::CliChatbot,
     completion::ToolDefinition,
     embeddings::EmbeddingsBuilder,
     providers::openai::{Client, TEXT_EMBEDDING_ADA_002},
@@ -252,7 +252,7 @@ async fn main() -> Result<(), anyhow::Error> {
         .await?;
 
     let vector_store =
-        InMemoryVectorStore::from_documents_with_id_f(embeddings, |tool| tool.name.clone());
+        InMemoryVectorStore::from_documents_with_id_f(embeddings, |tool| tool.name.clone())?;
     let index = vector_store.index(embedding_model);
 
     // Create RAG agent with a single context prompt and a dynamic tool source
@@ -277,7 +277,7 @@ async fn main() -> Result<(), anyhow::Error> {
 
     // Prompt the agent and print the response
 
-    cli_chatbot(calculator_rag).await?;
+    CliChatbot::new(calculator_rag).run().await?;
 
     Ok(())
 }
@@ -25,7 +25,7 @@ async fn main() -> Result<(), anyhow::Error> {
         .await?;
 
     // Create vector store with the embeddings
-    let vector_store = InMemoryVectorStore::from_documents(embeddings);
+    let vector_store = InMemoryVectorStore::from_documents(embeddings)?;
 
     // Create vector store index
     let index = vector_store.index(embedding_model);
@@ -157,7 +157,7 @@ async fn main() -> Result<(), anyhow::Error> {
 
     // Create vector store with the embeddings
     let vector_store =
-        InMemoryVectorStore::from_documents_with_id_f(embeddings, |tool| tool.name.clone());
+        InMemoryVectorStore::from_documents_with_id_f(embeddings, |tool| tool.name.clone())?;
 
     // Create vector store index
     let index = vector_store.index(embedding_model);
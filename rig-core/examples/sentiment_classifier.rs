@@ -5,31 +5,34 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Deserialize, JsonSchema, Serialize)]
 /// An enum representing the sentiment of a document
 enum Sentiment {
+    /// The document expresses a positive opinion or emotion
     Positive,
+    /// The document expresses a negative opinion or emotion
     Negative,
+    /// The document is factual or does not express any opinion
     Neutral,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Serialize)]
-struct DocumentSentiment {
-    /// The sentiment of the document
-    sentiment: Sentiment,
-}
-
 #[tokio::main]
 async fn main() {
     // Create OpenAI client
     let openai_client = openai::Client::from_env();
 
-    // Create extractor
-    let data_extractor = openai_client
-        .extractor::<DocumentSentiment>("gpt-4")
+    // Create classifier
+    let classifier = openai_client
+        .classifier::<Sentiment>(openai::GPT_4O)
+        .preamble("Classify the sentiment of the provided document.")
+        .example("The store opens at 9am.", [Sentiment::Neutral])
+        .example("Worst purchase I ever made.", [Sentiment::Negative])
         .build();
 
-    let sentiment = data_extractor
-        .extract("I am happy")
+    let classification = classifier
+        .classify("I am happy")
         .await
-        .expect("Failed to extract sentiment");
+        .expect("Failed to classify sentiment");
 
-    println!("GPT-4: {:?}", sentiment);
+    println!(
+        "GPT-4o: {:?} (confidence: {}) - {}",
+        classification.label, classification.confidence, classification.rationale
+    );
 }
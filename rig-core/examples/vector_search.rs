@@ -58,7 +58,7 @@ async fn main() -> Result<(), anyhow::Error> {
 
     // Create vector store with the embeddings
     let vector_store =
-        InMemoryVectorStore::from_documents_with_id_f(embeddings, |doc| doc.id.clone());
+        InMemoryVectorStore::from_documents_with_id_f(embeddings, |doc| doc.id.clone())?;
 
     // Create vector store index
     let index = vector_store.index(embedding_model);
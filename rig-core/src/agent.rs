@@ -88,7 +88,6 @@
 //!     .expect("Failed to build embeddings");
 //!
 //! vector_store.add_documents(embeddings)
-//!     .await
 //!     .expect("Failed to add documents");
 //!
 //! // Create vector store index
@@ -112,8 +111,9 @@ use futures::{stream, StreamExt, TryStreamExt};
 
 use crate::{
     completion::{
-        Chat, Completion, CompletionError, CompletionModel, CompletionRequestBuilder,
-        CompletionResponse, Document, Message, ModelChoice, Prompt, PromptError,
+        self, CacheBreakpoints, Chat, Citation, Completion, CompletionError, CompletionModel,
+        CompletionRequestBuilder, CompletionResponse, Document, Message, ModelChoice, Prompt,
+        PromptError,
     },
     tool::{Tool, ToolSet},
     vector_store::{VectorStoreError, VectorStoreIndexDyn},
@@ -143,7 +143,7 @@ pub struct Agent<M: CompletionModel> {
     /// Completion model (e.g.: OpenAI's gpt-3.5-turbo-1106, Cohere's command-r)
     model: M,
     /// System prompt
-    preamble: String,
+    pub(crate) preamble: String,
     /// Context documents always available to the agent
     static_context: Vec<Document>,
     /// Tools that are always available to the agent (identified by their name)
@@ -162,6 +162,10 @@ pub struct Agent<M: CompletionModel> {
     pub tools: ToolSet,
     /// List of image URLs to be included in completion requests
     image_urls: Option<Vec<String>>,
+    /// Whether the model is instructed to cite the context documents it relies on
+    cite_documents: bool,
+    /// Prompt caching breakpoints of the requests
+    cache_breakpoints: CacheBreakpoints,
 }
 
 impl<M: CompletionModel> Completion<M> for Agent<M> {
@@ -237,17 +241,111 @@ impl<M: CompletionModel> Completion<M> for Agent<M> {
         Ok(self
             .model
             .completion_request(prompt)
-            .preamble(self.preamble.clone())
+            .preamble(match self.cite_documents {
+                true => format!("{}\n{}", self.preamble, CITE_DOCUMENTS_PREAMBLE),
+                false => self.preamble.clone(),
+            })
             .messages(chat_history)
             .documents([self.static_context.clone(), dynamic_context].concat())
             .tools([static_tools.clone(), dynamic_tools].concat())
             .temperature_opt(self.temperature)
             .max_tokens_opt(self.max_tokens)
             .additional_params_opt(self.additional_params.clone())
-            .image_urls_opt(self.image_urls.clone()))
+            .image_urls_opt(self.image_urls.clone())
+            .cache_breakpoints(self.cache_breakpoints))
+    }
+}
+
+/// Instruction appended to the preamble of agents citing their context documents
+const CITE_DOCUMENTS_PREAMBLE: &str = "When a statement relies on one of the attached files, \
+    cite the file right after the statement by its id in square brackets, e.g.: [doc_id].";
+
+impl<M: CompletionModel> Agent<M> {
+    /// Prompt the agent and return its response along with the citations of the context
+    /// documents it relies on (see [AgentBuilder::cite_documents]).
+    pub async fn prompt_with_citations(
+        &self,
+        prompt: &str,
+    ) -> Result<(String, Vec<Citation>), PromptError> {
+        self.chat_with_citations(prompt, vec![]).await
+    }
+
+    /// Chat with the agent and return its response along with the citations of the context
+    /// documents it relies on (see [AgentBuilder::cite_documents]).
+    ///
+    /// The citations reported by the provider are mapped to the context documents by id or by
+    /// the `url` property of the documents, and the `[doc_id]` markers of the response are
+    /// resolved to the context documents. Tool calls return the tool output without citations.
+    pub async fn chat_with_citations(
+        &self,
+        prompt: &str,
+        chat_history: Vec<Message>,
+    ) -> Result<(String, Vec<Citation>), PromptError> {
+        let request = self.completion(prompt, chat_history).await?.build();
+        let documents = request.documents.clone();
+
+        match self.model.completion(request).await? {
+            CompletionResponse {
+                choice: ModelChoice::Message(msg),
+                citations,
+                ..
+            } => {
+                let citations = document_citations(&msg, &documents, citations);
+                Ok((msg, citations))
+            }
+            CompletionResponse {
+                choice: ModelChoice::ToolCall(toolname, args),
+                ..
+            } => Ok((self.tools.call(&toolname, args.to_string()).await?, vec![])),
+        }
     }
 }
 
+/// Resolve the provider `citations` and the `[doc_id]` markers of `text` to `documents`
+fn document_citations(
+    text: &str,
+    documents: &[Document],
+    citations: Vec<Citation>,
+) -> Vec<Citation> {
+    let cited_document = |citation: &Citation| {
+        documents.iter().find(|document| {
+            citation.source_id.as_ref() == Some(&document.id)
+                || citation.url.is_some()
+                    && citation.url.as_deref()
+                        == document.additional_props.get("url").map(String::as_str)
+        })
+    };
+
+    let mut resolved: Vec<Citation> = vec![];
+    let provider_citations =
+        citations
+            .into_iter()
+            .map(|citation| match cited_document(&citation) {
+                Some(document) => Citation {
+                    source_id: Some(document.id.clone()),
+                    ..citation
+                },
+                None => citation,
+            });
+    let marked_citations = completion::marked_citations(text, |reference| {
+        documents
+            .iter()
+            .find(|document| document.id == reference)
+            .map(|document| Citation {
+                source_id: Some(document.id.clone()),
+                url: document.additional_props.get("url").cloned(),
+                span: None,
+            })
+    });
+    for citation in provider_citations.chain(marked_citations) {
+        if !resolved.contains(&citation) {
+            resolved.push(citation);
+        }
+    }
+
+    resolved
+}
+
 impl<M: CompletionModel> Prompt for Agent<M> {
     async fn prompt(&self, prompt: &str) -> Result<String, PromptError> {
         self.chat(prompt, vec![]).await
@@ -319,6 +417,10 @@ pub struct AgentBuilder<M: CompletionModel> {
     tools: ToolSet,
     /// List of image URLs to be added to the completion request
     image_urls: Option<Vec<String>>,
+    /// Whether the model is instructed to cite the context documents it relies on
+    cite_documents: bool,
+    /// Prompt caching breakpoints of the requests
+    cache_breakpoints: CacheBreakpoints,
 }
 
 impl<M: CompletionModel> AgentBuilder<M> {
@@ -335,6 +437,8 @@ impl<M: CompletionModel> AgentBuilder<M> {
             dynamic_tools: vec![],
             tools: ToolSet::default(),
             image_urls: None,
+            cite_documents: false,
+            cache_breakpoints: CacheBreakpoints::default(),
         }
     }
 
@@ -421,6 +525,36 @@ impl<M: CompletionModel> AgentBuilder<M> {
         self
     }
 
+    /// Instruct the model to cite the context documents it relies on by their id, e.g.: `[doc_id]`.
+    /// The citations are returned by [Agent::prompt_with_citations] and
+    /// [Agent::chat_with_citations].
+    pub fn cite_documents(mut self) -> Self {
+        self.cite_documents = true;
+        self
+    }
+
+    /// Cache the preamble of the agent, for providers with explicit prompt caching (e.g.:
+    /// Anthropic, see [CacheBreakpoints]).
+    pub fn cache_preamble(mut self) -> Self {
+        self.cache_breakpoints.preamble = true;
+        self
+    }
+
+    /// Cache the static context documents added to the agent so far, for providers with
+    /// explicit prompt caching (e.g.: Anthropic, see [CacheBreakpoints]). Documents that change
+    /// between agents (e.g.: the current time) should be added afterwards.
+    pub fn cache_context(mut self) -> Self {
+        self.cache_breakpoints.documents = self.static_context.len();
+        self
+    }
+
+    /// Cache the static tool definitions added to the agent so far, for providers with explicit
+    /// prompt caching (e.g.: Anthropic, see [CacheBreakpoints]).
+    pub fn cache_tools(mut self) -> Self {
+        self.cache_breakpoints.tools = self.static_tools.len();
+        self
+    }
+
     /// Build the agent
     pub fn build(self) -> Agent<M> {
         Agent {
@@ -435,6 +569,84 @@ impl<M: CompletionModel> AgentBuilder<M> {
             dynamic_tools: self.dynamic_tools,
             tools: self.tools,
             image_urls: self.image_urls,
+            cite_documents: self.cite_documents,
+            cache_breakpoints: self.cache_breakpoints,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::completion::{CompletionRequest, Span};
+
+    #[derive(Clone, Default)]
+    struct MockModel {
+        requests: Arc<Mutex<Vec<CompletionRequest>>>,
+    }
+
+    impl CompletionModel for MockModel {
+        type Response = ();
+
+        async fn completion(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<CompletionResponse<()>, CompletionError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(CompletionResponse {
+                choice: ModelChoice::Message(
+                    "Rig is written in Rust [static_doc_0]. It has agents [static_doc_9].".into(),
+                ),
+                usage: None,
+                citations: vec![Citation {
+                    source_id: None,
+                    url: Some("https://rig.rs".into()),
+                    span: None,
+                }],
+                raw_response: (),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prompt_with_citations() {
+        let model = MockModel::default();
+        let mut agent = AgentBuilder::new(model.clone())
+            .preamble("You are a helpful assistant.")
+            .context("Rig is a Rust library.")
+            .cite_documents()
+            .build();
+        agent.static_context.push(Document {
+            id: "website".into(),
+            text: "Rig supports agents.".into(),
+            additional_props: HashMap::from([("url".into(), "https://rig.rs".into())]),
+        });
+
+        let (response, citations) = agent.prompt_with_citations("What is Rig?").await.unwrap();
+
+        assert!(response.starts_with("Rig is written in Rust"));
+        assert_eq!(
+            citations,
+            vec![
+                Citation {
+                    source_id: Some("website".into()),
+                    url: Some("https://rig.rs".into()),
+                    span: None,
+                },
+                Citation {
+                    source_id: Some("static_doc_0".into()),
+                    url: None,
+                    span: Some(Span {
+                        start: 0,
+                        end: 22,
+                        text: "Rig is written in Rust".into()
+                    }),
+                },
+            ]
+        );
+        let preamble = model.requests.lock().unwrap()[0].preamble.clone().unwrap();
+        assert!(preamble.ends_with(CITE_DOCUMENTS_PREAMBLE));
+    }
+}
@@ -0,0 +1,368 @@
+//! This module provides high-level abstractions for classifying text into a set of labels using
+//! LLMs. The [Classifier] is built on top of the [Extractor](crate::extractor::Extractor): the
+//! model is asked to submit the label(s) of the text along with its confidence and the rationale
+//! behind its choice.
+//!
+//! Note: The label type must implement the `serde::Deserialize`, `serde::Serialize`,
+//! and `schemars::JsonSchema` traits. It is usually an enum whose variants are documented, as
+//! the doc comments are included in the schema sent to the model.
+//!
+//! # Example
+//! ```
+//! use rig::providers::openai;
+//!
+//! #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+//! enum Sentiment {
+//!     /// The text expresses a positive opinion or emotion
+//!     Positive,
+//!     /// The text expresses a negative opinion or emotion
+//!     Negative,
+//!     /// The text is factual or does not express any opinion
+//!     Neutral,
+//! }
+//!
+//! let openai = openai::Client::new("your-open-ai-api-key");
+//!
+//! let classifier = openai.classifier::<Sentiment>(openai::GPT_4O)
+//!     .preamble("Classify the sentiment of the text.")
+//!     .example("I love this!", [Sentiment::Positive])
+//!     .example("The store opens at 9am.", [Sentiment::Neutral])
+//!     .build();
+//!
+//! let classification = classifier.classify("What a waste of time.").await?;
+//! assert_eq!(classification.label, Sentiment::Negative);
+//! println!("{} ({})", classification.rationale, classification.confidence);
+//! ```
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    completion::CompletionModel,
+    extractor::{ExtractionError, Extractor, ExtractorBuilder},
+};
+
+/// A label assigned to a text by a [Classifier]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct Classification<L> {
+    /// Short explanation of why the label applies to the text
+    pub rationale: String,
+    /// The label assigned to the text
+    pub label: L,
+    /// How confident you are that the label applies to the text, between 0.0 and 1.0
+    pub confidence: f64,
+}
+
+/// Data submitted by the model
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct Classifications<L> {
+    /// The labels that apply to the text
+    labels: Vec<Classification<L>>,
+}
+
+/// Classifier of text into labels of type `L`
+pub struct Classifier<
+    M: CompletionModel,
+    L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+> {
+    extractor: Extractor<M, Classifications<L>>,
+    min_confidence: f64,
+}
+
+impl<M, L> Classifier<M, L>
+where
+    M: CompletionModel + Sync,
+    L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+{
+    /// Classify `text`, returning the label with the highest confidence.
+    ///
+    /// Returns [ExtractionError::LowConfidence] if the confidence of every label is lower than
+    /// [min_confidence](ClassifierBuilder::min_confidence), and [ExtractionError::NoData] if the
+    /// model did not submit any label.
+    pub async fn classify(&self, text: &str) -> Result<Classification<L>, ExtractionError> {
+        match self.labels(text).await?.into_iter().next() {
+            Some(classification) if classification.confidence >= self.min_confidence => {
+                Ok(classification)
+            }
+            Some(classification) => Err(ExtractionError::LowConfidence(classification.confidence)),
+            None => Err(ExtractionError::NoData),
+        }
+    }
+
+    /// Classify `text`, returning all the labels which apply to it with a confidence of at least
+    /// [min_confidence](ClassifierBuilder::min_confidence), by decreasing confidence.
+    ///
+    /// Note: unless the classifier was built with [multi_label](ClassifierBuilder::multi_label),
+    /// the model is instructed to submit exactly one label.
+    pub async fn classify_multi(
+        &self,
+        text: &str,
+    ) -> Result<Vec<Classification<L>>, ExtractionError> {
+        Ok(self
+            .labels(text)
+            .await?
+            .into_iter()
+            .filter(|classification| classification.confidence >= self.min_confidence)
+            .collect())
+    }
+
+    /// All the labels submitted by the model, by decreasing confidence
+    async fn labels(&self, text: &str) -> Result<Vec<Classification<L>>, ExtractionError> {
+        let mut labels = self.extractor.extract(text).await?.labels;
+        labels.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        Ok(labels)
+    }
+}
+
+/// Builder for the Classifier
+pub struct ClassifierBuilder<
+    L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    M: CompletionModel,
+> {
+    extractor_builder: ExtractorBuilder<Classifications<L>, M>,
+    examples: Vec<(String, Vec<L>)>,
+    multi_label: bool,
+    min_confidence: f64,
+}
+
+impl<L, M> ClassifierBuilder<L, M>
+where
+    L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    M: CompletionModel,
+{
+    pub fn new(model: M) -> Self {
+        Self {
+            extractor_builder: ExtractorBuilder::new(model),
+            examples: vec![],
+            multi_label: false,
+            min_confidence: 0.0,
+        }
+    }
+
+    /// Add additional preamble to the classifier (e.g.: a description of the classification
+    /// task or of the labels)
+    pub fn preamble(mut self, preamble: &str) -> Self {
+        self.extractor_builder = self.extractor_builder.preamble(preamble);
+        self
+    }
+
+    /// Add a context document to the classifier
+    pub fn context(mut self, doc: &str) -> Self {
+        self.extractor_builder = self.extractor_builder.context(doc);
+        self
+    }
+
+    /// Add a few-shot example of a text along with its expected label(s)
+    pub fn example(mut self, text: &str, labels: impl IntoIterator<Item = L>) -> Self {
+        self.examples
+            .push((text.to_string(), labels.into_iter().collect()));
+        self
+    }
+
+    /// Allow the model to assign several labels to the same text (default: false)
+    pub fn multi_label(mut self, multi_label: bool) -> Self {
+        self.multi_label = multi_label;
+        self
+    }
+
+    /// Discard the labels assigned with a confidence lower than `min_confidence` (default: 0.0)
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Set the number of times the model is asked to correct an invalid classification
+    /// (see [ExtractorBuilder::retries])
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.extractor_builder = self.extractor_builder.retries(retries);
+        self
+    }
+
+    /// Build the Classifier
+    pub fn build(self) -> Classifier<M, L> {
+        let multi_label = self.multi_label;
+
+        let mut extractor_builder = self
+            .extractor_builder
+            .preamble(match multi_label {
+                true => "Classify the provided text: submit every label that applies to it.",
+                false => "Classify the provided text: submit exactly one label, the one that best applies to it.",
+            })
+            .validator(move |data: &Classifications<L>| {
+                if data.labels.is_empty() {
+                    return Err("at least one label must be submitted".to_string());
+                }
+                if !multi_label && data.labels.len() > 1 {
+                    return Err(format!(
+                        "exactly one label must be submitted, got {}",
+                        data.labels.len()
+                    ));
+                }
+                match data
+                    .labels
+                    .iter()
+                    .find(|label| !(0.0..=1.0).contains(&label.confidence))
+                {
+                    Some(label) => Err(format!(
+                        "confidence must be between 0.0 and 1.0, got {}",
+                        label.confidence
+                    )),
+                    None => Ok(()),
+                }
+            });
+
+        if !self.examples.is_empty() {
+            let examples = self
+                .examples
+                .iter()
+                .map(|(text, labels)| {
+                    let labels = labels
+                        .iter()
+                        .map(|label| serde_json::to_string(label).unwrap_or_default())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("Text: {text}\nLabels: {labels}")
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+
+            extractor_builder =
+                extractor_builder.preamble(&format!("Examples of classified texts:\n{examples}"));
+        }
+
+        Classifier {
+            extractor: extractor_builder.build(),
+            min_confidence: self.min_confidence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::completion::{
+        self, CompletionError, CompletionRequest, CompletionResponse, ModelChoice,
+    };
+
+    #[derive(Clone, Default)]
+    struct MockModel {
+        responses: Arc<Mutex<Vec<serde_json::Value>>>,
+        requests: Arc<Mutex<Vec<CompletionRequest>>>,
+    }
+
+    impl completion::CompletionModel for MockModel {
+        type Response = ();
+
+        async fn completion(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<CompletionResponse<()>, CompletionError> {
+            self.requests.lock().unwrap().push(request);
+            let args = self.responses.lock().unwrap().remove(0);
+            Ok(CompletionResponse {
+                choice: ModelChoice::ToolCall("submit".into(), args),
+                usage: None,
+                citations: vec![],
+                raw_response: (),
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+    enum Topic {
+        Sports,
+        Politics,
+        Finance,
+    }
+
+    #[tokio::test]
+    async fn test_classify() {
+        let model = MockModel::default();
+        *model.responses.lock().unwrap() = vec![
+            json!({"labels": [
+                {"rationale": "Mentions a match", "label": "Sports", "confidence": 0.6},
+                {"rationale": "Mentions a transfer fee", "label": "Finance", "confidence": 0.4},
+            ]}),
+            json!({"labels": [
+                {"rationale": "Mentions a match", "label": "Sports", "confidence": 0.9},
+            ]}),
+        ];
+
+        let classifier = ClassifierBuilder::<Topic, _>::new(model.clone())
+            .example("The senate voted the budget.", [Topic::Politics])
+            .retries(1)
+            .build();
+
+        let classification = classifier
+            .classify("The striker was sold for 100M after the match.")
+            .await
+            .unwrap();
+        assert_eq!(classification.label, Topic::Sports);
+        assert_eq!(classification.confidence, 0.9);
+
+        let requests = model.requests.lock().unwrap();
+        assert!(requests[0]
+            .preamble
+            .as_ref()
+            .unwrap()
+            .contains("Text: The senate voted the budget.\nLabels: \"Politics\""));
+        assert!(requests[1].prompt.contains("exactly one label"));
+    }
+
+    #[tokio::test]
+    async fn test_classify_multi() {
+        let model = MockModel::default();
+        *model.responses.lock().unwrap() = vec![json!({"labels": [
+            {"rationale": "Mentions a transfer fee", "label": "Finance", "confidence": 0.7},
+            {"rationale": "Mentions a match", "label": "Sports", "confidence": 0.9},
+            {"rationale": "Not political", "label": "Politics", "confidence": 0.1},
+        ]})];
+
+        let classifier = ClassifierBuilder::<Topic, _>::new(model)
+            .multi_label(true)
+            .min_confidence(0.5)
+            .build();
+
+        let labels = classifier
+            .classify_multi("The striker was sold for 100M after the match.")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|classification| classification.label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec![Topic::Sports, Topic::Finance]);
+    }
+
+    #[tokio::test]
+    async fn test_classify_low_confidence() {
+        let model = MockModel::default();
+        *model.responses.lock().unwrap() = vec![
+            json!({"labels": [
+                {"rationale": "Maybe about a match", "label": "Sports", "confidence": 0.3},
+            ]}),
+            json!({"labels": [
+                {"rationale": "Maybe about a match", "label": "Sports", "confidence": 0.3},
+            ]}),
+        ];
+
+        let classifier = ClassifierBuilder::<Topic, _>::new(model)
+            .multi_label(true)
+            .min_confidence(0.5)
+            .build();
+
+        let result = classifier.classify("It was a long day.").await;
+        assert!(
+            matches!(result, Err(ExtractionError::LowConfidence(confidence)) if confidence == 0.3)
+        );
+
+        let labels = classifier
+            .classify_multi("It was a long day.")
+            .await
+            .unwrap();
+        assert!(labels.is_empty());
+    }
+}
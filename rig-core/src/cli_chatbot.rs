@@ -2,8 +2,14 @@ use std::io::{self, Write};
 
 use crate::completion::{Chat, Message, PromptError};
 
+#[cfg(feature = "cli")]
+pub use repl::CliChatbot;
+
 /// Utility function to create a simple REPL CLI chatbot from a type that implements the
 /// `Chat` trait.
+///
+/// See [CliChatbot] (requires the `cli` feature) for an interactive chat with an agent
+/// supporting streaming, slash commands and persistent history.
 pub async fn cli_chatbot(chatbot: impl Chat) -> Result<(), PromptError> {
     let stdin = io::stdin();
     let mut stdout = io::stdout();
@@ -48,3 +54,402 @@ pub async fn cli_chatbot(chatbot: impl Chat) -> Result<(), PromptError> {
 
     Ok(())
 }
+
+#[cfg(feature = "cli")]
+mod repl {
+    use std::{
+        io::{self, Write},
+        path::PathBuf,
+    };
+
+    use futures::StreamExt;
+    use rustyline::{error::ReadlineError, DefaultEditor};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        agent::Agent,
+        completion::{Completion, CompletionModel, Message, PromptError, Usage},
+        streaming::StreamingChoice,
+    };
+
+    const HELP: &str = "\
+Commands:
+  /system [preamble]  Show the system prompt, or replace it for the rest of the session
+  /tools              List the tools available to the agent
+  /reset              Clear the chat history
+  /save <path>        Save the chat history (and system prompt) to a JSON file
+  /load <path>        Load the chat history (and system prompt) from a JSON file
+  /usage              Show the token usage of the session
+  /help               Show this message
+  /exit               Quit (or Ctrl-D)
+
+End a line with `\\` to continue the message on the next line, or wrap a multiline message
+between two lines containing only `\"\"\"`. Ctrl-C discards the current message.";
+
+    /// Interactive REPL to chat with an [Agent] from the terminal, e.g. to try out a preamble
+    /// and a set of tools.
+    ///
+    /// Responses are streamed as they are generated (see [CompletionModel::stream]), tool calls
+    /// and their results are displayed inline, and the token usage of each response is shown
+    /// after it. The chat can be controlled with slash commands (type `/help` to list them).
+    ///
+    /// # Example
+    /// ```rust
+    /// use rig::{cli_chatbot::CliChatbot, providers::openai};
+    ///
+    /// let openai = openai::Client::from_env();
+    /// let agent = openai.agent(openai::GPT_4O).preamble("You are a helpful assistant.").build();
+    ///
+    /// CliChatbot::new(agent)
+    ///     .history_file(".chat_history")
+    ///     .run()
+    ///     .await?;
+    /// ```
+    pub struct CliChatbot<M: CompletionModel> {
+        agent: Agent<M>,
+        history_file: Option<PathBuf>,
+        show_usage: bool,
+    }
+
+    /// Chat history saved and loaded by the `/save` and `/load` commands
+    #[derive(Default, Deserialize, Serialize)]
+    struct Transcript {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        preamble: Option<String>,
+        messages: Vec<Message>,
+    }
+
+    impl<M: CompletionModel> CliChatbot<M> {
+        pub fn new(agent: Agent<M>) -> Self {
+            Self {
+                agent,
+                history_file: None,
+                show_usage: true,
+            }
+        }
+
+        /// Persist the input history to `path`, so that previous messages can be recalled
+        /// (with the arrow keys or Ctrl-R) across sessions
+        pub fn history_file(mut self, path: impl Into<PathBuf>) -> Self {
+            self.history_file = Some(path.into());
+            self
+        }
+
+        /// Show the token usage after each response (default: true)
+        pub fn show_usage(mut self, show_usage: bool) -> Self {
+            self.show_usage = show_usage;
+            self
+        }
+
+        /// Run the REPL until the user exits. Errors of the agent are displayed and do not
+        /// end the session.
+        pub async fn run(self) -> Result<(), ReadlineError> {
+            let mut editor = DefaultEditor::new()?;
+            if let Some(path) = &self.history_file {
+                // The history file does not exist on the first run
+                let _ = editor.load_history(path);
+            }
+
+            let mut transcript = Transcript::default();
+            let mut session_usage = Usage::default();
+            let mut pending = MultilineInput::default();
+
+            println!("Welcome to the chatbot! Type /help for the list of commands, /exit to quit.");
+            loop {
+                let line = match editor.readline(if pending.is_empty() { "> " } else { "... " }) {
+                    Ok(line) => line,
+                    Err(ReadlineError::Interrupted) => {
+                        pending = MultilineInput::default();
+                        continue;
+                    }
+                    Err(ReadlineError::Eof) => break,
+                    Err(err) => return Err(err),
+                };
+
+                let Some(input) = pending.push(&line) else {
+                    continue;
+                };
+                if input.trim().is_empty() {
+                    continue;
+                }
+
+                editor.add_history_entry(input.as_str())?;
+                if let Some(path) = &self.history_file {
+                    if let Err(err) = editor.save_history(path) {
+                        println!("Error saving history: {err}");
+                    }
+                }
+
+                match Command::parse(&input) {
+                    Some(Ok(Command::Exit)) => break,
+                    Some(Ok(command)) => {
+                        self.command(command, &mut transcript, &session_usage).await
+                    }
+                    Some(Err(err)) => println!("{err}"),
+                    None => {
+                        tracing::info!("Prompt:\n{}\n", input);
+                        match self.respond(&input, &transcript).await {
+                            Ok((response, usage)) => {
+                                tracing::info!("Response:\n{}\n", response);
+                                transcript.messages.push(Message {
+                                    role: "user".into(),
+                                    content: input,
+                                });
+                                transcript.messages.push(Message {
+                                    role: "assistant".into(),
+                                    content: response,
+                                });
+
+                                if let Some(usage) = usage {
+                                    session_usage += usage;
+                                    if self.show_usage {
+                                        println!(
+                                            "[{usage} | Session total tokens: {}]",
+                                            session_usage.total_tokens()
+                                        );
+                                    }
+                                }
+                            }
+                            Err(err) => println!("\nError: {err}"),
+                        }
+                    }
+                }
+                println!();
+            }
+
+            Ok(())
+        }
+
+        /// Stream the response of the agent to `prompt`, calling the tools selected by the
+        /// agent. As with [Chat](crate::completion::Chat), the result of a tool call is used as
+        /// the response.
+        async fn respond(
+            &self,
+            prompt: &str,
+            transcript: &Transcript,
+        ) -> Result<(String, Option<Usage>), PromptError> {
+            let mut request = self
+                .agent
+                .completion(prompt, transcript.messages.clone())
+                .await?;
+            if let Some(preamble) = &transcript.preamble {
+                request = request.preamble(preamble.clone());
+            }
+            let mut stream = request.stream().await?;
+
+            let mut stdout = io::stdout();
+            let mut response = String::new();
+            let mut usage = None;
+
+            while let Some(chunk) = stream.next().await {
+                match chunk? {
+                    StreamingChoice::Message(text) => {
+                        print!("{text}");
+                        let _ = stdout.flush();
+                        response.push_str(&text);
+                    }
+                    StreamingChoice::ToolCall(name, args) => {
+                        if !response.is_empty() && !response.ends_with('\n') {
+                            println!();
+                        }
+                        println!("[Tool call] {name}({args})");
+                        let result = self.agent.tools.call(&name, args.to_string()).await?;
+                        println!("[Tool result] {result}");
+                        response.push_str(&result);
+                    }
+                    StreamingChoice::Usage(chunk_usage) => usage = Some(chunk_usage),
+                }
+            }
+            if !response.ends_with('\n') {
+                println!();
+            }
+
+            Ok((response, usage))
+        }
+
+        async fn command(&self, command: Command, transcript: &mut Transcript, usage: &Usage) {
+            match command {
+                Command::Help => println!("{HELP}"),
+                Command::Reset => {
+                    transcript.messages.clear();
+                    println!("Chat history cleared.");
+                }
+                Command::Save(path) => {
+                    match serde_json::to_string_pretty(transcript)
+                        .map_err(io::Error::from)
+                        .and_then(|json| std::fs::write(&path, json))
+                    {
+                        Ok(()) => {
+                            println!("Saved {} messages to {path}.", transcript.messages.len())
+                        }
+                        Err(err) => println!("Error saving {path}: {err}"),
+                    }
+                }
+                Command::Load(path) => {
+                    match std::fs::read_to_string(&path)
+                        .and_then(|json| serde_json::from_str(&json).map_err(io::Error::from))
+                    {
+                        Ok(loaded) => {
+                            *transcript = loaded;
+                            println!("Loaded {} messages from {path}.", transcript.messages.len());
+                        }
+                        Err(err) => println!("Error loading {path}: {err}"),
+                    }
+                }
+                Command::System(None) => match &transcript.preamble {
+                    Some(preamble) => println!("{preamble}"),
+                    None => println!("{}", self.agent.preamble),
+                },
+                Command::System(Some(preamble)) => {
+                    transcript.preamble = Some(preamble);
+                    println!("System prompt updated.");
+                }
+                Command::Tools => {
+                    let definitions = self.agent.tools.definitions("").await;
+                    if definitions.is_empty() {
+                        println!("No tools available.");
+                    }
+                    for definition in definitions {
+                        println!("- {}: {}", definition.name, definition.description);
+                        println!("  Parameters: {}", definition.parameters);
+                    }
+                }
+                Command::Usage => println!("{usage}"),
+                Command::Exit => {}
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Command {
+        Help,
+        Exit,
+        Reset,
+        Save(String),
+        Load(String),
+        System(Option<String>),
+        Tools,
+        Usage,
+    }
+
+    impl Command {
+        /// Parse a slash command, or return `None` if the input is a message to the agent
+        fn parse(input: &str) -> Option<Result<Self, String>> {
+            let input = input.trim();
+            if input == "exit" {
+                return Some(Ok(Command::Exit));
+            }
+
+            let command = input.strip_prefix('/')?;
+            let (name, arg) = match command.split_once(char::is_whitespace) {
+                Some((name, arg)) => (name, arg.trim()),
+                None => (command, ""),
+            };
+
+            Some(match (name, arg) {
+                ("help", _) => Ok(Command::Help),
+                ("exit" | "quit", _) => Ok(Command::Exit),
+                ("reset", _) => Ok(Command::Reset),
+                ("save" | "load", "") => Err(format!("Usage: /{name} <path>")),
+                ("save", path) => Ok(Command::Save(path.to_string())),
+                ("load", path) => Ok(Command::Load(path.to_string())),
+                ("system", "") => Ok(Command::System(None)),
+                ("system", preamble) => Ok(Command::System(Some(preamble.to_string()))),
+                ("tools", _) => Ok(Command::Tools),
+                ("usage", _) => Ok(Command::Usage),
+                _ => Err(format!(
+                    "Unknown command /{name}. Type /help for the list of commands."
+                )),
+            })
+        }
+    }
+
+    /// Accumulates the lines of a multiline message
+    #[derive(Default)]
+    struct MultilineInput {
+        lines: Vec<String>,
+        block: bool,
+    }
+
+    impl MultilineInput {
+        /// Add a line to the message, returning the message once complete
+        fn push(&mut self, line: &str) -> Option<String> {
+            if line.trim() == "\"\"\"" {
+                if self.block {
+                    self.block = false;
+                    return Some(self.take());
+                }
+                if self.lines.is_empty() {
+                    self.block = true;
+                    return None;
+                }
+            }
+
+            if self.block {
+                self.lines.push(line.to_string());
+                return None;
+            }
+
+            match line.strip_suffix('\\') {
+                Some(line) => {
+                    self.lines.push(line.to_string());
+                    None
+                }
+                None => {
+                    self.lines.push(line.to_string());
+                    Some(self.take())
+                }
+            }
+        }
+
+        fn is_empty(&self) -> bool {
+            self.lines.is_empty() && !self.block
+        }
+
+        fn take(&mut self) -> String {
+            std::mem::take(&mut self.lines).join("\n")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_command_parse() {
+            assert_eq!(Command::parse("Hello there"), None);
+            assert_eq!(Command::parse("exit"), Some(Ok(Command::Exit)));
+            assert_eq!(Command::parse("/quit"), Some(Ok(Command::Exit)));
+            assert_eq!(
+                Command::parse("/save  chats/today.json "),
+                Some(Ok(Command::Save("chats/today.json".to_string())))
+            );
+            assert!(matches!(Command::parse("/load"), Some(Err(_))));
+            assert_eq!(Command::parse("/system"), Some(Ok(Command::System(None))));
+            assert_eq!(
+                Command::parse("/system You are a pirate.\nSpeak like one."),
+                Some(Ok(Command::System(Some(
+                    "You are a pirate.\nSpeak like one.".to_string()
+                ))))
+            );
+            assert!(matches!(Command::parse("/frobnicate"), Some(Err(_))));
+        }
+
+        #[test]
+        fn test_multiline_input() {
+            let mut input = MultilineInput::default();
+            assert_eq!(input.push("hello"), Some("hello".to_string()));
+
+            assert_eq!(input.push("first\\"), None);
+            assert!(!input.is_empty());
+            assert_eq!(input.push("second"), Some("first\nsecond".to_string()));
+            assert!(input.is_empty());
+
+            assert_eq!(input.push("\"\"\""), None);
+            assert_eq!(input.push("fn main() {\\"), None);
+            assert_eq!(input.push(""), None);
+            assert_eq!(input.push("}"), None);
+            assert_eq!(input.push("\"\"\""), Some("fn main() {\\\n\n}".to_string()));
+        }
+    }
+}
@@ -64,10 +64,17 @@
 //! the individual traits, structs, and enums defined in this module.
 use std::collections::HashMap;
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::stream;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{json_utils, tool::ToolSetError};
+use crate::{
+    image_generation::image_mime_type,
+    json_utils,
+    streaming::{StreamingChoice, StreamingResult},
+    tool::ToolSetError,
+};
 
 // Errors
 #[derive(Debug, Error)]
@@ -211,10 +218,189 @@ pub trait Completion<M: CompletionModel> {
 pub struct CompletionResponse<T> {
     /// The completion choice returned by the completion model provider
     pub choice: ModelChoice,
+    /// The token usage of the completion, if reported by the completion model provider
+    pub usage: Option<Usage>,
+    /// The sources cited by the completion, if reported by the completion model provider
+    pub citations: Vec<Citation>,
     /// The raw response returned by the completion model provider
     pub raw_response: T,
 }
 
+/// Context documents rendered as the attachments of a prompt
+pub(crate) fn attachments(documents: &[Document]) -> String {
+    format!(
+        "<attachments>\n{}</attachments>\n",
+        documents
+            .iter()
+            .map(|doc| doc.to_string())
+            .collect::<Vec<_>>()
+            .join("")
+    )
+}
+
+/// Provider-neutral citation of a source by a completion (e.g.: a context document for Cohere,
+/// a web page for Perplexity).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Citation {
+    /// Id of the cited source, e.g.: the id of a [Document] of the request
+    pub source_id: Option<String>,
+    /// URL of the cited source
+    pub url: Option<String>,
+    /// Span of the completion supported by the source, if known
+    pub span: Option<Span>,
+}
+
+/// Span of a completion text, in characters.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Span {
+    /// Span of `text` between the character offsets `start` and `end`
+    pub fn new(text: &str, start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            text: text
+                .chars()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .collect(),
+        }
+    }
+}
+
+/// Citations marked in `text` by bracketed references (e.g.: `[1]`, `[doc1][doc2]`,
+/// `[doc1, doc2]`), spanning the sentence before the marker. `resolve` maps the references to
+/// citations, unknown references (e.g.: `[sic]`) being ignored.
+pub(crate) fn marked_citations(
+    text: &str,
+    resolve: impl Fn(&str) -> Option<Citation>,
+) -> Vec<Citation> {
+    let markers =
+        regex::Regex::new(r"\[([^\[\]\n]{1,200})\]").expect("Marker regex should be valid");
+
+    let mut citations = vec![];
+    // Consecutive markers (e.g.: `[1][2]`) cite the same sentence
+    let mut group: Option<(usize, usize)> = None;
+    for marker in markers.captures_iter(text) {
+        let whole = marker.get(0).expect("Match should exist");
+        let sentence_end = match group {
+            Some((start, end)) if text[end..whole.start()].trim().is_empty() => start,
+            _ => whole.start(),
+        };
+        group = Some((sentence_end, whole.end()));
+
+        let sentence_start = text[..sentence_end]
+            .char_indices()
+            .rev()
+            .find(|(i, c)| {
+                *c == '\n'
+                    || (matches!(c, '.' | '!' | '?')
+                        && text[i + 1..].starts_with(char::is_whitespace))
+            })
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let sentence = &text[sentence_start..sentence_end];
+        let start = sentence_start + (sentence.len() - sentence.trim_start().len());
+        let end = sentence_start + sentence.trim_end().len();
+
+        for reference in marker[1].split(',') {
+            if let Some(mut citation) = resolve(reference.trim()) {
+                citation.span = Some(Span {
+                    start: text[..start].chars().count(),
+                    end: text[..end].chars().count(),
+                    text: text[start..end].to_string(),
+                });
+                citations.push(citation);
+            }
+        }
+    }
+
+    citations
+}
+
+/// Provider-neutral token usage of a completion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Usage {
+    /// Number of tokens in the request (preamble, context documents, tools, chat history and prompt)
+    pub prompt_tokens: u64,
+    /// Number of tokens generated by the model
+    pub completion_tokens: u64,
+    /// Number of prompt tokens read from the provider's prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Number of prompt tokens written to the provider's prompt cache
+    #[serde(default)]
+    pub cache_write_tokens: u64,
+}
+
+impl Usage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            cache_read_tokens: 0,
+            cache_write_tokens: 0,
+        }
+    }
+
+    /// Sets the number of prompt tokens read from and written to the prompt cache
+    pub fn with_cache(mut self, cache_read_tokens: u64, cache_write_tokens: u64) -> Self {
+        self.cache_read_tokens = cache_read_tokens;
+        self.cache_write_tokens = cache_write_tokens;
+        self
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl std::ops::Add for Usage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.prompt_tokens + other.prompt_tokens,
+            self.completion_tokens + other.completion_tokens,
+        )
+        .with_cache(
+            self.cache_read_tokens + other.cache_read_tokens,
+            self.cache_write_tokens + other.cache_write_tokens,
+        )
+    }
+}
+
+impl std::ops::AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Prompt tokens: {} Completion tokens: {} Total tokens: {}",
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.total_tokens()
+        )?;
+        if self.cache_read_tokens > 0 || self.cache_write_tokens > 0 {
+            write!(
+                f,
+                " (Cache read tokens: {} Cache write tokens: {})",
+                self.cache_read_tokens, self.cache_write_tokens
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Enum representing the high-level completion choice returned by the completion model provider.
 #[derive(Debug)]
 pub enum ModelChoice {
@@ -243,6 +429,39 @@ pub trait CompletionModel: Clone + Send + Sync {
     fn completion_request(&self, prompt: &str) -> CompletionRequestBuilder<Self> {
         CompletionRequestBuilder::new(self.clone(), prompt.to_string())
     }
+
+    /// Provider-specific `additional_params` which constrain the model to respond with a JSON
+    /// message matching the JSON `schema` (e.g.: OpenAI's `response_format`), or `None` if the
+    /// model does not support native structured outputs (the default).
+    ///
+    /// Used by the [Extractor](crate::extractor::Extractor) instead of tool calling when available.
+    fn structured_output_params(
+        &self,
+        _name: &str,
+        _schema: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Generates a streaming completion response for the given completion request.
+    ///
+    /// Models without native streaming support (the default) send the whole completion as a
+    /// single chunk, followed by its token usage (if available).
+    fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> impl std::future::Future<Output = Result<StreamingResult, CompletionError>> + Send {
+        async move {
+            let response = self.completion(request).await?;
+
+            let mut chunks = vec![Ok(StreamingChoice::from(response.choice))];
+            if let Some(usage) = response.usage {
+                chunks.push(Ok(StreamingChoice::Usage(usage)));
+            }
+
+            Ok(Box::pin(stream::iter(chunks)) as StreamingResult)
+        }
+    }
 }
 
 /// Struct representing a general completion request that can be sent to a completion model provider.
@@ -266,24 +485,115 @@ pub struct CompletionRequest {
 
     /// The image urls to be sent to the completion model provider
     pub image_urls: Option<Vec<String>>,
+    /// The prompt caching breakpoints of the request
+    pub cache_breakpoints: CacheBreakpoints,
+}
+
+/// Prompt caching breakpoints of a completion request, for providers with explicit prompt
+/// caching (e.g.: Anthropic). The request is cached up to each breakpoint, so the breakpoints
+/// should be placed after the parts of the request that do not change between requests (e.g.:
+/// the static tools and context documents of an agent). Other providers ignore them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheBreakpoints {
+    /// Cache the request up to the preamble
+    pub preamble: bool,
+    /// Cache the request up to the first `tools` tool definitions
+    pub tools: usize,
+    /// Cache the request up to the first `documents` context documents
+    pub documents: usize,
 }
 
 impl CompletionRequest {
     pub(crate) fn prompt_with_context(&self) -> String {
         if !self.documents.is_empty() {
-            format!(
-                "<attachments>\n{}</attachments>\n\n{}",
-                self.documents
-                    .iter()
-                    .map(|doc| doc.to_string())
-                    .collect::<Vec<_>>()
-                    .join(""),
-                self.prompt
-            )
+            format!("{}\n{}", attachments(&self.documents), self.prompt)
         } else {
             self.prompt.clone()
         }
     }
+
+    /// The images of the request, parsed from [CompletionRequest::image_urls]
+    pub fn images(&self) -> Vec<Image> {
+        self.image_urls
+            .iter()
+            .flatten()
+            .map(|url| Image::parse(url))
+            .collect()
+    }
+}
+
+/// Image input of a completion request: either inline data (from a `data:` URI) or the URL of a
+/// remote image. Providers only taking inline images use [Image::fetch] to download remote ones.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Image {
+    /// Base64-encoded image data
+    Base64 { mime_type: String, data: String },
+    /// URL of a remote image
+    Url(String),
+}
+
+impl Image {
+    /// Parse an image URL. The MIME type of `data:` URIs is sniffed from the image data (see
+    /// [image_mime_type]), falling back to the MIME type of the URI, since data URIs built from
+    /// downloaded images are often mislabeled (e.g.: `application/octet-stream`).
+    pub fn parse(url: &str) -> Self {
+        let Some((header, data)) = url
+            .strip_prefix("data:")
+            .and_then(|uri| uri.split_once(','))
+        else {
+            return Image::Url(url.to_string());
+        };
+
+        let (declared, data) = match header.strip_suffix(";base64") {
+            Some(declared) => (declared, data.to_string()),
+            None => (header, STANDARD.encode(data)),
+        };
+
+        // 16 base64 characters decode to the 12 bytes needed to sniff any supported format
+        let prefix = data.get(..16).unwrap_or(&data);
+        let mime_type = STANDARD
+            .decode(prefix)
+            .ok()
+            .and_then(|bytes| image_mime_type(&bytes))
+            .or_else(|| declared.split(';').next().filter(|mime| !mime.is_empty()))
+            .unwrap_or("application/octet-stream");
+
+        Image::Base64 {
+            mime_type: mime_type.to_string(),
+            data,
+        }
+    }
+
+    /// The image as a URL, i.e.: the remote URL or a `data:` URI with the sniffed MIME type
+    pub fn url(&self) -> String {
+        match self {
+            Image::Base64 { mime_type, data } => format!("data:{mime_type};base64,{data}"),
+            Image::Url(url) => url.clone(),
+        }
+    }
+
+    /// The MIME type and base64 data of the image, downloading remote images.
+    pub async fn fetch(self) -> Result<(String, String), CompletionError> {
+        let url = match self {
+            Image::Base64 { mime_type, data } => return Ok((mime_type, data)),
+            Image::Url(url) => url,
+        };
+
+        let response = reqwest::get(&url).await?.error_for_status()?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or(value).trim().to_string());
+        let bytes = response.bytes().await?;
+
+        let mime_type = image_mime_type(&bytes)
+            .map(str::to_string)
+            .or(content_type)
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+
+        Ok((mime_type, STANDARD.encode(bytes)))
+    }
 }
 
 /// Builder struct for constructing a completion request.
@@ -341,6 +651,7 @@ pub struct CompletionRequestBuilder<M: CompletionModel> {
     max_tokens: Option<u64>,
     additional_params: Option<serde_json::Value>,
     image_urls: Option<Vec<String>>,
+    cache_breakpoints: CacheBreakpoints,
 }
 
 impl<M: CompletionModel> CompletionRequestBuilder<M> {
@@ -356,6 +667,7 @@ impl<M: CompletionModel> CompletionRequestBuilder<M> {
             max_tokens: None,
             additional_params: None,
             image_urls: None,
+            cache_breakpoints: CacheBreakpoints::default(),
         }
     }
 
@@ -468,7 +780,8 @@ impl<M: CompletionModel> CompletionRequestBuilder<M> {
 
     /// Adds a list of image URLs to the completion request.
     pub fn image_urls(self, urls: Vec<String>) -> Self {
-        urls.into_iter().fold(self, |builder, url| builder.image_url(url))
+        urls.into_iter()
+            .fold(self, |builder, url| builder.image_url(url))
     }
 
     /// Sets the image URLs for the completion request.
@@ -477,6 +790,13 @@ impl<M: CompletionModel> CompletionRequestBuilder<M> {
         self
     }
 
+    /// Sets the prompt caching breakpoints for the completion request.
+    /// Note: This is only used by providers with explicit prompt caching (e.g.: Anthropic)
+    pub fn cache_breakpoints(mut self, cache_breakpoints: CacheBreakpoints) -> Self {
+        self.cache_breakpoints = cache_breakpoints;
+        self
+    }
+
     /// Builds the completion request.
     pub fn build(self) -> CompletionRequest {
         CompletionRequest {
@@ -489,6 +809,7 @@ impl<M: CompletionModel> CompletionRequestBuilder<M> {
             max_tokens: self.max_tokens,
             additional_params: self.additional_params,
             image_urls: self.image_urls,
+            cache_breakpoints: self.cache_breakpoints,
         }
     }
 
@@ -497,6 +818,13 @@ impl<M: CompletionModel> CompletionRequestBuilder<M> {
         let model = self.model.clone();
         model.completion(self.build()).await
     }
+
+    /// Sends the completion request to the completion model provider and returns a stream of
+    /// the completion response (see [CompletionModel::stream]).
+    pub async fn stream(self) -> Result<StreamingResult, CompletionError> {
+        let model = self.model.clone();
+        model.stream(self.build()).await
+    }
 }
 
 #[cfg(test)]
@@ -560,6 +888,7 @@ mod tests {
             max_tokens: None,
             additional_params: None,
             image_urls: None,
+            cache_breakpoints: CacheBreakpoints::default(),
         };
 
         let expected = concat!(
@@ -573,4 +902,62 @@ mod tests {
 
         assert_eq!(request.prompt_with_context(), expected);
     }
+
+    #[test]
+    fn test_marked_citations() {
+        let text = "Rig is written in Rust [doc1][doc2]. It supports 10+ providers [doc3, sic].\n\
+            Déjà vu [doc1]";
+        let citations = marked_citations(text, |reference| {
+            reference.starts_with("doc").then(|| Citation {
+                source_id: Some(reference.to_string()),
+                ..Default::default()
+            })
+        });
+
+        let spans = citations
+            .iter()
+            .map(|citation| {
+                let span = citation.span.clone().unwrap();
+                (citation.source_id.clone().unwrap(), span.text, span.start)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![
+                ("doc1".into(), "Rig is written in Rust".into(), 0),
+                ("doc2".into(), "Rig is written in Rust".into(), 0),
+                ("doc3".into(), "It supports 10+ providers".into(), 37),
+                ("doc1".into(), "Déjà vu".into(), 76),
+            ]
+        );
+        assert_eq!(Span::new(text, 76, 83).text, "Déjà vu");
+    }
+
+    #[test]
+    fn test_image_parse() {
+        // PNG labeled as octet-stream by the downloader
+        let png = STANDARD.encode([0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0]);
+        assert_eq!(
+            Image::parse(&format!("data:application/octet-stream;base64,{png}")),
+            Image::Base64 {
+                mime_type: "image/png".to_string(),
+                data: png.clone(),
+            }
+        );
+        assert_eq!(
+            Image::parse("data:image/heic;base64,AAAA"),
+            Image::Base64 {
+                mime_type: "image/heic".to_string(),
+                data: "AAAA".to_string(),
+            }
+        );
+        assert_eq!(
+            Image::parse(&format!("data:;base64,{png}")).url(),
+            format!("data:image/png;base64,{png}")
+        );
+        assert_eq!(
+            Image::parse("https://pbs.twimg.com/media/photo.jpg"),
+            Image::Url("https://pbs.twimg.com/media/photo.jpg".to_string())
+        );
+    }
 }
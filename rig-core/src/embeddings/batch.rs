@@ -0,0 +1,520 @@
+//! This module defines the [BatchEmbeddingModel] trait, which represents an embedding model that
+//! can embed texts with asynchronous batch jobs (e.g.: OpenAI's Batch API). Batch jobs are
+//! cheaper and have higher rate limits than synchronous requests, which makes them suitable for
+//! the ingestion of large corpora.
+//!
+//! Batch jobs are run by [EmbeddingsBuilder::build_batch](super::EmbeddingsBuilder::build_batch),
+//! which submits the jobs, polls them until they complete and downloads their embeddings. The ids
+//! of the submitted jobs are saved in a local state file (see [BatchConfig]), so that an
+//! interrupted ingestion resumes polling the same jobs instead of submitting them again. The state
+//! file is removed once all the embeddings are downloaded.
+//!
+//! Jobs are limited by both [BatchEmbeddingModel::MAX_REQUESTS] and
+//! [BatchEmbeddingModel::MAX_INPUTS]. Requests which fail within a completed job are resubmitted
+//! in a new job, so that the rest of the job does not have to be embedded again.
+
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use futures_timer::Delay;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{Embedding, EmbeddingError, EmbeddingModel};
+
+/// Trait for embedding models that can embed texts with asynchronous batch jobs.
+pub trait BatchEmbeddingModel: EmbeddingModel {
+    /// The maximum number of requests in a single batch job.
+    const MAX_REQUESTS: usize;
+
+    /// The maximum number of texts embedded by all the requests of a single batch job.
+    const MAX_INPUTS: usize = usize::MAX;
+
+    /// Submit a batch job embedding the texts of each request, and return the id of the job.
+    fn submit_batch(
+        &self,
+        requests: Vec<BatchRequest>,
+    ) -> impl Future<Output = Result<String, EmbeddingError>> + Send;
+
+    /// Get the status of the batch job `id`.
+    fn batch_status(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<BatchStatus, EmbeddingError>> + Send;
+
+    /// Get the embeddings of the completed batch job `id`, as pairs of request `custom_id` and
+    /// embeddings of the texts of the request, or error of the request if it failed. The
+    /// `document` of the embeddings can be left empty, as it is set to the embedded text
+    /// afterwards.
+    fn batch_results(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<Vec<(String, BatchResult)>, EmbeddingError>> + Send;
+}
+
+/// Request of a batch job, embedding `texts` in a single request of at most
+/// [EmbeddingModel::MAX_DOCUMENTS] texts.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BatchRequest {
+    /// Id of the request in the batch job
+    pub custom_id: String,
+    /// Texts to embed
+    pub texts: Vec<String>,
+}
+
+/// Embeddings of the texts of a [BatchRequest], or error of the request.
+pub type BatchResult = Result<Vec<Embedding>, EmbeddingError>;
+
+/// Status of a batch job.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchStatus {
+    /// The job is being validated, queued or processed
+    InProgress,
+    /// The job completed and its results can be downloaded
+    Completed,
+    /// The job failed, expired or was cancelled, for the given reason
+    Failed(String),
+}
+
+/// Configuration of the batch jobs run by
+/// [EmbeddingsBuilder::build_batch](super::EmbeddingsBuilder::build_batch).
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// use rig::embeddings::{batch::BatchConfig, EmbeddingsBuilder};
+///
+/// let embeddings = EmbeddingsBuilder::new(model)
+///     .documents(documents)?
+///     .build_batch(
+///         BatchConfig::new("data/embeddings_batch.json").poll_interval(Duration::from_secs(300)),
+///     )
+///     .await?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    state_file: PathBuf,
+    poll_interval: Duration,
+}
+
+impl BatchConfig {
+    /// Create a new batch configuration saving the state of the jobs in `state_file`. If the file
+    /// exists, the jobs it references are resumed instead of being submitted again. The file is
+    /// removed when the ingestion completes, so the same `state_file` can be used by every
+    /// ingestion that is not run concurrently with another one.
+    pub fn new(state_file: impl Into<PathBuf>) -> Self {
+        Self {
+            state_file: state_file.into(),
+            poll_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Set the interval between two polls of the status of the jobs (default: 60 seconds)
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+/// State of the batch jobs of an ingestion, saved after each submission.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+struct BatchState {
+    /// SHA-256 hash of the requests, so that a state file is only resumed for the same texts
+    fingerprint: String,
+    /// The jobs of the ingestion, followed by the jobs resubmitting the requests which failed
+    jobs: Vec<BatchJob>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+struct BatchJob {
+    /// Indexes of the requests of the job
+    requests: Vec<usize>,
+    /// Id of the job, if submitted
+    id: Option<String>,
+}
+
+impl BatchState {
+    fn load(path: &Path) -> Result<Option<Self>, EmbeddingError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(EmbeddingError::IoError(e)),
+        }
+    }
+
+    /// Save the state to a temporary file first so that it is never left half-written.
+    fn save(&self, path: &Path) -> Result<(), EmbeddingError> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Maximum number of times the failed requests of the jobs are resubmitted
+const MAX_RESUBMISSIONS: usize = 3;
+
+fn fingerprint(requests: &[BatchRequest], max_requests: usize, max_inputs: usize) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(max_requests.to_le_bytes());
+    hasher.update(max_inputs.to_le_bytes());
+    for request in requests {
+        hasher.update(request.texts.len().to_le_bytes());
+        for text in &request.texts {
+            hasher.update(text.len().to_le_bytes());
+            hasher.update(text.as_bytes());
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Group the `requests` (by index) into jobs of at most `max_requests` requests and
+/// `max_inputs` texts. A request with more than `max_inputs` texts gets a job of its own.
+fn plan_jobs(
+    requests: &[BatchRequest],
+    indexes: impl IntoIterator<Item = usize>,
+    max_requests: usize,
+    max_inputs: usize,
+) -> Vec<BatchJob> {
+    let mut jobs: Vec<BatchJob> = vec![];
+    let mut inputs = 0;
+    for i in indexes {
+        let texts = requests[i].texts.len();
+        match jobs.last_mut() {
+            Some(job) if job.requests.len() < max_requests && inputs + texts <= max_inputs => {
+                job.requests.push(i);
+                inputs += texts;
+            }
+            _ => {
+                jobs.push(BatchJob {
+                    requests: vec![i],
+                    id: None,
+                });
+                inputs = texts;
+            }
+        }
+    }
+    jobs
+}
+
+/// Embed `texts` with batch jobs, returning their embeddings in the same order.
+pub(crate) async fn embed_texts<M: BatchEmbeddingModel>(
+    model: &M,
+    texts: Vec<String>,
+    config: &BatchConfig,
+) -> Result<Vec<Embedding>, EmbeddingError> {
+    let requests = texts
+        .chunks(M::MAX_DOCUMENTS)
+        .enumerate()
+        .map(|(i, texts)| BatchRequest {
+            custom_id: i.to_string(),
+            texts: texts.to_vec(),
+        })
+        .collect::<Vec<_>>();
+
+    let fingerprint = fingerprint(&requests, M::MAX_REQUESTS, M::MAX_INPUTS);
+    let mut state = match BatchState::load(&config.state_file)? {
+        Some(state) if state.fingerprint == fingerprint => {
+            tracing::info!(target: "rig",
+                "Resuming embedding batch jobs from {}",
+                config.state_file.display()
+            );
+            state
+        }
+        Some(_) => {
+            return Err(EmbeddingError::DocumentError(
+                format!(
+                    "Batch state file {} belongs to an ingestion of other texts",
+                    config.state_file.display()
+                )
+                .into(),
+            ))
+        }
+        None => BatchState {
+            fingerprint,
+            jobs: plan_jobs(&requests, 0..requests.len(), M::MAX_REQUESTS, M::MAX_INPUTS),
+        },
+    };
+
+    let mut embeddings: Vec<Option<Vec<Embedding>>> = vec![None; requests.len()];
+    // Jobs whose results were downloaded
+    let mut downloaded = 0;
+    let mut resubmissions = 0;
+    loop {
+        // Submit the jobs that were not submitted yet (or that failed during a previous run),
+        // saving the state after each submission so that no job is ever submitted twice
+        for i in downloaded..state.jobs.len() {
+            if state.jobs[i].id.is_none() {
+                let job_requests = state.jobs[i]
+                    .requests
+                    .iter()
+                    .map(|request| requests[*request].clone())
+                    .collect();
+                let id = model.submit_batch(job_requests).await?;
+                tracing::info!(target: "rig", "Submitted embedding batch job {}", id);
+                state.jobs[i].id = Some(id);
+                state.save(&config.state_file)?;
+            }
+        }
+
+        // Poll the jobs until they all complete
+        let mut pending = (downloaded..state.jobs.len()).collect::<Vec<_>>();
+        loop {
+            let mut in_progress = vec![];
+            for i in pending {
+                let id = state.jobs[i].id.clone().unwrap_or_default();
+                match model.batch_status(&id).await? {
+                    BatchStatus::Completed => {
+                        tracing::info!(target: "rig", "Embedding batch job {} completed", id);
+                    }
+                    BatchStatus::InProgress => in_progress.push(i),
+                    BatchStatus::Failed(reason) => {
+                        // Forget the job so that it is submitted again when the ingestion is
+                        // resumed
+                        state.jobs[i].id = None;
+                        state.save(&config.state_file)?;
+                        return Err(EmbeddingError::ProviderError(format!(
+                            "Embedding batch job {} failed: {}",
+                            id, reason
+                        )));
+                    }
+                }
+            }
+
+            if in_progress.is_empty() {
+                break;
+            }
+            pending = in_progress;
+            Delay::new(config.poll_interval).await;
+        }
+
+        // Download the embeddings of the jobs and put them back in the order of the requests.
+        // Requests which failed in a job may have been resubmitted in a later one.
+        let mut errors = vec![];
+        for job in &state.jobs[downloaded..] {
+            let id = job.id.as_deref().unwrap_or_default();
+            for (custom_id, result) in model.batch_results(id).await? {
+                let request = custom_id
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|i| job.requests.contains(i))
+                    .ok_or_else(|| {
+                        EmbeddingError::ResponseError(format!(
+                            "Embedding batch job {} returned unknown request {}",
+                            id, custom_id
+                        ))
+                    })?;
+                let request_embeddings = match result {
+                    Ok(request_embeddings) => request_embeddings,
+                    Err(e) => {
+                        errors.push(format!("request {} of job {}: {}", custom_id, id, e));
+                        continue;
+                    }
+                };
+                if request_embeddings.len() != requests[request].texts.len() {
+                    return Err(EmbeddingError::ResponseError(format!(
+                        "Expected {} embeddings for request {} of embedding batch job {}, got {}",
+                        requests[request].texts.len(),
+                        custom_id,
+                        id,
+                        request_embeddings.len()
+                    )));
+                }
+                embeddings[request] = Some(
+                    request_embeddings
+                        .into_iter()
+                        .zip(&requests[request].texts)
+                        .map(|(embedding, text)| Embedding {
+                            document: text.clone(),
+                            ..embedding
+                        })
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+        downloaded = state.jobs.len();
+
+        let missing = (0..requests.len())
+            .filter(|i| embeddings[*i].is_none())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            break;
+        }
+        if resubmissions == MAX_RESUBMISSIONS {
+            return Err(EmbeddingError::ProviderError(format!(
+                "Embedding batch jobs returned no embeddings for {} requests: {}",
+                missing.len(),
+                errors.join(", ")
+            )));
+        }
+
+        // Resubmit the requests which failed (or are missing from the results) in new jobs
+        tracing::warn!(target: "rig",
+            "Resubmitting {} failed requests of embedding batch jobs: {}",
+            missing.len(),
+            errors.join(", ")
+        );
+        resubmissions += 1;
+        state.jobs.extend(plan_jobs(
+            &requests,
+            missing,
+            M::MAX_REQUESTS,
+            M::MAX_INPUTS,
+        ));
+        state.save(&config.state_file)?;
+    }
+
+    // The ingestion is done: remove its state so that the state file can be reused by the next
+    // ingestion
+    match fs::remove_file(&config.state_file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    Ok(embeddings.into_iter().flatten().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{embed_texts, BatchConfig, BatchEmbeddingModel, BatchRequest, BatchResult};
+    use crate::embeddings::{batch::BatchStatus, Embedding, EmbeddingError, EmbeddingModel};
+
+    #[derive(Clone, Default)]
+    struct MockModel {
+        /// Requests of the submitted jobs
+        jobs: Arc<Mutex<Vec<Vec<BatchRequest>>>>,
+        /// Requests which fail the next time they are run
+        failing: Arc<Mutex<HashSet<String>>>,
+    }
+
+    impl MockModel {
+        fn submitted(&self) -> Vec<Vec<String>> {
+            self.jobs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|job| job.iter().map(|r| r.custom_id.clone()).collect())
+                .collect()
+        }
+    }
+
+    impl EmbeddingModel for MockModel {
+        const MAX_DOCUMENTS: usize = 2;
+
+        fn ndims(&self) -> usize {
+            1
+        }
+
+        async fn embed_texts(
+            &self,
+            _documents: impl IntoIterator<Item = String> + Send,
+        ) -> Result<Vec<Embedding>, EmbeddingError> {
+            unimplemented!("Only batch jobs are used")
+        }
+    }
+
+    impl BatchEmbeddingModel for MockModel {
+        const MAX_REQUESTS: usize = 3;
+        const MAX_INPUTS: usize = 4;
+
+        async fn submit_batch(
+            &self,
+            requests: Vec<BatchRequest>,
+        ) -> Result<String, EmbeddingError> {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push(requests);
+            Ok((jobs.len() - 1).to_string())
+        }
+
+        async fn batch_status(&self, _id: &str) -> Result<BatchStatus, EmbeddingError> {
+            Ok(BatchStatus::Completed)
+        }
+
+        async fn batch_results(
+            &self,
+            id: &str,
+        ) -> Result<Vec<(String, BatchResult)>, EmbeddingError> {
+            let job = self.jobs.lock().unwrap()[id.parse::<usize>().unwrap()].clone();
+            let mut failing = self.failing.lock().unwrap();
+
+            Ok(job
+                .into_iter()
+                .map(|request| {
+                    let result = match failing.remove(&request.custom_id) {
+                        true => Err(EmbeddingError::ProviderError("server_error".into())),
+                        false => Ok(request
+                            .texts
+                            .iter()
+                            .map(|text| Embedding {
+                                document: String::new(),
+                                vec: vec![text.len() as f64],
+                            })
+                            .collect()),
+                    };
+                    (request.custom_id, result)
+                })
+                .collect())
+        }
+    }
+
+    fn texts() -> Vec<String> {
+        (1..=9).map(|n| "a".repeat(n)).collect()
+    }
+
+    fn config(temp: &assert_fs::TempDir) -> BatchConfig {
+        BatchConfig::new(temp.path().join("batch.json")).poll_interval(Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn test_jobs_limits() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let model = MockModel::default();
+
+        let embeddings = embed_texts(&model, texts(), &config(&temp)).await.unwrap();
+
+        // 5 requests of up to 2 texts, in jobs of up to 3 requests and 4 texts
+        assert_eq!(
+            model.submitted(),
+            vec![vec!["0", "1"], vec!["2", "3"], vec!["4"]]
+        );
+        assert_eq!(
+            embeddings.iter().map(|e| e.vec[0]).collect::<Vec<_>>(),
+            (1..=9).map(|n| n as f64).collect::<Vec<_>>()
+        );
+        assert_eq!(embeddings[8].document, "a".repeat(9));
+    }
+
+    #[tokio::test]
+    async fn test_failed_requests_resubmitted() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let model = MockModel::default();
+        model.failing.lock().unwrap().extend(["1".to_string()]);
+
+        let embeddings = embed_texts(&model, texts(), &config(&temp)).await.unwrap();
+
+        assert_eq!(
+            model.submitted(),
+            vec![vec!["0", "1"], vec!["2", "3"], vec!["4"], vec!["1"]]
+        );
+        assert_eq!(embeddings.len(), 9);
+        assert_eq!(embeddings[2].vec, vec![3.0]);
+        assert!(!temp.path().join("batch.json").exists());
+    }
+}
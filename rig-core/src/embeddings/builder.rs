@@ -8,7 +8,9 @@ use futures::{stream, StreamExt};
 
 use crate::{
     embeddings::{
-        embed::TextEmbedder, Embed, EmbedError, Embedding, EmbeddingError, EmbeddingModel,
+        batch::{self, BatchConfig, BatchEmbeddingModel},
+        embed::TextEmbedder,
+        Embed, EmbedError, Embedding, EmbeddingError, EmbeddingModel,
     },
     OneOrMany,
 };
@@ -141,6 +143,34 @@ impl<M: EmbeddingModel, T: Embed + Send> EmbeddingsBuilder<M, T> {
     }
 }
 
+impl<M: BatchEmbeddingModel, T: Embed + Send> EmbeddingsBuilder<M, T> {
+    /// Generate embeddings for all documents in the builder with asynchronous batch jobs instead
+    /// of synchronous requests (see [batch]). The jobs are resumed from the state file of
+    /// `config` if it exists, e.g.: when a previous call was interrupted.
+    /// Returns a vector of tuples, where the first element is the document and the second element is the embeddings (either one embedding or many).
+    pub async fn build_batch(
+        self,
+        config: BatchConfig,
+    ) -> Result<Vec<(T, OneOrMany<Embedding>)>, EmbeddingError> {
+        let (docs, texts): (Vec<_>, Vec<_>) = self.documents.into_iter().unzip();
+        let counts = texts.iter().map(Vec::len).collect::<Vec<_>>();
+
+        let mut embeddings =
+            batch::embed_texts(&self.model, texts.into_iter().flatten().collect(), &config)
+                .await?
+                .into_iter();
+
+        docs.into_iter()
+            .zip(counts)
+            .map(|(doc, count)| {
+                let embeddings = OneOrMany::many(embeddings.by_ref().take(count).collect())
+                    .map_err(|e| EmbeddingError::DocumentError(Box::new(e)))?;
+                Ok((doc, embeddings))
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -1,3 +1,5 @@
+use crate::embeddings::Embedding;
+
 pub trait VectorDistance {
     /// Get dot product of two embedding vectors
     fn dot_product(&self, other: &Self) -> f64;
@@ -132,9 +134,310 @@ mod rayon {
     }
 }
 
+impl VectorDistance for crate::embeddings::Int8Embedding {
+    fn dot_product(&self, other: &Self) -> f64 {
+        self.vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(x, y)| *x as i64 * *y as i64)
+            .sum::<i64>() as f64
+    }
+
+    /// Quantized embeddings are never unit-norm, even when quantized from normalized embeddings,
+    /// so the dot product is always divided by the magnitudes and `normalized` is ignored.
+    /// The similarity of an all-zero embedding is 0.0.
+    fn cosine_similarity(&self, other: &Self, _normalized: bool) -> f64 {
+        let dot_product = self.dot_product(other);
+        let magnitude1 = self.dot_product(self).sqrt();
+        let magnitude2 = other.dot_product(other).sqrt();
+
+        if magnitude1 == 0.0 || magnitude2 == 0.0 {
+            return 0.0;
+        }
+        dot_product / (magnitude1 * magnitude2)
+    }
+
+    fn angular_distance(&self, other: &Self, normalized: bool) -> f64 {
+        let cosine_sim = self.cosine_similarity(other, normalized).clamp(-1.0, 1.0);
+        cosine_sim.acos() / std::f64::consts::PI
+    }
+
+    fn euclidean_distance(&self, other: &Self) -> f64 {
+        (self
+            .vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(x, y)| (*x as i64 - *y as i64).pow(2))
+            .sum::<i64>() as f64)
+            .sqrt()
+    }
+
+    fn manhattan_distance(&self, other: &Self) -> f64 {
+        self.vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(x, y)| (*x as i64 - *y as i64).abs())
+            .sum::<i64>() as f64
+    }
+
+    fn chebyshev_distance(&self, other: &Self) -> f64 {
+        self.vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(x, y)| (*x as i64 - *y as i64).abs())
+            .max()
+            .unwrap_or(0) as f64
+    }
+}
+
+pub trait BinaryDistance {
+    /// Get hamming distance of two binary embedding vectors, i.e.: the number of differing bits.
+    fn hamming_distance(&self, other: &Self) -> u32;
+}
+
+impl BinaryDistance for crate::embeddings::BinaryEmbedding {
+    fn hamming_distance(&self, other: &Self) -> u32 {
+        self.vec
+            .iter()
+            .zip(other.vec.iter())
+            .map(|(x, y)| (x ^ y).count_ones())
+            .sum()
+    }
+}
+
+/// Embeddings stored contiguously as rows of `f32`, for batched similarity computations.
+///
+/// Comparing a query to many embeddings one pair at a time (with [VectorDistance]) is dominated
+/// by pointer chasing and `f64` arithmetic. An [EmbeddingMatrix] stores the embeddings in a
+/// single buffer with their precomputed norms and compares them with SIMD kernels, in parallel
+/// with the `rayon` feature.
+///
+/// # Example
+/// ```rust
+/// use rig::embeddings::distance::EmbeddingMatrix;
+///
+/// let matrix = EmbeddingMatrix::from_embeddings(&embeddings);
+///
+/// // One-vs-many: similarity of the query to each row of the matrix
+/// let similarities = matrix.cosine_similarities(&query);
+///
+/// // Many-vs-many: similarity of each row of `queries` to each row of the matrix
+/// let similarities = EmbeddingMatrix::from_embeddings(&queries).pairwise_cosine_similarities(&matrix);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmbeddingMatrix {
+    ndims: usize,
+    data: Vec<f32>,
+    norms: Vec<f32>,
+}
+
+impl EmbeddingMatrix {
+    /// Create a matrix from embeddings, which must all have the same number of dimensions.
+    pub fn from_embeddings<'a>(embeddings: impl IntoIterator<Item = &'a Embedding>) -> Self {
+        let mut matrix = Self::default();
+        embeddings
+            .into_iter()
+            .for_each(|embedding| matrix.push(embedding));
+        matrix
+    }
+
+    /// Append an embedding as the last row of the matrix.
+    ///
+    /// # Panics
+    /// If the matrix is not empty and the embedding has a different number of dimensions.
+    pub fn push(&mut self, embedding: &Embedding) {
+        if self.is_empty() {
+            self.ndims = embedding.vec.len();
+            self.data.clear();
+        }
+        assert_eq!(
+            embedding.vec.len(),
+            self.ndims,
+            "Embedding has {} dimensions, expected {}",
+            embedding.vec.len(),
+            self.ndims
+        );
+
+        let row = to_f32(embedding);
+        self.norms.push(kernels::dot(&row, &row).sqrt());
+        self.data.extend(row);
+    }
+
+    /// Remove the row `index` of the matrix, replacing it with the last row.
+    pub fn swap_remove(&mut self, index: usize) {
+        let last = self.len() - 1;
+        if index != last {
+            self.data.copy_within(
+                last * self.ndims..(last + 1) * self.ndims,
+                index * self.ndims,
+            );
+        }
+        self.data.truncate(last * self.ndims);
+        self.norms.swap_remove(index);
+    }
+
+    /// Get the row `index` of the matrix.
+    pub fn row(&self, index: usize) -> &[f32] {
+        &self.data[index * self.ndims..(index + 1) * self.ndims]
+    }
+
+    /// Number of dimensions of the rows of the matrix
+    pub fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    /// Number of rows of the matrix
+    pub fn len(&self) -> usize {
+        self.norms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.norms.is_empty()
+    }
+
+    /// Get the dot product of `query` with each row of the matrix.
+    ///
+    /// # Panics
+    /// If the matrix is not empty and the query has a different number of dimensions.
+    pub fn dot_products(&self, query: &Embedding) -> Vec<f32> {
+        let query = self.query(query);
+        self.map_rows(|row, _| kernels::dot(&query, row))
+    }
+
+    /// Get the cosine similarity of `query` with each row of the matrix. The similarity of a
+    /// zero vector is 0.0.
+    ///
+    /// # Panics
+    /// If the matrix is not empty and the query has a different number of dimensions.
+    pub fn cosine_similarities(&self, query: &Embedding) -> Vec<f32> {
+        let query = self.query(query);
+        let norm = kernels::dot(&query, &query).sqrt();
+        self.map_rows(|row, row_norm| cosine(kernels::dot(&query, row), norm, row_norm))
+    }
+
+    /// Get the cosine similarity of each row of the matrix with each row of `other`: the
+    /// similarity of the row `i` with the row `j` of `other` is at `[i][j]`. The similarity of a
+    /// zero vector is 0.0.
+    ///
+    /// # Panics
+    /// If neither matrix is empty and they have different numbers of dimensions.
+    pub fn pairwise_cosine_similarities(&self, other: &EmbeddingMatrix) -> Vec<Vec<f32>> {
+        if !other.is_empty() {
+            assert_eq!(
+                self.ndims, other.ndims,
+                "Matrices have different dimensions"
+            );
+        }
+        self.map_rows(|row, norm| {
+            other
+                .data
+                .chunks_exact(other.ndims.max(1))
+                .zip(&other.norms)
+                .map(|(other_row, other_norm)| {
+                    cosine(kernels::dot(row, other_row), norm, *other_norm)
+                })
+                .collect()
+        })
+    }
+
+    fn query(&self, query: &Embedding) -> Vec<f32> {
+        if !self.is_empty() {
+            assert_eq!(
+                query.vec.len(),
+                self.ndims,
+                "Query has {} dimensions, expected {}",
+                query.vec.len(),
+                self.ndims
+            );
+        }
+        to_f32(query)
+    }
+
+    /// Apply `f` to each row of the matrix and its norm, in parallel with the `rayon` feature.
+    fn map_rows<T: Send>(&self, f: impl Fn(&[f32], f32) -> T + Send + Sync) -> Vec<T> {
+        // Rows of 0 dimensions take no space in `data`
+        if self.ndims == 0 {
+            return self.norms.iter().map(|norm| f(&[], *norm)).collect();
+        }
+
+        #[cfg(feature = "rayon")]
+        {
+            use ::rayon::prelude::*;
+
+            self.data
+                .par_chunks_exact(self.ndims)
+                .zip(self.norms.par_iter())
+                .with_min_len(64)
+                .map(|(row, norm)| f(row, *norm))
+                .collect()
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        self.data
+            .chunks_exact(self.ndims)
+            .zip(&self.norms)
+            .map(|(row, norm)| f(row, *norm))
+            .collect()
+    }
+}
+
+/// Cosine similarity from a dot product and norms, or 0.0 for zero vectors instead of NaN
+fn cosine(dot: f32, norm1: f32, norm2: f32) -> f32 {
+    if norm1 == 0.0 || norm2 == 0.0 {
+        return 0.0;
+    }
+    dot / (norm1 * norm2)
+}
+
+fn to_f32(embedding: &Embedding) -> Vec<f32> {
+    embedding.vec.iter().map(|x| *x as f32).collect()
+}
+
+/// `f32` kernels written over fixed-size lanes, so that they are vectorized by the compiler, and
+/// compiled for AVX2 when it is available at runtime on x86-64.
+mod kernels {
+    /// Number of independent accumulators: two 256-bit registers of `f32`.
+    const LANES: usize = 16;
+
+    pub(super) fn dot(a: &[f32], b: &[f32]) -> f32 {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU supports AVX2
+            return unsafe { dot_avx2(a, b) };
+        }
+
+        dot_lanes(a, b)
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    unsafe fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+        dot_lanes(a, b)
+    }
+
+    #[inline(always)]
+    fn dot_lanes(a: &[f32], b: &[f32]) -> f32 {
+        let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+        let remainder = a_chunks
+            .remainder()
+            .iter()
+            .zip(b_chunks.remainder())
+            .map(|(x, y)| x * y)
+            .sum::<f32>();
+
+        let mut acc = [0.0; LANES];
+        for (x, y) in a_chunks.zip(b_chunks) {
+            for ((acc, x), y) in acc.iter_mut().zip(x).zip(y) {
+                *acc += x * y;
+            }
+        }
+        acc.iter().sum::<f32>() + remainder
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::VectorDistance;
+    use super::{BinaryDistance, EmbeddingMatrix, VectorDistance};
     use crate::embeddings::Embedding;
 
     fn embeddings() -> (Embedding, Embedding) {
@@ -198,4 +501,137 @@ mod tests {
 
         assert_eq!(embedding_1.chebyshev_distance(&embedding_2), 4.0)
     }
+
+    #[test]
+    fn test_int8_distances() {
+        let (embedding_1, embedding_2) = embeddings();
+        let int8_1 = embedding_1.truncate(3).quantize_int8();
+        let int8_2 = embedding_2.truncate(3).quantize_int8();
+
+        let truncated = embedding_1.truncate(2);
+        assert_eq!(truncated.vec.len(), 2);
+        assert!((truncated.dot_product(&truncated) - 1.0).abs() < 1e-9);
+
+        assert_eq!(int8_1.vec, vec![33, 67, 101]);
+        assert_eq!(int8_1.euclidean_distance(&int8_1), 0.0);
+        assert_eq!(int8_1.manhattan_distance(&int8_2), 26.0);
+        assert!(
+            (int8_1.cosine_similarity(&int8_2, false)
+                - embedding_1.cosine_similarity(&embedding_2, false))
+            .abs()
+                < 0.01
+        );
+    }
+
+    #[test]
+    fn test_int8_cosine_similarity_of_normalized_embeddings() {
+        let (embedding_1, embedding_2) = embeddings();
+        let (embedding_1, embedding_2) = (embedding_1.truncate(3), embedding_2.truncate(3));
+        let (int8_1, int8_2) = (embedding_1.quantize_int8(), embedding_2.quantize_int8());
+
+        // The quantized embeddings are not normalized, even though their source embeddings are
+        let cosine = embedding_1.cosine_similarity(&embedding_2, true);
+        assert!((int8_1.cosine_similarity(&int8_2, true) - cosine).abs() < 0.01);
+        assert_eq!(
+            int8_1.cosine_similarity(&int8_2, true),
+            int8_1.cosine_similarity(&int8_2, false)
+        );
+
+        let angular_distance = int8_1.angular_distance(&int8_2, true);
+        assert!(!angular_distance.is_nan());
+        assert!((angular_distance - embedding_1.angular_distance(&embedding_2, true)).abs() < 0.01);
+        assert_eq!(int8_1.angular_distance(&int8_1, true), 0.0);
+    }
+
+    #[test]
+    fn test_int8_cosine_similarity_of_zero_embedding() {
+        let zero = crate::embeddings::Int8Embedding {
+            document: "zero".to_string(),
+            vec: vec![0, 0, 0],
+        };
+        let (embedding, _) = embeddings();
+        let int8 = embedding.truncate(3).quantize_int8();
+
+        assert_eq!(zero.cosine_similarity(&int8, false), 0.0);
+        assert_eq!(int8.cosine_similarity(&zero, true), 0.0);
+        assert_eq!(zero.angular_distance(&zero, false), 0.5);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        let embedding_1 = Embedding {
+            document: "test".to_string(),
+            vec: vec![0.1, -0.2, 0.3, -0.4, 0.5, -0.6, 0.7, -0.8, 0.9],
+        };
+        let embedding_2 = Embedding {
+            document: "test".to_string(),
+            vec: vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, -0.9],
+        };
+
+        let binary_1 = embedding_1.quantize_binary();
+        assert_eq!(binary_1.vec, vec![0b0101_0101, 0b1]);
+        assert_eq!(binary_1.ndims, 9);
+        assert_eq!(binary_1.hamming_distance(&embedding_2.quantize_binary()), 5);
+    }
+
+    #[test]
+    fn test_embedding_matrix() {
+        let (embedding_1, embedding_2) = embeddings();
+        let embedding_3 = Embedding {
+            document: "test".to_string(),
+            vec: (0..37).map(|i| i as f64 / 10.0).collect(),
+        };
+        let embedding_4 = Embedding {
+            document: "test".to_string(),
+            vec: (0..37).map(|i| (i as f64).sin()).collect(),
+        };
+
+        let mut matrix = EmbeddingMatrix::from_embeddings([&embedding_1, &embedding_2]);
+        assert_eq!(matrix.len(), 2);
+        assert_eq!(matrix.ndims(), 3);
+        assert_eq!(matrix.row(1), &[1.0, 5.0, 7.0]);
+        assert_eq!(matrix.dot_products(&embedding_1), vec![14.0, 32.0]);
+
+        let similarities = matrix.cosine_similarities(&embedding_2);
+        assert!(
+            (similarities[0] as f64 - embedding_1.cosine_similarity(&embedding_2, false)).abs()
+                < 1e-6
+        );
+        assert!((similarities[1] - 1.0).abs() < 1e-6);
+
+        let pairwise = matrix.pairwise_cosine_similarities(&matrix);
+        assert_eq!(pairwise.len(), 2);
+        assert_eq!(pairwise[0][1], pairwise[1][0]);
+        assert_eq!(pairwise[0][1], similarities[0]);
+
+        matrix.swap_remove(0);
+        assert_eq!(matrix.len(), 1);
+        assert_eq!(matrix.row(0), &[1.0, 5.0, 7.0]);
+        matrix.swap_remove(0);
+        assert!(matrix.is_empty());
+
+        // Rows longer than the SIMD lanes, with a remainder
+        matrix.push(&embedding_3);
+        matrix.push(&embedding_4);
+        let dot_products = matrix.dot_products(&embedding_4);
+        assert!((dot_products[0] as f64 - embedding_3.dot_product(&embedding_4)).abs() < 1e-3);
+        assert!((dot_products[1] as f64 - embedding_4.dot_product(&embedding_4)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_embedding_matrix_zero_norms() {
+        let (embedding, _) = embeddings();
+        let zero = Embedding {
+            document: "zero".to_string(),
+            vec: vec![0.0; 3],
+        };
+        let matrix = EmbeddingMatrix::from_embeddings([&embedding, &zero]);
+
+        assert_eq!(matrix.cosine_similarities(&embedding)[1], 0.0);
+        assert_eq!(matrix.cosine_similarities(&zero), vec![0.0, 0.0]);
+        assert_eq!(
+            matrix.pairwise_cosine_similarities(&matrix)[1],
+            vec![0.0, 0.0]
+        );
+    }
 }
@@ -18,6 +18,10 @@ pub enum EmbeddingError {
     #[error("JsonError: {0}")]
     JsonError(#[from] serde_json::Error),
 
+    /// Io error (e.g.: reading or writing the state of a batch job)
+    #[error("IoError: {0}")]
+    IoError(#[from] std::io::Error),
+
     /// Error processing the document for embedding
     #[error("DocumentError: {0}")]
     DocumentError(Box<dyn std::error::Error + Send + Sync + 'static>),
@@ -76,3 +80,72 @@ impl PartialEq for Embedding {
 }
 
 impl Eq for Embedding {}
+
+impl Embedding {
+    /// Truncate the embedding to its first `ndims` dimensions and normalize it again.
+    /// Only meaningful for models trained with Matryoshka representation learning (e.g.: OpenAI's
+    /// `text-embedding-3` models), whose first dimensions carry most of the information.
+    pub fn truncate(&self, ndims: usize) -> Self {
+        let vec = &self.vec[..ndims.min(self.vec.len())];
+        let magnitude = vec.iter().map(|x| x.powi(2)).sum::<f64>().sqrt();
+
+        Self {
+            document: self.document.clone(),
+            vec: match magnitude > 0.0 {
+                true => vec.iter().map(|x| x / magnitude).collect(),
+                false => vec.to_vec(),
+            },
+        }
+    }
+
+    /// Quantize the embedding to one signed byte per dimension, mapping `[-1.0, 1.0]` to
+    /// `[-128, 127]` like sqlite-vec's `vec_quantize_int8(vector, 'unit')`. Values out of the
+    /// range are clamped, so the embedding should be normalized.
+    pub fn quantize_int8(&self) -> Int8Embedding {
+        Int8Embedding {
+            document: self.document.clone(),
+            vec: self
+                .vec
+                .iter()
+                .map(|x| ((x.clamp(-1.0, 1.0) + 1.0) * 127.5 - 128.0) as i8)
+                .collect(),
+        }
+    }
+
+    /// Quantize the embedding to one bit per dimension, set for positive values and packed least
+    /// significant bit first like sqlite-vec's `vec_quantize_binary(vector)`.
+    pub fn quantize_binary(&self) -> BinaryEmbedding {
+        let mut vec = vec![0u8; self.vec.len().div_ceil(8)];
+        for (i, x) in self.vec.iter().enumerate() {
+            if *x > 0.0 {
+                vec[i / 8] |= 1 << (i % 8);
+            }
+        }
+
+        BinaryEmbedding {
+            document: self.document.clone(),
+            vec,
+            ndims: self.vec.len(),
+        }
+    }
+}
+
+/// Embedding quantized to one signed byte per dimension (see [Embedding::quantize_int8]).
+#[derive(Clone, Default, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct Int8Embedding {
+    /// The document that was embedded. Used for debugging.
+    pub document: String,
+    /// The quantized embedding vector
+    pub vec: Vec<i8>,
+}
+
+/// Embedding quantized to one bit per dimension (see [Embedding::quantize_binary]).
+#[derive(Clone, Default, Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct BinaryEmbedding {
+    /// The document that was embedded. Used for debugging.
+    pub document: String,
+    /// The bits of the quantized embedding vector, least significant bit first
+    pub vec: Vec<u8>,
+    /// The number of dimensions of the embedding
+    pub ndims: usize,
+}
@@ -3,13 +3,15 @@
 //! natural language processing (NLP) tasks such as text classification, information retrieval,
 //! and document similarity.
 
+pub mod batch;
 pub mod builder;
 pub mod embed;
 pub mod embedding;
 pub mod tool;
 
 pub mod distance;
+pub use batch::{BatchConfig, BatchEmbeddingModel};
 pub use builder::EmbeddingsBuilder;
 pub use embed::{to_texts, Embed, EmbedError, TextEmbedder};
-pub use embedding::{Embedding, EmbeddingError, EmbeddingModel};
+pub use embedding::{BinaryEmbedding, Embedding, EmbeddingError, EmbeddingModel, Int8Embedding};
 pub use tool::ToolSchema;
@@ -0,0 +1,167 @@
+//! Datasets of evaluation cases.
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::EvalError;
+
+/// A single evaluation case: an input along with the optional expected output.
+///
+/// In JSONL datasets, each line is a JSON object with the following fields:
+/// ```json
+/// {"id": "greeting", "input": "Hi!", "expected": "Hello!", "tags": ["discord"], "metadata": {"user": "alice"}}
+/// ```
+/// Only `input` is required. If `id` is missing, the case is identified by its line number.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EvalCase {
+    #[serde(default)]
+    pub id: String,
+    pub input: String,
+    /// Reference output used by metrics such as [ExactMatch](super::ExactMatch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Arbitrary data available to custom targets and metrics
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
+}
+
+impl EvalCase {
+    pub fn new(id: &str, input: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            input: input.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the expected output of the case
+    pub fn expected(mut self, expected: &str) -> Self {
+        self.expected = Some(expected.to_string());
+        self
+    }
+
+    /// Add a tag to the case
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Targets taking the input string of the case
+impl From<&EvalCase> for String {
+    fn from(case: &EvalCase) -> Self {
+        case.input.clone()
+    }
+}
+
+/// Targets taking the whole case (e.g.: to use its tags or metadata)
+impl From<&EvalCase> for EvalCase {
+    fn from(case: &EvalCase) -> Self {
+        case.clone()
+    }
+}
+
+/// An ordered list of [EvalCase]s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dataset {
+    cases: Vec<EvalCase>,
+}
+
+impl Dataset {
+    pub fn new(cases: impl IntoIterator<Item = EvalCase>) -> Self {
+        Self {
+            cases: cases.into_iter().collect(),
+        }
+    }
+
+    /// Load a dataset from a JSONL file (one [EvalCase] per line). Empty lines are skipped.
+    pub fn from_jsonl(path: impl AsRef<Path>) -> Result<Self, EvalError> {
+        Self::from_jsonl_str(&fs::read_to_string(path)?)
+    }
+
+    /// Parse a dataset from JSONL content (one [EvalCase] per line). Empty lines are skipped.
+    pub fn from_jsonl_str(content: &str) -> Result<Self, EvalError> {
+        let cases = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let mut case = serde_json::from_str::<EvalCase>(line).map_err(|source| {
+                    EvalError::InvalidCase {
+                        line: index + 1,
+                        source,
+                    }
+                })?;
+                if case.id.is_empty() {
+                    case.id = format!("line-{}", index + 1);
+                }
+                Ok(case)
+            })
+            .collect::<Result<Vec<_>, EvalError>>()?;
+
+        Ok(Self { cases })
+    }
+
+    /// Keep only the cases with the given tag
+    pub fn with_tag(&self, tag: &str) -> Self {
+        Self::new(self.cases.iter().filter(|case| case.has_tag(tag)).cloned())
+    }
+
+    pub fn cases(&self) -> &[EvalCase] {
+        &self.cases
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EvalCase> {
+        self.cases.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+}
+
+impl FromIterator<EvalCase> for Dataset {
+    fn from_iter<I: IntoIterator<Item = EvalCase>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_jsonl_str() {
+        let dataset = Dataset::from_jsonl_str(concat!(
+            "{\"id\": \"greeting\", \"input\": \"Hi!\", \"expected\": \"Hello!\", \"tags\": [\"discord\"]}\n",
+            "\n",
+            "{\"input\": \"gm\", \"tags\": [\"twitter\"], \"metadata\": {\"user\": \"alice\"}}\n",
+        ))
+        .unwrap();
+
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(
+            dataset.cases()[0],
+            EvalCase::new("greeting", "Hi!")
+                .expected("Hello!")
+                .tag("discord")
+        );
+        assert_eq!(dataset.cases()[1].id, "line-3");
+        assert_eq!(dataset.cases()[1].metadata["user"], "alice");
+        assert_eq!(dataset.with_tag("twitter").len(), 1);
+
+        let err = Dataset::from_jsonl_str("{\"input\": \"ok\"}\n{\"id\": 1}").unwrap_err();
+        assert!(matches!(err, EvalError::InvalidCase { line: 2, .. }));
+    }
+}
@@ -0,0 +1,324 @@
+//! Metrics scoring the output of a target on an [EvalCase].
+use std::future::Future;
+
+use futures::future::BoxFuture;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{EvalCase, EvalError};
+use crate::{
+    completion::CompletionModel,
+    embeddings::{distance::VectorDistance, EmbeddingModel},
+    extractor::{Extractor, ExtractorBuilder},
+};
+
+/// Score given by a [Metric] to an output, between 0.0 (worst) and 1.0 (best).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub value: f64,
+    /// Optional explanation of the score (e.g.: the reasoning of an LLM judge)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Score {
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            reason: None,
+        }
+    }
+
+    /// 1.0 if `pass` is true, 0.0 otherwise
+    pub fn pass(pass: bool) -> Self {
+        Self::new(if pass { 1.0 } else { 0.0 })
+    }
+
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+}
+
+/// Trait for metrics scoring the `output` of a target on an evaluation `case`.
+pub trait Metric: Send + Sync {
+    /// Name of the metric in reports. Must be unique among the metrics of an evaluation.
+    fn name(&self) -> String;
+
+    fn score(
+        &self,
+        case: &EvalCase,
+        output: &str,
+    ) -> impl Future<Output = Result<Score, EvalError>> + Send;
+}
+
+/// Object-safe version of [Metric], so that evaluations can use several metric types.
+pub(crate) trait MetricDyn: Send + Sync {
+    fn metric_name(&self) -> String;
+
+    fn score_boxed<'a>(
+        &'a self,
+        case: &'a EvalCase,
+        output: &'a str,
+    ) -> BoxFuture<'a, Result<Score, EvalError>>;
+}
+
+impl<T: Metric> MetricDyn for T {
+    fn metric_name(&self) -> String {
+        self.name()
+    }
+
+    fn score_boxed<'a>(
+        &'a self,
+        case: &'a EvalCase,
+        output: &'a str,
+    ) -> BoxFuture<'a, Result<Score, EvalError>> {
+        Box::pin(self.score(case, output))
+    }
+}
+
+fn expected<'a>(metric: &impl Metric, case: &'a EvalCase) -> Result<&'a str, EvalError> {
+    case.expected
+        .as_deref()
+        .ok_or_else(|| EvalError::MissingExpected {
+            metric: metric.name(),
+            case: case.id.clone(),
+        })
+}
+
+// ================================================================
+// Exact match
+// ================================================================
+
+/// Scores 1.0 if the output is equal to the expected output of the case (ignoring leading and
+/// trailing whitespace), 0.0 otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct ExactMatch {
+    case_insensitive: bool,
+}
+
+impl ExactMatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore case when comparing the output to the expected output
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+}
+
+impl Metric for ExactMatch {
+    fn name(&self) -> String {
+        "exact_match".to_string()
+    }
+
+    async fn score(&self, case: &EvalCase, output: &str) -> Result<Score, EvalError> {
+        let (expected, output) = (expected(self, case)?.trim(), output.trim());
+
+        Ok(Score::pass(match self.case_insensitive {
+            true => expected.to_lowercase() == output.to_lowercase(),
+            false => expected == output,
+        }))
+    }
+}
+
+// ================================================================
+// Regex match
+// ================================================================
+
+/// Scores 1.0 if the output matches the regex, 0.0 otherwise (or the opposite, see
+/// [RegexMatch::must_not_match]).
+#[derive(Clone, Debug)]
+pub struct RegexMatch {
+    name: String,
+    regex: Regex,
+    must_match: bool,
+}
+
+impl RegexMatch {
+    pub fn new(name: &str, pattern: &str) -> Result<Self, EvalError> {
+        Ok(Self {
+            name: name.to_string(),
+            regex: Regex::new(pattern)?,
+            must_match: true,
+        })
+    }
+
+    /// Score 1.0 if the output does NOT match the regex (e.g.: to penalize forbidden phrases)
+    pub fn must_not_match(mut self) -> Self {
+        self.must_match = false;
+        self
+    }
+}
+
+impl Metric for RegexMatch {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn score(&self, _case: &EvalCase, output: &str) -> Result<Score, EvalError> {
+        let score = Score::pass(self.regex.is_match(output) == self.must_match);
+        Ok(match self.regex.find(output) {
+            Some(m) => score.reason(format!("Matched `{}`", m.as_str())),
+            None => score,
+        })
+    }
+}
+
+// ================================================================
+// Embedding similarity
+// ================================================================
+
+/// Scores the cosine similarity between the embeddings of the output and of the expected output
+/// of the case (negative similarities are clamped to 0.0).
+#[derive(Clone)]
+pub struct EmbeddingSimilarity<M: EmbeddingModel> {
+    model: M,
+}
+
+impl<M: EmbeddingModel> EmbeddingSimilarity<M> {
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+}
+
+impl<M: EmbeddingModel> Metric for EmbeddingSimilarity<M> {
+    fn name(&self) -> String {
+        "embedding_similarity".to_string()
+    }
+
+    async fn score(&self, case: &EvalCase, output: &str) -> Result<Score, EvalError> {
+        let expected = expected(self, case)?;
+        let embeddings = self
+            .model
+            .embed_texts(vec![expected.to_string(), output.to_string()])
+            .await?;
+
+        match embeddings.as_slice() {
+            [expected, output] => Ok(Score::new(
+                expected.cosine_similarity(output, false).clamp(0.0, 1.0),
+            )),
+            _ => Err(EvalError::MetricError(format!(
+                "Expected 2 embeddings, got {}",
+                embeddings.len()
+            ))),
+        }
+    }
+}
+
+// ================================================================
+// LLM-as-judge
+// ================================================================
+
+/// Judgment submitted by the judge model
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+struct Judgment {
+    /// Step by step reasoning about how well the output satisfies the rubric
+    reasoning: String,
+    /// Grade of the output, from 1 (does not satisfy the rubric at all) to 5 (fully satisfies
+    /// the rubric)
+    grade: u8,
+}
+
+/// Uses a completion model to grade the output from 1 to 5 according to a rubric. The grade is
+/// normalized between 0.0 and 1.0, and the reasoning of the judge is kept as the reason of the
+/// score.
+pub struct LlmJudge<M: CompletionModel> {
+    name: String,
+    extractor: Extractor<M, Judgment>,
+}
+
+impl<M: CompletionModel> LlmJudge<M> {
+    pub fn new(name: &str, model: M, rubric: &str) -> Self {
+        let extractor = ExtractorBuilder::new(model)
+            .preamble(&format!(
+                "You are an impartial judge evaluating the output of an AI assistant.\n\
+                Grade the output according to the following rubric:\n{rubric}\n\n\
+                If a reference output is provided, use it as an example of a good output, \
+                not as the only acceptable answer."
+            ))
+            .validator(|judgment: &Judgment| match judgment.grade {
+                1..=5 => Ok(()),
+                grade => Err(format!("grade must be between 1 and 5, got {grade}")),
+            })
+            .retries(1)
+            .build();
+
+        Self {
+            name: name.to_string(),
+            extractor,
+        }
+    }
+}
+
+impl<M: CompletionModel> Metric for LlmJudge<M> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn score(&self, case: &EvalCase, output: &str) -> Result<Score, EvalError> {
+        let prompt = match &case.expected {
+            Some(expected) => format!(
+                "Input:\n{}\n\nReference output:\n{expected}\n\nOutput to grade:\n{output}",
+                case.input
+            ),
+            None => format!("Input:\n{}\n\nOutput to grade:\n{output}", case.input),
+        };
+        let judgment = self.extractor.extract(&prompt).await?;
+
+        Ok(Score::new((judgment.grade as f64 - 1.0) / 4.0).reason(judgment.reasoning))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_exact_match() {
+        let case = EvalCase::new("1", "2 + 2?").expected("Four");
+
+        assert_eq!(
+            ExactMatch::new().score(&case, " Four\n").await.unwrap(),
+            Score::new(1.0)
+        );
+        assert_eq!(
+            ExactMatch::new().score(&case, "four").await.unwrap(),
+            Score::new(0.0)
+        );
+        assert_eq!(
+            ExactMatch::new()
+                .case_insensitive()
+                .score(&case, "four")
+                .await
+                .unwrap(),
+            Score::new(1.0)
+        );
+        assert!(matches!(
+            ExactMatch::new()
+                .score(&EvalCase::new("2", "hi"), "hello")
+                .await,
+            Err(EvalError::MissingExpected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_regex_match() {
+        let case = EvalCase::new("1", "Who are you?");
+        let metric = RegexMatch::new("no_disclaimer", r"(?i)as an ai")
+            .unwrap()
+            .must_not_match();
+
+        assert_eq!(metric.score(&case, "I'm Yuri").await.unwrap().value, 1.0);
+
+        let score = metric
+            .score(&case, "As an AI language model, I...")
+            .await
+            .unwrap();
+        assert_eq!(score.value, 0.0);
+        assert_eq!(score.reason.as_deref(), Some("Matched `As an AI`"));
+    }
+}
@@ -0,0 +1,254 @@
+//! This module provides an evaluation harness for agents and pipelines: a target is run over a
+//! [Dataset] of cases and its outputs are scored with pluggable [Metric]s. The resulting
+//! [Report] can be saved as JSON, rendered as Markdown and compared with the report of a
+//! previous run (e.g.: before and after changing a preamble or swapping models) with
+//! [Report::diff].
+//!
+//! The following metrics are provided:
+//! - [ExactMatch]: the output is equal to the expected output of the case
+//! - [RegexMatch]: the output matches (or does not match) a regex
+//! - [EmbeddingSimilarity]: cosine similarity between the output and the expected output
+//! - [LlmJudge]: a completion model grades the output according to a rubric
+//!
+//! Custom metrics can be defined by implementing the [Metric] trait.
+//!
+//! # Example
+//! ```rust
+//! use rig::{
+//!     eval::{Dataset, Evaluator, LlmJudge, RegexMatch, Report},
+//!     pipeline::{self, Op},
+//!     providers::openai,
+//! };
+//!
+//! let openai = openai::Client::from_env();
+//! let agent = openai.agent(openai::GPT_4O_MINI).preamble("You are a helpful assistant.").build();
+//!
+//! let dataset = Dataset::from_jsonl("evals/support.jsonl")?;
+//!
+//! let report = Evaluator::new("gpt-4o-mini", pipeline::new().prompt(agent))
+//!     .metric(RegexMatch::new("no_disclaimer", r"(?i)as an ai")?.must_not_match())
+//!     .metric(LlmJudge::new(
+//!         "helpfulness",
+//!         openai.completion_model(openai::GPT_4O),
+//!         "The answer is accurate, concise and polite.",
+//!     ))
+//!     .concurrency(4)
+//!     .run(&dataset)
+//!     .await;
+//!
+//! println!("{}", report.to_markdown());
+//!
+//! let baseline = Report::load("evals/baseline.json")?;
+//! println!("{}", report.diff(&baseline).to_markdown());
+//! report.save("evals/latest.json")?;
+//! ```
+use std::time::Instant;
+
+use futures::{stream, StreamExt};
+
+use crate::{embeddings::EmbeddingError, extractor::ExtractionError, pipeline::TryOp};
+
+pub mod dataset;
+pub mod metrics;
+pub mod report;
+
+pub use dataset::{Dataset, EvalCase};
+pub use metrics::{EmbeddingSimilarity, ExactMatch, LlmJudge, Metric, RegexMatch, Score};
+pub use report::{CaseDiff, CaseResult, MetricDiff, MetricSummary, Report, ReportDiff};
+
+use metrics::MetricDyn;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EvalError {
+    #[error("IoError: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JsonError: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Invalid case on line {line}: {source}")]
+    InvalidCase {
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    #[error("Metric {metric} requires an expected output, but case {case} has none")]
+    MissingExpected { metric: String, case: String },
+
+    #[error("RegexError: {0}")]
+    RegexError(#[from] regex::Error),
+
+    #[error("EmbeddingError: {0}")]
+    EmbeddingError(#[from] EmbeddingError),
+
+    #[error("ExtractionError: {0}")]
+    ExtractionError(#[from] ExtractionError),
+
+    #[error("MetricError: {0}")]
+    MetricError(String),
+}
+
+/// Runs a target over a [Dataset] and scores its outputs with a set of [Metric]s.
+///
+/// The target can be any [TryOp] (e.g.: a pipeline, or `pipeline::new().prompt(agent)`)
+/// outputting a string. Its input is built from each case, and is either the `input` string of
+/// the case or the whole [EvalCase] (e.g.: to use its tags or metadata).
+pub struct Evaluator<T> {
+    name: String,
+    target: T,
+    metrics: Vec<Box<dyn MetricDyn>>,
+    concurrency: usize,
+}
+
+impl<T> Evaluator<T>
+where
+    T: TryOp<Output = String>,
+    T::Input: for<'a> From<&'a EvalCase>,
+    T::Error: std::fmt::Display,
+{
+    /// Create an evaluator for `target`. The `name` of the run (e.g.: the model or the version
+    /// of the prompt) is used in reports.
+    pub fn new(name: &str, target: T) -> Self {
+        Self {
+            name: name.to_string(),
+            target,
+            metrics: vec![],
+            concurrency: 1,
+        }
+    }
+
+    /// Add a metric to the evaluation
+    pub fn metric(mut self, metric: impl Metric + 'static) -> Self {
+        self.metrics.push(Box::new(metric));
+        self
+    }
+
+    /// Set the number of cases evaluated concurrently (default: 1)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Run the evaluation. Errors of the target or of the metrics do not stop the evaluation
+    /// and are recorded in the report instead.
+    pub async fn run(&self, dataset: &Dataset) -> Report {
+        let results = stream::iter(dataset.iter())
+            .map(|case| self.evaluate(case))
+            .buffered(self.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        Report::new(&self.name, results)
+    }
+
+    async fn evaluate(&self, case: &EvalCase) -> CaseResult {
+        let start = Instant::now();
+        let output = self.target.try_call(T::Input::from(case)).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let mut result = CaseResult {
+            id: case.id.clone(),
+            input: case.input.clone(),
+            expected: case.expected.clone(),
+            output: None,
+            error: None,
+            scores: Default::default(),
+            metric_errors: Default::default(),
+            latency_ms,
+        };
+
+        let output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                tracing::warn!(target: "rig", "Evaluation of case {} failed: {}", case.id, err);
+                result.error = Some(err.to_string());
+                return result;
+            }
+        };
+
+        for metric in &self.metrics {
+            match metric.score_boxed(case, &output).await {
+                Ok(score) => {
+                    result.scores.insert(metric.metric_name(), score);
+                }
+                Err(err) => {
+                    result
+                        .metric_errors
+                        .insert(metric.metric_name(), err.to_string());
+                }
+            }
+        }
+        result.output = Some(output);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{map, then};
+
+    #[tokio::test]
+    async fn test_evaluator() {
+        let dataset = Dataset::new([
+            EvalCase::new("add", "2 + 2").expected("4"),
+            EvalCase::new("mul", "2 * 3").expected("6"),
+            EvalCase::new("div", "1 / 0").expected("inf"),
+        ]);
+
+        let calculator =
+            map(
+                |input: String| match input.split(' ').collect::<Vec<_>>().as_slice() {
+                    [a, "+", b] => {
+                        Ok((a.parse::<i32>().unwrap() + b.parse::<i32>().unwrap()).to_string())
+                    }
+                    // Buggy multiplication
+                    [a, "*", b] => {
+                        Ok((a.parse::<i32>().unwrap() + b.parse::<i32>().unwrap()).to_string())
+                    }
+                    _ => Err("unsupported operation"),
+                },
+            );
+
+        let report = Evaluator::new("calculator", calculator)
+            .metric(ExactMatch::new())
+            .metric(RegexMatch::new("digits", r"^\d+$").unwrap())
+            .concurrency(2)
+            .run(&dataset)
+            .await;
+
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.results[0].scores["exact_match"].value, 1.0);
+        assert_eq!(report.results[1].scores["exact_match"].value, 0.0);
+        assert_eq!(report.results[1].scores["digits"].value, 1.0);
+        assert_eq!(
+            report.results[2].error.as_deref(),
+            Some("unsupported operation")
+        );
+        assert_eq!(report.summary()["exact_match"].mean, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_evaluator_with_case_input() {
+        let dataset = Dataset::new([
+            EvalCase::new("1", "hello").tag("twitter"),
+            EvalCase::new("2", "hello").tag("discord"),
+        ]);
+
+        let target = then(|case: EvalCase| async move {
+            Ok::<_, String>(match case.has_tag("twitter") {
+                true => case.input.to_uppercase(),
+                false => case.input,
+            })
+        });
+
+        let report = Evaluator::new("channels", target)
+            .metric(RegexMatch::new("uppercase", r"^[A-Z]+$").unwrap())
+            .run(&dataset)
+            .await;
+
+        assert_eq!(report.results[0].scores["uppercase"].value, 1.0);
+        assert_eq!(report.results[1].scores["uppercase"].value, 0.0);
+    }
+}
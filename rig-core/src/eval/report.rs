@@ -0,0 +1,413 @@
+//! Evaluation reports and comparison of two runs.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{EvalError, Score};
+
+/// Result of the evaluation of a single case.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub input: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Output of the target, or `None` if the target failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Error returned by the target, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Scores by metric name
+    pub scores: BTreeMap<String, Score>,
+    /// Errors of the metrics which could not score the output, by metric name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metric_errors: BTreeMap<String, String>,
+    pub latency_ms: u64,
+}
+
+/// Aggregated scores of a metric over all the cases of a report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Number of scored cases
+    pub count: usize,
+}
+
+/// Report of an evaluation run. Reports can be saved as JSON to be compared with later runs
+/// (see [Report::diff]) and rendered as Markdown.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub name: String,
+    pub results: Vec<CaseResult>,
+}
+
+impl Report {
+    pub fn new(name: &str, results: Vec<CaseResult>) -> Self {
+        Self {
+            name: name.to_string(),
+            results,
+        }
+    }
+
+    /// Summary of the scores of each metric
+    pub fn summary(&self) -> BTreeMap<String, MetricSummary> {
+        let mut scores = BTreeMap::<&str, Vec<f64>>::new();
+        for result in &self.results {
+            for (metric, score) in &result.scores {
+                scores.entry(metric).or_default().push(score.value);
+            }
+        }
+
+        scores
+            .into_iter()
+            .map(|(metric, values)| {
+                let summary = MetricSummary {
+                    mean: values.iter().sum::<f64>() / values.len() as f64,
+                    min: values.iter().copied().fold(f64::INFINITY, f64::min),
+                    max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                    count: values.len(),
+                };
+                (metric.to_string(), summary)
+            })
+            .collect()
+    }
+
+    /// Number of cases for which the target failed
+    pub fn error_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CaseResult> {
+        self.results.iter().find(|result| result.id == id)
+    }
+
+    pub fn to_json(&self) -> Result<String, EvalError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Save the report as a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), EvalError> {
+        Ok(fs::write(path, self.to_json()?)?)
+    }
+
+    /// Load a report previously saved with [Report::save]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EvalError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Render the report as Markdown: a summary table of the metrics followed by a table of the
+    /// scores of each case.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Evaluation report: {}\n\n", self.name);
+
+        let summary = self.summary();
+        let _ = writeln!(
+            md,
+            "{} cases, {} errors\n",
+            self.results.len(),
+            self.error_count()
+        );
+        md.push_str("| Metric | Mean | Min | Max | Cases |\n|---|---|---|---|---|\n");
+        for (metric, s) in &summary {
+            let _ = writeln!(
+                md,
+                "| {metric} | {:.3} | {:.3} | {:.3} | {} |",
+                s.mean, s.min, s.max, s.count
+            );
+        }
+
+        md.push_str("\n## Cases\n\n| Case |");
+        for metric in summary.keys() {
+            let _ = write!(md, " {metric} |");
+        }
+        md.push_str(" Latency (ms) | Error |\n|---|");
+        md.push_str(&"---|".repeat(summary.len() + 2));
+        md.push('\n');
+
+        for result in &self.results {
+            let _ = write!(md, "| {} |", escape(&result.id));
+            for metric in summary.keys() {
+                match (result.scores.get(metric), result.metric_errors.get(metric)) {
+                    (Some(score), _) => {
+                        let _ = write!(md, " {:.3} |", score.value);
+                    }
+                    (None, Some(_)) => md.push_str(" error |"),
+                    (None, None) => md.push_str(" - |"),
+                }
+            }
+            let _ = writeln!(
+                md,
+                " {} | {} |",
+                result.latency_ms,
+                escape(result.error.as_deref().unwrap_or(""))
+            );
+        }
+
+        md
+    }
+
+    /// Compare this report with a `baseline` report (e.g.: the report of the previous run).
+    /// Cases are matched by id.
+    pub fn diff(&self, baseline: &Report) -> ReportDiff {
+        let (summary, baseline_summary) = (self.summary(), baseline.summary());
+
+        let metrics = summary
+            .keys()
+            .chain(baseline_summary.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|metric| {
+                let diff = MetricDiff {
+                    baseline: baseline_summary.get(metric).map(|s| s.mean),
+                    current: summary.get(metric).map(|s| s.mean),
+                };
+                (metric.clone(), diff)
+            })
+            .collect();
+
+        let mut cases = vec![];
+        for result in &self.results {
+            let Some(baseline_result) = baseline.get(&result.id) else {
+                continue;
+            };
+            for (metric, score) in &result.scores {
+                if let Some(baseline_score) = baseline_result.scores.get(metric) {
+                    if baseline_score.value != score.value {
+                        cases.push(CaseDiff {
+                            id: result.id.clone(),
+                            metric: metric.clone(),
+                            baseline: baseline_score.value,
+                            current: score.value,
+                        });
+                    }
+                }
+            }
+        }
+
+        ReportDiff {
+            baseline: baseline.name.clone(),
+            current: self.name.clone(),
+            metrics,
+            cases,
+            added: self
+                .results
+                .iter()
+                .filter(|result| baseline.get(&result.id).is_none())
+                .map(|result| result.id.clone())
+                .collect(),
+            removed: baseline
+                .results
+                .iter()
+                .filter(|result| self.get(&result.id).is_none())
+                .map(|result| result.id.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Change of the mean score of a metric between two runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricDiff {
+    pub baseline: Option<f64>,
+    pub current: Option<f64>,
+}
+
+impl MetricDiff {
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.current? - self.baseline?)
+    }
+}
+
+/// Change of the score of a case between two runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaseDiff {
+    pub id: String,
+    pub metric: String,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl CaseDiff {
+    pub fn delta(&self) -> f64 {
+        self.current - self.baseline
+    }
+}
+
+/// Comparison of two [Report]s, see [Report::diff].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub baseline: String,
+    pub current: String,
+    /// Mean score changes by metric
+    pub metrics: BTreeMap<String, MetricDiff>,
+    /// Cases whose score changed
+    pub cases: Vec<CaseDiff>,
+    /// Ids of the cases only present in the current report
+    pub added: Vec<String>,
+    /// Ids of the cases only present in the baseline report
+    pub removed: Vec<String>,
+}
+
+impl ReportDiff {
+    /// Cases whose score decreased by more than `threshold`
+    pub fn regressions(&self, threshold: f64) -> impl Iterator<Item = &CaseDiff> {
+        self.cases
+            .iter()
+            .filter(move |case| case.delta() < -threshold)
+    }
+
+    /// Cases whose score increased by more than `threshold`
+    pub fn improvements(&self, threshold: f64) -> impl Iterator<Item = &CaseDiff> {
+        self.cases
+            .iter()
+            .filter(move |case| case.delta() > threshold)
+    }
+
+    /// Render the comparison as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# {} vs {}\n\n", self.current, self.baseline);
+
+        md.push_str("| Metric | Baseline | Current | Delta |\n|---|---|---|---|\n");
+        for (metric, diff) in &self.metrics {
+            let _ = writeln!(
+                md,
+                "| {metric} | {} | {} | {} |",
+                format_score(diff.baseline),
+                format_score(diff.current),
+                diff.delta()
+                    .map(|delta| format!("{delta:+.3}"))
+                    .unwrap_or_else(|| "-".to_string())
+            );
+        }
+
+        if !self.cases.is_empty() {
+            md.push_str(
+                "\n## Changed cases\n\n| Case | Metric | Baseline | Current | Delta |\n|---|---|---|---|---|\n",
+            );
+            let mut cases = self.cases.iter().collect::<Vec<_>>();
+            cases.sort_by(|a, b| a.delta().total_cmp(&b.delta()));
+            for case in cases {
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {:.3} | {:.3} | {:+.3} |",
+                    escape(&case.id),
+                    case.metric,
+                    case.baseline,
+                    case.current,
+                    case.delta()
+                );
+            }
+        }
+
+        if !self.added.is_empty() {
+            let _ = writeln!(md, "\nAdded cases: {}", self.added.join(", "));
+        }
+        if !self.removed.is_empty() {
+            let _ = writeln!(md, "\nRemoved cases: {}", self.removed.join(", "));
+        }
+
+        md
+    }
+}
+
+fn format_score(score: Option<f64>) -> String {
+    score
+        .map(|score| format!("{score:.3}"))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Escape a value so that it fits in a Markdown table cell
+fn escape(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, scores: &[(&str, f64)]) -> CaseResult {
+        CaseResult {
+            id: id.to_string(),
+            input: format!("input {id}"),
+            expected: None,
+            output: Some(format!("output {id}")),
+            error: None,
+            scores: scores
+                .iter()
+                .map(|(metric, value)| (metric.to_string(), Score::new(*value)))
+                .collect(),
+            metric_errors: BTreeMap::new(),
+            latency_ms: 10,
+        }
+    }
+
+    #[test]
+    fn test_summary_and_diff() {
+        let baseline = Report::new(
+            "gpt-4o",
+            vec![
+                result("a", &[("judge", 1.0), ("regex", 1.0)]),
+                result("b", &[("judge", 0.5), ("regex", 1.0)]),
+                result("c", &[("judge", 0.5)]),
+            ],
+        );
+        let current = Report::new(
+            "gpt-4o-mini",
+            vec![
+                result("a", &[("judge", 0.25), ("regex", 1.0)]),
+                result("b", &[("judge", 0.75), ("regex", 1.0)]),
+                result("d", &[("judge", 1.0)]),
+            ],
+        );
+
+        let summary = current.summary();
+        assert_eq!(summary["judge"].count, 3);
+        assert_eq!(summary["judge"].min, 0.25);
+        assert_eq!(summary["regex"].mean, 1.0);
+
+        let diff = current.diff(&baseline);
+        assert_eq!(diff.metrics["judge"].delta(), Some(0.0));
+        assert_eq!(diff.cases.len(), 2);
+        assert_eq!(
+            diff.regressions(0.1)
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a"]
+        );
+        assert_eq!(
+            diff.improvements(0.1)
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b"]
+        );
+        assert_eq!(diff.added, vec!["d"]);
+        assert_eq!(diff.removed, vec!["c"]);
+
+        let md = diff.to_markdown();
+        assert!(md.contains("| a | judge | 1.000 | 0.250 | -0.750 |"));
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let mut failed = result("b|c", &[]);
+        failed.output = None;
+        failed.error = Some("timeout".to_string());
+        let report = Report::new("run", vec![result("a", &[("judge", 0.5)]), failed]);
+
+        let md = report.to_markdown();
+        assert!(md.contains("2 cases, 1 errors"));
+        assert!(md.contains("| judge | 0.500 | 0.500 | 0.500 | 1 |"));
+        assert!(md.contains("| b\\|c | - | 10 | timeout |"));
+    }
+}
@@ -27,6 +27,27 @@
 //!     .await
 //!     .expect("Failed to extract data from text");
 //! ```
+//!
+//! # Self-correction
+//! By default, the extractor fails if the data returned by the model cannot be deserialized.
+//! With [ExtractorBuilder::retries], the deserialization error (or the error returned by the
+//! [validator](ExtractorBuilder::validator)) is sent back to the model, which gets another
+//! chance to fix its answer:
+//! ```
+//! let extractor = openai.extractor::<Token>(openai::GPT_4O)
+//!     .retries(2)
+//!     .validator(|token: &Token| match bs58::decode(&token.mint).into_vec() {
+//!         Ok(bytes) if bytes.len() == 32 => Ok(()),
+//!         _ => Err(format!("`{}` is not a valid base58 mint address", token.mint)),
+//!     })
+//!     .build();
+//! ```
+//!
+//! # Structured outputs
+//! If the model supports native structured outputs (see
+//! [CompletionModel::structured_output_params]), the extractor constrains the model to respond
+//! with JSON matching the schema of the target structure instead of relying on tool calling.
+//! This can be disabled with [ExtractorBuilder::native_structured_output].
 
 use std::marker::PhantomData;
 
@@ -36,7 +57,10 @@ use serde_json::json;
 
 use crate::{
     agent::{Agent, AgentBuilder},
-    completion::{CompletionModel, Prompt, PromptError, ToolDefinition},
+    completion::{
+        Completion, CompletionError, CompletionModel, Message, ModelChoice, PromptError,
+        ToolDefinition,
+    },
     tool::Tool,
 };
 
@@ -45,16 +69,46 @@ pub enum ExtractionError {
     #[error("No data extracted")]
     NoData,
 
+    /// The data was extracted, but none of it was extracted with enough confidence (e.g.: by a
+    /// [Classifier](crate::classifier::Classifier) with a minimum confidence). Contains the
+    /// highest confidence.
+    #[error("Confidence too low: {0}")]
+    LowConfidence(f64),
+
     #[error("Failed to deserialize the extracted data: {0}")]
     DeserializationError(#[from] serde_json::Error),
 
+    #[error("Invalid extracted data: {0}")]
+    ValidationError(String),
+
+    #[error("CompletionError: {0}")]
+    CompletionError(#[from] CompletionError),
+
     #[error("PromptError: {0}")]
     PromptError(#[from] PromptError),
 }
 
+impl ExtractionError {
+    /// Whether the model can be asked to correct its answer after this error.
+    fn is_correctable(&self) -> bool {
+        matches!(
+            self,
+            ExtractionError::NoData
+                | ExtractionError::DeserializationError(_)
+                | ExtractionError::ValidationError(_)
+        )
+    }
+}
+
+type Validator<T> = Box<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
 /// Extractor for structured data from text
 pub struct Extractor<M: CompletionModel, T: JsonSchema + for<'a> Deserialize<'a> + Send + Sync> {
     agent: Agent<M>,
+    /// Provider-specific parameters enabling native structured outputs, if used
+    structured_output: Option<serde_json::Value>,
+    retries: usize,
+    validator: Option<Validator<T>>,
     _t: PhantomData<T>,
 }
 
@@ -63,13 +117,78 @@ where
     M: Sync,
 {
     pub async fn extract(&self, text: &str) -> Result<T, ExtractionError> {
-        let summary = self.agent.prompt(text).await?;
+        let mut prompt = text.to_string();
+        let mut chat_history = vec![];
+        let mut attempt = 0;
+
+        loop {
+            let (output, result) = self.attempt(&prompt, chat_history.clone()).await?;
+
+            match result {
+                Err(err) if err.is_correctable() && attempt < self.retries => {
+                    attempt += 1;
+                    tracing::debug!(target: "rig", "Extraction failed, retrying ({}/{}): {}", attempt, self.retries, err);
+                    chat_history.push(Message {
+                        role: "user".into(),
+                        content: prompt,
+                    });
+                    chat_history.push(Message {
+                        role: "assistant".into(),
+                        content: output,
+                    });
+                    prompt = format!(
+                        "Your answer could not be accepted: {err}\n\
+                        Fix the error and submit the extracted data again."
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Run a single completion, returning the raw output of the model along with the
+    /// extracted data (or the reason why it could not be extracted).
+    async fn attempt(
+        &self,
+        prompt: &str,
+        chat_history: Vec<Message>,
+    ) -> Result<(String, Result<T, ExtractionError>), ExtractionError> {
+        let mut request = self.agent.completion(prompt, chat_history).await?;
+        if let Some(params) = &self.structured_output {
+            request = request.additional_params(params.clone());
+        }
+
+        let output = match request.send().await?.choice {
+            ModelChoice::ToolCall(_, args) => args.to_string(),
+            ModelChoice::Message(msg) => strip_code_fence(&msg).to_string(),
+        };
 
-        if summary.is_empty() {
-            return Err(ExtractionError::NoData);
+        if output.is_empty() {
+            return Ok((output, Err(ExtractionError::NoData)));
         }
 
-        Ok(serde_json::from_str(&summary)?)
+        let result = serde_json::from_str::<T>(&output)
+            .map_err(ExtractionError::from)
+            .and_then(|data| match &self.validator {
+                Some(validator) => validator(&data)
+                    .map(|_| data)
+                    .map_err(ExtractionError::ValidationError),
+                None => Ok(data),
+            });
+
+        Ok((output, result))
+    }
+}
+
+/// Remove the markdown code fence models sometimes wrap JSON answers in.
+fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    match text
+        .strip_prefix("```")
+        .and_then(|text| text.strip_suffix("```"))
+    {
+        Some(inner) => inner.trim_start_matches("json").trim(),
+        None => text,
     }
 }
 
@@ -78,7 +197,12 @@ pub struct ExtractorBuilder<
     T: JsonSchema + for<'a> Deserialize<'a> + Send + Sync + 'static,
     M: CompletionModel,
 > {
+    model: M,
     agent_builder: AgentBuilder<M>,
+    instructions: Vec<String>,
+    native_structured_output: bool,
+    retries: usize,
+    validator: Option<Validator<T>>,
     _t: PhantomData<T>,
 }
 
@@ -87,23 +211,19 @@ impl<T: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync, M: Compl
 {
     pub fn new(model: M) -> Self {
         Self {
-            agent_builder: AgentBuilder::new(model)
-                .preamble("\
-                    You are an AI assistant whose purpose is to extract structured data from the provided text.\n\
-                    You will have access to a `submit` function that defines the structure of the data to extract from the provided text.\n\
-                    Use the `submit` function to submit the structured data.\n\
-                    Be sure to fill out every field and ALWAYS CALL THE `submit` function, event with default values!!!.
-                ")
-                .tool(SubmitTool::<T> {_t: PhantomData}),
+            model: model.clone(),
+            agent_builder: AgentBuilder::new(model),
+            instructions: vec![],
+            native_structured_output: true,
+            retries: 0,
+            validator: None,
             _t: PhantomData,
         }
     }
 
     /// Add additional preamble to the extractor
     pub fn preamble(mut self, preamble: &str) -> Self {
-        self.agent_builder = self.agent_builder.append_preamble(&format!(
-            "\n=============== ADDITIONAL INSTRUCTIONS ===============\n{preamble}"
-        ));
+        self.instructions.push(preamble.to_string());
         self
     }
 
@@ -113,10 +233,77 @@ impl<T: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync, M: Compl
         self
     }
 
+    /// Set the number of times the model is asked to correct its answer when the extracted data
+    /// cannot be deserialized or is rejected by the validator (default: 0).
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Add a validator for semantic checks on the extracted data (e.g.: checking that an address
+    /// is valid). The error returned by the validator is sent back to the model if
+    /// [retries](Self::retries) are left, and returned as [ExtractionError::ValidationError]
+    /// otherwise.
+    pub fn validator<F, E>(mut self, validator: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), E> + Send + Sync + 'static,
+        E: std::fmt::Display,
+    {
+        self.validator = Some(Box::new(move |data| {
+            validator(data).map_err(|err| err.to_string())
+        }));
+        self
+    }
+
+    /// Whether to use the native structured outputs of the model when it supports them
+    /// (default: true). If disabled, or if the model does not support them, the extractor
+    /// relies on tool calling.
+    pub fn native_structured_output(mut self, enabled: bool) -> Self {
+        self.native_structured_output = enabled;
+        self
+    }
+
     /// Build the Extractor
     pub fn build(self) -> Extractor<M, T> {
+        let schema = json!(schema_for!(T));
+        let structured_output = self
+            .native_structured_output
+            .then(|| {
+                self.model
+                    .structured_output_params("extracted_data", &schema)
+            })
+            .flatten();
+
+        let agent_builder = match structured_output {
+            Some(_) => self.agent_builder.preamble("\
+                You are an AI assistant whose purpose is to extract structured data from the provided text.\n\
+                Respond with the extracted data as a JSON object and nothing else.\n\
+                Be sure to fill out every field, even with default values!!!.
+            "),
+            None => self.agent_builder
+                .preamble("\
+                    You are an AI assistant whose purpose is to extract structured data from the provided text.\n\
+                    You will have access to a `submit` function that defines the structure of the data to extract from the provided text.\n\
+                    Use the `submit` function to submit the structured data.\n\
+                    Be sure to fill out every field and ALWAYS CALL THE `submit` function, event with default values!!!.
+                ")
+                .tool(SubmitTool::<T> {_t: PhantomData}),
+        };
+
+        let agent_builder =
+            self.instructions
+                .iter()
+                .fold(agent_builder, |builder, instructions| {
+                    builder.append_preamble(&format!(
+                        "\n=============== ADDITIONAL INSTRUCTIONS ===============\n{instructions}"
+                    ))
+                });
+
         Extractor {
-            agent: self.agent_builder.build(),
+            agent: agent_builder.build(),
+            structured_output,
+            retries: self.retries,
+            validator: self.validator,
             _t: PhantomData,
         }
     }
@@ -150,3 +337,100 @@ impl<T: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync> Tool for
         Ok(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::completion::{self, CompletionRequest, CompletionResponse};
+
+    /// Completion model replaying scripted tool calls and recording the requests it receives
+    #[derive(Clone, Default)]
+    struct MockModel {
+        responses: Arc<Mutex<Vec<serde_json::Value>>>,
+        requests: Arc<Mutex<Vec<CompletionRequest>>>,
+    }
+
+    impl completion::CompletionModel for MockModel {
+        type Response = ();
+
+        async fn completion(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<CompletionResponse<()>, CompletionError> {
+            self.requests.lock().unwrap().push(request);
+            let args = self.responses.lock().unwrap().remove(0);
+            Ok(CompletionResponse {
+                choice: ModelChoice::ToolCall("submit".into(), args),
+                usage: None,
+                citations: vec![],
+                raw_response: (),
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize, JsonSchema)]
+    struct Person {
+        name: String,
+        age: u8,
+    }
+
+    #[tokio::test]
+    async fn test_extract_with_retries() {
+        let model = MockModel::default();
+        *model.responses.lock().unwrap() = vec![
+            json!({"name": "John Doe"}),
+            json!({"name": "John Doe", "age": 300}),
+            json!({"name": "", "age": 30}),
+            json!({"name": "John Doe", "age": 30}),
+        ];
+
+        let extractor = ExtractorBuilder::<Person, _>::new(model.clone())
+            .retries(3)
+            .validator(|person: &Person| match person.name.is_empty() {
+                true => Err("name must not be empty"),
+                false => Ok(()),
+            })
+            .build();
+
+        let person = extractor.extract("John Doe is 30.").await.unwrap();
+        assert_eq!(
+            person,
+            Person {
+                name: "John Doe".into(),
+                age: 30
+            }
+        );
+
+        let requests = model.requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].prompt.contains("missing field `age`"));
+        assert!(requests[3].prompt.contains("name must not be empty"));
+        assert_eq!(requests[3].chat_history.len(), 6);
+        assert_eq!(requests[3].chat_history[0].content, "John Doe is 30.");
+    }
+
+    #[tokio::test]
+    async fn test_extract_retries_exhausted() {
+        let model = MockModel::default();
+        *model.responses.lock().unwrap() = vec![json!({"name": "John Doe"}); 2];
+
+        let extractor = ExtractorBuilder::<Person, _>::new(model.clone())
+            .retries(1)
+            .build();
+
+        let result = extractor.extract("John Doe is 30.").await;
+        assert!(matches!(
+            result,
+            Err(ExtractionError::DeserializationError(_))
+        ));
+        assert_eq!(model.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(strip_code_fence("```json\n{\"a\": 1}\n```"), "{\"a\": 1}");
+        assert_eq!(strip_code_fence(" {\"a\": 1} "), "{\"a\": 1}");
+    }
+}
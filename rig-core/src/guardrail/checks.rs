@@ -0,0 +1,484 @@
+//! Rule-based guardrails: blocklists, PII detection and format validation.
+use std::collections::BTreeSet;
+
+use regex::Regex;
+
+use super::{Action, Guardrail, GuardrailError, Verdict};
+
+// ================================================================
+// Blocklist
+// ================================================================
+
+/// Flags outputs containing blocked words or matching blocked regexes.
+///
+/// Words are matched case-insensitively on word boundaries (e.g.: `scam` matches "Scam!" but not
+/// "scampi"). Redacted matches are replaced with `[REDACTED]` (see [Blocklist::replacement]).
+#[derive(Clone, Debug)]
+pub struct Blocklist {
+    patterns: Vec<Regex>,
+    action: Action,
+    replacement: String,
+}
+
+impl Blocklist {
+    pub fn new(action: Action) -> Self {
+        Self {
+            patterns: vec![],
+            action,
+            replacement: "[REDACTED]".to_string(),
+        }
+    }
+
+    /// Block a word or phrase
+    pub fn word(mut self, word: &str) -> Self {
+        let boundary = |c: Option<char>| match c {
+            Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+            _ => "",
+        };
+        let pattern = format!(
+            "(?i){}{}{}",
+            boundary(word.chars().next()),
+            regex::escape(word),
+            boundary(word.chars().last())
+        );
+        self.patterns
+            .push(Regex::new(&pattern).expect("Escaped word should be a valid regex"));
+        self
+    }
+
+    /// Block several words or phrases
+    pub fn words<'a>(self, words: impl IntoIterator<Item = &'a str>) -> Self {
+        words
+            .into_iter()
+            .fold(self, |blocklist, word| blocklist.word(word))
+    }
+
+    /// Block outputs matching a regex
+    pub fn pattern(mut self, pattern: &str) -> Result<Self, GuardrailError> {
+        self.patterns.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Set the text replacing redacted matches (default: `[REDACTED]`)
+    pub fn replacement(mut self, replacement: &str) -> Self {
+        self.replacement = replacement.to_string();
+        self
+    }
+}
+
+impl Guardrail for Blocklist {
+    fn name(&self) -> String {
+        "blocklist".to_string()
+    }
+
+    async fn check(&self, output: &str) -> Result<Verdict, GuardrailError> {
+        let Some(found) = self.patterns.iter().find_map(|regex| regex.find(output)) else {
+            return Ok(Verdict::Pass);
+        };
+
+        Ok(match self.action {
+            Action::Redact => Verdict::Redact(self.patterns.iter().fold(
+                output.to_string(),
+                |output, regex| {
+                    regex
+                        .replace_all(&output, regex::NoExpand(&self.replacement))
+                        .into_owned()
+                },
+            )),
+            action => Verdict::reject(
+                action,
+                format!("Output contains blocked term `{}`", found.as_str()),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// PII
+// ================================================================
+
+/// Kind of personal data detected by [Pii].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PiiKind {
+    /// Credit card numbers (validated with the Luhn checksum)
+    CreditCard,
+    /// US social security numbers
+    Ssn,
+    Email,
+    Phone,
+    /// IPv4 addresses
+    IpAddress,
+}
+
+impl PiiKind {
+    /// Every kind, in the order they are detected (longer numbers first)
+    pub const ALL: [PiiKind; 5] = [
+        PiiKind::CreditCard,
+        PiiKind::Ssn,
+        PiiKind::Email,
+        PiiKind::Phone,
+        PiiKind::IpAddress,
+    ];
+
+    /// Text replacing redacted data of this kind
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            PiiKind::CreditCard => "[CREDIT_CARD]",
+            PiiKind::Ssn => "[SSN]",
+            PiiKind::Email => "[EMAIL]",
+            PiiKind::Phone => "[PHONE]",
+            PiiKind::IpAddress => "[IP_ADDRESS]",
+        }
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            PiiKind::CreditCard => r"\b(?:\d[ -]?){12,18}\d\b",
+            PiiKind::Ssn => r"\b\d{3}-\d{2}-\d{4}\b",
+            PiiKind::Email => r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b",
+            PiiKind::Phone => {
+                r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-]?)\d{3}[\s.-]?\d{4}\b"
+            }
+            PiiKind::IpAddress => {
+                r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b"
+            }
+        }
+    }
+
+    fn is_valid(&self, candidate: &str) -> bool {
+        match self {
+            PiiKind::CreditCard => luhn(candidate),
+            _ => true,
+        }
+    }
+}
+
+/// Luhn checksum of the digits of `number`
+fn luhn(number: &str) -> bool {
+    let digits = number
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect::<Vec<_>>();
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match i % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+
+    digits.len() >= 13 && sum.is_multiple_of(10)
+}
+
+/// Flags outputs containing personal data. Redacted data is replaced by the placeholder of its
+/// kind (e.g.: `[EMAIL]`).
+///
+/// Detection is pattern-based and favors recall, so e.g. some 10-digit numbers are flagged as
+/// phone numbers.
+#[derive(Clone, Debug)]
+pub struct Pii {
+    kinds: Vec<(PiiKind, Regex)>,
+    action: Action,
+}
+
+impl Pii {
+    /// Detect every kind of personal data
+    pub fn new(action: Action) -> Self {
+        Self::only(action, PiiKind::ALL)
+    }
+
+    /// Detect the given kinds of personal data only
+    pub fn only(action: Action, kinds: impl IntoIterator<Item = PiiKind>) -> Self {
+        let kinds = kinds.into_iter().collect::<BTreeSet<_>>();
+        Self {
+            kinds: kinds
+                .into_iter()
+                .map(|kind| {
+                    (
+                        kind,
+                        Regex::new(kind.pattern()).expect("PII pattern should be a valid regex"),
+                    )
+                })
+                .collect(),
+            action,
+        }
+    }
+
+    /// Kinds of personal data found in `text`
+    pub fn detect(&self, text: &str) -> Vec<PiiKind> {
+        self.kinds
+            .iter()
+            .filter(|(kind, regex)| regex.find_iter(text).any(|m| kind.is_valid(m.as_str())))
+            .map(|(kind, _)| *kind)
+            .collect()
+    }
+
+    /// Replace the personal data found in `text` by placeholders
+    pub fn redact(&self, text: &str) -> String {
+        self.kinds
+            .iter()
+            .fold(text.to_string(), |text, (kind, regex)| {
+                regex
+                    .replace_all(&text, |caps: &regex::Captures| {
+                        match kind.is_valid(&caps[0]) {
+                            true => kind.placeholder().to_string(),
+                            false => caps[0].to_string(),
+                        }
+                    })
+                    .into_owned()
+            })
+    }
+}
+
+impl Guardrail for Pii {
+    fn name(&self) -> String {
+        "pii".to_string()
+    }
+
+    async fn check(&self, output: &str) -> Result<Verdict, GuardrailError> {
+        let found = self.detect(output);
+        if found.is_empty() {
+            return Ok(Verdict::Pass);
+        }
+
+        Ok(match self.action {
+            Action::Redact => Verdict::Redact(self.redact(output)),
+            action => Verdict::reject(
+                action,
+                format!(
+                    "Output contains personal data ({})",
+                    found
+                        .iter()
+                        .map(|kind| format!("{kind:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// Format
+// ================================================================
+
+/// Validates the length and format of outputs: minimum and maximum number of characters,
+/// required regexes and valid JSON.
+///
+/// Violations regenerate the output by default (see [Format::on_violation]). Outputs that are
+/// only too long can be truncated instead (see [Format::truncate]).
+#[derive(Clone, Debug)]
+pub struct Format {
+    min_chars: Option<usize>,
+    max_chars: Option<usize>,
+    patterns: Vec<Regex>,
+    json: bool,
+    truncate: bool,
+    action: Action,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self {
+            min_chars: None,
+            max_chars: None,
+            patterns: vec![],
+            json: false,
+            truncate: false,
+            action: Action::Regenerate,
+        }
+    }
+}
+
+impl Format {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Minimum number of characters of the output
+    pub fn min_chars(mut self, min_chars: usize) -> Self {
+        self.min_chars = Some(min_chars);
+        self
+    }
+
+    /// Maximum number of characters of the output
+    pub fn max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    /// Require the output to match a regex
+    pub fn matches(mut self, pattern: &str) -> Result<Self, GuardrailError> {
+        self.patterns.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Require the output to be valid JSON
+    pub fn json(mut self) -> Self {
+        self.json = true;
+        self
+    }
+
+    /// Truncate outputs longer than `max_chars` at a word boundary (ending them with `…`)
+    /// instead of treating them as violations
+    pub fn truncate(mut self) -> Self {
+        self.truncate = true;
+        self
+    }
+
+    /// Set the action on violations (default: [Action::Regenerate]). There is nothing to redact
+    /// in a badly formatted output, so [Action::Redact] is treated as [Action::Regenerate].
+    pub fn on_violation(mut self, action: Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    fn violation(&self, output: &str) -> Option<String> {
+        let chars = output.chars().count();
+
+        match (self.min_chars, self.max_chars) {
+            (Some(min_chars), _) if chars < min_chars => {
+                return Some(format!(
+                    "Output is {chars} characters long, the minimum is {min_chars}"
+                ))
+            }
+            (_, Some(max_chars)) if chars > max_chars && !self.truncate => {
+                return Some(format!(
+                    "Output is {chars} characters long, the maximum is {max_chars}"
+                ))
+            }
+            _ => {}
+        }
+
+        if let Some(regex) = self.patterns.iter().find(|regex| !regex.is_match(output)) {
+            return Some(format!("Output does not match `{}`", regex.as_str()));
+        }
+
+        if self.json {
+            if let Err(err) = serde_json::from_str::<serde_json::Value>(output) {
+                return Some(format!("Output is not valid JSON: {err}"));
+            }
+        }
+
+        None
+    }
+}
+
+/// Truncate `text` to at most `max_chars` characters (including the trailing `…`), at the last
+/// word boundary if there is one.
+fn truncate(text: &str, max_chars: usize) -> String {
+    let end = text
+        .char_indices()
+        .nth(max_chars.saturating_sub(1))
+        .map_or(text.len(), |(i, _)| i);
+    let end = match text[..end].rfind(char::is_whitespace) {
+        Some(space) if space > 0 => space,
+        _ => end,
+    };
+
+    format!("{}…", text[..end].trim_end())
+}
+
+impl Guardrail for Format {
+    fn name(&self) -> String {
+        "format".to_string()
+    }
+
+    async fn check(&self, output: &str) -> Result<Verdict, GuardrailError> {
+        if let Some(reason) = self.violation(output) {
+            return Ok(Verdict::reject(self.action, reason));
+        }
+
+        Ok(match self.max_chars {
+            Some(max_chars) if output.chars().count() > max_chars => {
+                Verdict::Redact(truncate(output, max_chars))
+            }
+            _ => Verdict::Pass,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocklist() {
+        let blocklist = Blocklist::new(Action::Redact).words(["scam", "$RUG"]);
+
+        assert_eq!(
+            blocklist.check("I love scampi").await.unwrap(),
+            Verdict::Pass
+        );
+        assert_eq!(
+            blocklist.check("Not a SCAM, buy $rug now").await.unwrap(),
+            Verdict::Redact("Not a [REDACTED], buy [REDACTED] now".to_string())
+        );
+
+        let blocklist = Blocklist::new(Action::Block)
+            .pattern(r"(?i)\bseed phrase\b")
+            .unwrap();
+        assert_eq!(
+            blocklist.check("Send me your Seed Phrase").await.unwrap(),
+            Verdict::Block("Output contains blocked term `Seed Phrase`".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pii() {
+        let pii = Pii::new(Action::Redact);
+
+        assert_eq!(
+            pii.redact(
+                "Mail jane.doe@example.com or call +1 (555) 123-4567, card 4111 1111 1111 1111, \
+                SSN 123-45-6789, server 192.168.0.1"
+            ),
+            "Mail [EMAIL] or call [PHONE], card [CREDIT_CARD], SSN [SSN], server [IP_ADDRESS]"
+        );
+
+        // Not a valid credit card number (Luhn checksum)
+        assert_eq!(pii.detect("Order 1234 5678 9012 3456"), vec![]);
+        assert_eq!(pii.check("Nothing to see").await.unwrap(), Verdict::Pass);
+
+        let pii = Pii::only(Action::Block, [PiiKind::Email]);
+        assert_eq!(pii.detect("call 555-123-4567"), vec![]);
+        assert_eq!(
+            pii.check("hi@rig.rs").await.unwrap(),
+            Verdict::Block("Output contains personal data (Email)".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_format() {
+        let format = Format::new().min_chars(3).max_chars(20);
+        assert_eq!(
+            format.check("Short and sweet").await.unwrap(),
+            Verdict::Pass
+        );
+        assert_eq!(
+            format.check("Hi").await.unwrap(),
+            Verdict::Regenerate("Output is 2 characters long, the minimum is 3".to_string())
+        );
+        assert_eq!(
+            format.check("This sentence is way too long").await.unwrap(),
+            Verdict::Regenerate("Output is 29 characters long, the maximum is 20".to_string())
+        );
+
+        let format = Format::new().max_chars(20).truncate();
+        assert_eq!(
+            format.check("This sentence is way too long").await.unwrap(),
+            Verdict::Redact("This sentence is…".to_string())
+        );
+
+        let format = Format::new().json().on_violation(Action::Block);
+        assert_eq!(format.check(r#"{"a": 1}"#).await.unwrap(), Verdict::Pass);
+        assert!(matches!(
+            format.check("{a: 1}").await.unwrap(),
+            Verdict::Block(reason) if reason.starts_with("Output is not valid JSON")
+        ));
+    }
+}
@@ -0,0 +1,362 @@
+//! This module provides output guardrails: checks run on the output of a model after it is
+//! generated and before it is delivered (e.g.: posted, sent to a user). Each [Guardrail] gives a
+//! [Verdict] on the output, which either passes it, redacts parts of it, asks for a new output
+//! or blocks it.
+//!
+//! The following guardrails are provided:
+//! - [Blocklist]: blocked words and regexes
+//! - [Pii]: personal data (emails, phone numbers, credit cards, etc.)
+//! - [Format]: length limits, required patterns and JSON outputs
+//! - [LlmModeration]: a completion model classifies the output according to a content policy
+//!
+//! Providers with a moderation endpoint also implement [Guardrail] (e.g.: OpenAI's
+//! `ModerationModel`), and custom guardrails can be defined by implementing the trait.
+//!
+//! [Guardrails] runs a list of guardrails in order, applying redactions as it goes, and can
+//! regenerate the output with the reason of the rejection as feedback.
+//!
+//! # Example
+//! ```rust
+//! use rig::{
+//!     guardrail::{Action, Blocklist, Format, Guardrails, LlmModeration, Pii},
+//!     providers::openai,
+//! };
+//!
+//! let openai = openai::Client::from_env();
+//! let agent = openai.agent(openai::GPT_4O).preamble("You are a witty poster.").build();
+//!
+//! let guardrails = Guardrails::new()
+//!     .guardrail(Blocklist::new(Action::Regenerate).words(["guaranteed returns", "airdrop"]))
+//!     .guardrail(Pii::new(Action::Redact))
+//!     .guardrail(Format::new().max_chars(280).truncate())
+//!     .guardrail(openai.moderation_model(openai::OMNI_MODERATION_LATEST))
+//!     .guardrail(LlmModeration::new(openai.completion_model(openai::GPT_4O)))
+//!     .max_regenerations(2);
+//!
+//! // Errors if an output is blocked or still rejected after 2 regenerations
+//! let post = guardrails.prompt(&agent, "Share a thought about the weekend").await?;
+//! ```
+use std::future::Future;
+
+use futures::future::BoxFuture;
+
+use crate::{
+    completion::{Prompt, PromptError},
+    extractor::ExtractionError,
+};
+
+pub mod checks;
+pub mod moderation;
+
+pub use checks::{Blocklist, Format, Pii, PiiKind};
+pub use moderation::{LlmModeration, ModerationResult};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GuardrailError {
+    /// Http error (e.g.: error calling a moderation endpoint)
+    #[error("HttpError: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("RegexError: {0}")]
+    RegexError(#[from] regex::Error),
+
+    #[error("ExtractionError: {0}")]
+    ExtractionError(#[from] ExtractionError),
+
+    /// Error generating the output
+    #[error("GenerationError: {0}")]
+    GenerationError(#[from] PromptError),
+
+    /// The output was blocked by a guardrail
+    #[error("Output blocked by {guardrail}: {reason}")]
+    Blocked { guardrail: String, reason: String },
+
+    /// Every regenerated output was rejected by a guardrail
+    #[error("Output still rejected by {guardrail} after {regenerations} regenerations: {reason}")]
+    RegenerationLimit {
+        guardrail: String,
+        reason: String,
+        regenerations: usize,
+    },
+
+    /// Error returned by a guardrail (e.g.: provider error of a moderation endpoint)
+    #[error("CheckError: {0}")]
+    CheckError(String),
+}
+
+/// What a guardrail does with an output violating it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Remove the offending parts of the output. Guardrails with nothing to redact (e.g.:
+    /// moderation) treat it as [Action::Regenerate].
+    Redact,
+    /// Ask for a new output
+    Regenerate,
+    /// Reject the output
+    Block,
+}
+
+/// Verdict of a guardrail on an output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    /// The output is acceptable
+    Pass,
+    /// The output is acceptable once replaced by the given redacted output
+    Redact(String),
+    /// A new output should be generated, for the given reason
+    Regenerate(String),
+    /// The output must not be delivered, for the given reason
+    Block(String),
+}
+
+impl Verdict {
+    /// Verdict of a violation described by `reason` for guardrails with nothing to redact
+    pub fn reject(action: Action, reason: impl Into<String>) -> Self {
+        match action {
+            Action::Redact | Action::Regenerate => Verdict::Regenerate(reason.into()),
+            Action::Block => Verdict::Block(reason.into()),
+        }
+    }
+}
+
+/// Trait for guardrails checking the output of a model.
+pub trait Guardrail: Send + Sync {
+    /// Name of the guardrail, used in errors and logs
+    fn name(&self) -> String;
+
+    fn check(&self, output: &str) -> impl Future<Output = Result<Verdict, GuardrailError>> + Send;
+}
+
+/// Object-safe version of [Guardrail], so that [Guardrails] can use several guardrail types.
+pub(crate) trait GuardrailDyn: Send + Sync {
+    fn guardrail_name(&self) -> String;
+
+    fn check_boxed<'a>(&'a self, output: &'a str)
+        -> BoxFuture<'a, Result<Verdict, GuardrailError>>;
+}
+
+impl<T: Guardrail> GuardrailDyn for T {
+    fn guardrail_name(&self) -> String {
+        self.name()
+    }
+
+    fn check_boxed<'a>(
+        &'a self,
+        output: &'a str,
+    ) -> BoxFuture<'a, Result<Verdict, GuardrailError>> {
+        Box::pin(self.check(output))
+    }
+}
+
+/// Outcome of running [Guardrails] on an output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// The output passed every guardrail, with the redactions applied
+    Pass(String),
+    /// The guardrail asked for a new output
+    Regenerate { guardrail: String, reason: String },
+    /// The guardrail blocked the output
+    Block { guardrail: String, reason: String },
+}
+
+/// Pipeline of guardrails, run in order on each output.
+///
+/// The output is replaced by its redacted version as soon as a guardrail redacts it, so later
+/// guardrails check the redacted output. Errors of a guardrail (e.g.: the moderation endpoint is
+/// down) are returned rather than ignored, so that unchecked outputs are never delivered.
+#[derive(Default)]
+pub struct Guardrails {
+    guardrails: Vec<Box<dyn GuardrailDyn>>,
+    max_regenerations: usize,
+}
+
+impl Guardrails {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a guardrail to the pipeline. Cheap guardrails should come first, since the pipeline
+    /// stops at the first guardrail regenerating or blocking the output.
+    pub fn guardrail(mut self, guardrail: impl Guardrail + 'static) -> Self {
+        self.guardrails.push(Box::new(guardrail));
+        self
+    }
+
+    /// Set the maximum number of times the output is regenerated by [Guardrails::run] and
+    /// [Guardrails::prompt] (default: 0)
+    pub fn max_regenerations(mut self, max_regenerations: usize) -> Self {
+        self.max_regenerations = max_regenerations;
+        self
+    }
+
+    /// Run the guardrails on `output`.
+    pub async fn check(&self, output: &str) -> Result<Outcome, GuardrailError> {
+        let mut output = output.to_string();
+
+        for guardrail in &self.guardrails {
+            match guardrail.check_boxed(&output).await? {
+                Verdict::Pass => {}
+                Verdict::Redact(redacted) => {
+                    tracing::debug!(target: "rig", "Output redacted by guardrail {}", guardrail.guardrail_name());
+                    output = redacted;
+                }
+                Verdict::Regenerate(reason) => {
+                    return Ok(Outcome::Regenerate {
+                        guardrail: guardrail.guardrail_name(),
+                        reason,
+                    })
+                }
+                Verdict::Block(reason) => {
+                    return Ok(Outcome::Block {
+                        guardrail: guardrail.guardrail_name(),
+                        reason,
+                    })
+                }
+            }
+        }
+
+        Ok(Outcome::Pass(output))
+    }
+
+    /// Generate an output with `generate` and run the guardrails on it, regenerating it up to
+    /// `max_regenerations` times. `generate` is given the reason of the previous rejection, if
+    /// any, so that it can be used as feedback. Returns the (possibly redacted) output.
+    pub async fn run<F, Fut, E>(&self, mut generate: F) -> Result<String, GuardrailError>
+    where
+        F: FnMut(Option<String>) -> Fut,
+        Fut: Future<Output = Result<String, E>>,
+        GuardrailError: From<E>,
+    {
+        let mut feedback = None;
+        let mut regenerations = 0;
+
+        loop {
+            let output = generate(feedback.take()).await?;
+
+            match self.check(&output).await? {
+                Outcome::Pass(output) => return Ok(output),
+                Outcome::Block { guardrail, reason } => {
+                    return Err(GuardrailError::Blocked { guardrail, reason })
+                }
+                Outcome::Regenerate { guardrail, reason } => {
+                    if regenerations == self.max_regenerations {
+                        return Err(GuardrailError::RegenerationLimit {
+                            guardrail,
+                            reason,
+                            regenerations,
+                        });
+                    }
+                    regenerations += 1;
+                    tracing::debug!(target: "rig", "Output rejected by guardrail {} ({}), regenerating ({}/{})", guardrail, reason, regenerations, self.max_regenerations);
+                    feedback = Some(reason);
+                }
+            }
+        }
+    }
+
+    /// Prompt `agent` (or any other [Prompt] implementation) and run the guardrails on its
+    /// response. When a response is rejected, the prompt is sent again along with the reason of
+    /// the rejection.
+    pub async fn prompt(
+        &self,
+        agent: &impl Prompt,
+        prompt: &str,
+    ) -> Result<String, GuardrailError> {
+        self.run(|feedback| async move {
+            match feedback {
+                Some(reason) => {
+                    agent
+                        .prompt(&format!(
+                            "{prompt}\n\nA previous answer to this was rejected: {reason}\n\
+                            Write a new answer that does not have this problem."
+                        ))
+                        .await
+                }
+                None => agent.prompt(prompt).await,
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Prompt returning canned responses and recording the prompts it receives
+    struct Canned {
+        responses: Mutex<Vec<&'static str>>,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl Canned {
+        fn new(responses: Vec<&'static str>) -> Self {
+            Self {
+                responses: Mutex::new(responses),
+                prompts: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    impl Prompt for Canned {
+        async fn prompt(&self, prompt: &str) -> Result<String, PromptError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.responses.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_applies_redactions_in_order() {
+        let guardrails = Guardrails::new()
+            .guardrail(Pii::new(Action::Redact))
+            .guardrail(Blocklist::new(Action::Block).word("[EMAIL]"));
+
+        assert_eq!(
+            guardrails
+                .check("Write to me at jane@example.com")
+                .await
+                .unwrap(),
+            Outcome::Block {
+                guardrail: "blocklist".to_string(),
+                reason: "Output contains blocked term `[EMAIL]`".to_string()
+            }
+        );
+        assert_eq!(
+            guardrails.check("Hello world").await.unwrap(),
+            Outcome::Pass("Hello world".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prompt_regenerates_with_feedback() {
+        let agent = Canned::new(vec!["Buy $RIG, guaranteed returns!", "Building in public."]);
+        let guardrails = Guardrails::new()
+            .guardrail(Blocklist::new(Action::Regenerate).word("guaranteed returns"))
+            .max_regenerations(1);
+
+        let output = guardrails.prompt(&agent, "Tweet something").await.unwrap();
+        assert_eq!(output, "Building in public.");
+
+        let prompts = agent.prompts.lock().unwrap();
+        assert_eq!(prompts[0], "Tweet something");
+        assert!(prompts[1].contains("Output contains blocked term `guaranteed returns`"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_regeneration_limit() {
+        let agent = Canned::new(vec!["Airdrop soon", "Airdrop tomorrow"]);
+        let guardrails = Guardrails::new()
+            .guardrail(Blocklist::new(Action::Regenerate).word("airdrop"))
+            .max_regenerations(1);
+
+        assert!(matches!(
+            guardrails.prompt(&agent, "Tweet something").await,
+            Err(GuardrailError::RegenerationLimit {
+                regenerations: 1,
+                ..
+            })
+        ));
+    }
+}
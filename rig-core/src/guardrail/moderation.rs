@@ -0,0 +1,99 @@
+//! Guardrail using a completion model to moderate outputs according to a content policy.
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Guardrail, GuardrailError, Verdict};
+use crate::{completion::CompletionModel, extractor::ExtractorBuilder};
+
+/// Default content policy of [LlmModeration]
+pub const DEFAULT_POLICY: &str = "\
+- Hate speech, harassment, insults or threats
+- Sexual content
+- Violence, self-harm or dangerous activities
+- Illegal activities
+- Personal data of real people
+- Financial advice, promises of returns, or requests to send money or tokens
+- Political or religious controversy
+- Anything that could embarrass or damage the reputation of the author";
+
+/// Moderation result submitted by the moderation model
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ModerationResult {
+    /// Step by step reasoning about whether the text violates the policy
+    pub reasoning: String,
+    /// Whether the text violates the policy
+    pub flagged: bool,
+    /// Items of the policy violated by the text, if any
+    pub categories: Vec<String>,
+}
+
+/// Uses a completion model to flag outputs violating a content policy (by default
+/// [DEFAULT_POLICY]). Flagged outputs are regenerated by default (see [LlmModeration::action]).
+#[derive(Clone)]
+pub struct LlmModeration<M: CompletionModel> {
+    model: M,
+    policy: String,
+    action: Action,
+}
+
+impl<M: CompletionModel> LlmModeration<M> {
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            policy: DEFAULT_POLICY.to_string(),
+            action: Action::Regenerate,
+        }
+    }
+
+    /// Set the content policy, i.e.: a list of what outputs must not contain
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = policy.to_string();
+        self
+    }
+
+    /// Set the action on flagged outputs (default: [Action::Regenerate]). There is nothing to
+    /// redact in a flagged output, so [Action::Redact] is treated as [Action::Regenerate].
+    pub fn action(mut self, action: Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Moderate `text` according to the policy
+    pub async fn moderate(&self, text: &str) -> Result<ModerationResult, GuardrailError> {
+        let extractor = ExtractorBuilder::<ModerationResult, M>::new(self.model.clone())
+            .preamble(&format!(
+                "You are a content moderator reviewing text before it is published.\n\
+                Flag the text if it contains any of the following:\n{}\n\n\
+                Do not flag text merely for being informal, opinionated or humorous.",
+                self.policy
+            ))
+            .retries(1)
+            .build();
+
+        Ok(extractor
+            .extract(&format!("Text to moderate:\n{text}"))
+            .await?)
+    }
+}
+
+impl<M: CompletionModel> Guardrail for LlmModeration<M> {
+    fn name(&self) -> String {
+        "llm_moderation".to_string()
+    }
+
+    async fn check(&self, output: &str) -> Result<Verdict, GuardrailError> {
+        let result = self.moderate(output).await?;
+
+        Ok(match result.flagged {
+            true => Verdict::reject(
+                self.action,
+                format!(
+                    "Output violates the content policy ({}): {}",
+                    result.categories.join(", "),
+                    result.reasoning
+                ),
+            ),
+            false => Verdict::Pass,
+        })
+    }
+}
@@ -0,0 +1,260 @@
+//! This module provides functionality for working with image generation models.
+//!
+//! The [ImageGenerationModel] trait is the interface between providers (e.g.: OpenAI Images,
+//! Heurist) and the library, in the same way as [CompletionModel](crate::completion::CompletionModel)
+//! for completion models. Requests are created with [ImageGenerationModel::image_generation_request]
+//! and return the generated image as bytes along with its MIME type.
+//!
+//! # Example
+//! ```rust
+//! use rig::{image_generation::ImageGenerationModel, providers::openai};
+//!
+//! let openai = openai::Client::from_env();
+//! let dall_e = openai.image_generation_model(openai::DALL_E_3);
+//!
+//! let response = dall_e
+//!     .image_generation_request("A lighthouse on a cliff at dawn, watercolor")
+//!     .negative_prompt("people, text")
+//!     .size(1024, 1024)
+//!     .send()
+//!     .await?;
+//!
+//! std::fs::write("lighthouse.png", &response.image)?;
+//! ```
+use serde::{Deserialize, Serialize};
+
+use crate::json_utils;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageGenerationError {
+    /// Http error (e.g.: connection error, timeout, etc.)
+    #[error("HttpError: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    /// Json error (e.g.: serialization, deserialization)
+    #[error("JsonError: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// Error building the image generation request
+    #[error("RequestError: {0}")]
+    RequestError(String),
+
+    /// Error parsing the image generation response
+    #[error("ResponseError: {0}")]
+    ResponseError(String),
+
+    /// Error returned by the image generation model provider
+    #[error("ProviderError: {0}")]
+    ProviderError(String),
+}
+
+/// Struct representing a general image generation request that can be sent to an image
+/// generation model provider.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ImageGenerationRequest {
+    /// Description of the image to generate
+    pub prompt: String,
+    /// Description of what the image should not contain. Providers without native support
+    /// append it to the prompt.
+    pub negative_prompt: Option<String>,
+    /// Width of the image, in pixels
+    pub width: u32,
+    /// Height of the image, in pixels
+    pub height: u32,
+    /// Seed of the generation, for reproducible images (if supported by the provider)
+    pub seed: Option<u64>,
+    /// Additional provider-specific parameters to be sent to the image generation model provider
+    pub additional_params: Option<serde_json::Value>,
+}
+
+/// General image generation response struct that contains the generated image and the raw
+/// response.
+#[derive(Debug)]
+pub struct ImageGenerationResponse<T> {
+    /// The generated image
+    pub image: Vec<u8>,
+    /// The MIME type of the generated image (e.g.: `image/png`)
+    pub mime_type: String,
+    /// The raw response returned by the image generation model provider
+    pub raw_response: T,
+}
+
+/// Trait defining an image generation model, either from a third party provider (e.g.: OpenAI)
+/// or a local model. Test doubles can implement it to return fixed images.
+pub trait ImageGenerationModel: Clone + Send + Sync {
+    /// The raw response type returned by the underlying image generation model.
+    type Response: Send + Sync;
+
+    /// Generates an image for the given image generation request.
+    fn image_generation(
+        &self,
+        request: ImageGenerationRequest,
+    ) -> impl std::future::Future<
+        Output = Result<ImageGenerationResponse<Self::Response>, ImageGenerationError>,
+    > + Send;
+
+    /// Generates an image generation request builder for the given `prompt`.
+    fn image_generation_request(&self, prompt: &str) -> ImageGenerationRequestBuilder<Self> {
+        ImageGenerationRequestBuilder::new(self.clone(), prompt)
+    }
+}
+
+/// Builder struct for constructing an image generation request.
+///
+/// # Example
+/// ```rust
+/// use rig::{image_generation::ImageGenerationModel, providers::heurist};
+///
+/// let heurist = heurist::Client::from_env();
+/// let model = heurist.image_generation_model(heurist::BLUE_PENCIL_REALISTIC);
+///
+/// let request = model
+///     .image_generation_request("A cat wearing a space suit")
+///     .negative_prompt("blurry, low quality")
+///     .size(512, 768)
+///     .seed(42)
+///     .build();
+/// ```
+pub struct ImageGenerationRequestBuilder<M: ImageGenerationModel> {
+    model: M,
+    prompt: String,
+    negative_prompt: Option<String>,
+    width: u32,
+    height: u32,
+    seed: Option<u64>,
+    additional_params: Option<serde_json::Value>,
+}
+
+impl<M: ImageGenerationModel> ImageGenerationRequestBuilder<M> {
+    pub fn new(model: M, prompt: &str) -> Self {
+        Self {
+            model,
+            prompt: prompt.to_string(),
+            negative_prompt: None,
+            width: 1024,
+            height: 1024,
+            seed: None,
+            additional_params: None,
+        }
+    }
+
+    /// Sets the negative prompt of the request
+    pub fn negative_prompt(mut self, negative_prompt: &str) -> Self {
+        self.negative_prompt = Some(negative_prompt.to_string());
+        self
+    }
+
+    /// Sets the size of the image, in pixels (default: 1024x1024)
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the seed of the request
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Adds additional parameters to the image generation request.
+    pub fn additional_params(mut self, additional_params: serde_json::Value) -> Self {
+        match self.additional_params {
+            Some(params) => {
+                self.additional_params = Some(json_utils::merge(params, additional_params));
+            }
+            None => {
+                self.additional_params = Some(additional_params);
+            }
+        }
+        self
+    }
+
+    /// Builds the image generation request.
+    pub fn build(self) -> ImageGenerationRequest {
+        ImageGenerationRequest {
+            prompt: self.prompt,
+            negative_prompt: self.negative_prompt,
+            width: self.width,
+            height: self.height,
+            seed: self.seed,
+            additional_params: self.additional_params,
+        }
+    }
+
+    /// Sends the image generation request to the image generation model provider and returns
+    /// the generated image.
+    pub async fn send(self) -> Result<ImageGenerationResponse<M::Response>, ImageGenerationError> {
+        let model = self.model.clone();
+        model.image_generation(self.build()).await
+    }
+}
+
+/// Detect the MIME type of an image from its first bytes (PNG, JPEG, GIF or WebP).
+pub fn image_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct StubModel;
+
+    impl ImageGenerationModel for StubModel {
+        type Response = ImageGenerationRequest;
+
+        async fn image_generation(
+            &self,
+            request: ImageGenerationRequest,
+        ) -> Result<ImageGenerationResponse<Self::Response>, ImageGenerationError> {
+            Ok(ImageGenerationResponse {
+                image: vec![0x89, b'P', b'N', b'G'],
+                mime_type: "image/png".to_string(),
+                raw_response: request,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_image_generation_request_builder() {
+        let response = StubModel
+            .image_generation_request("A cat")
+            .negative_prompt("dogs")
+            .size(512, 768)
+            .seed(7)
+            .additional_params(serde_json::json!({"steps": 20}))
+            .send()
+            .await
+            .unwrap();
+
+        let request = response.raw_response;
+        assert_eq!(request.prompt, "A cat");
+        assert_eq!(request.negative_prompt.as_deref(), Some("dogs"));
+        assert_eq!((request.width, request.height), (512, 768));
+        assert_eq!(request.seed, Some(7));
+        assert_eq!(request.additional_params.unwrap()["steps"], 20);
+        assert_eq!(image_mime_type(&response.image), Some("image/png"));
+    }
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(
+            image_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(image_mime_type(b"GIF89a"), Some("image/gif"));
+        assert_eq!(
+            image_mime_type(b"RIFF\x10\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(image_mime_type(b"<html>"), None);
+    }
+}
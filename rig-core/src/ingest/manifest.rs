@@ -0,0 +1,123 @@
+//! Manifest of the files that were ingested into a vector store.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::IngestError;
+
+/// What the manifest remembers about an ingested file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// SHA-256 hash of the content of the file when it was last embedded
+    pub hash: String,
+    /// Ids of the documents stored in the vector store for this file
+    pub document_ids: Vec<String>,
+}
+
+/// [Manifest] keeps track of the content hash of every ingested file along with the ids of the
+///  documents it produced, so that unchanged files are not embedded again and the documents of
+///  modified or deleted files can be removed from the vector store.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    files: BTreeMap<PathBuf, ManifestEntry>,
+}
+
+impl Manifest {
+    /// Load a manifest from a JSON file. Returns an empty manifest if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, IngestError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(IngestError::IoError(e)),
+        }
+    }
+
+    /// Save the manifest as a JSON file, creating its parent directories if needed.
+    /// The manifest is written to a temporary file first so that it is never left half-written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), IngestError> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Hash the content of a file the way the manifest does.
+    pub fn hash(content: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(content))
+    }
+
+    /// Returns true if `path` was ingested with exactly this `hash`.
+    pub fn is_unchanged(&self, path: &Path, hash: &str) -> bool {
+        self.files.get(path).is_some_and(|entry| entry.hash == hash)
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&ManifestEntry> {
+        self.files.get(path)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, entry: ManifestEntry) -> Option<ManifestEntry> {
+        self.files.insert(path, entry)
+    }
+
+    pub fn remove(&mut self, path: &Path) -> Option<ManifestEntry> {
+        self.files.remove(path)
+    }
+
+    /// Paths of all the files in the manifest.
+    pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use assert_fs::prelude::PathChild;
+
+    use super::{Manifest, ManifestEntry};
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        let path = temp.child("index/manifest.json");
+
+        let mut manifest = Manifest::load(path.path()).unwrap();
+        assert!(manifest.is_empty());
+
+        let hash = Manifest::hash(b"foo");
+        manifest.insert(
+            PathBuf::from("docs/foo.txt"),
+            ManifestEntry {
+                hash: hash.clone(),
+                document_ids: vec!["docs/foo.txt#0".to_string()],
+            },
+        );
+        manifest.save(path.path()).unwrap();
+
+        let manifest = Manifest::load(path.path()).unwrap();
+        assert!(manifest.is_unchanged(Path::new("docs/foo.txt"), &hash));
+        assert!(!manifest.is_unchanged(Path::new("docs/foo.txt"), &Manifest::hash(b"bar")));
+        assert!(!manifest.is_unchanged(Path::new("docs/bar.txt"), &hash));
+    }
+}
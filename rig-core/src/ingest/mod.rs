@@ -0,0 +1,455 @@
+//! This module provides an incremental ingestion driver that keeps a vector store in sync with
+//! the files it was built from.
+//!
+//! The [Ingestor] splits files into [Chunk]s, embeds them and stores them in any vector store
+//! implementing [VectorStoreMut]. The content hash of every ingested file is tracked in a
+//! [Manifest] which can be persisted to disk: when the ingestion is run again (e.g.: on the next
+//! start of the application), only new or modified files are embedded again, and the documents
+//! of deleted files are removed from the vector store.
+//!
+//! With the `watch` feature enabled, [Ingestor::watch] keeps the vector store up to date in
+//! long-running processes by listening to filesystem notifications.
+//!
+//! # Example
+//! ```rust
+//! use rig::{
+//!     ingest::Ingestor,
+//!     loaders::{splitter::RecursiveCharacterSplitter, FileLoader},
+//!     providers::openai,
+//!     vector_store::in_memory_store::InMemoryVectorStore,
+//! };
+//!
+//! let openai = openai::Client::from_env();
+//! let model = openai.embedding_model(openai::TEXT_EMBEDDING_ADA_002);
+//!
+//! let mut ingestor = Ingestor::new(
+//!     model.clone(),
+//!     InMemoryVectorStore::from_documents(vec![])?,
+//!     RecursiveCharacterSplitter::new(1000, 200),
+//! )
+//! .with_manifest("index/manifest.json")?;
+//!
+//! // Only files that changed since the last run are embedded
+//! let report = ingestor
+//!     .sync(FileLoader::with_glob("docs/**/*.md")?.ignore_errors())
+//!     .await?;
+//! println!("{} added, {} updated, {} removed", report.added.len(), report.updated.len(), report.removed.len());
+//!
+//! let index = ingestor.into_store().index(model);
+//! ```
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    embeddings::{EmbedError, EmbeddingError, EmbeddingModel, EmbeddingsBuilder},
+    loaders::splitter::{Chunk, TextSplitter},
+    vector_store::{VectorStoreError, VectorStoreMut},
+};
+
+pub mod manifest;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use manifest::{Manifest, ManifestEntry};
+
+#[derive(Error, Debug)]
+pub enum IngestError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Embed error: {0}")]
+    EmbedError(#[from] EmbedError),
+
+    #[error("Embedding error: {0}")]
+    EmbeddingError(#[from] EmbeddingError),
+
+    #[error("Vector store error: {0}")]
+    VectorStoreError(#[from] VectorStoreError),
+
+    /// Error while reading or writing the manifest
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] serde_json::Error),
+
+    #[error("Pattern error: {0}")]
+    PatternError(#[from] glob::PatternError),
+
+    #[cfg(feature = "watch")]
+    #[error("Watch error: {0}")]
+    WatchError(#[from] notify::Error),
+}
+
+/// Outcome of the ingestion of a single file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    /// The file was not in the manifest and was embedded
+    Added,
+    /// The file changed since it was last ingested and was embedded again
+    Updated,
+    /// The file did not change since it was last ingested
+    Unchanged,
+}
+
+/// Summary of an [Ingestor::sync] run.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    pub added: Vec<PathBuf>,
+    pub updated: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    /// Files that could not be read. Their previously ingested documents (if any) are kept.
+    pub failed: Vec<(PathBuf, std::io::Error)>,
+}
+
+impl IngestReport {
+    /// Returns true if the vector store was modified.
+    pub fn has_changes(&self) -> bool {
+        !(self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty())
+    }
+}
+
+/// [Ingestor] embeds files into a vector store, re-embedding only the files whose content
+///  changed since they were last ingested (see the [module level documentation](self)).
+///
+/// Each file is split into [Chunk]s using the given [TextSplitter]. The id of a chunk in the
+///  vector store is `"{path}#{index}"`.
+pub struct Ingestor<M: EmbeddingModel, S: VectorStoreMut<Chunk>, T: TextSplitter> {
+    model: M,
+    store: S,
+    splitter: T,
+    manifest: Manifest,
+    manifest_path: Option<PathBuf>,
+}
+
+impl<M: EmbeddingModel, S: VectorStoreMut<Chunk>, T: TextSplitter> Ingestor<M, S, T> {
+    /// Create a new [Ingestor] with an empty, in-memory manifest.
+    pub fn new(model: M, store: S, splitter: T) -> Self {
+        Self {
+            model,
+            store,
+            splitter,
+            manifest: Manifest::default(),
+            manifest_path: None,
+        }
+    }
+
+    /// Load the manifest from the given file (if it exists) and save it there after every change.
+    ///
+    /// The manifest must describe the content of the vector store given to [Ingestor::new],
+    ///  i.e.: a persistent manifest should be used along with a persistent vector store.
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>) -> Result<Self, IngestError> {
+        let path = path.into();
+        self.manifest = Manifest::load(&path)?;
+        self.manifest_path = Some(path);
+        Ok(self)
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Synchronize the vector store with the given files, which are considered to be the
+    ///  complete set of files to index (paths are normalized, see [Ingestor::update_file]):
+    /// - new and modified files are (re-)embedded,
+    /// - files in the manifest that are not part of `paths` anymore are removed from the store,
+    /// - unchanged files are skipped.
+    pub async fn sync(
+        &mut self,
+        paths: impl IntoIterator<Item = PathBuf>,
+    ) -> Result<IngestReport, IngestError> {
+        let mut report = IngestReport::default();
+        let mut seen = HashSet::new();
+
+        for path in paths {
+            let path = normalize(&path);
+            seen.insert(path.clone());
+
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    tracing::warn!(target: "rig", "Failed to read {}: {}", path.display(), e);
+                    report.failed.push((path, e));
+                    continue;
+                }
+            };
+
+            match self.ingest(path.clone(), &content).await? {
+                FileStatus::Added => report.added.push(path),
+                FileStatus::Updated => report.updated.push(path),
+                FileStatus::Unchanged => report.unchanged.push(path),
+            }
+        }
+
+        let removed = self
+            .manifest
+            .paths()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed {
+            self.remove_file(&path).await?;
+            report.removed.push(path);
+        }
+
+        tracing::info!(target: "rig",
+            "Ingestion: {} added, {} updated, {} unchanged, {} removed, {} failed",
+            report.added.len(),
+            report.updated.len(),
+            report.unchanged.len(),
+            report.removed.len(),
+            report.failed.len()
+        );
+
+        Ok(report)
+    }
+
+    /// Ingest a single file, embedding it only if it is new or changed since it was last ingested.
+    /// The file is tracked by its path without `.` components, so that the same file given as
+    ///  `./docs/a.md` and `docs/a.md` is only ingested once.
+    pub async fn update_file(&mut self, path: PathBuf) -> Result<FileStatus, IngestError> {
+        let content = fs::read_to_string(&path)?;
+        self.ingest(normalize(&path), &content).await
+    }
+
+    /// Remove the documents of a file from the vector store.
+    /// Returns false if the file was not in the manifest.
+    pub async fn remove_file(&mut self, path: &Path) -> Result<bool, IngestError> {
+        let path = &normalize(path);
+        let Some(entry) = self.manifest.get(path) else {
+            return Ok(false);
+        };
+
+        self.store
+            .delete_documents(entry.document_ids.clone())
+            .await?;
+        self.manifest.remove(path);
+        self.save_manifest()?;
+
+        Ok(true)
+    }
+
+    async fn ingest(&mut self, path: PathBuf, content: &str) -> Result<FileStatus, IngestError> {
+        let hash = Manifest::hash(content.as_bytes());
+        if self.manifest.is_unchanged(&path, &hash) {
+            return Ok(FileStatus::Unchanged);
+        }
+
+        let chunks = self.splitter.split_with_path(&path, content);
+        let documents = if chunks.is_empty() {
+            vec![]
+        } else {
+            EmbeddingsBuilder::new(self.model.clone())
+                .documents(chunks)?
+                .build()
+                .await?
+                .into_iter()
+                .map(|(chunk, embeddings)| (chunk_id(&path, &chunk), chunk, embeddings))
+                .collect::<Vec<_>>()
+        };
+        let document_ids = documents
+            .iter()
+            .map(|(id, _, _)| id.clone())
+            .collect::<Vec<_>>();
+
+        // Insert the new documents before deleting the stale ones so that the store never
+        //  lacks the content of the file
+        self.store.upsert_documents(documents).await?;
+
+        let stale = self
+            .manifest
+            .get(&path)
+            .map(|previous| {
+                previous
+                    .document_ids
+                    .iter()
+                    .filter(|id| !document_ids.contains(id))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !stale.is_empty() {
+            self.store.delete_documents(stale).await?;
+        }
+
+        let status = match self
+            .manifest
+            .insert(path, ManifestEntry { hash, document_ids })
+        {
+            Some(_) => FileStatus::Updated,
+            None => FileStatus::Added,
+        };
+        self.save_manifest()?;
+
+        Ok(status)
+    }
+
+    fn save_manifest(&self) -> Result<(), IngestError> {
+        match &self.manifest_path {
+            Some(path) => self.manifest.save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Remove the `.` components of `path`, so that e.g. `./docs/a.md` and `docs/a.md` are the same
+///  file in the manifest.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, std::path::Component::CurDir))
+        .collect()
+}
+
+fn chunk_id(path: &Path, chunk: &Chunk) -> String {
+    format!("{}#{}", path.display(), chunk.metadata.index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    use super::{FileStatus, Ingestor};
+    use crate::{
+        embeddings::{Embedding, EmbeddingError, EmbeddingModel},
+        loaders::splitter::RecursiveCharacterSplitter,
+        vector_store::in_memory_store::InMemoryVectorStore,
+    };
+
+    #[derive(Clone, Default)]
+    struct Model {
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl EmbeddingModel for Model {
+        const MAX_DOCUMENTS: usize = 5;
+
+        fn ndims(&self) -> usize {
+            1
+        }
+
+        async fn embed_texts(
+            &self,
+            documents: impl IntoIterator<Item = String> + Send,
+        ) -> Result<Vec<Embedding>, EmbeddingError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(documents
+                .into_iter()
+                .map(|doc| Embedding {
+                    vec: vec![doc.len() as f64],
+                    document: doc,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        let foo = temp.child("foo.txt");
+        let bar = temp.child("bar.txt");
+        foo.write_str("First paragraph.\n\nSecond paragraph.")
+            .expect("Failed to write to foo");
+        bar.write_str("bar").expect("Failed to write to bar");
+        let manifest = temp.child("manifest.json");
+
+        let model = Model::default();
+        let splitter = RecursiveCharacterSplitter::new(20, 0);
+        let mut ingestor = Ingestor::new(
+            model.clone(),
+            InMemoryVectorStore::from_documents(vec![]).unwrap(),
+            splitter.clone(),
+        )
+        .with_manifest(manifest.path())
+        .unwrap();
+
+        let report = ingestor
+            .sync([foo.to_path_buf(), bar.to_path_buf()])
+            .await
+            .unwrap();
+        assert_eq!(report.added.len(), 2);
+        assert_eq!(ingestor.store().len(), 3);
+        let calls = model.calls.load(std::sync::atomic::Ordering::SeqCst);
+
+        // Restart with the persisted manifest and store: nothing to re-embed
+        let store = ingestor.into_store();
+        let mut ingestor = Ingestor::new(model.clone(), store, splitter)
+            .with_manifest(manifest.path())
+            .unwrap();
+        let report = ingestor
+            .sync([foo.to_path_buf(), bar.to_path_buf()])
+            .await
+            .unwrap();
+        assert!(!report.has_changes());
+        assert_eq!(report.unchanged.len(), 2);
+        assert_eq!(model.calls.load(std::sync::atomic::Ordering::SeqCst), calls);
+
+        // Shrinking a file removes its stale chunks, deleting a file removes all of them
+        foo.write_str("Only paragraph.")
+            .expect("Failed to write to foo");
+        std::fs::remove_file(bar.path()).expect("Failed to remove bar");
+        let report = ingestor.sync([foo.to_path_buf()]).await.unwrap();
+        assert_eq!(report.updated, vec![foo.to_path_buf()]);
+        assert_eq!(report.removed, vec![bar.to_path_buf()]);
+        assert_eq!(
+            ingestor
+                .store()
+                .iter()
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>(),
+            vec![format!("{}#0", foo.path().display())]
+        );
+
+        assert_eq!(
+            ingestor.update_file(foo.to_path_buf()).await.unwrap(),
+            FileStatus::Unchanged
+        );
+    }
+
+    #[tokio::test]
+    async fn test_normalized_paths() {
+        let model = Model::default();
+        let mut ingestor = Ingestor::new(
+            model.clone(),
+            InMemoryVectorStore::from_documents(vec![]).unwrap(),
+            RecursiveCharacterSplitter::new(1000, 0),
+        );
+
+        let report = ingestor
+            .sync([PathBuf::from("./Cargo.toml")])
+            .await
+            .unwrap();
+        assert_eq!(report.added, vec![PathBuf::from("Cargo.toml")]);
+        let calls = model.calls.load(std::sync::atomic::Ordering::SeqCst);
+
+        assert_eq!(
+            ingestor
+                .update_file(PathBuf::from("Cargo.toml"))
+                .await
+                .unwrap(),
+            FileStatus::Unchanged
+        );
+        assert_eq!(ingestor.manifest().len(), 1);
+        assert_eq!(model.calls.load(std::sync::atomic::Ordering::SeqCst), calls);
+
+        assert!(ingestor
+            .remove_file(Path::new("./Cargo.toml"))
+            .await
+            .unwrap());
+        assert!(ingestor.manifest().is_empty());
+        assert!(ingestor.store().is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Watch mode for the [Ingestor], built on filesystem notifications.
+use std::path::{Component, Path, PathBuf};
+
+use futures::{channel::mpsc, StreamExt};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use super::{normalize, IngestError, Ingestor};
+use crate::{
+    embeddings::EmbeddingModel,
+    loaders::splitter::{Chunk, TextSplitter},
+    vector_store::VectorStoreMut,
+};
+
+impl<M: EmbeddingModel, S: VectorStoreMut<Chunk>, T: TextSplitter> Ingestor<M, S, T> {
+    /// Synchronize the vector store with the files matching the glob `pattern`, then keep
+    ///  watching them: created or modified files are embedded again (if their content changed)
+    ///  and deleted files are removed from the store. Changes made during the initial
+    ///  synchronization are picked up once it is done.
+    ///
+    /// This method only returns if the watcher could not be started or stops unexpectedly.
+    ///  Errors related to a single file are logged and the file is picked up again on its next
+    ///  change.
+    ///
+    /// # Example
+    /// ```rust
+    /// let mut ingestor = Ingestor::new(model, store, RecursiveCharacterSplitter::new(1000, 200))
+    ///     .with_manifest("index/manifest.json")?;
+    ///
+    /// tokio::spawn(async move { ingestor.watch("docs/**/*.md").await });
+    /// ```
+    pub async fn watch(&mut self, pattern: &str) -> Result<(), IngestError> {
+        // The pattern and the paths of notifications are normalized like the paths of the
+        //  manifest, so that e.g. `./docs/*.md` matches the notifications of `docs/a.md`
+        let matcher = glob::Pattern::new(&normalize(Path::new(pattern)).to_string_lossy())?;
+
+        // Start watching before the initial sync, so that the changes made while it embeds the
+        //  files are buffered and handled once it is done
+        let (tx, mut rx) = mpsc::unbounded();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.unbounded_send(event);
+        })?;
+        watcher.watch(&watch_root(pattern), RecursiveMode::Recursive)?;
+
+        let paths = glob::glob(pattern)?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file());
+        self.sync(paths).await?;
+
+        // Notifications report absolute paths, while the manifest uses the paths as matched by
+        //  the pattern
+        let cwd = Path::new(pattern)
+            .is_relative()
+            .then(std::env::current_dir)
+            .transpose()?;
+
+        while let Some(event) = rx.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!(target: "rig", "File watcher error: {}", e);
+                    continue;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+
+            for path in event.paths {
+                let path = event_path(path, cwd.as_deref());
+                if !matcher.matches_path(&path) {
+                    continue;
+                }
+
+                let result = if path.is_file() {
+                    self.update_file(path.clone()).await.map(
+                        |status| tracing::info!(target: "rig", "{}: {:?}", path.display(), status),
+                    )
+                } else if !path.exists() {
+                    self.remove_file(&path).await.map(|removed| {
+                        if removed {
+                            tracing::info!(target: "rig", "{}: Removed", path.display())
+                        }
+                    })
+                } else {
+                    Ok(())
+                };
+
+                if let Err(e) = result {
+                    tracing::warn!(target: "rig", "Failed to ingest {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Path of a notification relative to `cwd` (if the pattern is relative), normalized like the
+///  paths matched by the pattern.
+fn event_path(path: PathBuf, cwd: Option<&Path>) -> PathBuf {
+    match cwd.and_then(|cwd| path.strip_prefix(cwd).ok()) {
+        Some(relative) => normalize(relative),
+        None => normalize(&path),
+    }
+}
+
+/// Longest directory prefix of a glob pattern that does not contain any special character.
+fn watch_root(pattern: &str) -> PathBuf {
+    let root = Path::new(pattern)
+        .components()
+        .take_while(|component| match component {
+            Component::Normal(part) => !part
+                .to_string_lossy()
+                .contains(['*', '?', '[', ']', '{', '}']),
+            _ => true,
+        })
+        .collect::<PathBuf>();
+
+    let root = match root.is_file() {
+        true => root.parent().map(Path::to_path_buf).unwrap_or_default(),
+        false => root,
+    };
+    match root.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => root,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{event_path, watch_root};
+    use crate::ingest::normalize;
+
+    #[test]
+    fn test_watch_root() {
+        assert_eq!(watch_root("docs/**/*.md"), PathBuf::from("docs"));
+        assert_eq!(watch_root("/data/src/*.rs"), PathBuf::from("/data/src"));
+        assert_eq!(watch_root("*.txt"), PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_event_path() {
+        let cwd = Path::new("/work");
+        let matcher =
+            glob::Pattern::new(&normalize(Path::new("./docs/*.md")).to_string_lossy()).unwrap();
+
+        let path = event_path(PathBuf::from("/work/docs/a.md"), Some(cwd));
+        assert_eq!(path, normalize(Path::new("./docs/a.md")));
+        assert!(matcher.matches_path(&path));
+        assert!(!matcher.matches_path(&event_path(PathBuf::from("/other/docs/a.md"), Some(cwd))));
+    }
+}
@@ -79,16 +79,25 @@
 //! implement the [VectorStoreIndex](crate::vector_store::VectorStoreIndex) trait.
 
 pub mod agent;
+pub mod classifier;
 pub mod cli_chatbot;
 pub mod completion;
 pub mod embeddings;
+pub mod eval;
 pub mod extractor;
+pub mod guardrail;
+pub mod image_generation;
+pub mod ingest;
 pub(crate) mod json_utils;
 pub mod loaders;
 pub mod one_or_many;
 pub mod pipeline;
 pub mod providers;
+pub mod speech;
+pub mod streaming;
 pub mod tool;
+pub mod tool_emulation;
+pub mod transcription;
 pub mod vector_store;
 
 // Re-export commonly used types and traits
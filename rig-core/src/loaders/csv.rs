@@ -0,0 +1,322 @@
+use std::{fs, path::PathBuf};
+
+use glob::glob;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{
+    file::FileLoaderError,
+    template::{DocumentTemplate, Row, TemplateError},
+};
+use crate::completion::Document;
+
+#[derive(Error, Debug)]
+pub enum CsvLoaderError {
+    #[error("{0}")]
+    FileLoaderError(#[from] FileLoaderError),
+
+    #[error("CSV error: {0}")]
+    CsvError(#[from] csv::Error),
+
+    #[error("{0}")]
+    TemplateError(#[from] TemplateError),
+}
+
+/// A row loaded from a file, along with the path of the file and the index of the row
+///  (starting at 0, excluding the header row).
+pub type CsvRow = (PathBuf, usize, Row);
+
+// ================================================================
+// Implementing Loadable trait for loading rows from CSV files
+// ================================================================
+
+pub(crate) trait Loadable {
+    fn load_rows(self, delimiter: u8) -> Box<dyn Iterator<Item = Result<CsvRow, CsvLoaderError>>>;
+}
+
+impl Loadable for PathBuf {
+    fn load_rows(self, delimiter: u8) -> Box<dyn Iterator<Item = Result<CsvRow, CsvLoaderError>>> {
+        let mut reader = match csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_path(&self)
+        {
+            Ok(reader) => reader,
+            Err(e) => return Box::new(std::iter::once(Err(e.into()))),
+        };
+        let headers = match reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(e) => return Box::new(std::iter::once(Err(e.into()))),
+        };
+
+        Box::new(
+            reader
+                .into_records()
+                .enumerate()
+                .map(move |(index, record)| {
+                    let row = headers
+                        .iter()
+                        .zip(record?.iter())
+                        .map(|(column, value)| {
+                            (column.to_string(), Value::String(value.to_string()))
+                        })
+                        .collect::<Row>();
+                    Ok((self.clone(), index, row))
+                }),
+        )
+    }
+}
+impl<T: Loadable> Loadable for Result<T, CsvLoaderError> {
+    fn load_rows(self, delimiter: u8) -> Box<dyn Iterator<Item = Result<CsvRow, CsvLoaderError>>> {
+        match self {
+            Ok(t) => t.load_rows(delimiter),
+            Err(e) => Box::new(std::iter::once(Err(e))),
+        }
+    }
+}
+
+// ================================================================
+// CsvFileLoader definitions and implementations
+// ================================================================
+
+/// [CsvFileLoader] is a utility for loading CSV files from the filesystem using glob patterns or
+///  directory paths. Each record is loaded as a [Row] keyed by the header of the file, and can be
+///  turned into a [Document] using a [DocumentTemplate].
+///
+/// # Errors
+///
+/// This module defines a custom error type [CsvLoaderError] which can represent any
+///  [FileLoaderError] alongside CSV parsing and template rendering errors.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use rig::loaders::{template::DocumentTemplate, CsvFileLoader};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let template = DocumentTemplate::new("{question}\n{answer}").id_column("id");
+///
+///     // Turn every row of the matching files into a document, ignoring any errors
+///     let documents = CsvFileLoader::with_glob("data/*.csv")?
+///         .documents(template)
+///         .ignore_errors()
+///         .into_iter()
+///         .collect::<Vec<_>>();
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [CsvFileLoader] uses strict typing between the iterator methods to ensure that transitions
+///  between different implementations of the loaders and it's methods are handled properly by
+///  the compiler.
+pub struct CsvFileLoader<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a> CsvFileLoader<'a, Result<PathBuf, CsvLoaderError>> {
+    /// Loads the records of the files within the iterator returned by [CsvFileLoader::with_glob]
+    ///  or [CsvFileLoader::with_dir] as [Row]s, flattened as a single iterator.
+    ///
+    /// # Example
+    /// Load files in directory "data/*.csv" and print each row.
+    ///
+    /// ```rust
+    /// let rows = CsvFileLoader::with_glob("data/*.csv")?.rows().into_iter();
+    /// for result in rows {
+    ///     match result {
+    ///         Ok(row) => println!("{:?}", row),
+    ///         Err(e) => eprintln!("Error reading row: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn rows(self) -> CsvFileLoader<'a, Result<Row, CsvLoaderError>> {
+        CsvFileLoader {
+            iterator: Box::new(
+                self.rows_with_path()
+                    .iterator
+                    .map(|res| res.map(|(_, _, row)| row)),
+            ),
+        }
+    }
+
+    /// Loads the records of the files within the iterator as [Row]s along with the path of the
+    ///  file and the index of the row within that file.
+    pub fn rows_with_path(self) -> CsvFileLoader<'a, Result<CsvRow, CsvLoaderError>> {
+        self.rows_with_delimiter(b',')
+    }
+
+    /// Same as [CsvFileLoader::rows_with_path], for files using a different field delimiter
+    ///  (e.g.: `b';'` or `b'\t'`).
+    pub fn rows_with_delimiter(
+        self,
+        delimiter: u8,
+    ) -> CsvFileLoader<'a, Result<CsvRow, CsvLoaderError>> {
+        CsvFileLoader {
+            iterator: Box::new(self.iterator.flat_map(move |res| res.load_rows(delimiter))),
+        }
+    }
+
+    /// Renders every record of the files within the iterator as a [Document] using the given
+    ///  [DocumentTemplate]. Documents without an id column are identified as `{path}#{index}`.
+    pub fn documents(
+        self,
+        template: DocumentTemplate,
+    ) -> CsvFileLoader<'a, Result<Document, CsvLoaderError>> {
+        self.rows_with_path().documents(template)
+    }
+}
+
+impl<'a> CsvFileLoader<'a, Result<CsvRow, CsvLoaderError>> {
+    /// Renders the loaded rows as [Document]s using the given [DocumentTemplate].
+    ///  Documents without an id column are identified as `{path}#{index}`.
+    pub fn documents(
+        self,
+        template: DocumentTemplate,
+    ) -> CsvFileLoader<'a, Result<Document, CsvLoaderError>> {
+        CsvFileLoader {
+            iterator: Box::new(self.iterator.map(move |res| {
+                let (path, index, row) = res?;
+                let default_id = format!("{}#{}", path.display(), index);
+                Ok(template.document(&default_id, &row)?)
+            })),
+        }
+    }
+}
+
+impl<'a, T: 'a> CsvFileLoader<'a, Result<T, CsvLoaderError>> {
+    /// Ignores errors in the iterator, returning only successful results. This can be used on any
+    ///  [CsvFileLoader] state of iterator whose items are results.
+    ///
+    /// # Example
+    /// Read rows in directory "data/*.csv" and ignore errors from unreadable files or records.
+    ///
+    /// ```rust
+    /// let rows = CsvFileLoader::with_glob("data/*.csv")?.rows().ignore_errors().into_iter();
+    /// for row in rows {
+    ///     println!("{:?}", row)
+    /// }
+    /// ```
+    pub fn ignore_errors(self) -> CsvFileLoader<'a, T> {
+        CsvFileLoader {
+            iterator: Box::new(self.iterator.filter_map(|res| res.ok())),
+        }
+    }
+}
+
+impl CsvFileLoader<'_, Result<PathBuf, CsvLoaderError>> {
+    /// Creates a new [CsvFileLoader] using a glob pattern to match files.
+    ///
+    /// # Example
+    /// Create a [CsvFileLoader] for all `.csv` files that match the glob "data/*.csv".
+    ///
+    /// ```rust
+    /// let loader = CsvFileLoader::with_glob("data/*.csv")?;
+    /// ```
+    pub fn with_glob(
+        pattern: &str,
+    ) -> Result<CsvFileLoader<'_, Result<PathBuf, CsvLoaderError>>, CsvLoaderError> {
+        let paths = glob(pattern).map_err(FileLoaderError::PatternError)?;
+        Ok(CsvFileLoader {
+            iterator: Box::new(paths.into_iter().map(|path| {
+                path.map_err(FileLoaderError::GlobError)
+                    .map_err(CsvLoaderError::FileLoaderError)
+            })),
+        })
+    }
+
+    /// Creates a new [CsvFileLoader] on all files within a directory.
+    ///
+    /// # Example
+    /// Create a [CsvFileLoader] for all files that are in the directory "data".
+    ///
+    /// ```rust
+    /// let loader = CsvFileLoader::with_dir("data")?;
+    /// ```
+    pub fn with_dir(
+        directory: &str,
+    ) -> Result<CsvFileLoader<'_, Result<PathBuf, CsvLoaderError>>, CsvLoaderError> {
+        Ok(CsvFileLoader {
+            iterator: Box::new(
+                fs::read_dir(directory)
+                    .map_err(FileLoaderError::IoError)?
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        path.is_file().then_some(Ok(path))
+                    }),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// CsvFileLoader iterator implementations
+// ================================================================
+
+pub struct IntoIter<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> IntoIterator for CsvFileLoader<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iterator: self.iterator,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    use super::CsvFileLoader;
+    use crate::loaders::template::DocumentTemplate;
+
+    #[test]
+    fn test_csv_loader() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        let faq = temp.child("faq.csv");
+        faq.write_str("id,question,answer\nq1,What is a flurbo?,A green alien\nq2,\"Where, exactly?\",Cold planets\n")
+            .expect("Failed to write to faq");
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.csv";
+
+        let rows = CsvFileLoader::with_glob(&glob)
+            .unwrap()
+            .rows()
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["question"], "Where, exactly?");
+
+        let documents = CsvFileLoader::with_glob(&glob)
+            .unwrap()
+            .documents(DocumentTemplate::new("Q: {question}\nA: {answer}").id_column("id"))
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].id, "q1");
+        assert_eq!(documents[0].text, "Q: What is a flurbo?\nA: A green alien");
+
+        let documents = CsvFileLoader::with_glob(&glob)
+            .unwrap()
+            .documents(DocumentTemplate::new("{answer}"))
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(documents[1].id, format!("{}#1", faq.path().display()));
+    }
+}
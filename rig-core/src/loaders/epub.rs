@@ -0,0 +1,599 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, Seek},
+    path::PathBuf,
+};
+
+use glob::glob;
+use quick_xml::events::{BytesStart, Event};
+use thiserror::Error;
+
+use super::{
+    file::FileLoaderError,
+    html::HtmlDocument,
+    splitter::{Chunk, TextSplitter},
+};
+
+#[derive(Error, Debug)]
+pub enum EpubLoaderError {
+    #[error("{0}")]
+    FileLoaderError(#[from] FileLoaderError),
+
+    #[error("Zip error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("XML error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid EPUB: {0}")]
+    InvalidEpub(String),
+}
+
+impl From<quick_xml::events::attributes::AttrError> for EpubLoaderError {
+    fn from(err: quick_xml::events::attributes::AttrError) -> Self {
+        EpubLoaderError::XmlError(err.into())
+    }
+}
+
+// ================================================================
+// EPUB parsing
+// ================================================================
+
+/// A chapter of an EPUB book, i.e.: one of the documents of its reading order (spine).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpubChapter {
+    /// Path of the chapter within the EPUB archive
+    pub href: String,
+    /// Title of the chapter (i.e.: the `<title>` of its XHTML document), if any
+    pub title: Option<String>,
+    /// Text content of the chapter
+    pub text: String,
+}
+
+/// Text content of an EPUB book, chapter by chapter in reading order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpubDocument {
+    /// Title of the book (`dc:title`), if any
+    pub title: Option<String>,
+    /// Chapters of the book, in reading order. Chapters without any text are skipped.
+    pub chapters: Vec<EpubChapter>,
+}
+
+impl EpubDocument {
+    /// Parse an EPUB archive. The text of each chapter is extracted using [HtmlDocument::parse].
+    pub fn parse<R: Read + Seek>(reader: R) -> Result<Self, EpubLoaderError> {
+        let mut archive = zip::ZipArchive::new(reader)?;
+
+        // The container points to the package document (OPF) describing the book
+        let container = read_entry(&mut archive, "META-INF/container.xml")?;
+        let opf_path = find_rootfile(&container)?;
+        let opf = read_entry(&mut archive, &opf_path)?;
+        let package = Package::parse(&opf)?;
+
+        let base = opf_path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir)
+            .unwrap_or_default();
+
+        let chapters = package
+            .spine
+            .iter()
+            .filter_map(|idref| package.manifest.get(idref))
+            .map(|href| {
+                let href = resolve(base, href);
+                let html = read_entry(&mut archive, &href)?;
+                let document = HtmlDocument::parse(&html);
+                Ok(EpubChapter {
+                    href,
+                    title: document.title,
+                    text: document.text,
+                })
+            })
+            .filter(|chapter| !matches!(chapter, Ok(chapter) if chapter.text.is_empty()))
+            .collect::<Result<Vec<_>, EpubLoaderError>>()?;
+
+        Ok(Self {
+            title: package.title,
+            chapters,
+        })
+    }
+
+    /// Text of the whole book, chapters separated by a blank line.
+    pub fn text(&self) -> String {
+        self.chapters
+            .iter()
+            .map(|chapter| chapter.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, EpubLoaderError> {
+    let mut content = String::new();
+    archive.by_name(name)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, EpubLoaderError> {
+    Ok(match element.try_get_attribute(name)? {
+        Some(attr) => Some(attr.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// Find the path of the package document in `META-INF/container.xml`.
+fn find_rootfile(container: &str) -> Result<String, EpubLoaderError> {
+    let mut reader = quick_xml::Reader::from_str(container);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"rootfile" => {
+                if let Some(path) = attribute(&e, "full-path")? {
+                    return Ok(path);
+                }
+            }
+            Event::Eof => {
+                return Err(EpubLoaderError::InvalidEpub(
+                    "no rootfile in META-INF/container.xml".to_string(),
+                ))
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The parts of the package document needed to extract the text of a book.
+struct Package {
+    title: Option<String>,
+    /// Manifest item id -> href
+    manifest: HashMap<String, String>,
+    /// Manifest item ids, in reading order
+    spine: Vec<String>,
+}
+
+impl Package {
+    fn parse(opf: &str) -> Result<Self, EpubLoaderError> {
+        let mut reader = quick_xml::Reader::from_str(opf);
+        let mut package = Package {
+            title: None,
+            manifest: HashMap::new(),
+            spine: vec![],
+        };
+        let mut in_title = false;
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) if e.local_name().as_ref() == b"title" => in_title = true,
+                Event::End(e) if e.local_name().as_ref() == b"title" => in_title = false,
+                Event::Text(text) if in_title && package.title.is_none() => {
+                    let title = text.unescape()?.trim().to_string();
+                    package.title = (!title.is_empty()).then_some(title);
+                }
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"item" => {
+                        if let (Some(id), Some(href)) =
+                            (attribute(&e, "id")?, attribute(&e, "href")?)
+                        {
+                            package.manifest.insert(id, href);
+                        }
+                    }
+                    b"itemref" => {
+                        if let Some(idref) = attribute(&e, "idref")? {
+                            package.spine.push(idref);
+                        }
+                    }
+                    _ => (),
+                },
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+
+        if package.spine.is_empty() {
+            return Err(EpubLoaderError::InvalidEpub(
+                "package document has an empty spine".to_string(),
+            ));
+        }
+        Ok(package)
+    }
+}
+
+/// Resolve a manifest href relative to the directory of the package document.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts = base
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    for part in href.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/").replace("%20", " ")
+}
+
+// ================================================================
+// Implementing Loadable trait for loading epub books
+// ================================================================
+
+pub(crate) trait Loadable {
+    fn load(self) -> Result<EpubDocument, EpubLoaderError>;
+    fn load_with_path(self) -> Result<(PathBuf, EpubDocument), EpubLoaderError>;
+}
+
+impl Loadable for PathBuf {
+    fn load(self) -> Result<EpubDocument, EpubLoaderError> {
+        let file = fs::File::open(self).map_err(FileLoaderError::IoError)?;
+        EpubDocument::parse(file)
+    }
+    fn load_with_path(self) -> Result<(PathBuf, EpubDocument), EpubLoaderError> {
+        let file = fs::File::open(&self).map_err(FileLoaderError::IoError)?;
+        Ok((self, EpubDocument::parse(file)?))
+    }
+}
+impl<T: Loadable> Loadable for Result<T, EpubLoaderError> {
+    fn load(self) -> Result<EpubDocument, EpubLoaderError> {
+        self.map(|t| t.load())?
+    }
+    fn load_with_path(self) -> Result<(PathBuf, EpubDocument), EpubLoaderError> {
+        self.map(|t| t.load_with_path())?
+    }
+}
+
+// ================================================================
+// EpubFileLoader definitions and implementations
+// ================================================================
+
+/// [EpubFileLoader] is a utility for loading EPUB books from the filesystem using glob patterns or
+///  directory paths. It provides methods to read the text of the books and split them by chapter,
+///  keeping track of the chapter numbers along with their contents.
+///
+/// # Errors
+///
+/// This module defines a custom error type [EpubLoaderError] which can represent any
+///  [FileLoaderError] alongside archive and XML parsing errors.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use rig::loaders::EpubFileLoader;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Load the chapters of every book matching the glob, ignoring any errors
+///     let books = EpubFileLoader::with_glob("books/*.epub")?
+///         .load_with_path()
+///         .ignore_errors()
+///         .by_chapter();
+///
+///     for (path, chapters) in books {
+///         for (chapter_no, content) in chapters {
+///             println!("{:?} chapter {}: {}", path, chapter_no, content);
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [EpubFileLoader] uses strict typing between the iterator methods to ensure that transitions
+///  between different implementations of the loaders and it's methods are handled properly by
+///  the compiler.
+pub struct EpubFileLoader<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a> EpubFileLoader<'a, Result<PathBuf, EpubLoaderError>> {
+    /// Loads the books within the iterator returned by [EpubFileLoader::with_glob] or
+    ///  [EpubFileLoader::with_dir] as [EpubDocument]s.
+    ///
+    /// # Example
+    /// Load books in directory "books/*.epub" and print their titles.
+    ///
+    /// ```rust
+    /// let books = EpubFileLoader::with_glob("books/*.epub")?.load().into_iter();
+    /// for result in books {
+    ///     match result {
+    ///         Ok(book) => println!("{:?}", book.title),
+    ///         Err(e) => eprintln!("Error reading epub: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn load(self) -> EpubFileLoader<'a, Result<EpubDocument, EpubLoaderError>> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load())),
+        }
+    }
+
+    /// Loads the books within the iterator returned by [EpubFileLoader::with_glob] or
+    ///  [EpubFileLoader::with_dir] as [EpubDocument]s along with their path.
+    pub fn load_with_path(
+        self,
+    ) -> EpubFileLoader<'a, Result<(PathBuf, EpubDocument), EpubLoaderError>> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load_with_path())),
+        }
+    }
+
+    /// Reads the text of the books within the iterator returned by [EpubFileLoader::with_glob]
+    ///  or [EpubFileLoader::with_dir].
+    pub fn read(self) -> EpubFileLoader<'a, Result<String, EpubLoaderError>> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.map(|res| Ok(res.load()?.text()))),
+        }
+    }
+
+    /// Reads the text of the books within the iterator returned by [EpubFileLoader::with_glob]
+    ///  or [EpubFileLoader::with_dir] and returns the path along with the content.
+    pub fn read_with_path(self) -> EpubFileLoader<'a, Result<(PathBuf, String), EpubLoaderError>> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.map(|res| {
+                let (path, book) = res.load_with_path()?;
+                Ok((path, book.text()))
+            })),
+        }
+    }
+}
+
+impl<'a> EpubFileLoader<'a, EpubDocument> {
+    /// Chunks the loaded books by chapter, flattened as a single iterator.
+    pub fn by_chapter(self) -> EpubFileLoader<'a, String> {
+        EpubFileLoader {
+            iterator: Box::new(
+                self.iterator
+                    .flat_map(|book| book.chapters.into_iter().map(|chapter| chapter.text)),
+            ),
+        }
+    }
+}
+
+impl<'a> EpubFileLoader<'a, (PathBuf, EpubDocument)> {
+    /// Chunks the loaded books by chapter, processed as a vector of chapters (along with their
+    ///  chapter number, starting at 0) by path.
+    pub fn by_chapter(self) -> EpubFileLoader<'a, (PathBuf, Vec<(usize, String)>)> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.map(|(path, book)| {
+                let chapters = book
+                    .chapters
+                    .into_iter()
+                    .map(|chapter| chapter.text)
+                    .enumerate()
+                    .collect();
+                (path, chapters)
+            })),
+        }
+    }
+}
+
+impl<'a> EpubFileLoader<'a, (PathBuf, Vec<(usize, String)>)> {
+    /// Splits the chapters of the loaded books into chunks using the given [TextSplitter].
+    ///  Each chunk keeps track of the path and chapter number (as its `page`) it was loaded from.
+    ///  Chunks never span multiple chapters.
+    ///
+    /// # Example
+    /// ```rust
+    /// let chunks = EpubFileLoader::with_glob("books/*.epub")?
+    ///     .load_with_path()
+    ///     .ignore_errors()
+    ///     .by_chapter()
+    ///     .split(RecursiveCharacterSplitter::new(1000, 200));
+    /// ```
+    pub fn split<S: TextSplitter + 'a>(self, splitter: S) -> EpubFileLoader<'a, Chunk> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.flat_map(move |(path, chapters)| {
+                chapters
+                    .into_iter()
+                    .flat_map(|(chapter_no, content)| {
+                        splitter.split_page(&path, chapter_no, &content)
+                    })
+                    .collect::<Vec<_>>()
+            })),
+        }
+    }
+}
+
+impl<'a, T: 'a> EpubFileLoader<'a, Result<T, EpubLoaderError>> {
+    /// Ignores errors in the iterator, returning only successful results. This can be used on any
+    ///  [EpubFileLoader] state of iterator whose items are results.
+    pub fn ignore_errors(self) -> EpubFileLoader<'a, T> {
+        EpubFileLoader {
+            iterator: Box::new(self.iterator.filter_map(|res| res.ok())),
+        }
+    }
+}
+
+impl EpubFileLoader<'_, Result<PathBuf, EpubLoaderError>> {
+    /// Creates a new [EpubFileLoader] using a glob pattern to match files.
+    ///
+    /// # Example
+    /// Create a [EpubFileLoader] for all `.epub` files that match the glob "books/*.epub".
+    ///
+    /// ```rust
+    /// let loader = EpubFileLoader::with_glob("books/*.epub")?;
+    /// ```
+    pub fn with_glob(
+        pattern: &str,
+    ) -> Result<EpubFileLoader<'_, Result<PathBuf, EpubLoaderError>>, EpubLoaderError> {
+        let paths = glob(pattern).map_err(FileLoaderError::PatternError)?;
+        Ok(EpubFileLoader {
+            iterator: Box::new(paths.into_iter().map(|path| {
+                path.map_err(FileLoaderError::GlobError)
+                    .map_err(EpubLoaderError::FileLoaderError)
+            })),
+        })
+    }
+
+    /// Creates a new [EpubFileLoader] on all files within a directory.
+    ///
+    /// # Example
+    /// Create a [EpubFileLoader] for all files that are in the directory "books".
+    ///
+    /// ```rust
+    /// let loader = EpubFileLoader::with_dir("books")?;
+    /// ```
+    pub fn with_dir(
+        directory: &str,
+    ) -> Result<EpubFileLoader<'_, Result<PathBuf, EpubLoaderError>>, EpubLoaderError> {
+        Ok(EpubFileLoader {
+            iterator: Box::new(
+                fs::read_dir(directory)
+                    .map_err(FileLoaderError::IoError)?
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        path.is_file().then_some(Ok(path))
+                    }),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// EpubFileLoader iterator implementations
+// ================================================================
+
+pub struct IntoIter<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> IntoIterator for EpubFileLoader<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iterator: self.iterator,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use assert_fs::prelude::PathChild;
+
+    use super::{resolve, EpubFileLoader};
+    use crate::loaders::splitter::RecursiveCharacterSplitter;
+
+    fn write_epub(path: &std::path::Path) {
+        let file = std::fs::File::create(path).expect("Failed to create epub");
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+
+        let entries = [
+            ("mimetype", "application/epub+zip"),
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0"?>
+                <container xmlns="urn:oasis:names:tc:opendocument:xmlns:container" version="1.0">
+                  <rootfiles>
+                    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+                  </rootfiles>
+                </container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<?xml version="1.0"?>
+                <package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+                  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+                    <dc:title>Flurbos &amp; Glarbs</dc:title>
+                  </metadata>
+                  <manifest>
+                    <item id="c2" href="text/two.xhtml" media-type="application/xhtml+xml"/>
+                    <item id="c1" href="text/one.xhtml" media-type="application/xhtml+xml"/>
+                  </manifest>
+                  <spine>
+                    <itemref idref="c1"/>
+                    <itemref idref="c2"/>
+                  </spine>
+                </package>"#,
+            ),
+            (
+                "OEBPS/text/one.xhtml",
+                "<html><head><title>One</title></head><body><p>A flurbo is green.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/two.xhtml",
+                "<html><head><title>Two</title></head><body><p>A glarb is blue.</p></body></html>",
+            ),
+        ];
+        for (name, content) in entries {
+            zip.start_file(name, options)
+                .expect("Failed to start entry");
+            zip.write_all(content.as_bytes())
+                .expect("Failed to write entry");
+        }
+        zip.finish().expect("Failed to finish epub");
+    }
+
+    #[test]
+    fn test_epub_loader() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        write_epub(temp.child("book.epub").path());
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.epub";
+
+        let books = EpubFileLoader::with_glob(&glob)
+            .unwrap()
+            .load()
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].title.as_deref(), Some("Flurbos & Glarbs"));
+        assert_eq!(
+            books[0]
+                .chapters
+                .iter()
+                .map(|chapter| (chapter.title.as_deref(), chapter.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some("One"), "A flurbo is green."),
+                (Some("Two"), "A glarb is blue.")
+            ]
+        );
+
+        let chunks = EpubFileLoader::with_glob(&glob)
+            .unwrap()
+            .load_with_path()
+            .ignore_errors()
+            .by_chapter()
+            .split(RecursiveCharacterSplitter::new(100, 0))
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].metadata.page, Some(1));
+        assert_eq!(chunks[1].text, "A glarb is blue.");
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        assert_eq!(
+            resolve("OEBPS", "text/one.xhtml#top"),
+            "OEBPS/text/one.xhtml"
+        );
+        assert_eq!(
+            resolve("OEBPS/text", "../images/a%20b.png"),
+            "OEBPS/images/a b.png"
+        );
+        assert_eq!(resolve("", "one.xhtml"), "one.xhtml");
+    }
+}
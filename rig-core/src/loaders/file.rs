@@ -3,6 +3,8 @@ use std::{fs, path::PathBuf};
 use glob::glob;
 use thiserror::Error;
 
+use super::splitter::{Chunk, TextSplitter};
+
 #[derive(Error, Debug)]
 pub enum FileLoaderError {
     #[error("Invalid glob pattern: {0}")]
@@ -162,6 +164,58 @@ impl<'a, T: 'a> FileLoader<'a, Result<T, FileLoaderError>> {
     }
 }
 
+impl<'a> FileLoader<'a, String> {
+    /// Splits the contents of the files within the iterator into chunks using the given
+    ///  [TextSplitter]. Chunks are flattened as a single iterator.
+    ///
+    /// # Example
+    /// Read files in directory "files/*.txt" and split them into chunks of at most 1000 characters.
+    ///
+    /// ```rust
+    /// let chunks = FileLoader::with_glob("files/*.txt")?
+    ///     .read()
+    ///     .ignore_errors()
+    ///     .split(RecursiveCharacterSplitter::new(1000, 200));
+    /// for chunk in chunks {
+    ///     println!("{}", chunk.text);
+    /// }
+    /// ```
+    pub fn split<S: TextSplitter + 'a>(self, splitter: S) -> FileLoader<'a, Chunk> {
+        FileLoader {
+            iterator: Box::new(
+                self.iterator
+                    .flat_map(move |content| splitter.split(&content)),
+            ),
+        }
+    }
+}
+
+impl<'a> FileLoader<'a, (PathBuf, String)> {
+    /// Splits the contents of the files within the iterator into chunks using the given
+    ///  [TextSplitter]. Each chunk keeps track of the path of the file it was loaded from.
+    ///
+    /// # Example
+    /// Read files in directory "files/*.txt" and split them into chunks of at most 1000 characters.
+    ///
+    /// ```rust
+    /// let chunks = FileLoader::with_glob("files/*.txt")?
+    ///     .read_with_path()
+    ///     .ignore_errors()
+    ///     .split(RecursiveCharacterSplitter::new(1000, 200));
+    /// for chunk in chunks {
+    ///     println!("{:?}: {}", chunk.metadata.path, chunk.text);
+    /// }
+    /// ```
+    pub fn split<S: TextSplitter + 'a>(self, splitter: S) -> FileLoader<'a, Chunk> {
+        FileLoader {
+            iterator: Box::new(
+                self.iterator
+                    .flat_map(move |(path, content)| splitter.split_with_path(&path, &content)),
+            ),
+        }
+    }
+}
+
 impl FileLoader<'_, Result<PathBuf, FileLoaderError>> {
     /// Creates a new [FileLoader] using a glob pattern to match files.
     ///
@@ -240,6 +294,7 @@ mod tests {
     use assert_fs::prelude::{FileTouch, FileWriteStr, PathChild};
 
     use super::FileLoader;
+    use crate::loaders::splitter::RecursiveCharacterSplitter;
 
     #[test]
     fn test_file_loader() {
@@ -270,4 +325,34 @@ mod tests {
         assert!(!actual.is_empty());
         assert!(expected == actual)
     }
+
+    #[test]
+    fn test_file_loader_split() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        let foo_file = temp.child("foo.txt");
+        foo_file
+            .write_str("First paragraph.\n\nSecond paragraph.")
+            .expect("Failed to write to foo");
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.txt";
+
+        let chunks = FileLoader::with_glob(&glob)
+            .unwrap()
+            .read_with_path()
+            .ignore_errors()
+            .split(RecursiveCharacterSplitter::new(20, 0))
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["First paragraph.", "Second paragraph."]
+        );
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.metadata.path.as_deref() == Some(foo_file.path())));
+    }
 }
@@ -0,0 +1,512 @@
+use std::{fs, path::PathBuf};
+
+use glob::glob;
+use scraper::{ElementRef, Html, Node, Selector};
+use thiserror::Error;
+
+use super::file::FileLoaderError;
+
+#[derive(Error, Debug)]
+pub enum HtmlLoaderError {
+    #[error("{0}")]
+    FileLoaderError(#[from] FileLoaderError),
+}
+
+// ================================================================
+// HTML text extraction
+// ================================================================
+
+/// Elements that never contain content worth embedding.
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "head", "nav", "header",
+    "footer", "aside", "form", "button", "select",
+];
+
+/// Words of class or id names commonly used by navigation, ads and other page chrome.
+const BOILERPLATE_HINTS: &[&str] = &[
+    "nav",
+    "navbar",
+    "navigation",
+    "menu",
+    "sidebar",
+    "footer",
+    "header",
+    "cookie",
+    "cookies",
+    "consent",
+    "banner",
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "promo",
+    "breadcrumb",
+    "breadcrumbs",
+    "share",
+    "sharing",
+    "social",
+    "comment",
+    "comments",
+];
+
+/// Elements that start a new line in the extracted text.
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "table",
+    "tr",
+    "pre",
+    "blockquote",
+    "figure",
+    "figcaption",
+    "hr",
+];
+
+/// Text extracted from an HTML page with its boilerplate (navigation, headers, footers, scripts,
+/// etc.) removed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtmlDocument {
+    /// Content of the `<title>` element, if any
+    pub title: Option<String>,
+    /// Main text content of the page
+    pub text: String,
+}
+
+impl HtmlDocument {
+    /// Extract the main text content of an HTML page.
+    ///
+    /// If the page has a `<main>` or `<article>` element (or an element with `role="main"`),
+    /// only its content is kept. Otherwise, the whole `<body>` is used. In both cases, elements
+    /// that are typically boilerplate (e.g.: `<nav>`, `<footer>`, `<script>`, or elements whose
+    /// class or id suggests a menu, sidebar or cookie banner) are dropped.
+    pub fn parse(html: &str) -> Self {
+        let document = Html::parse_document(html);
+
+        let title = Selector::parse("title").ok().and_then(|selector| {
+            document
+                .select(&selector)
+                .next()
+                .map(|title| collapse_whitespace(&title.text().collect::<String>()))
+                .filter(|title| !title.is_empty())
+        });
+
+        let root = ["main", "article", "[role=main]", "body"]
+            .iter()
+            .filter_map(|selector| Selector::parse(selector).ok())
+            .find_map(|selector| document.select(&selector).next())
+            .unwrap_or_else(|| document.root_element());
+
+        let mut text = String::new();
+        write_text(root, &mut text, false);
+
+        Self {
+            title,
+            text: normalize_lines(&text),
+        }
+    }
+}
+
+/// Returns true for skipped elements and elements with a boilerplate class or id. Names are
+/// matched on their last `-` or `_` separated word, which usually is their role (e.g.:
+/// `site-header`, `cookie_banner`), so that `article-header-title` or `shareholder-letter` are kept.
+fn is_boilerplate(element: &ElementRef) -> bool {
+    let value = element.value();
+    if SKIPPED_TAGS.contains(&value.name()) {
+        return true;
+    }
+
+    value
+        .attr("class")
+        .into_iter()
+        .chain(value.attr("id"))
+        .flat_map(str::split_whitespace)
+        .any(|name| {
+            let name = name.to_lowercase();
+            name.rsplit(['-', '_'])
+                .next()
+                .is_some_and(|role| BOILERPLATE_HINTS.contains(&role))
+        })
+}
+
+fn write_text(element: ElementRef, out: &mut String, preformatted: bool) {
+    let name = element.value().name();
+    let preformatted = preformatted || name == "pre";
+    let is_block = BLOCK_TAGS.contains(&name);
+
+    if is_block {
+        out.push('\n');
+    }
+
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) if preformatted => out.push_str(text),
+            Node::Text(text) => {
+                let text = collapse_whitespace(text);
+                if !text.is_empty() {
+                    if !out.is_empty() && !out.ends_with(char::is_whitespace) {
+                        out.push(' ');
+                    }
+                    out.push_str(&text);
+                }
+            }
+            Node::Element(child_element) if child_element.name() == "br" => out.push('\n'),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    if !is_boilerplate(&child) {
+                        write_text(child, out, preformatted);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if is_block {
+        out.push('\n');
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Trim every line and collapse runs of blank lines into a single one.
+fn normalize_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = vec![];
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(if line.trim().is_empty() { "" } else { line });
+    }
+    lines.join("\n").trim().to_string()
+}
+
+// ================================================================
+// Implementing Loadable trait for loading html pages
+// ================================================================
+
+pub(crate) trait Loadable {
+    fn load(self) -> Result<HtmlDocument, HtmlLoaderError>;
+    fn load_with_path(self) -> Result<(PathBuf, HtmlDocument), HtmlLoaderError>;
+}
+
+impl Loadable for PathBuf {
+    fn load(self) -> Result<HtmlDocument, HtmlLoaderError> {
+        let html = fs::read_to_string(self).map_err(FileLoaderError::IoError)?;
+        Ok(HtmlDocument::parse(&html))
+    }
+    fn load_with_path(self) -> Result<(PathBuf, HtmlDocument), HtmlLoaderError> {
+        let html = fs::read_to_string(&self).map_err(FileLoaderError::IoError)?;
+        Ok((self, HtmlDocument::parse(&html)))
+    }
+}
+impl<T: Loadable> Loadable for Result<T, HtmlLoaderError> {
+    fn load(self) -> Result<HtmlDocument, HtmlLoaderError> {
+        self.map(|t| t.load())?
+    }
+    fn load_with_path(self) -> Result<(PathBuf, HtmlDocument), HtmlLoaderError> {
+        self.map(|t| t.load_with_path())?
+    }
+}
+
+// ================================================================
+// HtmlFileLoader definitions and implementations
+// ================================================================
+
+/// [HtmlFileLoader] is a utility for loading HTML files from the filesystem using glob patterns or
+///  directory paths. Loaded pages have their boilerplate (navigation, headers, footers, scripts,
+///  etc.) removed so that only the main text content is kept (see [HtmlDocument::parse]).
+///
+/// # Errors
+///
+/// This module defines a custom error type [HtmlLoaderError] which can represent any
+///  [FileLoaderError] that might occur while loading the pages.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use rig::loaders::HtmlFileLoader;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Create a HtmlFileLoader using a glob pattern
+///     let loader = HtmlFileLoader::with_glob("docs/**/*.html")?;
+///
+///     // Read the text content of the pages, ignoring any errors
+///     let contents: Vec<String> = loader
+///         .read()
+///         .ignore_errors()
+///         .into_iter()
+///         .collect();
+///
+///     for content in contents {
+///         println!("{}", content);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [HtmlFileLoader] uses strict typing between the iterator methods to ensure that transitions
+///  between different implementations of the loaders and it's methods are handled properly by
+///  the compiler.
+pub struct HtmlFileLoader<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a> HtmlFileLoader<'a, Result<PathBuf, HtmlLoaderError>> {
+    /// Loads the pages within the iterator returned by [HtmlFileLoader::with_glob] or
+    ///  [HtmlFileLoader::with_dir] as [HtmlDocument]s (title and text content).
+    ///
+    /// # Example
+    /// Load pages in directory "docs/*.html" and print their titles.
+    ///
+    /// ```rust
+    /// let content = HtmlFileLoader::with_glob("docs/*.html")?.load().into_iter();
+    /// for result in content {
+    ///     match result {
+    ///         Ok(doc) => println!("{:?}", doc.title),
+    ///         Err(e) => eprintln!("Error reading page: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn load(self) -> HtmlFileLoader<'a, Result<HtmlDocument, HtmlLoaderError>> {
+        HtmlFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load())),
+        }
+    }
+
+    /// Loads the pages within the iterator returned by [HtmlFileLoader::with_glob] or
+    ///  [HtmlFileLoader::with_dir] as [HtmlDocument]s along with their path.
+    pub fn load_with_path(
+        self,
+    ) -> HtmlFileLoader<'a, Result<(PathBuf, HtmlDocument), HtmlLoaderError>> {
+        HtmlFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load_with_path())),
+        }
+    }
+
+    /// Reads the text content of the pages within the iterator returned by
+    ///  [HtmlFileLoader::with_glob] or [HtmlFileLoader::with_dir].
+    ///
+    /// # Example
+    /// Read pages in directory "docs/*.html" and print their text content.
+    ///
+    /// ```rust
+    /// let content = HtmlFileLoader::with_glob("docs/*.html")?.read().into_iter();
+    /// for result in content {
+    ///     match result {
+    ///         Ok(content) => println!("{}", content),
+    ///         Err(e) => eprintln!("Error reading page: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn read(self) -> HtmlFileLoader<'a, Result<String, HtmlLoaderError>> {
+        HtmlFileLoader {
+            iterator: Box::new(self.iterator.map(|res| Ok(res.load()?.text))),
+        }
+    }
+
+    /// Reads the text content of the pages within the iterator returned by
+    ///  [HtmlFileLoader::with_glob] or [HtmlFileLoader::with_dir] and returns the path along with
+    ///  the content.
+    pub fn read_with_path(self) -> HtmlFileLoader<'a, Result<(PathBuf, String), HtmlLoaderError>> {
+        HtmlFileLoader {
+            iterator: Box::new(self.iterator.map(|res| {
+                let (path, doc) = res.load_with_path()?;
+                Ok((path, doc.text))
+            })),
+        }
+    }
+}
+
+impl<'a, T: 'a> HtmlFileLoader<'a, Result<T, HtmlLoaderError>> {
+    /// Ignores errors in the iterator, returning only successful results. This can be used on any
+    ///  [HtmlFileLoader] state of iterator whose items are results.
+    ///
+    /// # Example
+    /// Read pages in directory "docs/*.html" and ignore errors from unreadable files.
+    ///
+    /// ```rust
+    /// let content = HtmlFileLoader::with_glob("docs/*.html")?.read().ignore_errors().into_iter();
+    /// for content in content {
+    ///     println!("{}", content)
+    /// }
+    /// ```
+    pub fn ignore_errors(self) -> HtmlFileLoader<'a, T> {
+        HtmlFileLoader {
+            iterator: Box::new(self.iterator.filter_map(|res| res.ok())),
+        }
+    }
+}
+
+impl HtmlFileLoader<'_, Result<PathBuf, HtmlLoaderError>> {
+    /// Creates a new [HtmlFileLoader] using a glob pattern to match files.
+    ///
+    /// # Example
+    /// Create a [HtmlFileLoader] for all `.html` files that match the glob "docs/*.html".
+    ///
+    /// ```rust
+    /// let loader = HtmlFileLoader::with_glob("docs/*.html")?;
+    /// ```
+    pub fn with_glob(
+        pattern: &str,
+    ) -> Result<HtmlFileLoader<'_, Result<PathBuf, HtmlLoaderError>>, HtmlLoaderError> {
+        let paths = glob(pattern).map_err(FileLoaderError::PatternError)?;
+        Ok(HtmlFileLoader {
+            iterator: Box::new(paths.into_iter().map(|path| {
+                path.map_err(FileLoaderError::GlobError)
+                    .map_err(HtmlLoaderError::FileLoaderError)
+            })),
+        })
+    }
+
+    /// Creates a new [HtmlFileLoader] on all files within a directory.
+    ///
+    /// # Example
+    /// Create a [HtmlFileLoader] for all files that are in the directory "docs".
+    ///
+    /// ```rust
+    /// let loader = HtmlFileLoader::with_dir("docs")?;
+    /// ```
+    pub fn with_dir(
+        directory: &str,
+    ) -> Result<HtmlFileLoader<'_, Result<PathBuf, HtmlLoaderError>>, HtmlLoaderError> {
+        Ok(HtmlFileLoader {
+            iterator: Box::new(
+                fs::read_dir(directory)
+                    .map_err(FileLoaderError::IoError)?
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        path.is_file().then_some(Ok(path))
+                    }),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// HtmlFileLoader iterator implementations
+// ================================================================
+
+pub struct IntoIter<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> IntoIterator for HtmlFileLoader<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iterator: self.iterator,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    use super::{HtmlDocument, HtmlFileLoader};
+
+    const PAGE: &str = r#"
+        <html>
+          <head><title> Flurbo docs </title><style>body { color: red; }</style></head>
+          <body>
+            <nav><a href="/">Home</a></nav>
+            <div class="cookie-banner">We use cookies</div>
+            <main>
+              <h1>What is a flurbo?</h1>
+              <p>A flurbo is a <b>green</b> alien
+                 that lives on cold planets.</p>
+              <script>console.log("tracking")</script>
+              <pre>let x = 1;
+let y = 2;</pre>
+            </main>
+            <footer>Copyright</footer>
+          </body>
+        </html>
+    "#;
+
+    #[test]
+    fn test_html_document_parse() {
+        let doc = HtmlDocument::parse(PAGE);
+
+        assert_eq!(doc.title.as_deref(), Some("Flurbo docs"));
+        assert_eq!(
+            doc.text,
+            "What is a flurbo?\n\nA flurbo is a green alien that lives on cold planets.\n\nlet x = 1;\nlet y = 2;"
+        );
+    }
+
+    #[test]
+    fn test_html_document_without_main() {
+        let doc = HtmlDocument::parse(
+            "<body><header>Site</header><p>Hello</p><aside>Links</aside><p>World</p></body>",
+        );
+
+        assert_eq!(doc.title, None);
+        assert_eq!(doc.text, "Hello\n\nWorld");
+    }
+
+    #[test]
+    fn test_html_document_boilerplate_classes() {
+        let doc = HtmlDocument::parse(
+            r#"<body>
+              <div class="site-header">Site</div>
+              <div id="comments">Nice post</div>
+              <div class="page cookie_banner">We use cookies</div>
+              <h1 class="article-header-title">Results</h1>
+              <p class="shareholder-letter">Dear shareholders</p>
+              <p id="comments-policy">Be kind</p>
+              <p class="menu-of-the-day">Soup</p>
+            </body>"#,
+        );
+
+        assert_eq!(doc.text, "Results\n\nDear shareholders\n\nBe kind\n\nSoup");
+    }
+
+    #[test]
+    fn test_html_loader() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        temp.child("page.html")
+            .write_str(PAGE)
+            .expect("Failed to write page");
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.html";
+        let actual = HtmlFileLoader::with_glob(&glob)
+            .unwrap()
+            .read()
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual.len(), 1);
+        assert!(actual[0].starts_with("What is a flurbo?"));
+        assert!(!actual[0].contains("Copyright"));
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+
+use glob::glob;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::{
+    file::FileLoaderError,
+    template::{DocumentTemplate, Row, TemplateError},
+};
+use crate::completion::Document;
+
+#[derive(Error, Debug)]
+pub enum JsonlLoaderError {
+    #[error("{0}")]
+    FileLoaderError(#[from] FileLoaderError),
+
+    #[error("JSON error on line {line}: {source}")]
+    JsonError {
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    #[error("Line {0} is not a JSON object")]
+    NotAnObject(usize),
+
+    #[error("{0}")]
+    TemplateError(#[from] TemplateError),
+}
+
+/// A row loaded from a file, along with the path of the file and the index of the row
+///  (starting at 0, blank lines excluded).
+pub type JsonlRow = (PathBuf, usize, Row);
+
+// ================================================================
+// Implementing Loadable trait for loading rows from JSONL files
+// ================================================================
+
+pub(crate) trait Loadable {
+    fn load_rows(self) -> Box<dyn Iterator<Item = Result<JsonlRow, JsonlLoaderError>>>;
+}
+
+impl Loadable for PathBuf {
+    fn load_rows(self) -> Box<dyn Iterator<Item = Result<JsonlRow, JsonlLoaderError>>> {
+        let file = match fs::File::open(&self) {
+            Ok(file) => file,
+            Err(e) => return Box::new(std::iter::once(Err(FileLoaderError::IoError(e).into()))),
+        };
+
+        Box::new(
+            BufReader::new(file)
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .enumerate()
+                .map(move |(index, (line_no, line))| {
+                    let line = line.map_err(FileLoaderError::IoError)?;
+                    match serde_json::from_str(&line) {
+                        Ok(Value::Object(row)) => Ok((self.clone(), index, row)),
+                        Ok(_) => Err(JsonlLoaderError::NotAnObject(line_no + 1)),
+                        Err(source) => Err(JsonlLoaderError::JsonError {
+                            line: line_no + 1,
+                            source,
+                        }),
+                    }
+                }),
+        )
+    }
+}
+impl<T: Loadable> Loadable for Result<T, JsonlLoaderError> {
+    fn load_rows(self) -> Box<dyn Iterator<Item = Result<JsonlRow, JsonlLoaderError>>> {
+        match self {
+            Ok(t) => t.load_rows(),
+            Err(e) => Box::new(std::iter::once(Err(e))),
+        }
+    }
+}
+
+// ================================================================
+// JsonlFileLoader definitions and implementations
+// ================================================================
+
+/// [JsonlFileLoader] is a utility for loading JSONL files from the filesystem using glob patterns or
+///  directory paths. Each line holds a JSON object which is loaded as a [Row], and can be turned
+///  into a [Document] using a [DocumentTemplate] (nested values are accessed using dotted paths).
+///
+/// # Errors
+///
+/// This module defines a custom error type [JsonlLoaderError] which can represent any
+///  [FileLoaderError] alongside JSON parsing and template rendering errors.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use rig::loaders::{template::DocumentTemplate, JsonlFileLoader};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let template = DocumentTemplate::new("{question}\n{answer}").id_column("id");
+///
+///     // Turn every row of the matching files into a document, ignoring any errors
+///     let documents = JsonlFileLoader::with_glob("data/*.jsonl")?
+///         .documents(template)
+///         .ignore_errors()
+///         .into_iter()
+///         .collect::<Vec<_>>();
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [JsonlFileLoader] uses strict typing between the iterator methods to ensure that transitions
+///  between different implementations of the loaders and it's methods are handled properly by
+///  the compiler.
+pub struct JsonlFileLoader<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a> JsonlFileLoader<'a, Result<PathBuf, JsonlLoaderError>> {
+    /// Loads the lines of the files within the iterator returned by [JsonlFileLoader::with_glob]
+    ///  or [JsonlFileLoader::with_dir] as [Row]s, flattened as a single iterator.
+    ///
+    /// # Example
+    /// Load files in directory "data/*.jsonl" and print each row.
+    ///
+    /// ```rust
+    /// let rows = JsonlFileLoader::with_glob("data/*.jsonl")?.rows().into_iter();
+    /// for result in rows {
+    ///     match result {
+    ///         Ok(row) => println!("{:?}", row),
+    ///         Err(e) => eprintln!("Error reading row: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn rows(self) -> JsonlFileLoader<'a, Result<Row, JsonlLoaderError>> {
+        JsonlFileLoader {
+            iterator: Box::new(
+                self.rows_with_path()
+                    .iterator
+                    .map(|res| res.map(|(_, _, row)| row)),
+            ),
+        }
+    }
+
+    /// Loads the lines of the files within the iterator as [Row]s along with the path of the
+    ///  file and the index of the row within that file.
+    pub fn rows_with_path(self) -> JsonlFileLoader<'a, Result<JsonlRow, JsonlLoaderError>> {
+        JsonlFileLoader {
+            iterator: Box::new(self.iterator.flat_map(|res| res.load_rows())),
+        }
+    }
+
+    /// Renders every line of the files within the iterator as a [Document] using the given
+    ///  [DocumentTemplate]. Documents without an id column are identified as `{path}#{index}`.
+    pub fn documents(
+        self,
+        template: DocumentTemplate,
+    ) -> JsonlFileLoader<'a, Result<Document, JsonlLoaderError>> {
+        self.rows_with_path().documents(template)
+    }
+}
+
+impl<'a> JsonlFileLoader<'a, Result<JsonlRow, JsonlLoaderError>> {
+    /// Renders the loaded rows as [Document]s using the given [DocumentTemplate].
+    ///  Documents without an id column are identified as `{path}#{index}`.
+    pub fn documents(
+        self,
+        template: DocumentTemplate,
+    ) -> JsonlFileLoader<'a, Result<Document, JsonlLoaderError>> {
+        JsonlFileLoader {
+            iterator: Box::new(self.iterator.map(move |res| {
+                let (path, index, row) = res?;
+                let default_id = format!("{}#{}", path.display(), index);
+                Ok(template.document(&default_id, &row)?)
+            })),
+        }
+    }
+}
+
+impl<'a, T: 'a> JsonlFileLoader<'a, Result<T, JsonlLoaderError>> {
+    /// Ignores errors in the iterator, returning only successful results. This can be used on any
+    ///  [JsonlFileLoader] state of iterator whose items are results.
+    ///
+    /// # Example
+    /// Read rows in directory "data/*.jsonl" and ignore errors from unreadable files or invalid lines.
+    ///
+    /// ```rust
+    /// let rows = JsonlFileLoader::with_glob("data/*.jsonl")?.rows().ignore_errors().into_iter();
+    /// for row in rows {
+    ///     println!("{:?}", row)
+    /// }
+    /// ```
+    pub fn ignore_errors(self) -> JsonlFileLoader<'a, T> {
+        JsonlFileLoader {
+            iterator: Box::new(self.iterator.filter_map(|res| res.ok())),
+        }
+    }
+}
+
+impl JsonlFileLoader<'_, Result<PathBuf, JsonlLoaderError>> {
+    /// Creates a new [JsonlFileLoader] using a glob pattern to match files.
+    ///
+    /// # Example
+    /// Create a [JsonlFileLoader] for all `.jsonl` files that match the glob "data/*.jsonl".
+    ///
+    /// ```rust
+    /// let loader = JsonlFileLoader::with_glob("data/*.jsonl")?;
+    /// ```
+    pub fn with_glob(
+        pattern: &str,
+    ) -> Result<JsonlFileLoader<'_, Result<PathBuf, JsonlLoaderError>>, JsonlLoaderError> {
+        let paths = glob(pattern).map_err(FileLoaderError::PatternError)?;
+        Ok(JsonlFileLoader {
+            iterator: Box::new(paths.into_iter().map(|path| {
+                path.map_err(FileLoaderError::GlobError)
+                    .map_err(JsonlLoaderError::FileLoaderError)
+            })),
+        })
+    }
+
+    /// Creates a new [JsonlFileLoader] on all files within a directory.
+    ///
+    /// # Example
+    /// Create a [JsonlFileLoader] for all files that are in the directory "data".
+    ///
+    /// ```rust
+    /// let loader = JsonlFileLoader::with_dir("data")?;
+    /// ```
+    pub fn with_dir(
+        directory: &str,
+    ) -> Result<JsonlFileLoader<'_, Result<PathBuf, JsonlLoaderError>>, JsonlLoaderError> {
+        Ok(JsonlFileLoader {
+            iterator: Box::new(
+                fs::read_dir(directory)
+                    .map_err(FileLoaderError::IoError)?
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        path.is_file().then_some(Ok(path))
+                    }),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// JsonlFileLoader iterator implementations
+// ================================================================
+
+pub struct IntoIter<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> IntoIterator for JsonlFileLoader<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iterator: self.iterator,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+
+    use super::{JsonlFileLoader, JsonlLoaderError};
+    use crate::loaders::template::DocumentTemplate;
+
+    #[test]
+    fn test_jsonl_loader() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        temp.child("posts.jsonl")
+            .write_str(concat!(
+                "{\"id\": 1, \"title\": \"Flurbos\", \"author\": {\"name\": \"Rick\"}}\n",
+                "\n",
+                "[1, 2]\n",
+                "{\"id\": 2, \"title\": \"Glarbs\", \"author\": {\"name\": \"Morty\"}}\n",
+            ))
+            .expect("Failed to write to posts");
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.jsonl";
+
+        let rows = JsonlFileLoader::with_glob(&glob)
+            .unwrap()
+            .rows()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert!(matches!(rows[1], Err(JsonlLoaderError::NotAnObject(3))));
+
+        let documents = JsonlFileLoader::with_glob(&glob)
+            .unwrap()
+            .documents(
+                DocumentTemplate::new("{title} by {author.name}")
+                    .id_column("id")
+                    .metadata_columns(["author.name"]),
+            )
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[1].id, "2");
+        assert_eq!(documents[1].text, "Glarbs by Morty");
+        assert_eq!(documents[1].additional_props["author.name"], "Morty");
+    }
+}
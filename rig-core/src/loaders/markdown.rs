@@ -0,0 +1,377 @@
+use std::{fs, path::PathBuf};
+
+use glob::glob;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::file::FileLoaderError;
+
+#[derive(Error, Debug)]
+pub enum MarkdownLoaderError {
+    #[error("{0}")]
+    FileLoaderError(#[from] FileLoaderError),
+
+    #[error("YAML front matter error: {0}")]
+    YamlError(#[from] serde_yaml::Error),
+
+    #[error("TOML front matter error: {0}")]
+    TomlError(#[from] toml::de::Error),
+
+    #[error("Front matter is not a map of key-value pairs")]
+    InvalidFrontMatter,
+}
+
+// ================================================================
+// Front matter parsing
+// ================================================================
+
+/// A Markdown document split into its front matter metadata and its body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkdownDocument {
+    /// Metadata from the YAML (`---`) or TOML (`+++`) front matter block, if any
+    pub front_matter: Map<String, Value>,
+    /// Markdown content following the front matter
+    pub body: String,
+}
+
+impl MarkdownDocument {
+    /// Parse a Markdown document, extracting its front matter if present.
+    ///
+    /// The front matter must be at the very start of the document, delimited by `---` lines
+    /// (YAML) or `+++` lines (TOML).
+    pub fn parse(text: &str) -> Result<Self, MarkdownLoaderError> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+        let Some((delimiter, front_matter, body)) =
+            ["---", "+++"].into_iter().find_map(|delimiter| {
+                split_front_matter(text, delimiter).map(|(fm, body)| (delimiter, fm, body))
+            })
+        else {
+            return Ok(Self {
+                front_matter: Map::new(),
+                body: text.to_string(),
+            });
+        };
+
+        let front_matter: Value = if front_matter.trim().is_empty() {
+            Value::Object(Map::new())
+        } else if delimiter == "---" {
+            serde_yaml::from_str(front_matter)?
+        } else {
+            toml::from_str(front_matter)?
+        };
+
+        match front_matter {
+            Value::Object(front_matter) => Ok(Self {
+                front_matter,
+                body: body.to_string(),
+            }),
+            Value::Null => Ok(Self {
+                front_matter: Map::new(),
+                body: body.to_string(),
+            }),
+            _ => Err(MarkdownLoaderError::InvalidFrontMatter),
+        }
+    }
+
+    /// Get a front matter value rendered as a string (strings are returned without quotes).
+    pub fn metadata(&self, key: &str) -> Option<String> {
+        self.front_matter.get(key).map(|value| match value {
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        })
+    }
+}
+
+/// Split `text` into its front matter (delimited by `delimiter` lines) and its body.
+fn split_front_matter<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let rest = text.strip_prefix(delimiter)?;
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+// ================================================================
+// Implementing Loadable trait for loading markdown files
+// ================================================================
+
+pub(crate) trait Loadable {
+    fn load(self) -> Result<MarkdownDocument, MarkdownLoaderError>;
+    fn load_with_path(self) -> Result<(PathBuf, MarkdownDocument), MarkdownLoaderError>;
+}
+
+impl Loadable for PathBuf {
+    fn load(self) -> Result<MarkdownDocument, MarkdownLoaderError> {
+        let text = fs::read_to_string(self).map_err(FileLoaderError::IoError)?;
+        MarkdownDocument::parse(&text)
+    }
+    fn load_with_path(self) -> Result<(PathBuf, MarkdownDocument), MarkdownLoaderError> {
+        let text = fs::read_to_string(&self).map_err(FileLoaderError::IoError)?;
+        Ok((self, MarkdownDocument::parse(&text)?))
+    }
+}
+impl<T: Loadable> Loadable for Result<T, MarkdownLoaderError> {
+    fn load(self) -> Result<MarkdownDocument, MarkdownLoaderError> {
+        self.map(|t| t.load())?
+    }
+    fn load_with_path(self) -> Result<(PathBuf, MarkdownDocument), MarkdownLoaderError> {
+        self.map(|t| t.load_with_path())?
+    }
+}
+
+// ================================================================
+// MarkdownFileLoader definitions and implementations
+// ================================================================
+
+/// [MarkdownFileLoader] is a utility for loading Markdown files from the filesystem using glob
+///  patterns or directory paths. Loaded files are split into their front matter metadata and
+///  their body (see [MarkdownDocument::parse]).
+///
+/// # Errors
+///
+/// This module defines a custom error type [MarkdownLoaderError] which can represent any
+///  [FileLoaderError] alongside front matter parsing errors.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use rig::loaders::MarkdownFileLoader;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Create a MarkdownFileLoader using a glob pattern
+///     let loader = MarkdownFileLoader::with_glob("docs/*.md")?;
+///
+///     // Load the documents with their front matter, ignoring any errors
+///     for (path, doc) in loader.load_with_path().ignore_errors() {
+///         println!("{:?} {:?}: {}", path, doc.metadata("title"), doc.body);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+///
+/// [MarkdownFileLoader] uses strict typing between the iterator methods to ensure that transitions
+///  between different implementations of the loaders and it's methods are handled properly by
+///  the compiler.
+pub struct MarkdownFileLoader<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a> MarkdownFileLoader<'a, Result<PathBuf, MarkdownLoaderError>> {
+    /// Loads the files within the iterator returned by [MarkdownFileLoader::with_glob] or
+    ///  [MarkdownFileLoader::with_dir] as [MarkdownDocument]s (front matter and body).
+    ///
+    /// # Example
+    /// Load files in directory "docs/*.md" and print their front matter.
+    ///
+    /// ```rust
+    /// let content = MarkdownFileLoader::with_glob("docs/*.md")?.load().into_iter();
+    /// for result in content {
+    ///     match result {
+    ///         Ok(doc) => println!("{:?}", doc.front_matter),
+    ///         Err(e) => eprintln!("Error reading file: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn load(self) -> MarkdownFileLoader<'a, Result<MarkdownDocument, MarkdownLoaderError>> {
+        MarkdownFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load())),
+        }
+    }
+
+    /// Loads the files within the iterator returned by [MarkdownFileLoader::with_glob] or
+    ///  [MarkdownFileLoader::with_dir] as [MarkdownDocument]s along with their path.
+    pub fn load_with_path(
+        self,
+    ) -> MarkdownFileLoader<'a, Result<(PathBuf, MarkdownDocument), MarkdownLoaderError>> {
+        MarkdownFileLoader {
+            iterator: Box::new(self.iterator.map(|res| res.load_with_path())),
+        }
+    }
+
+    /// Reads the body (i.e.: the content without the front matter) of the files within the
+    ///  iterator returned by [MarkdownFileLoader::with_glob] or [MarkdownFileLoader::with_dir].
+    pub fn read(self) -> MarkdownFileLoader<'a, Result<String, MarkdownLoaderError>> {
+        MarkdownFileLoader {
+            iterator: Box::new(self.iterator.map(|res| Ok(res.load()?.body))),
+        }
+    }
+
+    /// Reads the body (i.e.: the content without the front matter) of the files within the
+    ///  iterator returned by [MarkdownFileLoader::with_glob] or [MarkdownFileLoader::with_dir]
+    ///  and returns the path along with the content.
+    pub fn read_with_path(
+        self,
+    ) -> MarkdownFileLoader<'a, Result<(PathBuf, String), MarkdownLoaderError>> {
+        MarkdownFileLoader {
+            iterator: Box::new(self.iterator.map(|res| {
+                let (path, doc) = res.load_with_path()?;
+                Ok((path, doc.body))
+            })),
+        }
+    }
+}
+
+impl<'a, T: 'a> MarkdownFileLoader<'a, Result<T, MarkdownLoaderError>> {
+    /// Ignores errors in the iterator, returning only successful results. This can be used on any
+    ///  [MarkdownFileLoader] state of iterator whose items are results.
+    ///
+    /// # Example
+    /// Read files in directory "docs/*.md" and ignore errors from unreadable files.
+    ///
+    /// ```rust
+    /// let content = MarkdownFileLoader::with_glob("docs/*.md")?.read().ignore_errors().into_iter();
+    /// for content in content {
+    ///     println!("{}", content)
+    /// }
+    /// ```
+    pub fn ignore_errors(self) -> MarkdownFileLoader<'a, T> {
+        MarkdownFileLoader {
+            iterator: Box::new(self.iterator.filter_map(|res| res.ok())),
+        }
+    }
+}
+
+impl MarkdownFileLoader<'_, Result<PathBuf, MarkdownLoaderError>> {
+    /// Creates a new [MarkdownFileLoader] using a glob pattern to match files.
+    ///
+    /// # Example
+    /// Create a [MarkdownFileLoader] for all `.md` files that match the glob "docs/*.md".
+    ///
+    /// ```rust
+    /// let loader = MarkdownFileLoader::with_glob("docs/*.md")?;
+    /// ```
+    pub fn with_glob(
+        pattern: &str,
+    ) -> Result<MarkdownFileLoader<'_, Result<PathBuf, MarkdownLoaderError>>, MarkdownLoaderError>
+    {
+        let paths = glob(pattern).map_err(FileLoaderError::PatternError)?;
+        Ok(MarkdownFileLoader {
+            iterator: Box::new(paths.into_iter().map(|path| {
+                path.map_err(FileLoaderError::GlobError)
+                    .map_err(MarkdownLoaderError::FileLoaderError)
+            })),
+        })
+    }
+
+    /// Creates a new [MarkdownFileLoader] on all files within a directory.
+    ///
+    /// # Example
+    /// Create a [MarkdownFileLoader] for all files that are in the directory "docs".
+    ///
+    /// ```rust
+    /// let loader = MarkdownFileLoader::with_dir("docs")?;
+    /// ```
+    pub fn with_dir(
+        directory: &str,
+    ) -> Result<MarkdownFileLoader<'_, Result<PathBuf, MarkdownLoaderError>>, MarkdownLoaderError>
+    {
+        Ok(MarkdownFileLoader {
+            iterator: Box::new(
+                fs::read_dir(directory)
+                    .map_err(FileLoaderError::IoError)?
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        path.is_file().then_some(Ok(path))
+                    }),
+            ),
+        })
+    }
+}
+
+// ================================================================
+// MarkdownFileLoader iterator implementations
+// ================================================================
+
+pub struct IntoIter<'a, T> {
+    iterator: Box<dyn Iterator<Item = T> + 'a>,
+}
+
+impl<'a, T> IntoIterator for MarkdownFileLoader<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iterator: self.iterator,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iterator.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+    use serde_json::json;
+
+    use super::{MarkdownDocument, MarkdownFileLoader};
+
+    #[test]
+    fn test_yaml_front_matter() {
+        let doc = MarkdownDocument::parse(
+            "---\ntitle: Flurbo\ntags: [alien, green]\n---\n# Flurbo\nA green alien.\n",
+        )
+        .unwrap();
+
+        assert_eq!(doc.metadata("title").as_deref(), Some("Flurbo"));
+        assert_eq!(doc.front_matter["tags"], json!(["alien", "green"]));
+        assert_eq!(doc.body, "# Flurbo\nA green alien.\n");
+    }
+
+    #[test]
+    fn test_toml_front_matter() {
+        let doc =
+            MarkdownDocument::parse("+++\ntitle = \"Flurbo\"\nweight = 3\n+++\nBody").unwrap();
+
+        assert_eq!(doc.metadata("title").as_deref(), Some("Flurbo"));
+        assert_eq!(doc.metadata("weight").as_deref(), Some("3"));
+        assert_eq!(doc.body, "Body");
+    }
+
+    #[test]
+    fn test_no_front_matter() {
+        let doc = MarkdownDocument::parse("# Title\n---\nNot front matter").unwrap();
+
+        assert!(doc.front_matter.is_empty());
+        assert_eq!(doc.body, "# Title\n---\nNot front matter");
+    }
+
+    #[test]
+    fn test_markdown_loader() {
+        let temp = assert_fs::TempDir::new().expect("Failed to create temp dir");
+        temp.child("foo.md")
+            .write_str("---\ntitle: Foo\n---\nfoo")
+            .expect("Failed to write to foo");
+        temp.child("bar.md")
+            .write_str("---\n: invalid: yaml\n---\nbar")
+            .expect("Failed to write to bar");
+
+        let glob = temp.path().to_string_lossy().to_string() + "/*.md";
+        let actual = MarkdownFileLoader::with_glob(&glob)
+            .unwrap()
+            .load()
+            .ignore_errors()
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].metadata("title").as_deref(), Some("Foo"));
+        assert_eq!(actual[0].body, "foo");
+    }
+}
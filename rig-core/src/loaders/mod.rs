@@ -9,8 +9,22 @@
 //! and keeping track of the page numbers along with their contents.
 //!
 //! Note: The [PdfFileLoader] requires the `pdf` feature to be enabled in the `Cargo.toml` file.
+//!
+//! The [splitter] module provides text splitters that break the loaded contents into chunks
+//! small enough to be embedded, keeping track of where each chunk came from.
+//!
+//! Format-specific loaders follow the same conventions as the [FileLoader] (glob or directory
+//! sources, `ignore_errors`, path tracking) and are each gated behind a feature of the same name:
+//! - `html`: [HtmlFileLoader] extracts the main text of HTML pages, dropping boilerplate such as
+//!   navigation, headers, footers and scripts.
+//! - `markdown`: [MarkdownFileLoader] separates the YAML or TOML front matter from the body.
+//! - `csv` and `jsonl`: [CsvFileLoader] and [JsonlFileLoader] turn each row into a document
+//!   using a [template::DocumentTemplate].
+//! - `epub`: [EpubFileLoader] extracts the text of EPUB books chapter by chapter.
 
 pub mod file;
+pub mod splitter;
+pub mod template;
 
 pub use file::FileLoader;
 
@@ -19,3 +33,33 @@ pub mod pdf;
 
 #[cfg(feature = "pdf")]
 pub use pdf::PdfFileLoader;
+
+#[cfg(feature = "html")]
+pub mod html;
+
+#[cfg(feature = "html")]
+pub use html::HtmlFileLoader;
+
+#[cfg(feature = "markdown")]
+pub mod markdown;
+
+#[cfg(feature = "markdown")]
+pub use markdown::MarkdownFileLoader;
+
+#[cfg(feature = "csv")]
+pub mod csv;
+
+#[cfg(feature = "csv")]
+pub use csv::CsvFileLoader;
+
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+
+#[cfg(feature = "jsonl")]
+pub use jsonl::JsonlFileLoader;
+
+#[cfg(feature = "epub")]
+pub mod epub;
+
+#[cfg(feature = "epub")]
+pub use epub::EpubFileLoader;
@@ -4,7 +4,10 @@ use glob::glob;
 use lopdf::{Document, Error as LopdfError};
 use thiserror::Error;
 
-use super::file::FileLoaderError;
+use super::{
+    file::FileLoaderError,
+    splitter::{Chunk, TextSplitter},
+};
 
 #[derive(Error, Debug)]
 pub enum PdfLoaderError {
@@ -315,6 +318,37 @@ impl<'a> PdfFileLoader<'a, ByPage> {
     }
 }
 
+impl<'a> PdfFileLoader<'a, (PathBuf, Vec<(usize, String)>)> {
+    /// Splits the pages of the loaded documents into chunks using the given [TextSplitter].
+    ///  Each chunk keeps track of the path and page number it was loaded from. Chunks never
+    ///  span multiple pages.
+    ///
+    /// # Example
+    /// Read pdfs in directory "tests/data/*.pdf" and split their pages into chunks.
+    ///
+    /// ```rust
+    /// let chunks = PdfFileLoader::with_glob("tests/data/*.pdf")?
+    ///     .load_with_path()
+    ///     .ignore_errors()
+    ///     .by_page()
+    ///     .ignore_errors()
+    ///     .split(RecursiveCharacterSplitter::new(1000, 200));
+    /// for chunk in chunks {
+    ///     println!("{:?} page {:?}: {}", chunk.metadata.path, chunk.metadata.page, chunk.text);
+    /// }
+    /// ```
+    pub fn split<S: TextSplitter + 'a>(self, splitter: S) -> PdfFileLoader<'a, Chunk> {
+        PdfFileLoader {
+            iterator: Box::new(self.iterator.flat_map(move |(path, pages)| {
+                pages
+                    .into_iter()
+                    .flat_map(|(page_no, content)| splitter.split_page(&path, page_no, &content))
+                    .collect::<Vec<_>>()
+            })),
+        }
+    }
+}
+
 impl<'a, T: 'a> PdfFileLoader<'a, Result<T, PdfLoaderError>> {
     /// Ignores errors in the iterator, returning only successful results. This can be used on any
     ///  [PdfFileLoader] state of iterator whose items are results.
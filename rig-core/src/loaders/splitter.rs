@@ -0,0 +1,1064 @@
+//! This module provides text splitters that break loaded documents into chunks small enough to
+//! be embedded.
+//!
+//! All splitters implement the [TextSplitter] trait and return [Chunk]s, which carry the chunk
+//! text along with provenance metadata (source path, page number, byte range within the source
+//! text and the section the chunk belongs to). [Chunk] implements [Embed], so the output of a
+//! splitter can be passed directly to the [EmbeddingsBuilder](crate::embeddings::EmbeddingsBuilder).
+//!
+//! The following splitters are provided:
+//! - [RecursiveCharacterSplitter]: splits on a list of separators (paragraphs, lines, words, ...),
+//!   only falling back to finer separators when a piece is still too large.
+//! - [SentenceSplitter]: splits on sentence boundaries.
+//! - [TokenSplitter]: splits on words, measuring chunk sizes in tokens with a pluggable tokenizer.
+//! - [MarkdownSplitter]: splits on Markdown headings and records the heading path of each chunk.
+//! - [CodeSplitter]: splits source code on top-level items (functions, impl blocks, classes, ...).
+//!
+//! # Example
+//! ```rust
+//! use rig::loaders::{FileLoader, splitter::RecursiveCharacterSplitter};
+//!
+//! let chunks = FileLoader::with_glob("docs/*.txt")?
+//!     .read_with_path()
+//!     .ignore_errors()
+//!     .split(RecursiveCharacterSplitter::new(1000, 200))
+//!     .into_iter()
+//!     .collect::<Vec<_>>();
+//!
+//! for chunk in chunks {
+//!     println!("{:?} {:?}: {}", chunk.metadata.path, chunk.metadata.byte_range, chunk.text);
+//! }
+//! ```
+
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::embeddings::{Embed, EmbedError, TextEmbedder};
+
+// ================================================================
+// Chunk definitions
+// ================================================================
+
+/// Provenance metadata of a [Chunk].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkMetadata {
+    /// Path of the file the chunk was loaded from, if any
+    pub path: Option<PathBuf>,
+    /// Page number of the chunk within the source document, for paged documents (e.g.: PDFs)
+    pub page: Option<usize>,
+    /// Byte range of the chunk within the source text
+    pub byte_range: Range<usize>,
+    /// Position of the chunk within the source text
+    pub index: usize,
+    /// Section the chunk belongs to (e.g.: the Markdown heading path or the code item signature)
+    pub section: Option<String>,
+}
+
+/// A piece of a larger text, along with its provenance metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub text: String,
+    pub metadata: ChunkMetadata,
+}
+
+impl Embed for Chunk {
+    fn embed(&self, embedder: &mut TextEmbedder) -> Result<(), EmbedError> {
+        embedder.embed(self.text.clone());
+        Ok(())
+    }
+}
+
+/// A byte range of a text produced by a [TextSplitter], optionally tagged with the section it
+/// belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub range: Range<usize>,
+    pub section: Option<String>,
+}
+
+impl Span {
+    fn new(range: Range<usize>) -> Self {
+        Self {
+            range,
+            section: None,
+        }
+    }
+}
+
+/// Function used to measure the size of a piece of text
+pub type LengthFunction = Arc<dyn Fn(&str) -> usize + Send + Sync>;
+
+fn char_length() -> LengthFunction {
+    Arc::new(|text: &str| text.chars().count())
+}
+
+// ================================================================
+// TextSplitter trait
+// ================================================================
+
+/// Trait for types that can split a text into smaller chunks.
+///
+/// Implementors only need to provide [TextSplitter::split_spans], which returns the byte ranges
+/// of the chunks. The remaining methods turn those ranges into [Chunk]s with metadata.
+pub trait TextSplitter {
+    /// Split `text` into spans. Spans are byte ranges into `text` and may overlap.
+    fn split_spans(&self, text: &str) -> Vec<Span>;
+
+    /// Split `text` into chunks.
+    fn split(&self, text: &str) -> Vec<Chunk> {
+        self.split_spans(text)
+            .into_iter()
+            .filter_map(|span| {
+                let range = trim_range(text, span.range);
+                (!range.is_empty()).then_some(Span {
+                    range,
+                    section: span.section,
+                })
+            })
+            .enumerate()
+            .map(|(index, span)| Chunk {
+                text: text[span.range.clone()].to_string(),
+                metadata: ChunkMetadata {
+                    path: None,
+                    page: None,
+                    byte_range: span.range,
+                    index,
+                    section: span.section,
+                },
+            })
+            .collect()
+    }
+
+    /// Split `text`, loaded from the file at `path`, into chunks.
+    fn split_with_path(&self, path: &Path, text: &str) -> Vec<Chunk> {
+        self.split(text)
+            .into_iter()
+            .map(|mut chunk| {
+                chunk.metadata.path = Some(path.to_path_buf());
+                chunk
+            })
+            .collect()
+    }
+
+    /// Split `text`, loaded from page `page` of the file at `path`, into chunks.
+    fn split_page(&self, path: &Path, page: usize, text: &str) -> Vec<Chunk> {
+        self.split_with_path(path, text)
+            .into_iter()
+            .map(|mut chunk| {
+                chunk.metadata.page = Some(page);
+                chunk
+            })
+            .collect()
+    }
+}
+
+impl<T: TextSplitter + ?Sized> TextSplitter for &T {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        (*self).split_spans(text)
+    }
+}
+
+// ================================================================
+// Shared helpers
+// ================================================================
+
+/// Shrink `range` so that it does not start or end with whitespace.
+fn trim_range(text: &str, range: Range<usize>) -> Range<usize> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    start..end.max(start)
+}
+
+/// Split `range` of `text` after every occurrence of `separator`, keeping the separator attached
+/// to the end of the preceding piece so that the pieces stay contiguous.
+fn split_keep(text: &str, range: Range<usize>, separator: &str) -> Vec<Range<usize>> {
+    let mut pieces = vec![];
+    let mut start = range.start;
+    for (offset, _) in text[range.clone()].match_indices(separator) {
+        let end = range.start + offset + separator.len();
+        if end > start {
+            pieces.push(start..end);
+            start = end;
+        }
+    }
+    if start < range.end {
+        pieces.push(start..range.end);
+    }
+    pieces
+}
+
+/// Split `range` of `text` into pieces of at most `size` characters.
+fn split_chars(text: &str, range: Range<usize>, size: usize) -> Vec<Range<usize>> {
+    let size = size.max(1);
+    let mut pieces = vec![];
+    let mut start = range.start;
+    for (count, (offset, _)) in text[range.clone()].char_indices().enumerate() {
+        if count > 0 && count % size == 0 {
+            pieces.push(start..range.start + offset);
+            start = range.start + offset;
+        }
+    }
+    if start < range.end {
+        pieces.push(start..range.end);
+    }
+    pieces
+}
+
+/// Recursively split `range` of `text` using the first separator of `separators` found in it,
+/// moving on to the next separators only for pieces that are still larger than `chunk_size`.
+fn split_recursive(
+    text: &str,
+    range: Range<usize>,
+    separators: &[String],
+    chunk_size: usize,
+    length: &LengthFunction,
+    out: &mut Vec<Range<usize>>,
+) {
+    if length(&text[range.clone()]) <= chunk_size {
+        out.push(range);
+        return;
+    }
+
+    let position = separators
+        .iter()
+        .position(|sep| !sep.is_empty() && text[range.clone()].contains(sep.as_str()));
+
+    match position {
+        Some(i) => split_keep(text, range, &separators[i])
+            .into_iter()
+            .for_each(|piece| {
+                split_recursive(text, piece, &separators[i + 1..], chunk_size, length, out)
+            }),
+        None => out.extend(split_chars(text, range, chunk_size)),
+    }
+}
+
+/// Greedily merge contiguous `segments` of `text` into chunks of at most `chunk_size` (as
+/// measured by `length`). Consecutive chunks share trailing segments totalling at most
+/// `chunk_overlap`. A chunk takes the section of its first segment.
+fn merge(
+    text: &str,
+    segments: Vec<Span>,
+    chunk_size: usize,
+    chunk_overlap: usize,
+    length: &LengthFunction,
+) -> Vec<Span> {
+    let chunk_overlap = chunk_overlap.min(chunk_size.saturating_sub(1));
+
+    let mut chunks = vec![];
+    let mut window: Vec<(Span, usize)> = vec![];
+    let mut window_len = 0;
+
+    let flush = |window: &[(Span, usize)], chunks: &mut Vec<Span>| {
+        if let (Some((first, _)), Some((last, _))) = (window.first(), window.last()) {
+            chunks.push(Span {
+                range: first.range.start..last.range.end,
+                section: first.section.clone(),
+            });
+        }
+    };
+
+    for segment in segments {
+        let segment_len = length(&text[segment.range.clone()]);
+
+        if window_len + segment_len > chunk_size && !window.is_empty() {
+            flush(&window, &mut chunks);
+
+            // Keep the trailing segments of the chunk as overlap for the next one
+            while window_len > chunk_overlap
+                || (window_len + segment_len > chunk_size && window_len > 0)
+            {
+                let (_, len) = window.remove(0);
+                window_len -= len;
+            }
+        }
+
+        window_len += segment_len;
+        window.push((segment, segment_len));
+    }
+    flush(&window, &mut chunks);
+
+    chunks
+}
+
+// ================================================================
+// RecursiveCharacterSplitter
+// ================================================================
+
+/// Splits text by recursively trying a list of separators, from the coarsest (paragraphs) to the
+/// finest (single characters), until every piece fits in the chunk size. Pieces are then merged
+/// back into chunks of up to `chunk_size` characters, with `chunk_overlap` characters of overlap.
+///
+/// # Example
+/// ```rust
+/// use rig::loaders::splitter::{RecursiveCharacterSplitter, TextSplitter};
+///
+/// let splitter = RecursiveCharacterSplitter::new(1000, 100);
+/// let chunks = splitter.split(&text);
+/// ```
+#[derive(Clone)]
+pub struct RecursiveCharacterSplitter {
+    chunk_size: usize,
+    chunk_overlap: usize,
+    separators: Vec<String>,
+    length: LengthFunction,
+}
+
+impl RecursiveCharacterSplitter {
+    /// Create a new splitter producing chunks of at most `chunk_size` characters,
+    /// with `chunk_overlap` characters of overlap between consecutive chunks.
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            chunk_size,
+            chunk_overlap,
+            separators: ["\n\n", "\n", ". ", " ", ""]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            length: char_length(),
+        }
+    }
+
+    /// Set the separators to split on, from coarsest to finest.
+    pub fn separators(mut self, separators: Vec<String>) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Set the function used to measure the size of chunks (defaults to the number of characters).
+    pub fn length_function(
+        mut self,
+        length: impl Fn(&str) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        self.length = Arc::new(length);
+        self
+    }
+}
+
+impl TextSplitter for RecursiveCharacterSplitter {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        let mut segments = vec![];
+        split_recursive(
+            text,
+            0..text.len(),
+            &self.separators,
+            self.chunk_size,
+            &self.length,
+            &mut segments,
+        );
+
+        merge(
+            text,
+            segments.into_iter().map(Span::new).collect(),
+            self.chunk_size,
+            self.chunk_overlap,
+            &self.length,
+        )
+    }
+}
+
+// ================================================================
+// SentenceSplitter
+// ================================================================
+
+/// Splits text on sentence boundaries, then merges sentences into chunks of up to `chunk_size`
+/// characters with `chunk_overlap` characters of overlap. Sentences are never cut unless a
+/// single sentence is larger than the chunk size.
+///
+/// A sentence boundary is a `.`, `!` or `?` followed by whitespace and a character that is not a
+/// lowercase letter (so that abbreviations such as "e.g. this" are not split), or a blank line.
+#[derive(Clone)]
+pub struct SentenceSplitter {
+    chunk_size: usize,
+    chunk_overlap: usize,
+}
+
+impl SentenceSplitter {
+    /// Create a new splitter producing chunks of at most `chunk_size` characters,
+    /// with `chunk_overlap` characters of overlap between consecutive chunks.
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            chunk_size,
+            chunk_overlap,
+        }
+    }
+}
+
+/// Return the byte ranges of the sentences of `text`.
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = vec![];
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let is_terminator = matches!(c, '.' | '!' | '?');
+        let is_break = c == '\n' && matches!(chars.peek(), Some((_, '\n')));
+        if !is_terminator && !is_break {
+            continue;
+        }
+
+        // Consume the closing punctuation and the whitespace following the sentence
+        let mut end = offset + c.len_utf8();
+        let mut saw_whitespace = is_break;
+        while let Some(&(offset, next)) = chars.peek() {
+            if next.is_whitespace() {
+                saw_whitespace = true;
+            } else if saw_whitespace || !matches!(next, '.' | '!' | '?' | '"' | '\'' | ')') {
+                break;
+            }
+            end = offset + next.len_utf8();
+            chars.next();
+        }
+
+        let next_is_lowercase = matches!(chars.peek(), Some((_, next)) if next.is_lowercase());
+        if saw_whitespace && (is_break || !next_is_lowercase) || end == text.len() {
+            sentences.push(start..end);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        sentences.push(start..text.len());
+    }
+
+    sentences
+}
+
+impl TextSplitter for SentenceSplitter {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        let length = char_length();
+        let fallback = [" ".to_string(), "".to_string()];
+
+        let mut segments = vec![];
+        for sentence in sentences(text) {
+            split_recursive(
+                text,
+                sentence,
+                &fallback,
+                self.chunk_size,
+                &length,
+                &mut segments,
+            );
+        }
+
+        merge(
+            text,
+            segments.into_iter().map(Span::new).collect(),
+            self.chunk_size,
+            self.chunk_overlap,
+            &length,
+        )
+    }
+}
+
+// ================================================================
+// TokenSplitter
+// ================================================================
+
+/// Splits text on word boundaries into chunks of up to `chunk_size` tokens, with `chunk_overlap`
+/// tokens of overlap between consecutive chunks.
+///
+/// Tokens are counted with a pluggable tokenizer function (see [TokenSplitter::tokenizer]).
+/// By default, tokens are estimated at one token per four characters, which is a reasonable
+/// approximation for English text and most BPE tokenizers. Token counts are computed per word,
+/// so the size of a chunk is the sum of the token counts of its words.
+///
+/// # Example
+/// ```rust
+/// use rig::loaders::splitter::{TokenSplitter, TextSplitter};
+///
+/// // Stay well below the 8191 token limit of OpenAI embedding models
+/// let splitter = TokenSplitter::new(512, 64)
+///     .tokenizer(|word| tokenizer.encode(word).len());
+/// let chunks = splitter.split(&text);
+/// ```
+#[derive(Clone)]
+pub struct TokenSplitter {
+    chunk_size: usize,
+    chunk_overlap: usize,
+    tokenizer: LengthFunction,
+}
+
+impl TokenSplitter {
+    /// Create a new splitter producing chunks of at most `chunk_size` tokens,
+    /// with `chunk_overlap` tokens of overlap between consecutive chunks.
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            chunk_size,
+            chunk_overlap,
+            tokenizer: Arc::new(estimate_tokens),
+        }
+    }
+
+    /// Set the function used to count the tokens of a piece of text.
+    pub fn tokenizer(mut self, tokenizer: impl Fn(&str) -> usize + Send + Sync + 'static) -> Self {
+        self.tokenizer = Arc::new(tokenizer);
+        self
+    }
+}
+
+/// Estimate the number of tokens of `text` at one token per four characters.
+pub fn estimate_tokens(text: &str) -> usize {
+    let chars = text.trim().chars().count();
+    chars.div_ceil(4)
+}
+
+impl TextSplitter for TokenSplitter {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        let mut segments = vec![];
+        let mut start = 0;
+        let mut in_word = false;
+        for (offset, c) in text.char_indices() {
+            if !c.is_whitespace() && !in_word && offset > start {
+                segments.push(Span::new(start..offset));
+                start = offset;
+            }
+            in_word = !c.is_whitespace();
+        }
+        if start < text.len() {
+            segments.push(Span::new(start..text.len()));
+        }
+
+        merge(
+            text,
+            segments,
+            self.chunk_size,
+            self.chunk_overlap,
+            &self.tokenizer,
+        )
+    }
+}
+
+// ================================================================
+// MarkdownSplitter
+// ================================================================
+
+/// Splits Markdown text on headings, so that chunks never span two sections. Sections larger than
+/// `chunk_size` characters are further split with a [RecursiveCharacterSplitter].
+///
+/// The heading path of each chunk (e.g.: `"Installation > Linux"`) is recorded in
+/// [ChunkMetadata::section]. Headings inside fenced code blocks are ignored.
+#[derive(Clone)]
+pub struct MarkdownSplitter {
+    inner: RecursiveCharacterSplitter,
+    max_level: usize,
+}
+
+impl MarkdownSplitter {
+    /// Create a new splitter producing chunks of at most `chunk_size` characters,
+    /// with `chunk_overlap` characters of overlap between consecutive chunks of a section.
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            inner: RecursiveCharacterSplitter::new(chunk_size, chunk_overlap),
+            max_level: 6,
+        }
+    }
+
+    /// Only split on headings up to level `max_level` (e.g.: `2` splits on `#` and `##`).
+    pub fn max_level(mut self, max_level: usize) -> Self {
+        self.max_level = max_level;
+        self
+    }
+}
+
+/// Parse an ATX heading line (e.g.: `## Title`), returning its level and title.
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_end();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+impl TextSplitter for MarkdownSplitter {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        let mut sections: Vec<Span> = vec![];
+        let mut headings: Vec<(usize, String)> = vec![];
+        let mut start = 0;
+        let mut offset = 0;
+        let mut fence: Option<&str> = None;
+
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+
+            match fence {
+                Some(marker) if trimmed.starts_with(marker) => fence = None,
+                Some(_) => {}
+                None if trimmed.starts_with("```") => fence = Some("```"),
+                None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+                None => {
+                    if let Some((level, title)) =
+                        parse_heading(line).filter(|(level, _)| *level <= self.max_level)
+                    {
+                        if offset > start {
+                            sections.push(Span {
+                                range: start..offset,
+                                section: section_name(&headings),
+                            });
+                        }
+                        start = offset;
+                        headings.retain(|(l, _)| *l < level);
+                        headings.push((level, title.to_string()));
+                    }
+                }
+            }
+
+            offset += line.len();
+        }
+        if start < text.len() {
+            sections.push(Span {
+                range: start..text.len(),
+                section: section_name(&headings),
+            });
+        }
+
+        sections
+            .into_iter()
+            .flat_map(|section| {
+                let offset = section.range.start;
+                self.inner
+                    .split_spans(&text[section.range])
+                    .into_iter()
+                    .map(move |span| Span {
+                        range: span.range.start + offset..span.range.end + offset,
+                        section: section.section.clone(),
+                    })
+            })
+            .collect()
+    }
+}
+
+fn section_name(headings: &[(usize, String)]) -> Option<String> {
+    (!headings.is_empty()).then(|| {
+        headings
+            .iter()
+            .map(|(_, title)| title.as_str())
+            .collect::<Vec<_>>()
+            .join(" > ")
+    })
+}
+
+// ================================================================
+// CodeSplitter
+// ================================================================
+
+/// Programming languages supported by the [CodeSplitter].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Java,
+    C,
+    Cpp,
+    Solidity,
+}
+
+impl Language {
+    /// Guess the language of a file from its extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "rs" => Some(Self::Rust),
+            "py" => Some(Self::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "ts" | "tsx" => Some(Self::TypeScript),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            "c" | "h" => Some(Self::C),
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" => Some(Self::Cpp),
+            "sol" => Some(Self::Solidity),
+            _ => None,
+        }
+    }
+
+    /// Guess the language of a file from its path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    fn is_indentation_based(&self) -> bool {
+        matches!(self, Self::Python)
+    }
+
+    fn single_quote_strings(&self) -> bool {
+        !matches!(self, Self::Rust)
+    }
+}
+
+/// Splits source code on top-level items (functions, impl blocks, classes, ...), so that chunks
+/// contain whole items whenever possible. Consecutive small items (e.g.: imports) are merged into
+/// chunks of up to `chunk_size` characters, and items larger than `chunk_size` are further split
+/// on blank lines, then lines.
+///
+/// Items are detected with a lightweight scanner (brace depth for C-like languages, indentation for
+/// Python) rather than a full parser. Comments and attributes directly preceding an item are kept
+/// with it. The signature of the first item of each chunk is recorded in [ChunkMetadata::section].
+///
+/// # Example
+/// ```rust
+/// use rig::loaders::splitter::{CodeSplitter, Language, TextSplitter};
+///
+/// let splitter = CodeSplitter::new(Language::Rust, 2000);
+/// let chunks = splitter.split(&source);
+/// ```
+#[derive(Clone)]
+pub struct CodeSplitter {
+    language: Language,
+    chunk_size: usize,
+    chunk_overlap: usize,
+}
+
+impl CodeSplitter {
+    /// Create a new splitter for `language` producing chunks of at most `chunk_size` characters.
+    pub fn new(language: Language, chunk_size: usize) -> Self {
+        Self {
+            language,
+            chunk_size,
+            chunk_overlap: 0,
+        }
+    }
+
+    /// Set the number of characters of overlap between consecutive chunks (defaults to 0).
+    pub fn chunk_overlap(mut self, chunk_overlap: usize) -> Self {
+        self.chunk_overlap = chunk_overlap;
+        self
+    }
+
+    /// Return the byte ranges of the top-level items of `text`.
+    fn items(&self, text: &str) -> Vec<Range<usize>> {
+        if self.language.is_indentation_based() {
+            indented_items(text)
+        } else {
+            braced_items(text, self.language.single_quote_strings())
+        }
+    }
+}
+
+/// Top-level items of brace-delimited languages: an item ends on the line where the brace depth
+/// returns to zero, or on a top-level line ending with `;`.
+fn braced_items(text: &str, single_quote_strings: bool) -> Vec<Range<usize>> {
+    let mut items = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+    let mut depth: usize = 0;
+    let mut in_block_comment = false;
+    let mut item_started = false;
+
+    for line in text.split_inclusive('\n') {
+        let mut closed_block = false;
+        let mut string: Option<char> = None;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if in_block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            match string {
+                Some(_) if c == '\\' => {
+                    chars.next();
+                }
+                Some(quote) if c == quote => string = None,
+                Some(_) => {}
+                None => match c {
+                    '/' if chars.peek() == Some(&'/') => break,
+                    '/' if chars.peek() == Some(&'*') => {
+                        chars.next();
+                        in_block_comment = true;
+                    }
+                    '"' | '`' => string = Some(c),
+                    '\'' if single_quote_strings => string = Some(c),
+                    // Rust char literal, as opposed to a lifetime
+                    '\'' => {
+                        if let Some(len) = char_literal_len(chars.clone()) {
+                            chars.nth(len - 1);
+                        }
+                    }
+                    '{' => depth += 1,
+                    '}' => {
+                        depth = depth.saturating_sub(1);
+                        closed_block |= depth == 0;
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        offset += line.len();
+        item_started |= !line.trim().is_empty();
+
+        let statement_end = depth == 0 && line.trim_end().ends_with(';');
+        if item_started && depth == 0 && (closed_block || statement_end) {
+            items.push(start..offset);
+            start = offset;
+            item_started = false;
+        }
+    }
+    if start < text.len() {
+        items.push(start..text.len());
+    }
+
+    items
+}
+
+/// Number of characters of the Rust char literal following its opening `'` in `rest` (e.g.:
+/// `x'`, `\''` or `\u{7FFF}'`), or `None` if `rest` follows the `'` of a lifetime.
+fn char_literal_len(mut rest: impl Iterator<Item = char>) -> Option<usize> {
+    match rest.next()? {
+        '\\' => {
+            let mut len = 1;
+            for c in rest {
+                len += 1;
+                if c == '\'' && len > 2 {
+                    return Some(len);
+                }
+                if c == '\n' || len > 12 {
+                    return None;
+                }
+            }
+            None
+        }
+        '\'' | '\n' => None,
+        _ => (rest.next()? == '\'').then_some(2),
+    }
+}
+
+/// Top-level items of indentation-based languages: an item starts at every non-indented line,
+/// unless the previous non-indented line is a decorator or a comment.
+fn indented_items(text: &str) -> Vec<Range<usize>> {
+    let mut items = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+    let mut attach_next = false;
+
+    for line in text.split_inclusive('\n') {
+        let is_top_level = !line.trim().is_empty() && !line.starts_with([' ', '\t']);
+        if is_top_level {
+            if !attach_next && offset > start {
+                items.push(start..offset);
+                start = offset;
+            }
+            attach_next = line.starts_with(['@', '#']);
+        }
+        offset += line.len();
+    }
+    if start < text.len() {
+        items.push(start..text.len());
+    }
+
+    items
+}
+
+/// Signature of a code item: its first line that is not a comment or attribute.
+fn item_signature(item: &str) -> Option<String> {
+    item.lines()
+        .map(str::trim)
+        .find(|line| {
+            !line.is_empty()
+                && !["//", "/*", "*", "#", "@"]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix))
+        })
+        .map(|line| line.trim_end_matches('{').trim_end().to_string())
+}
+
+impl TextSplitter for CodeSplitter {
+    fn split_spans(&self, text: &str) -> Vec<Span> {
+        let length = char_length();
+        let fallback = ["\n\n".to_string(), "\n".to_string(), " ".to_string()];
+
+        let mut segments = vec![];
+        for item in self.items(text) {
+            let section = item_signature(&text[item.clone()]);
+            let mut pieces = vec![];
+            split_recursive(text, item, &fallback, self.chunk_size, &length, &mut pieces);
+            segments.extend(pieces.into_iter().map(|range| Span {
+                range,
+                section: section.clone(),
+            }));
+        }
+
+        merge(text, segments, self.chunk_size, self.chunk_overlap, &length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_recursive_character_splitter() {
+        let text = "First paragraph is here.\n\nSecond paragraph, which is a bit longer than the first one.";
+        let chunks = RecursiveCharacterSplitter::new(30, 0).split(text);
+
+        assert!(chunks.iter().all(|chunk| chunk.text.chars().count() <= 30));
+        assert_eq!(chunks[0].text, "First paragraph is here.");
+        for chunk in &chunks {
+            assert_eq!(&text[chunk.metadata.byte_range.clone()], chunk.text);
+        }
+        assert_eq!(
+            chunks.iter().map(|c| c.metadata.index).collect::<Vec<_>>(),
+            (0..chunks.len()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_recursive_character_splitter_overlap() {
+        let text = "one two three four five six seven eight nine ten";
+        let chunks = RecursiveCharacterSplitter::new(14, 6).split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "one two three",
+                "three four",
+                "four five six",
+                "six seven",
+                "seven eight",
+                "eight nine ten"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sentence_splitter() {
+        let text = "Hello world. This is e.g. a test! Is it working? Yes.";
+        let chunks = SentenceSplitter::new(25, 0).split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "Hello world.",
+                "This is e.g. a test!",
+                "Is it working? Yes."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_splitter() {
+        let text = "alpha beta gamma delta epsilon";
+        let chunks = TokenSplitter::new(2, 1)
+            .tokenizer(|word| word.split_whitespace().count())
+            .split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec!["alpha beta", "beta gamma", "gamma delta", "delta epsilon"]
+        );
+    }
+
+    #[test]
+    fn test_markdown_splitter() {
+        let text = "# Guide\nIntro.\n## Install\nRun it.\n```sh\n# not a heading\n```\n## Usage\nUse it.\n";
+        let chunks = MarkdownSplitter::new(100, 0).split(text);
+
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.metadata.section.as_deref())
+                .collect::<Vec<_>>(),
+            vec![
+                Some("Guide"),
+                Some("Guide > Install"),
+                Some("Guide > Usage")
+            ]
+        );
+        assert_eq!(
+            chunks[1].text,
+            "## Install\nRun it.\n```sh\n# not a heading\n```"
+        );
+    }
+
+    #[test]
+    fn test_code_splitter_rust() {
+        let text = "use std::fmt;\n\n/// Adds one\nfn add_one(x: i32) -> i32 {\n    let s = \"}\";\n    x + 1\n}\n\nimpl Foo {\n    fn bar(&self) {}\n}\n";
+        let chunks = CodeSplitter::new(Language::Rust, 80).split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "use std::fmt;",
+                "/// Adds one\nfn add_one(x: i32) -> i32 {\n    let s = \"}\";\n    x + 1\n}",
+                "impl Foo {\n    fn bar(&self) {}\n}"
+            ]
+        );
+        assert_eq!(
+            chunks[1].metadata.section.as_deref(),
+            Some("fn add_one(x: i32) -> i32")
+        );
+    }
+
+    #[test]
+    fn test_code_splitter_rust_char_literals() {
+        let text = "struct Foo;\n\nfn open(c: char) -> bool {\n    matches!(c, '{' | '\"' | '\\'' | '\\u{7B}')\n}\n\nfn first<'a>(s: &'a str) -> &'a str {\n    s\n}\n";
+        let chunks = CodeSplitter::new(Language::Rust, 80).split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "struct Foo;",
+                "fn open(c: char) -> bool {\n    matches!(c, '{' | '\"' | '\\'' | '\\u{7B}')\n}",
+                "fn first<'a>(s: &'a str) -> &'a str {\n    s\n}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_splitter_python() {
+        let text = "import os\n\n@decorator\ndef foo():\n    return 1\n\nclass Bar:\n    pass\n";
+        let chunks = CodeSplitter::new(Language::Python, 40).split(text);
+
+        assert_eq!(
+            texts(&chunks),
+            vec![
+                "import os",
+                "@decorator\ndef foo():\n    return 1",
+                "class Bar:\n    pass"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_page_metadata() {
+        let chunks = RecursiveCharacterSplitter::new(100, 0).split_page(
+            Path::new("doc.pdf"),
+            3,
+            "Some page text",
+        );
+
+        assert_eq!(
+            chunks[0].metadata,
+            ChunkMetadata {
+                path: Some(PathBuf::from("doc.pdf")),
+                page: Some(3),
+                byte_range: 0..14,
+                index: 0,
+                section: None,
+            }
+        );
+    }
+}
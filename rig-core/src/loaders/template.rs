@@ -0,0 +1,200 @@
+//! Templates used to turn structured rows (e.g.: CSV records or JSONL objects) into
+//! [Document]s that can be embedded or added to an agent's context.
+//!
+//! # Example
+//! ```rust
+//! use rig::loaders::template::DocumentTemplate;
+//! use serde_json::json;
+//!
+//! let template = DocumentTemplate::new("{title}: {body}")
+//!     .id_column("id")
+//!     .metadata_columns(["author.name"]);
+//!
+//! let row = json!({"id": "42", "title": "Flurbos", "body": "...", "author": {"name": "Rick"}});
+//! let document = template.document("fallback-id", row.as_object().unwrap())?;
+//! assert_eq!(document.id, "42");
+//! assert_eq!(document.text, "Flurbos: ...");
+//! assert_eq!(document.additional_props["author.name"], "Rick");
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::completion::Document;
+
+/// A structured row, keyed by column name.
+pub type Row = Map<String, Value>;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TemplateError {
+    #[error("Missing column: {0}")]
+    MissingColumn(String),
+
+    #[error("Unclosed placeholder in template: {0}")]
+    UnclosedPlaceholder(String),
+}
+
+/// Renders [Row]s into [Document]s.
+///
+/// Placeholders are written as `{column}`. Nested JSON values can be accessed using dotted
+///  paths (e.g.: `{author.name}`) and literal braces are escaped as `{{` and `}}`.
+#[derive(Clone, Debug)]
+pub struct DocumentTemplate {
+    template: String,
+    id_column: Option<String>,
+    metadata_columns: Vec<String>,
+}
+
+impl DocumentTemplate {
+    /// Create a new template from a format string, e.g.: `"{title}: {body}"`.
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.to_string(),
+            id_column: None,
+            metadata_columns: vec![],
+        }
+    }
+
+    /// Use the value of `column` as the id of the rendered documents.
+    /// If not set, the documents are identified by their position in the loaded file.
+    pub fn id_column(mut self, column: &str) -> Self {
+        self.id_column = Some(column.to_string());
+        self
+    }
+
+    /// Copy the values of `columns` in the `additional_props` of the rendered documents.
+    pub fn metadata_columns(
+        mut self,
+        columns: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.metadata_columns = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Render the template text for a single row.
+    pub fn render(&self, row: &Row) -> Result<String, TemplateError> {
+        let mut output = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+
+        while let Some(pos) = rest.find(['{', '}']) {
+            output.push_str(&rest[..pos]);
+            let tail = &rest[pos..];
+
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                output.push_str(&tail[..1]);
+                rest = &tail[2..];
+            } else if let Some(column) = tail.strip_prefix('{') {
+                let end = column
+                    .find('}')
+                    .ok_or_else(|| TemplateError::UnclosedPlaceholder(tail.to_string()))?;
+                output.push_str(&lookup(row, column[..end].trim())?);
+                rest = &column[end + 1..];
+            } else {
+                // A lone closing brace is kept as is
+                output.push('}');
+                rest = &tail[1..];
+            }
+        }
+        output.push_str(rest);
+
+        Ok(output)
+    }
+
+    /// Render a row as a [Document]. `default_id` is used when no id column was configured.
+    pub fn document(&self, default_id: &str, row: &Row) -> Result<Document, TemplateError> {
+        let id = match &self.id_column {
+            Some(column) => lookup(row, column)?,
+            None => default_id.to_string(),
+        };
+
+        let additional_props = self
+            .metadata_columns
+            .iter()
+            .map(|column| Ok((column.clone(), lookup(row, column)?)))
+            .collect::<Result<HashMap<_, _>, TemplateError>>()?;
+
+        Ok(Document {
+            id,
+            text: self.render(row)?,
+            additional_props,
+        })
+    }
+}
+
+/// Look up a (possibly dotted) column in a row and render it as a string.
+/// Strings are returned without quotes, `null` values are rendered as an empty string.
+fn lookup(row: &Row, column: &str) -> Result<String, TemplateError> {
+    let value = row.get(column).or_else(|| {
+        let mut parts = column.split('.');
+        let first = row.get(parts.next()?)?;
+        parts.try_fold(first, |value, part| match value {
+            Value::Object(map) => map.get(part),
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+            _ => None,
+        })
+    });
+
+    match value {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(Value::Null) => Ok(String::new()),
+        Some(value) => Ok(value.to_string()),
+        None => Err(TemplateError::MissingColumn(column.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{DocumentTemplate, TemplateError};
+
+    #[test]
+    fn test_render_template() {
+        let row = json!({
+            "title": "Flurbo",
+            "count": 3,
+            "author": {"name": "Rick", "tags": ["a", "b"]},
+            "empty": null
+        });
+        let row = row.as_object().unwrap();
+
+        let template =
+            DocumentTemplate::new("{title} ({count}) by {author.name} {{{author.tags.1}}}{empty}");
+        assert_eq!(template.render(row).unwrap(), "Flurbo (3) by Rick {b}");
+
+        let template = DocumentTemplate::new("{missing}");
+        assert_eq!(
+            template.render(row),
+            Err(TemplateError::MissingColumn("missing".to_string()))
+        );
+
+        let template = DocumentTemplate::new("{title");
+        assert!(matches!(
+            template.render(row),
+            Err(TemplateError::UnclosedPlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn test_template_document() {
+        let row = json!({"id": 7, "title": "Flurbo", "lang": "en"});
+        let row = row.as_object().unwrap();
+
+        let document = DocumentTemplate::new("{title}")
+            .metadata_columns(["lang"])
+            .document("doc#0", row)
+            .unwrap();
+        assert_eq!(document.id, "doc#0");
+        assert_eq!(document.text, "Flurbo");
+        assert_eq!(document.additional_props["lang"], "en");
+
+        let document = DocumentTemplate::new("{title}")
+            .id_column("id")
+            .document("doc#0", row)
+            .unwrap();
+        assert_eq!(document.id, "7");
+        assert!(document.additional_props.is_empty());
+    }
+}
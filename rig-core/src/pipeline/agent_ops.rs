@@ -1,4 +1,5 @@
 use crate::{
+    classifier::{Classification, Classifier},
     completion::{self, CompletionModel},
     extractor::{ExtractionError, Extractor},
     vector_store,
@@ -150,6 +151,75 @@ where
     Extract::new(extractor)
 }
 
+pub struct Classify<M, Input, L>
+where
+    M: CompletionModel,
+    L: schemars::JsonSchema
+        + for<'a> serde::Deserialize<'a>
+        + serde::Serialize
+        + Send
+        + Sync
+        + 'static,
+{
+    classifier: Classifier<M, L>,
+    _in: std::marker::PhantomData<Input>,
+}
+
+impl<M, Input, L> Classify<M, Input, L>
+where
+    M: CompletionModel,
+    L: schemars::JsonSchema
+        + for<'a> serde::Deserialize<'a>
+        + serde::Serialize
+        + Send
+        + Sync
+        + 'static,
+{
+    pub(crate) fn new(classifier: Classifier<M, L>) -> Self {
+        Self {
+            classifier,
+            _in: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<M, Input, L> Op for Classify<M, Input, L>
+where
+    M: CompletionModel,
+    L: schemars::JsonSchema
+        + for<'a> serde::Deserialize<'a>
+        + serde::Serialize
+        + Send
+        + Sync
+        + 'static,
+    Input: Into<String> + Send + Sync,
+{
+    type Input = Input;
+    type Output = Result<Classification<L>, ExtractionError>;
+
+    async fn call(&self, input: Self::Input) -> Self::Output {
+        self.classifier.classify(&input.into()).await
+    }
+}
+
+/// Create a new classify operation.
+///
+/// The op will classify the input using the provided `classifier` and return the label with
+/// the highest confidence.
+pub fn classify<M, Input, L>(classifier: Classifier<M, L>) -> Classify<M, Input, L>
+where
+    M: CompletionModel,
+    L: schemars::JsonSchema
+        + for<'a> serde::Deserialize<'a>
+        + serde::Serialize
+        + Send
+        + Sync
+        + 'static,
+    Input: Into<String> + Send + Sync,
+{
+    Classify::new(classifier)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
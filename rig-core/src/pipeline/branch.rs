@@ -0,0 +1,224 @@
+//! Conditional ops that pick which sub-pipeline to run based on their input.
+use futures::future::BoxFuture;
+
+use super::Op;
+
+// ================================================================
+// Object-safe Op used to store heterogeneous routes
+// ================================================================
+trait DynOp<Input, Output>: Send + Sync {
+    fn call_boxed(&self, input: Input) -> BoxFuture<'_, Output>;
+}
+
+impl<T: Op> DynOp<T::Input, T::Output> for T {
+    fn call_boxed(&self, input: T::Input) -> BoxFuture<'_, T::Output> {
+        Box::pin(self.call(input))
+    }
+}
+
+// ================================================================
+// Branch: if/else on a predicate
+// ================================================================
+pub struct Branch<P, Op1, Op2> {
+    predicate: P,
+    if_true: Op1,
+    if_false: Op2,
+}
+
+impl<P, Op1, Op2> Branch<P, Op1, Op2> {
+    pub(crate) fn new(predicate: P, if_true: Op1, if_false: Op2) -> Self {
+        Self {
+            predicate,
+            if_true,
+            if_false,
+        }
+    }
+}
+
+impl<P, Op1, Op2> Op for Branch<P, Op1, Op2>
+where
+    P: Fn(&Op1::Input) -> bool + Send + Sync,
+    Op1: Op,
+    Op2: Op<Input = Op1::Input, Output = Op1::Output>,
+{
+    type Input = Op1::Input;
+    type Output = Op1::Output;
+
+    #[inline]
+    async fn call(&self, input: Self::Input) -> Self::Output {
+        if (self.predicate)(&input) {
+            self.if_true.call(input).await
+        } else {
+            self.if_false.call(input).await
+        }
+    }
+}
+
+/// Create an op that runs `if_true` on its input if `predicate` returns true for it, and
+/// `if_false` otherwise. Both ops must have the same input and output types.
+///
+/// # Example
+/// ```rust
+/// use rig::pipeline::{self, branch, map, Op};
+///
+/// let pipeline = pipeline::new()
+///     .chain(branch(
+///         |x: &i32| x % 2 == 0,
+///         map(|x: i32| format!("{x} is even")),
+///         map(|x: i32| format!("{x} is odd")),
+///     ));
+///
+/// let result = pipeline.call(2).await;
+/// assert_eq!(result, "2 is even");
+/// ```
+pub fn branch<P, Op1, Op2>(predicate: P, if_true: Op1, if_false: Op2) -> Branch<P, Op1, Op2>
+where
+    P: Fn(&Op1::Input) -> bool + Send + Sync,
+    Op1: Op,
+    Op2: Op<Input = Op1::Input, Output = Op1::Output>,
+{
+    Branch::new(predicate, if_true, if_false)
+}
+
+// ================================================================
+// Router: dispatch on the output of a classifier op
+// ================================================================
+type Route<K, Input, Output> = (K, Box<dyn DynOp<Input, Output>>);
+
+/// Builder for a [Router] op. See [router].
+pub struct RouterBuilder<C: Op, Output> {
+    classifier: C,
+    routes: Vec<Route<C::Output, C::Input, Output>>,
+}
+
+impl<C, Output> RouterBuilder<C, Output>
+where
+    C: Op,
+    C::Output: PartialEq,
+{
+    /// Run `op` when the classifier outputs `key`. If several routes have the same key, the
+    /// first one is used.
+    pub fn route<T>(mut self, key: C::Output, op: T) -> Self
+    where
+        T: Op<Input = C::Input, Output = Output> + 'static,
+    {
+        self.routes.push((key, Box::new(op)));
+        self
+    }
+
+    /// Run `op` when the classifier output does not match any route, and build the [Router].
+    pub fn otherwise<T>(self, op: T) -> Router<C, Output>
+    where
+        T: Op<Input = C::Input, Output = Output> + 'static,
+    {
+        Router {
+            classifier: self.classifier,
+            routes: self.routes,
+            default: Box::new(op),
+        }
+    }
+}
+
+/// An op that classifies its input with a classifier op (e.g.: a function, an extractor or
+/// an agent) and runs the sub-pipeline registered for the classifier output.
+pub struct Router<C: Op, Output> {
+    classifier: C,
+    routes: Vec<Route<C::Output, C::Input, Output>>,
+    default: Box<dyn DynOp<C::Input, Output>>,
+}
+
+impl<C, Output> Op for Router<C, Output>
+where
+    C: Op,
+    C::Input: Clone,
+    C::Output: PartialEq,
+    Output: Send + Sync,
+{
+    type Input = C::Input;
+    type Output = Output;
+
+    async fn call(&self, input: Self::Input) -> Self::Output {
+        let key = self.classifier.call(input.clone()).await;
+
+        let op = self
+            .routes
+            .iter()
+            .find(|(route, _)| *route == key)
+            .map(|(_, op)| op)
+            .unwrap_or(&self.default);
+
+        op.call_boxed(input).await
+    }
+}
+
+/// Create a [RouterBuilder] which dispatches its input to one of several sub-pipelines
+/// depending on the output of `classifier`. All sub-pipelines must have the same input
+/// and output types, and a default sub-pipeline must be provided.
+///
+/// # Example
+/// ```rust
+/// use rig::pipeline::{self, map, router, Op};
+///
+/// #[derive(PartialEq)]
+/// enum Intent { Question, Order, Other }
+///
+/// let pipeline = pipeline::new()
+///     .chain(
+///         router(map(|message: String| if message.ends_with('?') { Intent::Question } else { Intent::Other }))
+///             .route(Intent::Question, pipeline::new().prompt(support_agent))
+///             .route(Intent::Order, pipeline::new().prompt(sales_agent))
+///             .otherwise(map(|_| Ok("Sorry, I can't help with that.".to_string()))),
+///     );
+///
+/// let response = pipeline.call("How do I reset my password?".to_string()).await?;
+/// ```
+pub fn router<C, Output>(classifier: C) -> RouterBuilder<C, Output>
+where
+    C: Op,
+    C::Output: PartialEq,
+{
+    RouterBuilder {
+        classifier,
+        routes: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{self, map, then};
+
+    #[tokio::test]
+    async fn test_branch() {
+        let pipeline = pipeline::new().map(|x: i32| x + 1).chain(branch(
+            |x: &i32| x % 2 == 0,
+            map(|x: i32| format!("{x} is even")),
+            then(|x: i32| async move { format!("{x} is odd") }),
+        ));
+
+        assert_eq!(pipeline.call(1).await, "2 is even");
+        assert_eq!(pipeline.call(2).await, "3 is odd");
+    }
+
+    #[tokio::test]
+    async fn test_router() {
+        #[derive(PartialEq)]
+        enum Size {
+            Small,
+            Large,
+            Huge,
+        }
+
+        let router = router(map(|x: i32| match x {
+            x if x < 10 => Size::Small,
+            x if x < 100 => Size::Large,
+            _ => Size::Huge,
+        }))
+        .route(Size::Small, map(|x: i32| x + 1))
+        .route(Size::Large, map(|x: i32| x * 2))
+        .otherwise(map(|_: i32| 0));
+
+        let results = router.batch_call(3, vec![1, 10, 100]).await;
+        assert_eq!(results, vec![2, 20, 0]);
+    }
+}
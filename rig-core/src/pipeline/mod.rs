@@ -63,6 +63,20 @@
 //! assert_eq!(result, "Result: 2, 0");
 //! ```
 //!
+//! Fallible ops (i.e.: ops implementing [TryOp]) can be run concurrently with the
+//! [try_parallel!](crate::try_parallel!) macro, which fails as soon as one of the ops fails, or the
+//! [try_parallel_all!](crate::try_parallel_all!) macro, which waits for all ops to complete and
+//! returns every error that occurred. In both cases, the outputs of the ops can be of different
+//! types and are returned as a tuple.
+//!
+//! ## Control Flow
+//! Ops can be composed with control flow ops which are themselves regular ops:
+//! - [branch] runs one of two ops depending on a predicate, and [router] dispatches the input to
+//!   one of several sub-pipelines depending on the output of a classifier op.
+//! - [timeout](Op::timeout) and [try_timeout](TryOp::try_timeout) bound the time taken by an op.
+//! - [retry](TryOp::retry) retries a failed op according to a [Backoff](retry::Backoff) policy and
+//!   [fallback](TryOp::fallback) calls another op when an op fails.
+//!
 //! Notes:
 //! - The [chain](Op::chain) method is similar to the [map](Op::map) method but it allows
 //!   for chaining arbitrary operations, as long as they implement the [Op] trait.
@@ -88,17 +102,21 @@
 //! ```
 
 pub mod agent_ops;
+pub mod branch;
 pub mod op;
+pub mod retry;
+pub mod timeout;
 pub mod try_op;
 #[macro_use]
 pub mod parallel;
 
 use std::future::Future;
 
+pub use branch::{branch, router};
 pub use op::{map, passthrough, then, Op};
 pub use try_op::TryOp;
 
-use crate::{completion, extractor::Extractor, vector_store};
+use crate::{classifier::Classifier, completion, extractor::Extractor, vector_store};
 
 pub struct PipelineBuilder<E> {
     _error: std::marker::PhantomData<E>,
@@ -275,6 +293,46 @@ impl<E> PipelineBuilder<E> {
     {
         agent_ops::Extract::new(extractor)
     }
+
+    /// Add a classify operation to the current pipeline/op. The classify operation expects the
+    /// current pipeline to output a string. The classify operation will use the given `classifier`
+    /// to classify the string and return the label with the highest confidence.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rig::pipeline::{self, Op};
+    ///
+    /// #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize, schemars::JsonSchema)]
+    /// enum Intent {
+    ///     /// The user asks a question about the product
+    ///     Question,
+    ///     /// The user reports a bug
+    ///     BugReport,
+    /// }
+    ///
+    /// let classifier = openai_client.classifier::<Intent>("gpt-4o").build();
+    ///
+    /// let pipeline = pipeline::new().classify(classifier);
+    ///
+    /// let result = pipeline.call("The app crashes on startup".to_string()).await?;
+    /// assert_eq!(result.label, Intent::BugReport);
+    /// ```
+    pub fn classify<M, Input, L>(
+        self,
+        classifier: Classifier<M, L>,
+    ) -> agent_ops::Classify<M, Input, L>
+    where
+        M: completion::CompletionModel,
+        L: schemars::JsonSchema
+            + for<'a> serde::Deserialize<'a>
+            + serde::Serialize
+            + Send
+            + Sync
+            + 'static,
+        Input: Into<String> + Send + Sync,
+    {
+        agent_ops::Classify::new(classifier)
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -167,6 +167,31 @@ pub trait Op: Send + Sync {
     {
         Sequential::new(self, Prompt::new(prompt))
     }
+
+    /// Bound the time taken by the current op. The resulting op returns a
+    /// [TimeoutError](super::timeout::TimeoutError) if the current op does not complete within
+    /// `duration`, in which case the current op is cancelled.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rig::pipeline::{self, Op};
+    ///
+    /// let op = pipeline::new()
+    ///     .prompt(agent)
+    ///     .timeout(Duration::from_secs(30));
+    ///
+    /// match op.call("Hello!".to_string()).await {
+    ///     Ok(response) => println!("{:?}", response),
+    ///     Err(err) => eprintln!("{}", err),
+    /// }
+    /// ```
+    fn timeout(self, duration: std::time::Duration) -> super::timeout::Timeout<Self>
+    where
+        Self: Sized,
+    {
+        super::timeout::Timeout::new(self, duration)
+    }
 }
 
 impl<T: Op> Op for &T {
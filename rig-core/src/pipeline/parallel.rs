@@ -145,6 +145,19 @@ macro_rules! parallel_op {
     };
 }
 
+#[macro_export]
+macro_rules! try_parallel_op {
+    ($op1:tt, $op2:tt) => {
+        $crate::pipeline::try_op::TryParallel::new($op1, $op2)
+    };
+    ($op1:tt $(, $ops:tt)*) => {
+        $crate::pipeline::try_op::TryParallel::new(
+            $op1,
+            $crate::try_parallel_op!($($ops),*)
+        )
+    };
+}
+
 #[macro_export]
 macro_rules! tuple_pattern {
     ($id:ident +) => {
@@ -246,7 +259,7 @@ macro_rules! try_parallel_internal {
         munching: []
     ) => ({
         use $crate::pipeline::try_op::TryOp;
-        $crate::parallel_op!($($val),*)
+        $crate::try_parallel_op!($($val),*)
             .map_ok(|output| {
                 ($(
                     {
@@ -271,8 +284,42 @@ macro_rules! try_parallel {
     };
 }
 
+/// Runs all the given fallible ops concurrently and waits for all of them to complete, even if
+/// some fail. The resulting op returns the tuple of outputs if all ops succeeded, or the errors
+/// of all the ops that failed (in the order of the ops) otherwise.
+///
+/// Use [try_parallel!](crate::try_parallel!) instead to fail as soon as one of the ops fails.
+///
+/// # Example
+/// ```rust
+/// use rig::{pipeline::{self, map, then, TryOp}, try_parallel_all};
+///
+/// let pipeline = try_parallel_all!(
+///     then(|token: String| async move { gmgn.token_info(&token).await }),
+///     then(|token: String| async move { gmgn.holders(&token).await }),
+///     map(|token: String| notes.get(&token).cloned().ok_or(Error::NoNotes)),
+/// )
+/// .map_ok(|(info, holders, notes)| decide(info, holders, notes));
+///
+/// match pipeline.try_call("SOL".to_string()).await {
+///     Ok(decision) => println!("{decision:?}"),
+///     Err(errors) => errors.iter().for_each(|err| eprintln!("{err}")),
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_parallel_all {
+    ($($es:expr),+ $(,)?) => {{
+        use $crate::pipeline::{op::Op, try_op::CollectResults};
+
+        $crate::parallel!($($crate::pipeline::try_op::settle($es)),+)
+            .map(|results| results.collect_results())
+    }};
+}
+
 pub use parallel;
 pub use parallel_internal;
+pub use try_parallel;
+pub use try_parallel_all;
 
 #[cfg(test)]
 mod tests {
@@ -428,4 +475,34 @@ mod tests {
         let result = pipeline.try_call(1).await;
         assert_eq!(result, Err("1 is the number!".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_try_parallel_all_macro() {
+        let pipeline = try_parallel_all!(
+            map(|x: i32| Ok::<_, String>(x)),
+            then(|x: i32| async move { Ok::<_, String>(format!("{} is the number!", x)) }),
+            map(|x: i32| if x > 0 {
+                Ok(x == 1)
+            } else {
+                Err("x is not positive".to_string())
+            }),
+            map(|x: i32| if x > 1 {
+                Ok(x * 2)
+            } else {
+                Err("x is too small".to_string())
+            })
+        );
+
+        let result = pipeline.try_call(2).await;
+        assert_eq!(result, Ok((2, "2 is the number!".to_string(), false, 4)));
+
+        let result = pipeline.try_call(0).await;
+        assert_eq!(
+            result,
+            Err(vec![
+                "x is not positive".to_string(),
+                "x is too small".to_string()
+            ])
+        );
+    }
 }
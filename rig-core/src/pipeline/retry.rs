@@ -0,0 +1,249 @@
+//! Retry policies for fallible ops.
+use std::time::Duration;
+
+use futures_timer::Delay;
+
+use super::TryOp;
+
+// ================================================================
+// Backoff policy
+// ================================================================
+
+/// Backoff policy of a [Retry] op: how many times a failed op is retried and how long to wait
+/// between attempts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backoff {
+    max_retries: usize,
+    initial_delay: Duration,
+    multiplier: f64,
+    max_delay: Duration,
+}
+
+impl Backoff {
+    /// Retry up to 3 times, waiting `initial_delay` before the first retry and doubling the
+    /// delay after each subsequent failure (up to 30 seconds).
+    pub fn exponential(initial_delay: Duration) -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay,
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Retry up to 3 times, waiting `delay` between attempts.
+    pub fn constant(delay: Duration) -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: delay,
+            multiplier: 1.0,
+            max_delay: delay,
+        }
+    }
+
+    /// Retry up to 3 times without waiting between attempts.
+    pub fn immediate() -> Self {
+        Self::constant(Duration::ZERO)
+    }
+
+    /// Set the maximum number of retries (i.e.: the op is called at most `max_retries + 1` times).
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the factor by which the delay is multiplied after each failed retry.
+    /// Negative and NaN factors are replaced by 1.0, i.e.: a constant delay.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = if multiplier >= 0.0 { multiplier } else { 1.0 };
+        self
+    }
+
+    /// Set the upper bound of the delay between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Delay to wait before the given retry (starting at 0), or `None` if no retry is left.
+    pub fn delay(&self, retry: usize) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        if self.initial_delay.is_zero() {
+            return Some(Duration::ZERO);
+        }
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(retry as i32);
+        Some(Duration::from_secs_f64(
+            delay.min(self.max_delay.as_secs_f64()),
+        ))
+    }
+}
+
+// ================================================================
+// Retry op
+// ================================================================
+
+/// Decides whether a failed op should be retried given its error.
+/// Implemented for all `Fn(&E) -> bool` closures.
+pub trait RetryIf<E>: Send + Sync {
+    fn should_retry(&self, error: &E) -> bool;
+}
+
+impl<E, F> RetryIf<E> for F
+where
+    F: Fn(&E) -> bool + Send + Sync,
+{
+    fn should_retry(&self, error: &E) -> bool {
+        self(error)
+    }
+}
+
+/// Retry every error. Default condition of a [Retry] op.
+pub struct Always;
+
+impl<E> RetryIf<E> for Always {
+    fn should_retry(&self, _error: &E) -> bool {
+        true
+    }
+}
+
+pub struct Retry<Op, P = Always> {
+    op: Op,
+    backoff: Backoff,
+    retry_if: P,
+}
+
+impl<Op> Retry<Op> {
+    pub(crate) fn new(op: Op, backoff: Backoff) -> Self {
+        Self {
+            op,
+            backoff,
+            retry_if: Always,
+        }
+    }
+}
+
+impl<Op, P> Retry<Op, P> {
+    /// Only retry the errors for which `retry_if` returns true (e.g.: rate limits or network
+    /// errors). Other errors are returned immediately.
+    pub fn retry_if<Q>(self, retry_if: Q) -> Retry<Op, Q> {
+        Retry {
+            op: self.op,
+            backoff: self.backoff,
+            retry_if,
+        }
+    }
+}
+
+impl<Op, P> TryOp for Retry<Op, P>
+where
+    Op: TryOp,
+    Op::Input: Clone,
+    P: RetryIf<Op::Error>,
+{
+    type Input = Op::Input;
+    type Output = Op::Output;
+    type Error = Op::Error;
+
+    async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
+        let mut retry = 0;
+        loop {
+            match self.op.try_call(input.clone()).await {
+                Ok(output) => return Ok(output),
+                Err(err) if !self.retry_if.should_retry(&err) => return Err(err),
+                Err(err) => match self.backoff.delay(retry) {
+                    Some(delay) => {
+                        tracing::debug!(target: "rig", "Op failed, retrying in {:?} (retry {})", delay, retry + 1);
+                        if !delay.is_zero() {
+                            Delay::new(delay).await;
+                        }
+                        retry += 1;
+                    }
+                    None => return Err(err),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::Backoff;
+    use crate::pipeline::{map, TryOp};
+
+    #[test]
+    fn test_backoff_delays() {
+        let backoff = Backoff::exponential(Duration::from_millis(100))
+            .max_retries(4)
+            .max_delay(Duration::from_millis(500));
+
+        assert_eq!(
+            (0..5).map(|retry| backoff.delay(retry)).collect::<Vec<_>>(),
+            vec![
+                Some(Duration::from_millis(100)),
+                Some(Duration::from_millis(200)),
+                Some(Duration::from_millis(400)),
+                Some(Duration::from_millis(500)),
+                None
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let attempts = AtomicUsize::new(0);
+        let op = map(|x: i32| {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err("flaky")
+            } else {
+                Ok(x + 1)
+            }
+        })
+        .retry(Backoff::constant(Duration::from_millis(1)));
+
+        assert_eq!(op.try_call(1).await, Ok(2));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let op = map(|_: i32| Err::<i32, _>("fatal"))
+            .retry(Backoff::immediate().max_retries(5))
+            .retry_if(|err: &&str| *err != "fatal");
+        assert_eq!(op.try_call(1).await, Err("fatal"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let attempts = AtomicUsize::new(0);
+        let op = map(|_: i32| {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<i32, _>("down")
+        })
+        .retry(Backoff::immediate().max_retries(2));
+
+        assert_eq!(op.try_call(1).await, Err("down"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let results = op.try_batch_call(2, vec![1, 2]).await;
+        assert_eq!(results, Err("down"));
+    }
+
+    #[test]
+    fn test_backoff_invalid_multiplier() {
+        let initial_delay = Duration::from_millis(100);
+
+        for multiplier in [-2.0, f64::NAN] {
+            let backoff = Backoff::exponential(initial_delay).multiplier(multiplier);
+            assert_eq!(backoff.delay(2), Some(initial_delay));
+        }
+
+        let backoff = Backoff::exponential(Duration::ZERO).multiplier(f64::INFINITY);
+        assert_eq!(backoff.delay(2), Some(Duration::ZERO));
+        let backoff = Backoff::exponential(initial_delay).multiplier(f64::INFINITY);
+        assert_eq!(backoff.delay(2), Some(Duration::from_secs(30)));
+    }
+}
@@ -0,0 +1,117 @@
+//! Ops that bound the time taken by another op.
+use std::time::Duration;
+
+use futures::future::{self, Either};
+use futures_timer::Delay;
+
+use super::{Op, TryOp};
+
+/// Error returned when an op does not complete within its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Operation timed out after {0:?}")]
+pub struct TimeoutError(pub Duration);
+
+/// Op that returns [TimeoutError] if the wrapped op does not complete in time.
+/// The wrapped op is cancelled (i.e.: dropped) when the timeout expires.
+pub struct Timeout<Op> {
+    op: Op,
+    duration: Duration,
+}
+
+impl<Op> Timeout<Op> {
+    pub(crate) fn new(op: Op, duration: Duration) -> Self {
+        Self { op, duration }
+    }
+}
+
+impl<T: Op> Op for Timeout<T> {
+    type Input = T::Input;
+    type Output = Result<T::Output, TimeoutError>;
+
+    async fn call(&self, input: Self::Input) -> Self::Output {
+        let call = std::pin::pin!(self.op.call(input));
+        match future::select(call, Delay::new(self.duration)).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(_) => Err(TimeoutError(self.duration)),
+        }
+    }
+}
+
+/// Same as [Timeout] for fallible ops: the timeout is converted into the error type of the
+/// wrapped op, so that the result can be handled like any other failure (e.g.: retried).
+pub struct TryTimeout<Op> {
+    op: Op,
+    duration: Duration,
+}
+
+impl<Op> TryTimeout<Op> {
+    pub(crate) fn new(op: Op, duration: Duration) -> Self {
+        Self { op, duration }
+    }
+}
+
+impl<T> TryOp for TryTimeout<T>
+where
+    T: TryOp,
+    T::Error: From<TimeoutError>,
+{
+    type Input = T::Input;
+    type Output = T::Output;
+    type Error = T::Error;
+
+    async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
+        let call = std::pin::pin!(self.op.try_call(input));
+        match future::select(call, Delay::new(self.duration)).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(TimeoutError(self.duration).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::TimeoutError;
+    use crate::pipeline::{map, retry::Backoff, then, Op, TryOp};
+
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Timeout,
+    }
+
+    impl From<TimeoutError> for Error {
+        fn from(_: TimeoutError) -> Self {
+            Error::Timeout
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let op = map(|x: i32| x + 1).timeout(Duration::from_secs(1));
+        assert_eq!(op.call(1).await, Ok(2));
+
+        let op =
+            then(|_: i32| futures::future::pending::<i32>()).timeout(Duration::from_millis(10));
+        assert_eq!(
+            op.call(1).await,
+            Err(TimeoutError(Duration::from_millis(10)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_timeout_with_retry() {
+        let op = then(|x: i32| async move {
+            if x > 1 {
+                futures::future::pending().await
+            } else {
+                Ok::<_, Error>(x)
+            }
+        })
+        .try_timeout(Duration::from_millis(10))
+        .retry(Backoff::immediate().max_retries(1));
+
+        assert_eq!(op.try_call(1).await, Ok(1));
+        assert_eq!(op.try_call(2).await, Err(Error::Timeout));
+    }
+}
@@ -1,10 +1,13 @@
 use std::future::Future;
 
 use futures::stream;
-#[allow(unused_imports)] // Needed since this is used in a macro rule
 use futures::try_join;
 
-use super::op::{self};
+use super::{
+    op::{self},
+    retry::{Backoff, Retry},
+    timeout::{TimeoutError, TryTimeout},
+};
 
 // ================================================================
 // Core TryOp trait
@@ -186,6 +189,72 @@ pub trait TryOp: Send + Sync {
     {
         TrySequential::new(self, op)
     }
+
+    /// Retry the current op with the same input when it fails, waiting between attempts
+    /// according to the given [Backoff] policy. The last error is returned if all attempts fail.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rig::pipeline::{self, retry::Backoff, TryOp};
+    ///
+    /// let op = pipeline::new()
+    ///     .prompt(agent)
+    ///     .retry(Backoff::exponential(Duration::from_millis(500)).max_retries(5))
+    ///     .retry_if(|err: &PromptError| matches!(err, PromptError::CompletionError(_)));
+    ///
+    /// let result = op.try_call("Hello!".to_string()).await;
+    /// ```
+    fn retry(self, backoff: Backoff) -> Retry<Self>
+    where
+        Self::Input: Clone,
+        Self: Sized,
+    {
+        Retry::new(self, backoff)
+    }
+
+    /// Bound the time taken by the current op. If the op does not complete within `duration`,
+    /// it is cancelled and a [TimeoutError] converted into the error type of the op is returned.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use rig::pipeline::{self, TryOp};
+    ///
+    /// let op = pipeline::new()
+    ///     .then(|token: String| async move { gmgn.token_info(&token).await })
+    ///     .try_timeout(Duration::from_secs(5))
+    ///     .retry(Backoff::constant(Duration::from_secs(1)));
+    /// ```
+    fn try_timeout(self, duration: std::time::Duration) -> TryTimeout<Self>
+    where
+        Self::Error: From<TimeoutError>,
+        Self: Sized,
+    {
+        TryTimeout::new(self, duration)
+    }
+
+    /// Call the `fallback` op with the same input if the current op fails. The error of the
+    /// current op is discarded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rig::pipeline::{self, TryOp};
+    ///
+    /// let op = pipeline::new()
+    ///     .prompt(gpt4_agent)
+    ///     .fallback(pipeline::new().prompt(claude_agent));
+    ///
+    /// let result = op.try_call("Hello!".to_string()).await;
+    /// ```
+    fn fallback<T>(self, fallback: T) -> Fallback<Self, T>
+    where
+        T: TryOp<Input = Self::Input, Output = Self::Output>,
+        Self::Input: Clone,
+        Self: Sized,
+    {
+        Fallback::new(self, fallback)
+    }
 }
 
 impl<Op, T, E> TryOp for Op
@@ -345,33 +414,177 @@ where
     }
 }
 
-// TODO: Implement TryParallel
-// pub struct TryParallel<Op1, Op2> {
-//     op1: Op1,
-//     op2: Op2,
-// }
-
-// impl<Op1, Op2> TryParallel<Op1, Op2> {
-//     pub fn new(op1: Op1, op2: Op2) -> Self {
-//         Self { op1, op2 }
-//     }
-// }
-
-// impl<Op1, Op2> TryOp for TryParallel<Op1, Op2>
-// where
-//     Op1: TryOp,
-//     Op2: TryOp<Input = Op1::Input, Output = Op1::Output, Error = Op1::Error>,
-// {
-//     type Input = Op1::Input;
-//     type Output = (Op1::Output, Op2::Output);
-//     type Error = Op1::Error;
-
-//     #[inline]
-//     async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
-//         let (output1, output2) = tokio::join!(self.op1.try_call(input.clone()), self.op2.try_call(input));
-//         Ok((output1?, output2?))
-//     }
-// }
+pub struct Fallback<Op1, Op2> {
+    op: Op1,
+    fallback: Op2,
+}
+
+impl<Op1, Op2> Fallback<Op1, Op2> {
+    pub(crate) fn new(op: Op1, fallback: Op2) -> Self {
+        Self { op, fallback }
+    }
+}
+
+impl<Op1, Op2> TryOp for Fallback<Op1, Op2>
+where
+    Op1: TryOp,
+    Op1::Input: Clone,
+    Op2: TryOp<Input = Op1::Input, Output = Op1::Output>,
+{
+    type Input = Op1::Input;
+    type Output = Op1::Output;
+    type Error = Op2::Error;
+
+    #[inline]
+    async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
+        match self.op.try_call(input.clone()).await {
+            Ok(output) => Ok(output),
+            Err(_) => self.fallback.try_call(input).await,
+        }
+    }
+}
+
+/// Error handling mode of a [TryParallel] op where the first error is returned as soon as it
+/// occurs, cancelling the other op.
+pub struct FailFast;
+
+/// Error handling mode of a [TryParallel] op where both ops always run to completion and all
+/// errors are returned.
+pub struct CollectAll;
+
+/// Runs two fallible ops concurrently on (a clone of) the same input.
+///
+/// By default, [TryParallel] fails fast: the first error is returned and the other op is
+/// cancelled. Use [TryParallel::collect_all] to wait for both ops and get every error instead.
+///
+/// To run more than two ops concurrently, see the [try_parallel!](crate::try_parallel!) and
+/// [try_parallel_all!](crate::try_parallel_all!) macros.
+pub struct TryParallel<Op1, Op2, M = FailFast> {
+    op1: Op1,
+    op2: Op2,
+    _mode: std::marker::PhantomData<M>,
+}
+
+impl<Op1, Op2> TryParallel<Op1, Op2> {
+    pub fn new(op1: Op1, op2: Op2) -> Self {
+        Self {
+            op1,
+            op2,
+            _mode: std::marker::PhantomData,
+        }
+    }
+
+    /// Wait for both ops to complete and return all errors (in the order of the ops).
+    pub fn collect_all(self) -> TryParallel<Op1, Op2, CollectAll> {
+        TryParallel {
+            op1: self.op1,
+            op2: self.op2,
+            _mode: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Op1, Op2> TryOp for TryParallel<Op1, Op2, FailFast>
+where
+    Op1: TryOp,
+    Op1::Input: Clone,
+    Op2: TryOp<Input = Op1::Input, Error = Op1::Error>,
+{
+    type Input = Op1::Input;
+    type Output = (Op1::Output, Op2::Output);
+    type Error = Op1::Error;
+
+    #[inline]
+    async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
+        try_join!(self.op1.try_call(input.clone()), self.op2.try_call(input))
+    }
+}
+
+impl<Op1, Op2> TryOp for TryParallel<Op1, Op2, CollectAll>
+where
+    Op1: TryOp,
+    Op1::Input: Clone,
+    Op2: TryOp<Input = Op1::Input, Error = Op1::Error>,
+{
+    type Input = Op1::Input;
+    type Output = (Op1::Output, Op2::Output);
+    type Error = Vec<Op1::Error>;
+
+    #[inline]
+    async fn try_call(&self, input: Self::Input) -> Result<Self::Output, Self::Error> {
+        match futures::join!(self.op1.try_call(input.clone()), self.op2.try_call(input)) {
+            (Ok(output1), Ok(output2)) => Ok((output1, output2)),
+            (Err(err), Ok(_)) | (Ok(_), Err(err)) => Err(vec![err]),
+            (Err(err1), Err(err2)) => Err(vec![err1, err2]),
+        }
+    }
+}
+
+/// Turns a [TryOp] into an [Op](super::Op) whose output is the `Result` of the wrapped op,
+/// so that errors can be handled as regular values (e.g.: to wait for all ops of a
+/// [parallel!](crate::parallel!) to complete even if some of them fail).
+pub struct Settle<Op> {
+    op: Op,
+}
+
+impl<Op> Settle<Op> {
+    pub fn new(op: Op) -> Self {
+        Self { op }
+    }
+}
+
+impl<Op: TryOp> op::Op for Settle<Op> {
+    type Input = Op::Input;
+    type Output = Result<Op::Output, Op::Error>;
+
+    #[inline]
+    async fn call(&self, input: Self::Input) -> Self::Output {
+        self.op.try_call(input).await
+    }
+}
+
+/// Create a new [Settle] op wrapping `op`.
+pub fn settle<Op: TryOp>(op: Op) -> Settle<Op> {
+    Settle::new(op)
+}
+
+/// Fan-in of the results of parallel fallible ops: turns a tuple of `Result`s sharing the same
+/// error type into a `Result` of the tuple of outputs, or of all the errors that occurred.
+pub trait CollectResults {
+    type Output;
+    type Error;
+
+    fn collect_results(self) -> Result<Self::Output, Vec<Self::Error>>;
+}
+
+macro_rules! impl_collect_results {
+    ($($T:ident $r:ident),+) => {
+        impl<$($T,)+ E> CollectResults for ($(Result<$T, E>,)+) {
+            type Output = ($($T,)+);
+            type Error = E;
+
+            fn collect_results(self) -> Result<Self::Output, Vec<E>> {
+                let ($($r,)+) = self;
+                match ($($r,)+) {
+                    ($(Ok($r),)+) => Ok(($($r,)+)),
+                    ($($r,)+) => Err(vec![$($r.err()),+].into_iter().flatten().collect()),
+                }
+            }
+        }
+    };
+}
+
+impl_collect_results!(A a, B b);
+impl_collect_results!(A a, B b, C c);
+impl_collect_results!(A a, B b, C c, D d);
+impl_collect_results!(A a, B b, C c, D d, F f);
+impl_collect_results!(A a, B b, C c, D d, F f, G g);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h, I i);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h, I i, J j);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h, I i, J j, K k);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h, I i, J j, K k, L l);
+impl_collect_results!(A a, B b, C c, D d, F f, G g, H h, I i, J j, K k, L l, M m);
 
 #[cfg(test)]
 mod tests {
@@ -472,4 +685,63 @@ mod tests {
         let result = pipeline.try_call(1).await.unwrap();
         assert_eq!(result, 15);
     }
+
+    #[tokio::test]
+    async fn test_try_parallel_fail_fast() {
+        let pipeline = TryParallel::new(
+            map(|x: i32| Ok::<_, String>(x + 1)),
+            map(|x: i32| Ok::<_, String>(format!("{} is the number!", x))),
+        );
+        let result = pipeline.try_call(1).await;
+        assert_eq!(result, Ok((2, "1 is the number!".to_string())));
+
+        let pipeline = TryParallel::new(
+            then(|x: i32| async move { Err::<i32, _>(format!("op1 failed on {}", x)) }),
+            then(|_: i32| futures::future::pending::<Result<i32, String>>()),
+        );
+        let result = pipeline.try_call(1).await;
+        assert_eq!(result, Err("op1 failed on 1".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_try_parallel_collect_all() {
+        let pipeline = TryParallel::new(
+            map(|x: i32| if x > 0 { Ok(x) } else { Err("op1 failed") }),
+            map(|x: i32| if x > 1 { Ok(x) } else { Err("op2 failed") }),
+        )
+        .collect_all();
+
+        assert_eq!(pipeline.try_call(2).await, Ok((2, 2)));
+        assert_eq!(pipeline.try_call(1).await, Err(vec!["op2 failed"]));
+        assert_eq!(
+            pipeline.try_call(0).await,
+            Err(vec!["op1 failed", "op2 failed"])
+        );
+    }
+
+    #[test]
+    fn test_collect_results() {
+        let results = (Ok::<_, &str>(1), Ok("two"), Ok(3.0));
+        assert_eq!(results.collect_results(), Ok((1, "two", 3.0)));
+
+        let results = (Err::<i32, _>("a"), Ok("two"), Err::<f64, _>("c"));
+        assert_eq!(results.collect_results(), Err(vec!["a", "c"]));
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let op = map(|x: i32| if x % 2 == 0 { Ok(x) } else { Err("x is odd") }).fallback(map(
+            |x: i32| {
+                if x > 0 {
+                    Ok(x + 1)
+                } else {
+                    Err("x is negative")
+                }
+            },
+        ));
+
+        assert_eq!(op.try_call(2).await, Ok(2));
+        assert_eq!(op.try_call(3).await, Ok(4));
+        assert_eq!(op.try_call(-1).await, Err("x is negative"));
+    }
 }
@@ -1,6 +1,6 @@
 //! Anthropic client api implementation
 
-use crate::{agent::AgentBuilder, extractor::ExtractorBuilder};
+use crate::{agent::AgentBuilder, classifier::ClassifierBuilder, extractor::ExtractorBuilder};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -153,4 +153,13 @@ impl Client {
     ) -> ExtractorBuilder<T, CompletionModel> {
         ExtractorBuilder::new(self.completion_model(model))
     }
+
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        model: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(model))
+    }
 }
@@ -3,7 +3,7 @@
 use std::iter;
 
 use crate::{
-    completion::{self, CompletionError},
+    completion::{self, CompletionError, Document},
     json_utils,
 };
 
@@ -88,14 +88,31 @@ impl std::fmt::Display for Usage {
     }
 }
 
+impl From<&Usage> for completion::Usage {
+    fn from(usage: &Usage) -> Self {
+        let cache_read_tokens = usage.cache_read_input_tokens.unwrap_or(0);
+        let cache_write_tokens = usage.cache_creation_input_tokens.unwrap_or(0);
+
+        completion::Usage::new(
+            usage.input_tokens + cache_read_tokens + cache_write_tokens,
+            usage.output_tokens,
+        )
+        .with_cache(cache_read_tokens, cache_write_tokens)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ToolDefinition {
     pub name: String,
     pub description: Option<String>,
     pub input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<CacheControl>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Prompt caching breakpoint, set on the last tool definition or system block to cache (see
+/// [completion::CacheBreakpoints])
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CacheControl {
     Ephemeral,
@@ -109,11 +126,15 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
             [Content::String(text) | Content::Text { text, .. }, ..] => {
                 Ok(completion::CompletionResponse {
                     choice: completion::ModelChoice::Message(text.to_string()),
+                    usage: Some((&response.usage).into()),
+                    citations: vec![],
                     raw_response: response,
                 })
             }
             [Content::ToolUse { name, input, .. }, ..] => Ok(completion::CompletionResponse {
                 choice: completion::ModelChoice::ToolCall(name.clone(), input.clone()),
+                usage: Some((&response.usage).into()),
+                citations: vec![],
                 raw_response: response,
             }),
             _ => Err(CompletionError::ResponseError(
@@ -126,18 +147,108 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Message {
     pub role: String,
-    pub content: String,
+    pub content: MessageContent,
 }
 
 impl From<completion::Message> for Message {
     fn from(message: completion::Message) -> Self {
         Self {
             role: message.role,
-            content: message.content,
+            content: MessageContent::Text(message.content),
         }
     }
 }
 
+/// Content of a request message: a string, or content blocks for messages with images
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    Image {
+        source: ImageSource,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+impl From<completion::Image> for ImageSource {
+    fn from(image: completion::Image) -> Self {
+        match image {
+            completion::Image::Base64 { mime_type, data } => ImageSource::Base64 {
+                media_type: mime_type,
+                data,
+            },
+            completion::Image::Url(url) => ImageSource::Url { url },
+        }
+    }
+}
+
+/// Content of the prompt message, with images placed before the text as recommended by
+/// Anthropic
+fn prompt_content(prompt: String, images: Vec<completion::Image>) -> MessageContent {
+    if images.is_empty() {
+        return MessageContent::Text(prompt);
+    }
+
+    MessageContent::Blocks(
+        images
+            .into_iter()
+            .map(|image| ContentBlock::Image {
+                source: image.into(),
+            })
+            .chain(iter::once(ContentBlock::Text {
+                text: prompt,
+                cache_control: None,
+            }))
+            .collect(),
+    )
+}
+
+/// System prompt of a request: the preamble, followed by the cached context documents (see
+/// [completion::CacheBreakpoints]), which must be part of the system prompt to be cached
+/// independently of the chat history
+fn system_content(
+    preamble: String,
+    cache_preamble: bool,
+    cached_documents: &[Document],
+) -> MessageContent {
+    if (preamble.is_empty() || !cache_preamble) && cached_documents.is_empty() {
+        return MessageContent::Text(preamble);
+    }
+
+    let mut blocks = vec![];
+    if !preamble.is_empty() {
+        blocks.push(ContentBlock::Text {
+            text: preamble,
+            cache_control: cache_preamble.then_some(CacheControl::Ephemeral),
+        });
+    }
+    if !cached_documents.is_empty() {
+        blocks.push(ContentBlock::Text {
+            text: completion::attachments(cached_documents),
+            cache_control: Some(CacheControl::Ephemeral),
+        });
+    }
+
+    MessageContent::Blocks(blocks)
+}
+
 #[derive(Clone)]
 pub struct CompletionModel {
     client: Client,
@@ -178,26 +289,106 @@ struct Metadata {
     user_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// How the model uses the tools of the request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
-enum ToolChoice {
+pub enum ToolChoice {
+    /// The model decides whether to call a tool (default)
     Auto,
+    /// The model must call one of the tools
     Any,
+    /// The model must call the named tool
     Tool { name: String },
 }
 
-impl completion::CompletionModel for CompletionModel {
-    type Response = CompletionResponse;
+/// Anthropic-specific request parameters, to be passed as `additional_params` of a completion
+/// request or agent.
+///
+/// # Example
+/// ```
+/// use rig::providers::anthropic::{self, RequestParams, ToolChoice};
+///
+/// let client = anthropic::ClientBuilder::new("your-api-key").build();
+///
+/// let agent = client
+///     .agent(anthropic::CLAUDE_3_5_SONNET)
+///     .additional_params(
+///         RequestParams::default()
+///             .stop_sequences(["</answer>"])
+///             .top_k(40)
+///             .tool_choice(ToolChoice::Tool { name: "search".into() })
+///             .into(),
+///     )
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RequestParams {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
+}
 
-    async fn completion(
+impl RequestParams {
+    /// Sequences that stop the generation when generated
+    pub fn stop_sequences(
+        mut self,
+        stop_sequences: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.stop_sequences = stop_sequences.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only sample from the `top_k` most likely tokens
+    pub fn top_k(mut self, top_k: u64) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Only sample from the most likely tokens with a cumulative probability of `top_p`
+    pub fn top_p(mut self, top_p: f64) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// How the model uses the tools of the request. Ignored by requests without tools.
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+}
+
+impl From<RequestParams> for serde_json::Value {
+    fn from(params: RequestParams) -> Self {
+        serde_json::to_value(params).expect("Request params should serialize")
+    }
+}
+
+impl CompletionModel {
+    fn create_completion_request(
         &self,
-        completion_request: completion::CompletionRequest,
-    ) -> Result<completion::CompletionResponse<CompletionResponse>, CompletionError> {
+        mut completion_request: completion::CompletionRequest,
+    ) -> Result<serde_json::Value, CompletionError> {
         // Note: Ideally we'd introduce provider-specific Request models to handle the
         // specific requirements of each provider. For now, we just manually check while
         // building the request as a raw JSON document.
 
+        let cache_breakpoints = completion_request.cache_breakpoints;
+        let cached_documents = completion_request
+            .documents
+            .drain(
+                ..cache_breakpoints
+                    .documents
+                    .min(completion_request.documents.len()),
+            )
+            .collect::<Vec<_>>();
+
         let prompt_with_context = completion_request.prompt_with_context();
+        let images = completion_request.images();
 
         // Check if max_tokens is set, required for Anthropic
         let max_tokens = if let Some(tokens) = completion_request.max_tokens {
@@ -218,39 +409,64 @@ impl completion::CompletionModel for CompletionModel {
                 .map(Message::from)
                 .chain(iter::once(Message {
                     role: "user".to_owned(),
-                    content: prompt_with_context,
+                    content: prompt_content(prompt_with_context, images),
                 }))
                 .collect::<Vec<_>>(),
             "max_tokens": max_tokens,
-            "system": completion_request.preamble.unwrap_or("".to_string()),
+            "system": system_content(
+                completion_request.preamble.unwrap_or_default(),
+                cache_breakpoints.preamble,
+                &cached_documents,
+            ),
         });
 
         if let Some(temperature) = completion_request.temperature {
             json_utils::merge_inplace(&mut request, json!({ "temperature": temperature }));
         }
 
+        let mut additional_params = completion_request.additional_params;
         if !completion_request.tools.is_empty() {
+            let cached_tools = cache_breakpoints.tools.min(completion_request.tools.len());
             json_utils::merge_inplace(
                 &mut request,
                 json!({
                     "tools": completion_request
                         .tools
                         .into_iter()
-                        .map(|tool| ToolDefinition {
+                        .enumerate()
+                        .map(|(i, tool)| ToolDefinition {
                             name: tool.name,
                             description: Some(tool.description),
                             input_schema: tool.parameters,
+                            cache_control: (i + 1 == cached_tools)
+                                .then_some(CacheControl::Ephemeral),
                         })
                         .collect::<Vec<_>>(),
                     "tool_choice": ToolChoice::Auto,
                 }),
             );
+        } else if let Some(serde_json::Value::Object(params)) = &mut additional_params {
+            // Anthropic rejects a tool choice without tools
+            params.remove("tool_choice");
         }
 
-        if let Some(ref params) = completion_request.additional_params {
-            json_utils::merge_inplace(&mut request, params.clone())
+        if let Some(params) = additional_params {
+            json_utils::merge_inplace(&mut request, params)
         }
 
+        Ok(request)
+    }
+}
+
+impl completion::CompletionModel for CompletionModel {
+    type Response = CompletionResponse;
+
+    async fn completion(
+        &self,
+        completion_request: completion::CompletionRequest,
+    ) -> Result<completion::CompletionResponse<CompletionResponse>, CompletionError> {
+        let request = self.create_completion_request(completion_request)?;
+
         let response = self
             .client
             .post("/v1/messages")
@@ -286,3 +502,136 @@ enum ApiResponse<T> {
     Message(T),
     Error(ApiErrorResponse),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_content() {
+        let content = prompt_content(
+            "What is in this photo?".to_string(),
+            vec![
+                completion::Image::parse("data:image/jpeg;base64,/9j/4AAQSkZJRgABAQ"),
+                completion::Image::parse("https://example.com/cat.png"),
+            ],
+        );
+
+        assert_eq!(
+            serde_json::to_value(content).unwrap(),
+            json!([
+                {"type": "image", "source": {"type": "base64", "media_type": "image/jpeg", "data": "/9j/4AAQSkZJRgABAQ"}},
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/cat.png"}},
+                {"type": "text", "text": "What is in this photo?"},
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(prompt_content("Hi".to_string(), vec![])).unwrap(),
+            json!("Hi")
+        );
+    }
+
+    fn request(tools: usize) -> completion::CompletionRequest {
+        let model = CompletionModel::new(
+            super::super::ClientBuilder::new("key").build(),
+            CLAUDE_3_5_SONNET,
+        );
+        let document = |id: &str| Document {
+            id: id.to_string(),
+            text: format!("Text of {id}"),
+            additional_props: Default::default(),
+        };
+        let tool = |name: &str| completion::ToolDefinition {
+            name: name.to_string(),
+            description: format!("Tool {name}"),
+            parameters: json!({"type": "object"}),
+        };
+
+        completion::CompletionModel::completion_request(&model, "Hello")
+            .preamble("You are Yuri.".to_string())
+            .documents(vec![document("style"), document("dynamic")])
+            .tools([tool("search"), tool("dynamic_tool")][..tools].to_vec())
+            .additional_params(
+                RequestParams::default()
+                    .stop_sequences(["</answer>"])
+                    .top_k(40)
+                    .tool_choice(ToolChoice::Tool {
+                        name: "search".into(),
+                    })
+                    .into(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_cache_breakpoints() {
+        let model = CompletionModel::new(
+            super::super::ClientBuilder::new("key").build(),
+            CLAUDE_3_5_SONNET,
+        );
+        let mut completion_request = request(2);
+        completion_request.cache_breakpoints = completion::CacheBreakpoints {
+            preamble: true,
+            tools: 1,
+            documents: 1,
+        };
+        let request = model.create_completion_request(completion_request).unwrap();
+
+        assert_eq!(
+            request["system"],
+            json!([
+                {"type": "text", "text": "You are Yuri.", "cache_control": {"type": "ephemeral"}},
+                {
+                    "type": "text",
+                    "text": "<attachments>\n<file id: style>\nText of style\n</file>\n</attachments>\n",
+                    "cache_control": {"type": "ephemeral"}
+                },
+            ])
+        );
+        assert_eq!(
+            request["messages"][0]["content"],
+            "<attachments>\n<file id: dynamic>\nText of dynamic\n</file>\n</attachments>\n\nHello"
+        );
+        assert_eq!(
+            request["tools"][0]["cache_control"],
+            json!({"type": "ephemeral"})
+        );
+        assert!(request["tools"][1].get("cache_control").is_none());
+        assert_eq!(
+            request["tool_choice"],
+            json!({"type": "tool", "name": "search"})
+        );
+        assert_eq!(request["stop_sequences"], json!(["</answer>"]));
+        assert_eq!(request["top_k"], 40);
+        assert!(request.get("top_p").is_none());
+    }
+
+    #[test]
+    fn test_request_without_cache_breakpoints() {
+        let model = CompletionModel::new(
+            super::super::ClientBuilder::new("key").build(),
+            CLAUDE_3_5_SONNET,
+        );
+        let request = model.create_completion_request(request(0)).unwrap();
+
+        assert_eq!(request["system"], "You are Yuri.");
+        assert!(request.get("tools").is_none());
+        assert!(request.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn test_cache_usage() {
+        let usage: Usage = serde_json::from_value(json!({
+            "input_tokens": 10,
+            "cache_read_input_tokens": 2000,
+            "cache_creation_input_tokens": 100,
+            "output_tokens": 50
+        }))
+        .unwrap();
+
+        assert_eq!(
+            completion::Usage::from(&usage),
+            completion::Usage::new(2110, 50).with_cache(2000, 100)
+        );
+    }
+}
@@ -14,6 +14,6 @@ pub mod completion;
 
 pub use client::{Client, ClientBuilder};
 pub use completion::{
-    ANTHROPIC_VERSION_2023_01_01, ANTHROPIC_VERSION_2023_06_01, ANTHROPIC_VERSION_LATEST,
-    CLAUDE_3_5_SONNET, CLAUDE_3_HAIKU, CLAUDE_3_OPUS, CLAUDE_3_SONNET,
+    RequestParams, ToolChoice, ANTHROPIC_VERSION_2023_01_01, ANTHROPIC_VERSION_2023_06_01,
+    ANTHROPIC_VERSION_LATEST, CLAUDE_3_5_SONNET, CLAUDE_3_HAIKU, CLAUDE_3_OPUS, CLAUDE_3_SONNET,
 };
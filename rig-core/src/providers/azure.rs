@@ -0,0 +1,410 @@
+//! Azure OpenAI API client and Rig integration
+//!
+//! Azure OpenAI serves models through deployments of a resource: requests are sent to
+//! `{endpoint}/openai/deployments/{deployment}/...` with an `api-version` query parameter and
+//! authenticated with the `api-key` header. Deployments take the same requests and return the
+//! same responses as the OpenAI API, so the OpenAI types are reused.
+//!
+//! Models are created from deployment names rather than model names. The embedding dimensions
+//! and support for structured outputs are inferred from the deployment name when it is the name
+//! of the deployed model (e.g.: a `text-embedding-3-large` deployment), which is the default
+//! in the Azure portal.
+//!
+//! # Example
+//! ```
+//! use rig::providers::{azure, openai};
+//!
+//! let client = azure::Client::new("YOUR_API_KEY", "https://my-resource.openai.azure.com");
+//!
+//! let gpt4o = client.completion_model("gpt-4o");
+//! let embedding_model = client.embedding_model(openai::TEXT_EMBEDDING_3_LARGE);
+//! ```
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::openai::{
+    self, create_completion_request, streaming_result, structured_output_params, ApiResponse,
+};
+use crate::{
+    agent::AgentBuilder,
+    classifier::ClassifierBuilder,
+    completion::{self, CompletionError, CompletionRequest},
+    embeddings::{self, EmbeddingError, EmbeddingsBuilder},
+    extractor::ExtractorBuilder,
+    json_utils,
+    streaming::StreamingResult,
+    Embed,
+};
+
+// ================================================================
+// Main Azure OpenAI Client
+// ================================================================
+/// Default `api-version` query parameter (latest GA version)
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+#[derive(Clone)]
+pub struct Client {
+    endpoint: String,
+    api_version: String,
+    http_client: reqwest::Client,
+}
+
+impl Client {
+    /// Create a new Azure OpenAI client with the given API key and resource endpoint
+    /// (e.g.: `https://my-resource.openai.azure.com`).
+    pub fn new(api_key: &str, endpoint: &str) -> Self {
+        Self::from_api_version(api_key, endpoint, DEFAULT_API_VERSION)
+    }
+
+    /// Create a new Azure OpenAI client with the given API key, resource endpoint and
+    /// `api-version` (e.g.: `2024-10-21`, `2025-01-01-preview`).
+    pub fn from_api_version(api_key: &str, endpoint: &str, api_version: &str) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            api_version: api_version.to_string(),
+            http_client: reqwest::Client::builder()
+                .default_headers({
+                    let mut headers = reqwest::header::HeaderMap::new();
+                    headers.insert("api-key", api_key.parse().expect("API key should parse"));
+                    headers
+                })
+                .build()
+                .expect("Azure OpenAI reqwest client should build"),
+        }
+    }
+
+    /// Create a new Azure OpenAI client from the `AZURE_OPENAI_API_KEY` and
+    /// `AZURE_OPENAI_ENDPOINT` environment variables, and the optional
+    /// `AZURE_OPENAI_API_VERSION` environment variable.
+    /// Panics if the required environment variables are not set.
+    pub fn from_env() -> Self {
+        let api_key = std::env::var("AZURE_OPENAI_API_KEY").expect("AZURE_OPENAI_API_KEY not set");
+        let endpoint =
+            std::env::var("AZURE_OPENAI_ENDPOINT").expect("AZURE_OPENAI_ENDPOINT not set");
+        let api_version = std::env::var("AZURE_OPENAI_API_VERSION")
+            .unwrap_or_else(|_| DEFAULT_API_VERSION.to_string());
+        Self::from_api_version(&api_key, &endpoint, &api_version)
+    }
+
+    /// URL of `path` for `deployment`
+    fn deployment_url(&self, deployment: &str, path: &str) -> String {
+        format!(
+            "{}/openai/deployments/{}/{}",
+            self.endpoint,
+            deployment,
+            path.trim_start_matches('/')
+        )
+    }
+
+    fn post(&self, deployment: &str, path: &str) -> reqwest::RequestBuilder {
+        self.http_client
+            .post(self.deployment_url(deployment, path))
+            .query(&[("api-version", &self.api_version)])
+    }
+
+    /// Create an embedding model for the given deployment.
+    /// Note: default embedding dimension of 0 will be used if the deployment is not named
+    /// after a known model. If this is the case, it's better to use function
+    /// `embedding_model_with_ndims`
+    ///
+    /// # Example
+    /// ```
+    /// use rig::providers::{azure, openai};
+    ///
+    /// let azure = azure::Client::new("your-api-key", "https://my-resource.openai.azure.com");
+    ///
+    /// let embedding_model = azure.embedding_model(openai::TEXT_EMBEDDING_3_LARGE);
+    /// ```
+    pub fn embedding_model(&self, deployment: &str) -> EmbeddingModel {
+        let ndims = match deployment {
+            openai::TEXT_EMBEDDING_3_LARGE => 3072,
+            openai::TEXT_EMBEDDING_3_SMALL | openai::TEXT_EMBEDDING_ADA_002 => 1536,
+            _ => 0,
+        };
+        EmbeddingModel::new(self.clone(), deployment, ndims)
+    }
+
+    /// Create an embedding model for the given deployment and the number of dimensions of the
+    /// deployed model.
+    pub fn embedding_model_with_ndims(&self, deployment: &str, ndims: usize) -> EmbeddingModel {
+        EmbeddingModel::new(self.clone(), deployment, ndims)
+    }
+
+    /// Create an embedding builder with the given embedding deployment.
+    pub fn embeddings<D: Embed>(&self, deployment: &str) -> EmbeddingsBuilder<EmbeddingModel, D> {
+        EmbeddingsBuilder::new(self.embedding_model(deployment))
+    }
+
+    /// Create a completion model for the given deployment.
+    pub fn completion_model(&self, deployment: &str) -> CompletionModel {
+        CompletionModel::new(self.clone(), deployment)
+    }
+
+    /// Create an agent builder with the given completion deployment.
+    pub fn agent(&self, deployment: &str) -> AgentBuilder<CompletionModel> {
+        AgentBuilder::new(self.completion_model(deployment))
+    }
+
+    /// Create an extractor builder with the given completion deployment.
+    pub fn extractor<T: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync>(
+        &self,
+        deployment: &str,
+    ) -> ExtractorBuilder<T, CompletionModel> {
+        ExtractorBuilder::new(self.completion_model(deployment))
+    }
+
+    /// Create a classifier builder with the given completion deployment.
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        deployment: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(deployment))
+    }
+}
+
+// ================================================================
+// Azure OpenAI Embedding API
+// ================================================================
+#[derive(Clone)]
+pub struct EmbeddingModel {
+    client: Client,
+    /// Name of the deployment (e.g.: text-embedding-3-large)
+    pub deployment: String,
+    ndims: usize,
+}
+
+impl EmbeddingModel {
+    pub fn new(client: Client, deployment: &str, ndims: usize) -> Self {
+        Self {
+            client,
+            deployment: deployment.to_string(),
+            ndims,
+        }
+    }
+}
+
+impl embeddings::EmbeddingModel for EmbeddingModel {
+    const MAX_DOCUMENTS: usize = 1024;
+
+    fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    async fn embed_texts(
+        &self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
+        let documents = documents.into_iter().collect::<Vec<_>>();
+
+        let response = self
+            .client
+            .post(&self.deployment, "/embeddings")
+            .json(&json!({
+                "input": documents,
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(EmbeddingError::ProviderError(response.text().await?));
+        }
+
+        match response
+            .json::<ApiResponse<openai::EmbeddingResponse>>()
+            .await?
+        {
+            ApiResponse::Ok(response) => {
+                tracing::info!(target: "rig",
+                    "Azure OpenAI embedding token usage: {}",
+                    response.usage
+                );
+
+                if response.data.len() != documents.len() {
+                    return Err(EmbeddingError::ResponseError(
+                        "Response data length does not match input length".into(),
+                    ));
+                }
+
+                Ok(response
+                    .data
+                    .into_iter()
+                    .zip(documents)
+                    .map(|(embedding, document)| embeddings::Embedding {
+                        document,
+                        vec: embedding.embedding,
+                    })
+                    .collect())
+            }
+            ApiResponse::Err(err) => Err(EmbeddingError::ProviderError(err.message)),
+        }
+    }
+}
+
+// ================================================================
+// Azure OpenAI Completion API
+// ================================================================
+#[derive(Clone)]
+pub struct CompletionModel {
+    client: Client,
+    /// Name of the deployment (e.g.: gpt-4o)
+    pub deployment: String,
+}
+
+impl CompletionModel {
+    pub fn new(client: Client, deployment: &str) -> Self {
+        Self {
+            client,
+            deployment: deployment.to_string(),
+        }
+    }
+
+    /// The deployment determines the model, so the `model` field of OpenAI requests is omitted
+    fn create_completion_request(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> serde_json::Value {
+        let mut request = create_completion_request(&self.deployment, completion_request);
+        if let Some(request) = request.as_object_mut() {
+            request.remove("model");
+        }
+        request
+    }
+}
+
+impl completion::CompletionModel for CompletionModel {
+    type Response = openai::CompletionResponse;
+
+    async fn completion(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<openai::CompletionResponse>, CompletionError> {
+        let response = self
+            .client
+            .post(&self.deployment, "/chat/completions")
+            .json(&self.create_completion_request(completion_request))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(CompletionError::ProviderError(response.text().await?));
+        }
+
+        match response
+            .json::<ApiResponse<openai::CompletionResponse>>()
+            .await?
+        {
+            ApiResponse::Ok(response) => {
+                tracing::info!(target: "rig",
+                    "Azure OpenAI completion token usage: {:?}",
+                    response.usage.clone().map(|usage| format!("{usage}")).unwrap_or("N/A".to_string())
+                );
+                response.try_into()
+            }
+            ApiResponse::Err(err) => Err(CompletionError::ProviderError(err.message)),
+        }
+    }
+
+    fn structured_output_params(
+        &self,
+        name: &str,
+        schema: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        structured_output_params(&self.deployment, name, schema)
+    }
+
+    async fn stream(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<StreamingResult, CompletionError> {
+        let request = json_utils::merge(
+            self.create_completion_request(completion_request),
+            json!({
+                "stream": true,
+                "stream_options": {"include_usage": true},
+            }),
+        );
+
+        let response = self
+            .client
+            .post(&self.deployment, "/chat/completions")
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(CompletionError::ProviderError(response.text().await?));
+        }
+
+        Ok(streaming_result(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deployment_requests() {
+        let client = Client::new("key", "https://my-resource.openai.azure.com/");
+        let request = client.post("gpt-4o", "/chat/completions").build().unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "https://my-resource.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(
+            client.embedding_model(openai::TEXT_EMBEDDING_3_SMALL).ndims,
+            1536
+        );
+
+        let body = client
+            .completion_model("gpt-4o")
+            .create_completion_request(CompletionRequest {
+                prompt: "Hello".to_string(),
+                preamble: None,
+                chat_history: vec![],
+                documents: vec![],
+                tools: vec![],
+                temperature: None,
+                max_tokens: None,
+                additional_params: None,
+                image_urls: None,
+                cache_breakpoints: Default::default(),
+            });
+        assert!(body.get("model").is_none());
+        assert_eq!(body["messages"][0]["content"][0]["text"], "Hello");
+    }
+
+    #[test]
+    fn test_completion_response() {
+        // Azure adds content filter results to OpenAI responses
+        let response: ApiResponse<openai::CompletionResponse> = serde_json::from_str(
+            r#"{
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1730000000,
+                "model": "gpt-4o-2024-08-06",
+                "prompt_filter_results": [{"prompt_index": 0, "content_filter_results": {}}],
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": "Hi!"},
+                    "logprobs": null,
+                    "finish_reason": "stop",
+                    "content_filter_results": {"hate": {"filtered": false, "severity": "safe"}}
+                }],
+                "usage": {"prompt_tokens": 8, "completion_tokens": 2, "total_tokens": 10}
+            }"#,
+        )
+        .unwrap();
+
+        let ApiResponse::Ok(response) = response else {
+            panic!("Expected a completion response");
+        };
+        let response: completion::CompletionResponse<_> = response.try_into().unwrap();
+        assert!(matches!(response.choice, completion::ModelChoice::Message(text) if text == "Hi!"));
+        assert_eq!(response.usage, Some(completion::Usage::new(8, 2)));
+    }
+}
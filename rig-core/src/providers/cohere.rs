@@ -12,6 +12,7 @@ use std::collections::HashMap;
 
 use crate::{
     agent::AgentBuilder,
+    classifier::ClassifierBuilder,
     completion::{self, CompletionError},
     embeddings::{self, EmbeddingError, EmbeddingsBuilder},
     extractor::ExtractorBuilder,
@@ -114,6 +115,15 @@ impl Client {
     ) -> ExtractorBuilder<T, CompletionModel> {
         ExtractorBuilder::new(self.completion_model(model))
     }
+
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        model: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(model))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -157,7 +167,7 @@ pub struct EmbeddingResponse {
     pub meta: Option<Meta>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 pub struct Meta {
     pub api_version: ApiVersion,
     pub billed_units: BilledUnits,
@@ -165,7 +175,7 @@ pub struct Meta {
     pub warnings: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 pub struct ApiVersion {
     pub version: String,
     #[serde(default)]
@@ -316,6 +326,8 @@ pub struct CompletionResponse {
     pub tool_calls: Vec<ToolCall>,
     #[serde(default)]
     pub chat_history: Vec<ChatHistory>,
+    #[serde(default)]
+    pub meta: Option<Meta>,
 }
 
 impl From<CompletionResponse> for completion::CompletionResponse<CompletionResponse> {
@@ -333,8 +345,39 @@ impl From<CompletionResponse> for completion::CompletionResponse<CompletionRespo
             completion::ModelChoice::Message(text.clone())
         };
 
+        let usage = response.meta.as_ref().map(|meta| {
+            completion::Usage::new(
+                meta.billed_units.input_tokens as u64,
+                meta.billed_units.output_tokens as u64,
+            )
+        });
+
+        let citations = response
+            .citations
+            .iter()
+            .flat_map(|citation| {
+                citation.document_ids.iter().map(|id| completion::Citation {
+                    source_id: Some(id.clone()),
+                    url: response
+                        .documents
+                        .iter()
+                        .find(|document| document.id == *id)
+                        .and_then(|document| document.additional_prop.get("url"))
+                        .and_then(|url| url.as_str())
+                        .map(str::to_string),
+                    span: Some(completion::Span {
+                        start: citation.start as usize,
+                        end: citation.end as usize,
+                        text: citation.text.clone(),
+                    }),
+                })
+            })
+            .collect();
+
         completion::CompletionResponse {
             choice: model_response,
+            usage,
+            citations,
             raw_response: response,
         }
     }
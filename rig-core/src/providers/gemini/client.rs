@@ -1,5 +1,6 @@
 use crate::{
     agent::AgentBuilder,
+    classifier::ClassifierBuilder,
     embeddings::{self},
     extractor::ExtractorBuilder,
     Embed,
@@ -145,6 +146,16 @@ impl Client {
     ) -> ExtractorBuilder<T, CompletionModel> {
         ExtractorBuilder::new(self.completion_model(model))
     }
+
+    /// Create a classifier builder with the given completion model.
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        model: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(model))
+    }
 }
 
 #[derive(Debug, Deserialize)]
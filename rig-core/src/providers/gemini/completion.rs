@@ -13,11 +13,12 @@ pub const GEMINI_1_5_PRO_8B: &str = "gemini-1.5-pro-8b";
 pub const GEMINI_1_0_PRO: &str = "gemini-1.0-pro";
 
 use gemini_api_types::{
-    Content, ContentCandidate, FunctionDeclaration, GenerateContentRequest,
-    GenerateContentResponse, GenerationConfig, Part, Role, Tool,
+    Blob, CitationMetadata, Content, ContentCandidate, FileData, FunctionDeclaration,
+    GenerateContentRequest, GenerateContentResponse, GenerationConfig, Part, Role, Schema, Tool,
+    ToolConfig,
 };
-use serde_json::{Map, Value};
-use std::convert::TryFrom;
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, convert::TryFrom};
 
 use crate::completion::{self, CompletionError, CompletionRequest};
 
@@ -53,18 +54,28 @@ impl completion::CompletionModel for CompletionModel {
         full_history.append(&mut completion_request.chat_history);
 
         let prompt_with_context = completion_request.prompt_with_context();
-
-        full_history.push(completion::Message {
-            role: "user".into(),
-            content: prompt_with_context,
-        });
+        let mut images = Vec::new();
+        for image in completion_request.images() {
+            images.push(image_part(image).await?);
+        }
 
         // Handle Gemini specific parameters
-        let additional_params = completion_request
+        let mut additional_params = completion_request
             .additional_params
             .unwrap_or_else(|| Value::Object(Map::new()));
+        let tool_config = additional_params
+            .as_object_mut()
+            .and_then(|params| params.remove("toolConfig"))
+            .map(serde_json::from_value::<ToolConfig>)
+            .transpose()?;
         let mut generation_config = serde_json::from_value::<GenerationConfig>(additional_params)?;
 
+        let function_declarations = completion_request
+            .tools
+            .into_iter()
+            .map(FunctionDeclaration::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
         // Set temperature from completion_request or additional_params
         if let Some(temp) = completion_request.temperature {
             generation_config.temperature = Some(temp);
@@ -90,17 +101,25 @@ impl completion::CompletionModel for CompletionModel {
                         _ => None,
                     },
                 })
+                .chain(std::iter::once(Content {
+                    parts: std::iter::once(Part {
+                        text: Some(prompt_with_context),
+                        ..Default::default()
+                    })
+                    .chain(images)
+                    .collect(),
+                    role: Some(Role::User),
+                }))
                 .collect(),
             generation_config: Some(generation_config),
             safety_settings: None,
-            tools: Some(
-                completion_request
-                    .tools
-                    .into_iter()
-                    .map(Tool::from)
-                    .collect(),
-            ),
-            tool_config: None,
+            tools: (!function_declarations.is_empty()).then(|| {
+                vec![Tool {
+                    function_declarations,
+                    code_execution: None,
+                }]
+            }),
+            tool_config,
             system_instruction: Some(Content {
                 parts: vec![Part {
                     text: Some("system".to_string()),
@@ -136,18 +155,462 @@ impl completion::CompletionModel for CompletionModel {
 
         completion::CompletionResponse::try_from(response)
     }
+
+    fn structured_output_params(&self, name: &str, schema: &Value) -> Option<Value> {
+        let converted = match convert_schema(schema) {
+            Ok(converted) => converted,
+            Err(err) => {
+                tracing::warn!(target: "rig", "Schema of {} cannot be used by Gemini: {}", name, err);
+                return None;
+            }
+        };
+        for construct in converted.unsupported {
+            tracing::warn!(target: "rig",
+                "Schema of {} contains {}, which Gemini does not support",
+                name,
+                construct
+            );
+        }
+
+        Some(json!({
+            "responseMimeType": "application/json",
+            "responseSchema": converted.schema,
+        }))
+    }
 }
 
-impl From<completion::ToolDefinition> for Tool {
-    fn from(tool: completion::ToolDefinition) -> Self {
-        Self {
-            function_declaration: FunctionDeclaration {
-                name: tool.name,
-                description: tool.description,
-                parameters: None, // tool.parameters, TODO: Map Gemini
-            },
-            code_execution: None,
+/// Part of an image. Gemini only takes inline images and files it hosts, so other remote
+/// images are downloaded.
+async fn image_part(image: completion::Image) -> Result<Part, CompletionError> {
+    if let completion::Image::Url(url) = &image {
+        if url.starts_with("gs://") || url.starts_with("https://generativelanguage.googleapis.com/")
+        {
+            return Ok(Part {
+                file_data: Some(FileData {
+                    mime_type: None,
+                    file_uri: url.clone(),
+                }),
+                ..Default::default()
+            });
+        }
+    }
+
+    let (mime_type, data) = image.fetch().await?;
+    Ok(Part {
+        inline_data: Some(Blob { mime_type, data }),
+        ..Default::default()
+    })
+}
+
+/// Construct of a JSON schema without equivalent in Gemini's [Schema], dropped by
+/// [convert_schema]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsupportedConstruct {
+    /// Location of the construct in the JSON schema (e.g.: `#/properties/age`)
+    pub path: String,
+    /// Keyword of the construct (e.g.: `minimum`)
+    pub keyword: String,
+}
+
+impl std::fmt::Display for UnsupportedConstruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` at {}", self.keyword, self.path)
+    }
+}
+
+/// Gemini [Schema] converted from a JSON schema, with the constructs that could not be converted
+#[derive(Debug)]
+pub struct ConvertedSchema {
+    pub schema: Schema,
+    pub unsupported: Vec<UnsupportedConstruct>,
+}
+
+/// Convert a JSON schema (e.g.: tool parameters, or a schema generated by `schemars`) to the
+/// subset of the OpenAPI schema accepted by Gemini:
+/// - references to `definitions`/`$defs` are inlined (recursive references become objects)
+/// - `[T, "null"]` types and `anyOf`/`oneOf` alternatives with `null` become nullable types
+/// - `anyOf`/`oneOf` alternatives of string constants (i.e.: Rust enums) become enums
+/// - `allOf` objects are merged
+/// - `const` strings become single-value enums and integer formats become `int32`/`int64`
+///
+/// Constructs without equivalent (e.g.: `minimum`, `pattern`, unions of several types) are
+/// dropped and reported, while metadata (e.g.: `title`, `default`) is dropped silently.
+/// Errors if the schema is not an object or contains unresolvable references.
+pub fn convert_schema(schema: &Value) -> Result<ConvertedSchema, CompletionError> {
+    let mut definitions = Map::new();
+    for key in ["definitions", "$defs"] {
+        if let Some(defs) = schema.get(key).and_then(Value::as_object) {
+            definitions.extend(defs.iter().map(|(name, def)| (name.clone(), def.clone())));
+        }
+    }
+
+    let mut converter = SchemaConverter {
+        definitions,
+        resolving: vec![],
+        unsupported: vec![],
+    };
+    let schema = converter.convert(schema, "#")?;
+
+    Ok(ConvertedSchema {
+        schema,
+        unsupported: converter.unsupported,
+    })
+}
+
+/// Keywords dropped without being reported, since they do not constrain values
+const METADATA_KEYWORDS: [&str; 11] = [
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "definitions",
+    "$defs",
+    "default",
+    "examples",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+];
+
+/// Keywords converted by [SchemaConverter::convert]
+const CONVERTED_KEYWORDS: [&str; 15] = [
+    "$ref",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "type",
+    "format",
+    "description",
+    "nullable",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "items",
+    "minItems",
+    "maxItems",
+];
+
+struct SchemaConverter {
+    definitions: Map<String, Value>,
+    /// Definitions being resolved, to detect recursive references
+    resolving: Vec<String>,
+    unsupported: Vec<UnsupportedConstruct>,
+}
+
+impl SchemaConverter {
+    fn report(&mut self, path: &str, keyword: &str) {
+        self.unsupported.push(UnsupportedConstruct {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+        });
+    }
+
+    fn convert(&mut self, schema: &Value, path: &str) -> Result<Schema, CompletionError> {
+        let Some(obj) = schema.as_object() else {
+            return Err(CompletionError::RequestError(
+                format!("Expected a JSON schema object at {path}").into(),
+            ));
+        };
+        let description = obj
+            .get("description")
+            .or_else(|| obj.get("title"))
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+            let mut schema = self.resolve(reference, path)?;
+            schema.description = description.or(schema.description);
+            return Ok(schema);
+        }
+
+        if let Some(schemas) = obj.get("allOf").and_then(Value::as_array) {
+            let mut schema = self.convert_all_of(schemas, &format!("{path}/allOf"))?;
+            schema.description = description.or(schema.description);
+            return Ok(schema);
+        }
+
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(variants) = obj.get(keyword).and_then(Value::as_array) {
+                let mut schema = self.convert_union(variants, &format!("{path}/{keyword}"))?;
+                schema.description = description.or(schema.description);
+                return Ok(schema);
+            }
+        }
+
+        let mut schema = Schema {
+            description,
+            ..Default::default()
+        };
+
+        // Type, possibly nullable
+        match obj.get("type") {
+            Some(Value::Array(types)) => {
+                let types = types.iter().filter_map(Value::as_str).collect::<Vec<_>>();
+                let non_null = types.iter().filter(|t| **t != "null").collect::<Vec<_>>();
+                if non_null.len() > 1 {
+                    self.report(path, "type");
+                }
+                schema.r#type = non_null.first().map(|t| t.to_string()).unwrap_or_default();
+                if non_null.len() < types.len() {
+                    schema.nullable = Some(true);
+                }
+            }
+            Some(Value::String(t)) => schema.r#type = t.clone(),
+            _ => {}
+        }
+        if obj.get("nullable").and_then(Value::as_bool) == Some(true) {
+            schema.nullable = Some(true);
+        }
+
+        // Enums, which Gemini only supports for strings
+        let values = match (obj.get("enum"), obj.get("const")) {
+            (Some(Value::Array(values)), _) => Some(values.clone()),
+            (None, Some(value)) => Some(vec![value.clone()]),
+            _ => None,
+        };
+        if let Some(values) = values {
+            let keyword = if obj.contains_key("enum") {
+                "enum"
+            } else {
+                "const"
+            };
+            if values.contains(&Value::Null) {
+                schema.nullable = Some(true);
+            }
+            let strings = values
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(|value| value.as_str().map(String::from))
+                .collect::<Option<Vec<_>>>();
+            match strings {
+                Some(strings) if matches!(schema.r#type.as_str(), "" | "string") => {
+                    schema.r#type = "string".to_string();
+                    schema.r#enum = Some(strings);
+                }
+                _ => self.report(path, keyword),
+            }
+        }
+
+        // Infer missing types from the other keywords
+        if schema.r#type.is_empty() {
+            schema.r#type = if obj.contains_key("properties") {
+                "object".to_string()
+            } else if obj.contains_key("items") {
+                "array".to_string()
+            } else {
+                self.report(path, "type");
+                "string".to_string()
+            };
+        }
+
+        if let Some(format) = obj.get("format").and_then(Value::as_str) {
+            schema.format = match (schema.r#type.as_str(), format) {
+                ("integer", "int8" | "int16" | "int32" | "uint8" | "uint16") => Some("int32"),
+                ("integer", "int64" | "uint32" | "uint64" | "int" | "uint") => Some("int64"),
+                ("number", "float" | "double") | ("string", "date-time") => Some(format),
+                _ => {
+                    self.report(path, "format");
+                    None
+                }
+            }
+            .map(String::from);
+        }
+
+        if let Some(properties) = obj.get("properties").and_then(Value::as_object) {
+            let mut converted = HashMap::new();
+            for (name, property) in properties {
+                let property = self.convert(property, &format!("{path}/properties/{name}"))?;
+                converted.insert(name.clone(), property);
+            }
+            schema.required = obj
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|required| {
+                    required
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .filter(|name| properties.contains_key(*name))
+                        .map(String::from)
+                        .collect()
+                });
+            schema.properties = Some(converted);
+        }
+
+        match obj.get("items") {
+            Some(Value::Array(items)) => {
+                self.report(path, "items");
+                if let Some(first) = items.first() {
+                    schema.items = Some(Box::new(self.convert(first, &format!("{path}/items/0"))?));
+                }
+            }
+            Some(items) => {
+                schema.items = Some(Box::new(self.convert(items, &format!("{path}/items"))?));
+            }
+            None => {}
+        }
+        schema.min_items = obj
+            .get("minItems")
+            .and_then(Value::as_i64)
+            .map(|n| n as i32);
+        schema.max_items = obj
+            .get("maxItems")
+            .and_then(Value::as_i64)
+            .map(|n| n as i32);
+
+        for (keyword, value) in obj {
+            let implied = match keyword.as_str() {
+                // `false` is the default of Gemini, which rejects unknown properties
+                "additionalProperties" => value == &Value::Bool(false),
+                // Minimum of unsigned integers (e.g.: `usize`) generated by `schemars`
+                "minimum" => {
+                    value.as_f64() == Some(0.0)
+                        && obj
+                            .get("format")
+                            .and_then(Value::as_str)
+                            .is_some_and(|format| format.starts_with("uint"))
+                }
+                _ => false,
+            };
+            if !implied
+                && !METADATA_KEYWORDS.contains(&keyword.as_str())
+                && !CONVERTED_KEYWORDS.contains(&keyword.as_str())
+            {
+                self.report(path, keyword);
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// Inline a reference to a definition
+    fn resolve(&mut self, reference: &str, path: &str) -> Result<Schema, CompletionError> {
+        let name = reference
+            .strip_prefix("#/definitions/")
+            .or_else(|| reference.strip_prefix("#/$defs/"))
+            .ok_or_else(|| {
+                CompletionError::RequestError(
+                    format!("Unsupported reference `{reference}` at {path}").into(),
+                )
+            })?;
+        let definition = self.definitions.get(name).cloned().ok_or_else(|| {
+            CompletionError::RequestError(
+                format!("Undefined reference `{reference}` at {path}").into(),
+            )
+        })?;
+
+        if self.resolving.iter().any(|resolving| resolving == name) {
+            self.report(path, "$ref");
+            return Ok(Schema {
+                r#type: "object".to_string(),
+                ..Default::default()
+            });
+        }
+
+        self.resolving.push(name.to_string());
+        let schema = self.convert(&definition, path);
+        self.resolving.pop();
+        schema
+    }
+
+    /// Merge the schemas of `allOf`, which are objects when there are several of them
+    fn convert_all_of(&mut self, schemas: &[Value], path: &str) -> Result<Schema, CompletionError> {
+        let mut merged: Option<Schema> = None;
+        for (i, schema) in schemas.iter().enumerate() {
+            let schema = self.convert(schema, &format!("{path}/{i}"))?;
+            merged = Some(match merged {
+                None => schema,
+                Some(mut merged) if merged.r#type == "object" && schema.r#type == "object" => {
+                    merged
+                        .properties
+                        .get_or_insert_with(HashMap::new)
+                        .extend(schema.properties.unwrap_or_default());
+                    merged
+                        .required
+                        .get_or_insert_with(Vec::new)
+                        .extend(schema.required.unwrap_or_default());
+                    merged
+                }
+                Some(merged) => {
+                    self.report(path, "allOf");
+                    merged
+                }
+            });
+        }
+
+        merged
+            .ok_or_else(|| CompletionError::RequestError(format!("Empty `allOf` at {path}").into()))
+    }
+
+    /// Convert `anyOf`/`oneOf` alternatives, keeping the first one if they cannot be merged
+    fn convert_union(&mut self, variants: &[Value], path: &str) -> Result<Schema, CompletionError> {
+        let mut nullable = false;
+        let mut schemas = vec![];
+        for (i, variant) in variants.iter().enumerate() {
+            match variant.get("type").and_then(Value::as_str) {
+                Some("null") => nullable = true,
+                _ => schemas.push(self.convert(variant, &format!("{path}/{i}"))?),
+            }
+        }
+
+        let mut schema = if schemas.len() > 1
+            && schemas
+                .iter()
+                .all(|schema| schema.r#type == "string" && schema.r#enum.is_some())
+        {
+            // Enums with documented variants
+            Schema {
+                r#type: "string".to_string(),
+                r#enum: Some(
+                    schemas
+                        .iter()
+                        .flat_map(|schema| schema.r#enum.clone().unwrap_or_default())
+                        .collect(),
+                ),
+                ..Default::default()
+            }
+        } else {
+            if schemas.len() > 1 {
+                self.report(path, path.rsplit('/').next().unwrap_or("anyOf"));
+            }
+            schemas.into_iter().next().ok_or_else(|| {
+                CompletionError::RequestError(format!("Empty alternatives at {path}").into())
+            })?
+        };
+
+        if nullable {
+            schema.nullable = Some(true);
         }
+        Ok(schema)
+    }
+}
+
+impl TryFrom<completion::ToolDefinition> for FunctionDeclaration {
+    type Error = CompletionError;
+
+    fn try_from(tool: completion::ToolDefinition) -> Result<Self, Self::Error> {
+        let ConvertedSchema {
+            schema,
+            unsupported,
+        } = convert_schema(&tool.parameters)?;
+
+        for construct in unsupported {
+            tracing::warn!(target: "rig",
+                "Parameters of tool {} contain {}, which Gemini does not support",
+                tool.name,
+                construct
+            );
+        }
+
+        Ok(Self {
+            name: tool.name,
+            description: tool.description,
+            // Gemini rejects objects without properties, i.e.: tools without parameters
+            parameters: match &schema.properties {
+                Some(properties) if !properties.is_empty() => Some(schema),
+                _ => None,
+            },
+        })
     }
 }
 
@@ -156,7 +619,11 @@ impl TryFrom<GenerateContentResponse> for completion::CompletionResponse<Generat
 
     fn try_from(response: GenerateContentResponse) -> Result<Self, Self::Error> {
         match response.candidates.as_slice() {
-            [ContentCandidate { content, .. }, ..] => Ok(completion::CompletionResponse {
+            [ContentCandidate {
+                content,
+                citation_metadata,
+                ..
+            }, ..] => Ok(completion::CompletionResponse {
                 choice: match content.parts.first().unwrap() {
                     Part {
                         text: Some(text), ..
@@ -176,6 +643,14 @@ impl TryFrom<GenerateContentResponse> for completion::CompletionResponse<Generat
                         ))
                     }
                 },
+                usage: response
+                    .usage_metadata
+                    .as_ref()
+                    .map(completion::Usage::from),
+                citations: citation_metadata
+                    .as_ref()
+                    .map(|metadata| citations(content, metadata))
+                    .unwrap_or_default(),
                 raw_response: response,
             }),
             _ => Err(CompletionError::ResponseError(
@@ -185,6 +660,32 @@ impl TryFrom<GenerateContentResponse> for completion::CompletionResponse<Generat
     }
 }
 
+/// Citations of the candidate `content`. Gemini reports the cited segments in bytes of the
+/// candidate text.
+fn citations(content: &Content, metadata: &CitationMetadata) -> Vec<completion::Citation> {
+    let text = content
+        .parts
+        .iter()
+        .filter_map(|part| part.text.as_deref())
+        .collect::<String>();
+    let chars = |index: Option<i32>| {
+        let index = (index.unwrap_or(0).max(0) as usize).min(text.len());
+        text.char_indices().take_while(|(i, _)| *i < index).count()
+    };
+
+    metadata
+        .citation_sources
+        .iter()
+        .map(|source| completion::Citation {
+            source_id: None,
+            url: source.uri.clone(),
+            span: source.end_index.map(|end| {
+                completion::Span::new(&text, chars(source.start_index), chars(Some(end)))
+            }),
+        })
+        .collect()
+}
+
 pub mod gemini_api_types {
     use std::collections::HashMap;
 
@@ -195,7 +696,7 @@ pub mod gemini_api_types {
     use serde_json::{Map, Value};
 
     use crate::{
-        completion::CompletionError,
+        completion::{self, CompletionError},
         providers::gemini::gemini_api_types::{CodeExecutionResult, ExecutableCode},
     };
 
@@ -385,6 +886,15 @@ pub mod gemini_api_types {
         }
     }
 
+    impl From<&UsageMetadata> for completion::Usage {
+        fn from(usage: &UsageMetadata) -> Self {
+            completion::Usage::new(
+                usage.prompt_token_count as u64,
+                usage.candidates_token_count as u64,
+            )
+        }
+    }
+
     /// A set of the feedback metadata the prompt specified in [GenerateContentRequest.contents](GenerateContentRequest).
     #[derive(Debug, Deserialize)]
     #[serde(rename_all = "camelCase")]
@@ -557,17 +1067,27 @@ pub mod gemini_api_types {
     /// The Schema object allows the definition of input and output data types. These types can be objects, but also
     /// primitives and arrays. Represents a select subset of an OpenAPI 3.0 schema object.
     /// From [Gemini API Reference](https://ai.google.dev/api/caching#Schema)
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
     pub struct Schema {
         pub r#type: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub format: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub description: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub nullable: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub r#enum: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub max_items: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub min_items: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub properties: Option<HashMap<String, Schema>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub required: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub items: Option<Box<Schema>>,
     }
 
@@ -659,7 +1179,8 @@ pub mod gemini_api_types {
     #[derive(Debug, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Tool {
-        pub function_declaration: FunctionDeclaration,
+        pub function_declarations: Vec<FunctionDeclaration>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         pub code_execution: Option<CodeExecution>,
     }
 
@@ -668,13 +1189,73 @@ pub mod gemini_api_types {
     pub struct FunctionDeclaration {
         pub name: String,
         pub description: String,
-        pub parameters: Option<Vec<Schema>>,
+        /// Parameters of the function, as an object schema. Omitted for functions without
+        /// parameters.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub parameters: Option<Schema>,
     }
 
-    #[derive(Debug, Serialize)]
+    /// Tool configuration, which can be set with the `toolConfig` key of `additional_params`:
+    /// ```rust
+    /// use rig::providers::gemini::completion::gemini_api_types::{FunctionCallingMode, ToolConfig};
+    ///
+    /// let agent = gemini.agent(GEMINI_1_5_FLASH)
+    ///     .tool(Adder)
+    ///     .additional_params(json!({
+    ///         "toolConfig": ToolConfig::mode(FunctionCallingMode::Any),
+    ///     }))
+    ///     .build();
+    /// ```
+    /// From [Gemini API Reference](https://ai.google.dev/api/caching#ToolConfig)
+    #[derive(Debug, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct ToolConfig {
-        pub schema: Option<Schema>,
+        pub function_calling_config: Option<FunctionCallingConfig>,
+    }
+
+    impl ToolConfig {
+        /// Function calling in the given mode, with any function
+        pub fn mode(mode: FunctionCallingMode) -> Self {
+            Self {
+                function_calling_config: Some(FunctionCallingConfig {
+                    mode,
+                    allowed_function_names: None,
+                }),
+            }
+        }
+
+        /// Force a call to one of the given functions (i.e.: [FunctionCallingMode::Any] mode)
+        pub fn any_of(function_names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+            Self {
+                function_calling_config: Some(FunctionCallingConfig {
+                    mode: FunctionCallingMode::Any,
+                    allowed_function_names: Some(
+                        function_names.into_iter().map(Into::into).collect(),
+                    ),
+                }),
+            }
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FunctionCallingConfig {
+        pub mode: FunctionCallingMode,
+        /// Functions the model may call, only with [FunctionCallingMode::Any]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub allowed_function_names: Option<Vec<String>>,
+    }
+
+    #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum FunctionCallingMode {
+        /// The model decides whether to call a function or to answer (default)
+        #[default]
+        Auto,
+        /// The model always calls a function
+        Any,
+        /// The model never calls a function
+        None,
     }
 
     #[derive(Debug, Serialize)]
@@ -699,3 +1280,167 @@ pub mod gemini_api_types {
         Off,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gemini_api_types::FunctionCallingMode;
+
+    #[test]
+    fn test_convert_schema() {
+        let converted = convert_schema(&json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Order",
+            "type": "object",
+            "required": ["id", "items", "status"],
+            "properties": {
+                "id": {"type": "integer", "format": "uint64", "minimum": 0},
+                "items": {
+                    "type": "array",
+                    "items": {"$ref": "#/definitions/Item"},
+                    "minItems": 1
+                },
+                "status": {"oneOf": [
+                    {"type": "string", "enum": ["pending"]},
+                    {"type": "string", "const": "shipped"}
+                ]},
+                "note": {"type": ["string", "null"], "maxLength": 280}
+            },
+            "definitions": {
+                "Item": {
+                    "type": "object",
+                    "properties": {
+                        "sku": {"type": "string", "description": "Stock keeping unit"},
+                        "quantity": {"type": "number", "format": "double"}
+                    },
+                    "required": ["sku", "missing"]
+                }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&converted.schema).unwrap(),
+            json!({
+                "type": "object",
+                "description": "Order",
+                "required": ["id", "items", "status"],
+                "properties": {
+                    "id": {"type": "integer", "format": "int64"},
+                    "items": {
+                        "type": "array",
+                        "minItems": 1,
+                        "items": {
+                            "type": "object",
+                            "required": ["sku"],
+                            "properties": {
+                                "sku": {"type": "string", "description": "Stock keeping unit"},
+                                "quantity": {"type": "number", "format": "double"}
+                            }
+                        }
+                    },
+                    "status": {"type": "string", "enum": ["pending", "shipped"]},
+                    "note": {"type": "string", "nullable": true}
+                }
+            })
+        );
+        assert_eq!(
+            converted.unsupported,
+            vec![UnsupportedConstruct {
+                path: "#/properties/note".to_string(),
+                keyword: "maxLength".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_convert_schema_unsupported() {
+        let converted = convert_schema(&json!({
+            "type": "object",
+            "properties": {
+                "value": {"anyOf": [{"type": "string"}, {"type": "integer"}]},
+                "node": {"$ref": "#/$defs/Node"}
+            },
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": {"next": {"$ref": "#/$defs/Node"}}
+                }
+            }
+        }))
+        .unwrap();
+
+        let mut unsupported = converted
+            .unsupported
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        unsupported.sort();
+        assert_eq!(
+            unsupported,
+            vec![
+                "`$ref` at #/properties/node/properties/next",
+                "`anyOf` at #/properties/value/anyOf",
+            ]
+        );
+
+        assert!(convert_schema(&json!({"$ref": "#/definitions/Missing"})).is_err());
+    }
+
+    #[test]
+    fn test_function_declaration() {
+        let declaration = FunctionDeclaration::try_from(completion::ToolDefinition {
+            name: "now".to_string(),
+            description: "Current time".to_string(),
+            parameters: json!({"type": "object", "properties": {}}),
+        })
+        .unwrap();
+        assert!(declaration.parameters.is_none());
+
+        assert_eq!(
+            serde_json::to_value(ToolConfig::any_of(["add"])).unwrap(),
+            json!({"functionCallingConfig": {"mode": "ANY", "allowedFunctionNames": ["add"]}})
+        );
+        let config: ToolConfig =
+            serde_json::from_value(json!({"functionCallingConfig": {"mode": "NONE"}})).unwrap();
+        assert_eq!(
+            config.function_calling_config.unwrap().mode,
+            FunctionCallingMode::None
+        );
+    }
+
+    #[test]
+    fn test_citations() {
+        let response: GenerateContentResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [{"text": "Café au lait. Rig is in Rust."}]},
+                "citationMetadata": {"citationSources": [
+                    {"uri": "https://rig.rs", "startIndex": 15, "endIndex": 30},
+                    {"uri": "https://example.com"}
+                ]}
+            }]
+        }))
+        .unwrap();
+        let response = completion::CompletionResponse::try_from(response).unwrap();
+
+        assert_eq!(
+            response.citations,
+            vec![
+                completion::Citation {
+                    source_id: None,
+                    url: Some("https://rig.rs".into()),
+                    span: Some(completion::Span {
+                        start: 14,
+                        end: 29,
+                        text: "Rig is in Rust.".into()
+                    }),
+                },
+                completion::Citation {
+                    source_id: None,
+                    url: Some("https://example.com".into()),
+                    span: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Heurist API client and Rig integration
+//!
+//! # Example
+//! ```
+//! use rig::providers::heurist;
+//!
+//! let client = heurist::Client::new("YOUR_API_KEY");
+//!
+//! let blue_pencil = client.image_generation_model(heurist::BLUE_PENCIL_REALISTIC);
+//! ```
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+
+use crate::{
+    image_generation::{self, ImageGenerationError, ImageGenerationRequest},
+    json_utils,
+};
+
+// ================================================================
+// Main Heurist Client
+// ================================================================
+const HEURIST_API_BASE_URL: &str = "http://sequencer.heurist.xyz";
+
+#[derive(Clone)]
+pub struct Client {
+    base_url: String,
+    http_client: reqwest::Client,
+}
+
+impl Client {
+    /// Create a new Heurist client with the given API key.
+    pub fn new(api_key: &str) -> Self {
+        Self::from_url(api_key, HEURIST_API_BASE_URL)
+    }
+
+    /// Create a new Heurist client with the given API key and base API URL.
+    pub fn from_url(api_key: &str, base_url: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            http_client: reqwest::Client::builder()
+                .default_headers({
+                    let mut headers = reqwest::header::HeaderMap::new();
+                    headers.insert(
+                        "Authorization",
+                        format!("Bearer {}", api_key)
+                            .parse()
+                            .expect("Bearer token should parse"),
+                    );
+                    headers
+                })
+                .build()
+                .expect("Heurist reqwest client should build"),
+        }
+    }
+
+    /// Create a new Heurist client from the `HEURIST_API_KEY` environment variable.
+    /// Panics if the environment variable is not set.
+    pub fn from_env() -> Self {
+        let api_key = std::env::var("HEURIST_API_KEY").expect("HEURIST_API_KEY not set");
+        Self::new(&api_key)
+    }
+
+    fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{}", self.base_url, path).replace("//", "/");
+        self.http_client.post(url)
+    }
+
+    /// Create an image generation model with the given name.
+    pub fn image_generation_model(&self, model: &str) -> ImageGenerationModel {
+        ImageGenerationModel::new(self.clone(), model)
+    }
+}
+
+// ================================================================
+// Heurist Image Generation API
+// ================================================================
+/// `BluePencilRealistic` image generation model
+pub const BLUE_PENCIL_REALISTIC: &str = "BluePencilRealistic";
+
+/// Number of seconds the sequencer has to complete a job
+const JOB_DEADLINE_SECS: u64 = 300;
+
+/// Response of the Heurist sequencer, i.e.: the URL of the generated image
+#[derive(Debug)]
+pub struct ImageGenerationResponse {
+    pub url: String,
+}
+
+#[derive(Clone)]
+pub struct ImageGenerationModel {
+    client: Client,
+    /// Name of the model (e.g.: BluePencilRealistic)
+    pub model: String,
+}
+
+impl ImageGenerationModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl image_generation::ImageGenerationModel for ImageGenerationModel {
+    type Response = ImageGenerationResponse;
+
+    async fn image_generation(
+        &self,
+        request: ImageGenerationRequest,
+    ) -> Result<
+        image_generation::ImageGenerationResponse<ImageGenerationResponse>,
+        ImageGenerationError,
+    > {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| ImageGenerationError::RequestError(err.to_string()))?;
+
+        // Additional params are merged into the Stable Diffusion parameters
+        // (e.g.: `num_iterations`, `guidance_scale`)
+        let mut model_input = json!({
+            "width": request.width,
+            "height": request.height,
+            "prompt": request.prompt,
+            "neg_prompt": request.negative_prompt.unwrap_or_default(),
+            "num_iterations": 50,
+            "guidance_scale": 7.5,
+        });
+        if let Some(seed) = request.seed {
+            model_input["seed"] = seed.into();
+        }
+        if let Some(params) = request.additional_params {
+            model_input = json_utils::merge(model_input, params);
+        }
+
+        let response = self
+            .client
+            .post("/submit_job")
+            .json(&json!({
+                "model_input": {"SD": model_input},
+                "model_id": self.model,
+                "deadline": now.as_secs() + JOB_DEADLINE_SECS,
+                "priority": 1,
+                "job_id": format!("job_{}", now.as_millis()),
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ImageGenerationError::ProviderError(response.text().await?));
+        }
+
+        let url = response.text().await?.trim().trim_matches('"').to_string();
+        if !url.starts_with("http") {
+            return Err(ImageGenerationError::ProviderError(url));
+        }
+
+        // The image is hosted by a third party, which must not receive the API key
+        let image = reqwest::get(&url).await?;
+        if !image.status().is_success() {
+            return Err(ImageGenerationError::ResponseError(format!(
+                "Failed to download image {url}: {}",
+                image.status()
+            )));
+        }
+        let content_type = image
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .filter(|content_type| content_type.starts_with("image/"))
+            .map(str::to_string);
+        let image = image.bytes().await?.to_vec();
+
+        Ok(image_generation::ImageGenerationResponse {
+            mime_type: content_type
+                .or_else(|| image_generation::image_mime_type(&image).map(str::to_string))
+                .unwrap_or_else(|| "image/png".to_string()),
+            image,
+            raw_response: ImageGenerationResponse { url },
+        })
+    }
+}
@@ -3,9 +3,12 @@
 //! Currently, the following providers are supported:
 //! - Cohere
 //! - OpenAI
+//! - Azure OpenAI
 //! - Perplexity
 //! - Anthropic
 //! - Google Gemini
+//! - Heurist (image generation)
+//! - OpenAI-compatible providers (Groq, DeepSeek, Together, OpenRouter, LM Studio, vLLM, Ollama)
 //!
 //! Each provider has its own module, which contains a `Client` implementation that can
 //! be used to initialize completion and embedding models and execute requests to those models.
@@ -41,8 +44,11 @@
 //! Note: The example above uses the OpenAI provider client, but the same pattern can
 //! be used with the Cohere provider client.
 pub mod anthropic;
+pub mod azure;
 pub mod cohere;
 pub mod gemini;
+pub mod heurist;
 pub mod openai;
+pub mod openai_compatible;
 pub mod perplexity;
 pub mod xai;
@@ -8,13 +8,24 @@
 //!
 //! let gpt4o = client.completion_model(openai::GPT_4O);
 //! ```
+use std::collections::BTreeMap;
+
 use crate::{
     agent::AgentBuilder,
+    classifier::ClassifierBuilder,
     completion::{self, CompletionError, CompletionRequest},
     embeddings::{self, EmbeddingError, EmbeddingsBuilder},
     extractor::ExtractorBuilder,
-    json_utils, Embed,
+    guardrail::{self, Action, GuardrailError, Verdict},
+    image_generation::{self, ImageGenerationError, ImageGenerationRequest},
+    json_utils,
+    speech::{self, SpeechError, SpeechRequest},
+    streaming::{self, StreamingResult},
+    transcription::{self, TranscriptionError, TranscriptionRequest},
+    Embed,
 };
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{stream, StreamExt};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -68,6 +79,11 @@ impl Client {
         self.http_client.post(url)
     }
 
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{}", self.base_url, path).replace("//", "/");
+        self.http_client.get(url)
+    }
+
     /// Create an embedding model with the given name.
     /// Note: default embedding dimension of 0 will be used if model is not known.
     /// If this is the case, it's better to use function `embedding_model_with_ndims`
@@ -140,6 +156,66 @@ impl Client {
         CompletionModel::new(self.clone(), model)
     }
 
+    /// Create an image generation model with the given name.
+    ///
+    /// # Example
+    /// ```
+    /// use rig::providers::openai::{Client, self};
+    ///
+    /// // Initialize the OpenAI client
+    /// let openai = Client::new("your-open-ai-api-key");
+    ///
+    /// let dall_e = openai.image_generation_model(openai::DALL_E_3);
+    /// ```
+    pub fn image_generation_model(&self, model: &str) -> ImageGenerationModel {
+        ImageGenerationModel::new(self.clone(), model)
+    }
+
+    /// Create a transcription model with the given name.
+    ///
+    /// # Example
+    /// ```
+    /// use rig::providers::openai::{Client, self};
+    ///
+    /// // Initialize the OpenAI client
+    /// let openai = Client::new("your-open-ai-api-key");
+    ///
+    /// let whisper = openai.transcription_model(openai::WHISPER_1);
+    /// ```
+    pub fn transcription_model(&self, model: &str) -> TranscriptionModel {
+        TranscriptionModel::new(self.clone(), model)
+    }
+
+    /// Create a speech model with the given name.
+    ///
+    /// # Example
+    /// ```
+    /// use rig::providers::openai::{Client, self};
+    ///
+    /// // Initialize the OpenAI client
+    /// let openai = Client::new("your-open-ai-api-key");
+    ///
+    /// let tts = openai.speech_model(openai::TTS_1);
+    /// ```
+    pub fn speech_model(&self, model: &str) -> SpeechModel {
+        SpeechModel::new(self.clone(), model)
+    }
+
+    /// Create a moderation model with the given name, usable as an output guardrail.
+    ///
+    /// # Example
+    /// ```
+    /// use rig::providers::openai::{Client, self};
+    ///
+    /// // Initialize the OpenAI client
+    /// let openai = Client::new("your-open-ai-api-key");
+    ///
+    /// let moderation = openai.moderation_model(openai::OMNI_MODERATION_LATEST);
+    /// ```
+    pub fn moderation_model(&self, model: &str) -> ModerationModel {
+        ModerationModel::new(self.clone(), model)
+    }
+
     /// Create an agent builder with the given completion model.
     ///
     /// # Example
@@ -165,16 +241,26 @@ impl Client {
     ) -> ExtractorBuilder<T, CompletionModel> {
         ExtractorBuilder::new(self.completion_model(model))
     }
+
+    /// Create a classifier builder with the given completion model.
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        model: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(model))
+    }
 }
 
 #[derive(Debug, Deserialize)]
-struct ApiErrorResponse {
-    message: String,
+pub(crate) struct ApiErrorResponse {
+    pub(crate) message: String,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
-enum ApiResponse<T> {
+pub(crate) enum ApiResponse<T> {
     Ok(T),
     Err(ApiErrorResponse),
 }
@@ -235,6 +321,15 @@ impl std::fmt::Display for Usage {
     }
 }
 
+impl From<&Usage> for completion::Usage {
+    fn from(usage: &Usage) -> Self {
+        completion::Usage::new(
+            usage.prompt_tokens as u64,
+            usage.total_tokens.saturating_sub(usage.prompt_tokens) as u64,
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct EmbeddingModel {
     client: Client,
@@ -307,6 +402,205 @@ impl EmbeddingModel {
     }
 }
 
+// ================================================================
+// OpenAI Batch API
+// ================================================================
+#[derive(Debug, Deserialize)]
+pub struct FileObject {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Batch {
+    pub id: String,
+    pub status: String,
+    pub output_file_id: Option<String>,
+    pub error_file_id: Option<String>,
+    pub errors: Option<BatchErrors>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchErrors {
+    pub data: Vec<BatchError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchError {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Line of the output file of a batch
+#[derive(Debug, Deserialize)]
+pub struct BatchOutput {
+    pub custom_id: String,
+    pub response: Option<BatchResponse>,
+    pub error: Option<BatchError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchResponse {
+    pub status_code: u16,
+    pub body: serde_json::Value,
+}
+
+impl Client {
+    async fn batch(&self, id: &str) -> Result<Batch, EmbeddingError> {
+        let response = self.get(&format!("/batches/{}", id)).send().await?;
+
+        if response.status().is_success() {
+            match response.json::<ApiResponse<Batch>>().await? {
+                ApiResponse::Ok(batch) => Ok(batch),
+                ApiResponse::Err(err) => Err(EmbeddingError::ProviderError(err.message)),
+            }
+        } else {
+            Err(EmbeddingError::ProviderError(response.text().await?))
+        }
+    }
+}
+
+impl embeddings::BatchEmbeddingModel for EmbeddingModel {
+    const MAX_REQUESTS: usize = 50_000;
+    /// Batch jobs of the embeddings endpoint are limited to 50,000 inputs across all requests
+    const MAX_INPUTS: usize = 50_000;
+
+    async fn submit_batch(
+        &self,
+        requests: Vec<embeddings::batch::BatchRequest>,
+    ) -> Result<String, EmbeddingError> {
+        let jsonl = requests
+            .into_iter()
+            .map(|request| {
+                serde_json::to_string(&json!({
+                    "custom_id": request.custom_id,
+                    "method": "POST",
+                    "url": "/v1/embeddings",
+                    "body": {
+                        "model": self.model,
+                        "input": request.texts,
+                    },
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+
+        let form = reqwest::multipart::Form::new()
+            .text("purpose", "batch")
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(jsonl.into_bytes()).file_name("embeddings.jsonl"),
+            );
+        let response = self.client.post("/files").multipart(form).send().await?;
+        if !response.status().is_success() {
+            return Err(EmbeddingError::ProviderError(response.text().await?));
+        }
+        let file = match response.json::<ApiResponse<FileObject>>().await? {
+            ApiResponse::Ok(file) => file,
+            ApiResponse::Err(err) => return Err(EmbeddingError::ProviderError(err.message)),
+        };
+
+        let response = self
+            .client
+            .post("/batches")
+            .json(&json!({
+                "input_file_id": file.id,
+                "endpoint": "/v1/embeddings",
+                "completion_window": "24h",
+            }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(EmbeddingError::ProviderError(response.text().await?));
+        }
+        match response.json::<ApiResponse<Batch>>().await? {
+            ApiResponse::Ok(batch) => Ok(batch.id),
+            ApiResponse::Err(err) => Err(EmbeddingError::ProviderError(err.message)),
+        }
+    }
+
+    async fn batch_status(
+        &self,
+        id: &str,
+    ) -> Result<embeddings::batch::BatchStatus, EmbeddingError> {
+        use embeddings::batch::BatchStatus;
+
+        let batch = self.client.batch(id).await?;
+        Ok(match batch.status.as_str() {
+            "completed" => BatchStatus::Completed,
+            "failed" | "expired" | "cancelling" | "cancelled" => BatchStatus::Failed(
+                batch
+                    .errors
+                    .iter()
+                    .flat_map(|errors| &errors.data)
+                    .map(|error| error.message.clone())
+                    .chain(std::iter::once(format!("batch {}", batch.status)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            _ => BatchStatus::InProgress,
+        })
+    }
+
+    async fn batch_results(
+        &self,
+        id: &str,
+    ) -> Result<Vec<(String, embeddings::batch::BatchResult)>, EmbeddingError> {
+        let batch = self.client.batch(id).await?;
+        let output_file_id = batch.output_file_id.ok_or_else(|| {
+            EmbeddingError::ResponseError(format!("Batch {} has no output file", id))
+        })?;
+
+        let response = self
+            .client
+            .get(&format!("/files/{}/content", output_file_id))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(EmbeddingError::ProviderError(response.text().await?));
+        }
+
+        response
+            .text()
+            .await?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let output = serde_json::from_str::<BatchOutput>(line)?;
+                let result = match (output.response, output.error) {
+                    (_, Some(error)) => Err(EmbeddingError::ProviderError(error.message)),
+                    (Some(response), None) if response.status_code == 200 => {
+                        serde_json::from_value::<EmbeddingResponse>(response.body)
+                            .map_err(EmbeddingError::from)
+                    }
+                    (Some(response), None) => {
+                        Err(EmbeddingError::ProviderError(response.body.to_string()))
+                    }
+                    (None, None) => Err(EmbeddingError::ResponseError(format!(
+                        "Request {} of batch {} has no response",
+                        output.custom_id, id
+                    ))),
+                };
+
+                Ok((
+                    output.custom_id,
+                    result.map(|mut response| {
+                        response.data.sort_by_key(|data| data.index);
+                        response
+                            .data
+                            .into_iter()
+                            .map(|data| embeddings::Embedding {
+                                // The texts are not part of the output file, see `batch_results`
+                                document: String::new(),
+                                vec: data.embedding,
+                            })
+                            .collect()
+                    }),
+                ))
+            })
+            .collect()
+    }
+}
+
 // ================================================================
 // OpenAI Completion API
 // ================================================================
@@ -392,6 +686,8 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
                         call.function.name.clone(),
                         serde_json::from_str(&call.function.arguments)?,
                     ),
+                    usage: value.usage.as_ref().map(completion::Usage::from),
+                    citations: vec![],
                     raw_response: value,
                 })
             }
@@ -409,6 +705,8 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
                         .collect::<Vec<_>>()
                         .join("")
                 ),
+                usage: value.usage.as_ref().map(completion::Usage::from),
+                citations: vec![],
                 raw_response: value,
             }),
             _ => Err(CompletionError::ResponseError(
@@ -519,90 +817,97 @@ impl CompletionModel {
     }
 }
 
-impl completion::CompletionModel for CompletionModel {
-    type Response = CompletionResponse;
-
-    async fn completion(
-        &self,
-         completion_request: CompletionRequest,
-    ) -> Result<completion::CompletionResponse<CompletionResponse>, CompletionError> {
-        // Add preamble to chat history (if available)
-        let mut full_history = if let Some(preamble) = &completion_request.preamble {
-            vec![Message {
-                role: "system".into(),
-                content: Some(vec![ContentItem {
-                    content_type: "text".to_string(),
-                    text: Some(preamble.clone()),
-                    image_url: None,
-                }]),
-                tool_calls: None,
-            }]
-        } else {
-            vec![]
-        };
-
-        // Extend existing chat history
-        full_history.extend(completion_request.chat_history.clone().into_iter().map(|msg| Message {
-            role: msg.role,
+/// Create the body of a chat completions request for `model` (also used by Azure OpenAI, whose
+/// deployments take the same requests).
+pub(crate) fn create_completion_request(
+    model: &str,
+    completion_request: CompletionRequest,
+) -> serde_json::Value {
+    // Add preamble to chat history (if available)
+    let mut full_history = if let Some(preamble) = &completion_request.preamble {
+        vec![Message {
+            role: "system".into(),
             content: Some(vec![ContentItem {
                 content_type: "text".to_string(),
-                text: Some(msg.content),
+                text: Some(preamble.clone()),
                 image_url: None,
             }]),
             tool_calls: None,
-        }));
+        }]
+    } else {
+        vec![]
+    };
 
-        // Create final message content
-        let mut content = vec![ContentItem {
+    // Extend existing chat history
+    full_history.extend(completion_request.chat_history.clone().into_iter().map(|msg| Message {
+        role: msg.role,
+        content: Some(vec![ContentItem {
             content_type: "text".to_string(),
-            text: Some(completion_request.prompt_with_context()),
+            text: Some(msg.content),
             image_url: None,
-        }];
-
-        // Add image URLs if present
-        if let Some(urls) = completion_request.image_urls {
-            for url in urls {
-                content.push(ContentItem {
-                    content_type: "image_url".to_string(),
-                    text: None,
-                    image_url: Some(ImageUrl { url }),
-                });
-            }
+        }]),
+        tool_calls: None,
+    }));
+
+    // Create final message content
+    let mut content = vec![ContentItem {
+        content_type: "text".to_string(),
+        text: Some(completion_request.prompt_with_context()),
+        image_url: None,
+    }];
+
+    // Add image URLs if present
+    if let Some(urls) = completion_request.image_urls {
+        for url in urls {
+            content.push(ContentItem {
+                content_type: "image_url".to_string(),
+                text: None,
+                image_url: Some(ImageUrl { url }),
+            });
         }
+    }
 
-        // Add final message
-        full_history.push(Message {
-            role: "user".into(),
-            content: Some(content),
-            tool_calls: None,
-        });
+    // Add final message
+    full_history.push(Message {
+        role: "user".into(),
+        content: Some(content),
+        tool_calls: None,
+    });
 
-        let request = if completion_request.tools.is_empty() {
-            json!({
-                "model": self.model,
-                "messages": full_history,
-                "temperature": completion_request.temperature,
-            })
-        } else {
-            json!({
-                "model": self.model,
-                "messages": full_history,
-                "temperature": completion_request.temperature,
-                "tools": completion_request.tools.into_iter().map(ToolDefinition::from).collect::<Vec<_>>(),
-                "tool_choice": "auto",
-            })
-        };
+    let request = if completion_request.tools.is_empty() {
+        json!({
+            "model": model,
+            "messages": full_history,
+            "temperature": completion_request.temperature,
+        })
+    } else {
+        json!({
+            "model": model,
+            "messages": full_history,
+            "temperature": completion_request.temperature,
+            "tools": completion_request.tools.into_iter().map(ToolDefinition::from).collect::<Vec<_>>(),
+            "tool_choice": "auto",
+        })
+    };
 
+    if let Some(params) = completion_request.additional_params {
+        json_utils::merge(request, params)
+    } else {
+        request
+    }
+}
+
+impl completion::CompletionModel for CompletionModel {
+    type Response = CompletionResponse;
+
+    async fn completion(
+        &self,
+         completion_request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<CompletionResponse>, CompletionError> {
         let response = self
             .client
             .post("/chat/completions")
-            .json(
-                &if let Some(params) = completion_request.additional_params {
-                    json_utils::merge(request, params)
-                } else {
-                    request
-                },
-            )
+            .json(&create_completion_request(&self.model, completion_request))
             .send()
             .await?;
 
@@ -621,4 +926,809 @@ impl completion::CompletionModel for CompletionModel {
             Err(CompletionError::ProviderError(response.text().await?))
         }
     }
+
+    fn structured_output_params(
+        &self,
+        name: &str,
+        schema: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        structured_output_params(&self.model, name, schema)
+    }
+
+    async fn stream(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> Result<StreamingResult, CompletionError> {
+        let request = json_utils::merge(
+            create_completion_request(&self.model, completion_request),
+            json!({
+                "stream": true,
+                "stream_options": {"include_usage": true},
+            }),
+        );
+
+        let response = self
+            .client
+            .post("/chat/completions")
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(CompletionError::ProviderError(response.text().await?));
+        }
+
+        Ok(streaming_result(response))
+    }
+}
+
+/// Models (and their snapshots) supporting structured outputs with `response_format`
+const STRUCTURED_OUTPUT_MODELS: &[&str] = &[
+    GPT_4O,
+    "gpt-4o-2024-08-06",
+    "gpt-4o-2024-11-20",
+    "gpt-4o-mini",
+    "gpt-4o-mini-2024-07-18",
+    "gpt-4.1",
+    "gpt-4.1-2025-04-14",
+    "gpt-4.1-mini",
+    "gpt-4.1-mini-2025-04-14",
+    "gpt-4.1-nano",
+    "gpt-4.1-nano-2025-04-14",
+    "gpt-5",
+    "gpt-5-mini",
+    "gpt-5-nano",
+    "o1",
+    "o1-2024-12-17",
+    "o3",
+    "o3-2025-04-16",
+    "o3-mini",
+    "o3-mini-2025-01-31",
+    "o4-mini",
+    "o4-mini-2025-04-16",
+];
+
+/// `response_format` parameters for models supporting structured outputs (see
+/// [STRUCTURED_OUTPUT_MODELS]), or `None` if the model does not support them or if the schema
+/// cannot be enforced with strict mode (see [strict_schema]).
+pub(crate) fn structured_output_params(
+    model: &str,
+    name: &str,
+    schema: &serde_json::Value,
+) -> Option<serde_json::Value> {
+    if !STRUCTURED_OUTPUT_MODELS.contains(&model) {
+        return None;
+    }
+
+    Some(json!({
+        "response_format": {
+            "type": "json_schema",
+            "json_schema": {
+                "name": name,
+                "schema": strict_schema(schema)?,
+                "strict": true,
+            }
+        }
+    }))
+}
+
+/// Keywords that strict structured outputs do not support
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "allOf",
+    "oneOf",
+    "not",
+    "if",
+    "then",
+    "else",
+    "minLength",
+    "maxLength",
+    "patternProperties",
+    "unevaluatedProperties",
+    "propertyNames",
+    "minProperties",
+    "maxProperties",
+    "unevaluatedItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "uniqueItems",
+];
+
+/// Adapt a JSON schema (as generated by `schemars`) to strict structured outputs, which require
+/// every property of an object to be required and no additional properties:
+/// - optional properties that are nullable (e.g.: `Option` fields) are made required,
+/// - `additionalProperties` is set to false,
+/// - the `format` of numbers (e.g.: `uint32`) is removed.
+///
+/// Returns `None` if the schema cannot be adapted (e.g.: maps, optional properties that are not
+/// nullable or unsupported keywords), in which case it must not be used in strict mode.
+pub(crate) fn strict_schema(schema: &serde_json::Value) -> Option<serde_json::Value> {
+    let mut schema = schema.clone();
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("$schema");
+    }
+    make_strict(&mut schema).then_some(schema)
+}
+
+fn make_strict(schema: &mut serde_json::Value) -> bool {
+    let Some(schema) = schema.as_object_mut() else {
+        return false;
+    };
+    if UNSUPPORTED_KEYWORDS
+        .iter()
+        .any(|keyword| schema.contains_key(*keyword))
+    {
+        return false;
+    }
+
+    let types = match schema.get("type") {
+        Some(serde_json::Value::String(ty)) => vec![ty.clone()],
+        Some(serde_json::Value::Array(types)) => types
+            .iter()
+            .filter_map(|ty| ty.as_str().map(str::to_string))
+            .collect(),
+        _ => vec![],
+    };
+    if types.iter().any(|ty| ty == "integer" || ty == "number") {
+        schema.remove("format");
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        let required = schema
+            .get("required")
+            .and_then(|required| required.as_array())
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(|r| r.as_str())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if properties
+            .iter()
+            .any(|(name, property)| !required.contains(&name.as_str()) && !is_nullable(property))
+        {
+            return false;
+        }
+        let required = properties.keys().cloned().collect::<Vec<_>>();
+        schema.insert("required".to_string(), json!(required));
+    }
+    if schema.contains_key("properties") || types.iter().any(|ty| ty == "object") {
+        match schema.get("additionalProperties") {
+            None | Some(serde_json::Value::Bool(false)) => {
+                schema.insert("additionalProperties".to_string(), json!(false));
+            }
+            // Maps
+            Some(_) => return false,
+        }
+    }
+
+    // Check the nested schemas
+    if !schema
+        .iter_mut()
+        .filter(|(key, _)| ["properties", "definitions", "$defs"].contains(&key.as_str()))
+        .filter_map(|(_, map)| map.as_object_mut())
+        .flat_map(|map| map.values_mut())
+        .all(make_strict)
+    {
+        return false;
+    }
+    if let Some(items) = schema.get_mut("items") {
+        if !make_strict(items) {
+            return false;
+        }
+    }
+    match schema.get_mut("anyOf") {
+        Some(serde_json::Value::Array(variants)) => variants.iter_mut().all(make_strict),
+        Some(_) => false,
+        None => true,
+    }
+}
+
+fn is_nullable(schema: &serde_json::Value) -> bool {
+    let is_null = |schema: &serde_json::Value| schema["type"] == "null";
+
+    match &schema["type"] {
+        serde_json::Value::String(ty) => ty == "null",
+        serde_json::Value::Array(types) => types.iter().any(|ty| ty == "null"),
+        _ => schema["anyOf"]
+            .as_array()
+            .is_some_and(|variants| variants.iter().any(is_null)),
+    }
+}
+
+/// Stream of the choices of a streaming chat completions response
+pub(crate) fn streaming_result(response: reqwest::Response) -> StreamingResult {
+    let mut tool_calls = StreamingToolCalls::default();
+    let stream = streaming::sse_data(response)
+        .map(move |data| tool_calls.process(&data?))
+        .flat_map(|chunks| {
+            stream::iter(match chunks {
+                Ok(chunks) => chunks.into_iter().map(Ok).collect(),
+                Err(err) => vec![Err(err)],
+            })
+        });
+
+    Box::pin(stream)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamingCompletionChunk {
+    #[serde(default)]
+    pub choices: Vec<StreamingChoice>,
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamingChoice {
+    pub delta: StreamingDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamingDelta {
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<StreamingToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamingToolCall {
+    pub index: usize,
+    pub function: StreamingFunction,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamingFunction {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+/// Tool calls are streamed as fragments of their arguments, which are accumulated until the
+/// end of the choice.
+#[derive(Default)]
+struct StreamingToolCalls {
+    calls: BTreeMap<usize, (String, String)>,
+}
+
+impl StreamingToolCalls {
+    fn process(&mut self, data: &str) -> Result<Vec<streaming::StreamingChoice>, CompletionError> {
+        if data == "[DONE]" {
+            return self.flush();
+        }
+
+        let chunk = serde_json::from_str::<StreamingCompletionChunk>(data)?;
+        let mut chunks = vec![];
+
+        // Only the first choice is streamed, as in non-streaming completions
+        if let Some(choice) = chunk.choices.first() {
+            if let Some(content) = choice.delta.content.as_ref().filter(|c| !c.is_empty()) {
+                chunks.push(streaming::StreamingChoice::Message(content.clone()));
+            }
+
+            for call in &choice.delta.tool_calls {
+                let (name, arguments) = self.calls.entry(call.index).or_default();
+                if let Some(call_name) = &call.function.name {
+                    name.push_str(call_name);
+                }
+                arguments.push_str(&call.function.arguments);
+            }
+
+            if choice.finish_reason.is_some() {
+                chunks.extend(self.flush()?);
+            }
+        }
+
+        if let Some(usage) = &chunk.usage {
+            chunks.push(streaming::StreamingChoice::Usage(usage.into()));
+        }
+
+        Ok(chunks)
+    }
+
+    fn flush(&mut self) -> Result<Vec<streaming::StreamingChoice>, CompletionError> {
+        std::mem::take(&mut self.calls)
+            .into_values()
+            .map(|(name, arguments)| {
+                Ok(streaming::StreamingChoice::ToolCall(
+                    name,
+                    match arguments.as_str() {
+                        "" => json!({}),
+                        arguments => serde_json::from_str(arguments)?,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+// ================================================================
+// OpenAI Image Generation API
+// ================================================================
+/// `dall-e-2` image generation model
+pub const DALL_E_2: &str = "dall-e-2";
+/// `dall-e-3` image generation model
+pub const DALL_E_3: &str = "dall-e-3";
+
+#[derive(Debug, Deserialize)]
+pub struct ImageGenerationResponse {
+    pub created: u64,
+    pub data: Vec<ImageData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageData {
+    pub b64_json: Option<String>,
+    pub url: Option<String>,
+    pub revised_prompt: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ImageGenerationModel {
+    client: Client,
+    /// Name of the model (e.g.: dall-e-3)
+    pub model: String,
+}
+
+impl ImageGenerationModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl image_generation::ImageGenerationModel for ImageGenerationModel {
+    type Response = ImageGenerationResponse;
+
+    async fn image_generation(
+        &self,
+        request: ImageGenerationRequest,
+    ) -> Result<
+        image_generation::ImageGenerationResponse<ImageGenerationResponse>,
+        ImageGenerationError,
+    > {
+        if request.seed.is_some() {
+            tracing::warn!(target: "rig", "OpenAI image generation does not support seeds, ignoring it");
+        }
+
+        // The Images API has no negative prompt parameter
+        let prompt = match &request.negative_prompt {
+            Some(negative_prompt) => {
+                format!("{}\n\nDo not include: {negative_prompt}", request.prompt)
+            }
+            None => request.prompt,
+        };
+
+        let body = json!({
+            "model": self.model,
+            "prompt": prompt,
+            "n": 1,
+            "size": format!("{}x{}", request.width, request.height),
+            "response_format": "b64_json",
+        });
+
+        let response = self
+            .client
+            .post("/images/generations")
+            .json(&match request.additional_params {
+                Some(params) => json_utils::merge(body, params),
+                None => body,
+            })
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ImageGenerationError::ProviderError(response.text().await?));
+        }
+
+        match response
+            .json::<ApiResponse<ImageGenerationResponse>>()
+            .await?
+        {
+            ApiResponse::Ok(response) => {
+                let image = match response.data.first() {
+                    Some(ImageData {
+                        b64_json: Some(b64_json),
+                        ..
+                    }) => STANDARD
+                        .decode(b64_json)
+                        .map_err(|err| ImageGenerationError::ResponseError(err.to_string()))?,
+                    _ => {
+                        return Err(ImageGenerationError::ResponseError(
+                            "Response did not contain an image".into(),
+                        ))
+                    }
+                };
+
+                Ok(image_generation::ImageGenerationResponse {
+                    mime_type: image_generation::image_mime_type(&image)
+                        .unwrap_or("image/png")
+                        .to_string(),
+                    image,
+                    raw_response: response,
+                })
+            }
+            ApiResponse::Err(err) => Err(ImageGenerationError::ProviderError(err.message)),
+        }
+    }
+}
+
+// ================================================================
+// OpenAI Transcription API
+// ================================================================
+/// `whisper-1` transcription model
+pub const WHISPER_1: &str = "whisper-1";
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+}
+
+#[derive(Clone)]
+pub struct TranscriptionModel {
+    client: Client,
+    /// Name of the model (e.g.: whisper-1)
+    pub model: String,
+}
+
+impl TranscriptionModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl transcription::TranscriptionModel for TranscriptionModel {
+    type Response = TranscriptionResponse;
+
+    async fn transcription(
+        &self,
+        request: TranscriptionRequest,
+    ) -> Result<transcription::TranscriptionResponse<TranscriptionResponse>, TranscriptionError>
+    {
+        let mut form = reqwest::multipart::Form::new()
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(request.data).file_name(request.filename),
+            )
+            .text("model", self.model.clone())
+            .text("response_format", "json");
+
+        if let Some(language) = request.language {
+            form = form.text("language", language);
+        }
+        if let Some(prompt) = request.prompt {
+            form = form.text("prompt", prompt);
+        }
+        if let Some(temperature) = request.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+
+        // The endpoint takes form fields, so additional params are sent as strings
+        // (arrays as repeated `name[]` fields, e.g.: `timestamp_granularities[]`)
+        if let Some(serde_json::Value::Object(params)) = request.additional_params {
+            for (name, value) in params {
+                match value {
+                    serde_json::Value::String(value) => form = form.text(name, value),
+                    serde_json::Value::Array(values) => {
+                        for value in values {
+                            form = form.text(format!("{name}[]"), form_value(value));
+                        }
+                    }
+                    value => form = form.text(name, form_value(value)),
+                }
+            }
+        }
+
+        let response = self
+            .client
+            .post("/audio/transcriptions")
+            .multipart(form)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(TranscriptionError::ProviderError(response.text().await?));
+        }
+
+        match response
+            .json::<ApiResponse<TranscriptionResponse>>()
+            .await?
+        {
+            ApiResponse::Ok(response) => Ok(transcription::TranscriptionResponse {
+                text: response.text.clone(),
+                raw_response: response,
+            }),
+            ApiResponse::Err(err) => Err(TranscriptionError::ProviderError(err.message)),
+        }
+    }
+}
+
+fn form_value(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
+
+// ================================================================
+// OpenAI Speech API
+// ================================================================
+/// `tts-1` speech model
+pub const TTS_1: &str = "tts-1";
+/// `tts-1-hd` speech model
+pub const TTS_1_HD: &str = "tts-1-hd";
+
+/// Voice used when the request does not set one
+const DEFAULT_VOICE: &str = "alloy";
+
+/// The Speech API returns the audio as the response body, so the raw response is its headers
+#[derive(Debug)]
+pub struct SpeechResponse {
+    pub headers: reqwest::header::HeaderMap,
+}
+
+#[derive(Clone)]
+pub struct SpeechModel {
+    client: Client,
+    /// Name of the model (e.g.: tts-1)
+    pub model: String,
+}
+
+impl SpeechModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl speech::SpeechModel for SpeechModel {
+    type Response = SpeechResponse;
+
+    async fn speech(
+        &self,
+        request: SpeechRequest,
+    ) -> Result<speech::SpeechResponse<SpeechResponse>, SpeechError> {
+        let mut body = json!({
+            "model": self.model,
+            "input": request.text,
+            "voice": request.voice.as_deref().unwrap_or(DEFAULT_VOICE),
+            "response_format": request.format,
+        });
+        if let Some(speed) = request.speed {
+            body["speed"] = speed.into();
+        }
+
+        let response = self
+            .client
+            .post("/audio/speech")
+            .json(&match request.additional_params {
+                Some(params) => json_utils::merge(body, params),
+                None => body,
+            })
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(SpeechError::ProviderError(response.text().await?));
+        }
+
+        let headers = response.headers().clone();
+        Ok(speech::SpeechResponse {
+            audio: response.bytes().await?.to_vec(),
+            mime_type: request.format.mime_type().to_string(),
+            raw_response: SpeechResponse { headers },
+        })
+    }
+}
+
+// ================================================================
+// OpenAI Moderation API
+// ================================================================
+/// `omni-moderation-latest` moderation model
+pub const OMNI_MODERATION_LATEST: &str = "omni-moderation-latest";
+/// `text-moderation-latest` moderation model
+pub const TEXT_MODERATION_LATEST: &str = "text-moderation-latest";
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    pub categories: BTreeMap<String, bool>,
+    pub category_scores: BTreeMap<String, f64>,
+}
+
+/// Moderation model, used as a [Guardrail](guardrail::Guardrail) flagging outputs in a harmful
+/// category. Flagged outputs are blocked by default (see [ModerationModel::action]).
+#[derive(Clone)]
+pub struct ModerationModel {
+    client: Client,
+    /// Name of the model (e.g.: omni-moderation-latest)
+    pub model: String,
+    action: Action,
+}
+
+impl ModerationModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+            action: Action::Block,
+        }
+    }
+
+    /// Set the action on flagged outputs (default: [Action::Block]). [Action::Redact] is
+    /// treated as [Action::Regenerate].
+    pub fn action(mut self, action: Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Classify `input` with the moderation endpoint
+    pub async fn moderate(&self, input: &str) -> Result<ModerationResponse, GuardrailError> {
+        let response = self
+            .client
+            .post("/moderations")
+            .json(&json!({
+                "model": self.model,
+                "input": input,
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(GuardrailError::CheckError(response.text().await?));
+        }
+
+        match response.json::<ApiResponse<ModerationResponse>>().await? {
+            ApiResponse::Ok(response) => Ok(response),
+            ApiResponse::Err(err) => Err(GuardrailError::CheckError(err.message)),
+        }
+    }
+}
+
+impl guardrail::Guardrail for ModerationModel {
+    fn name(&self) -> String {
+        "openai_moderation".to_string()
+    }
+
+    async fn check(&self, output: &str) -> Result<Verdict, GuardrailError> {
+        let response = self.moderate(output).await?;
+
+        let categories = response
+            .results
+            .iter()
+            .filter(|result| result.flagged)
+            .flat_map(|result| {
+                result
+                    .categories
+                    .iter()
+                    .filter(|(_, flagged)| **flagged)
+                    .map(|(category, _)| category.as_str())
+            })
+            .collect::<Vec<_>>();
+
+        Ok(match response.results.iter().any(|result| result.flagged) {
+            true => Verdict::reject(
+                self.action,
+                format!("Output flagged by moderation ({})", categories.join(", ")),
+            ),
+            false => Verdict::Pass,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Person {
+        name: String,
+        age: u32,
+        nickname: Option<String>,
+        address: Option<Address>,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Inventory {
+        counts: std::collections::HashMap<String, u32>,
+    }
+
+    #[test]
+    fn test_structured_output_params() {
+        let schema = json!(schemars::schema_for!(Person));
+
+        for model in [GPT_4O, "gpt-4.1-mini", "o3-mini"] {
+            let params = structured_output_params(model, "person", &schema).unwrap();
+            assert_eq!(params["response_format"]["json_schema"]["strict"], true);
+        }
+        for model in [
+            GPT_4O_2024_05_13,
+            GPT_4_TURBO,
+            GPT_35_TURBO,
+            "gpt-4o-realtime",
+        ] {
+            assert!(structured_output_params(model, "person", &schema).is_none());
+        }
+
+        // Schemas that cannot be strict fall back to tool calling
+        let schema = json!(schemars::schema_for!(Inventory));
+        assert!(structured_output_params(GPT_4O, "inventory", &schema).is_none());
+    }
+
+    #[test]
+    fn test_strict_schema() {
+        let schema = strict_schema(&json!(schemars::schema_for!(Person))).unwrap();
+
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(
+            schema["required"],
+            json!(["address", "age", "name", "nickname"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["age"].get("format").is_none());
+        assert_eq!(
+            schema["definitions"]["Address"]["additionalProperties"],
+            false
+        );
+
+        // Optional properties must be nullable
+        let schema = json!({
+            "type": "object",
+            "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+            "required": ["name"],
+        });
+        assert!(strict_schema(&schema).is_none());
+    }
+
+    #[test]
+    fn test_streaming_tool_calls() {
+        let mut tool_calls = StreamingToolCalls::default();
+
+        let chunks = [
+            r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Let me "},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"content":"add that."},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"add","arguments":""}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"x\": 1, "}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"y\": 2}"}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":50,"completion_tokens":20,"total_tokens":70}}"#,
+            "[DONE]",
+        ]
+        .iter()
+        .flat_map(|data| tool_calls.process(data).unwrap())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            chunks,
+            vec![
+                streaming::StreamingChoice::Message("Let me ".to_string()),
+                streaming::StreamingChoice::Message("add that.".to_string()),
+                streaming::StreamingChoice::ToolCall("add".to_string(), json!({"x": 1, "y": 2})),
+                streaming::StreamingChoice::Usage(completion::Usage::new(50, 20)),
+            ]
+        );
+    }
 }
@@ -0,0 +1,824 @@
+//! Client and Rig integration for providers with an OpenAI-compatible API (e.g.: Groq, DeepSeek,
+//! Together, OpenRouter, LM Studio, vLLM, Ollama).
+//!
+//! These providers speak the OpenAI chat completions API with small differences, which are
+//! described by a [Profile]: base URL, extra headers and capability flags (e.g.: no native tool
+//! calling, plain string message contents). Profiles are provided for common providers, and can
+//! be adjusted or created from scratch for others.
+//!
+//! Models without native tool calling get tools through a ReAct-style prompt (see
+//! [tool_emulation](crate::tool_emulation)). Streaming completions are not streamed
+//! incrementally.
+//!
+//! # Example
+//! ```
+//! use rig::providers::openai_compatible::{self, Client, Profile};
+//!
+//! let groq = Client::new(Profile::groq(), "YOUR_API_KEY");
+//! let llama = groq.completion_model(openai_compatible::GROQ_LLAMA_3_1_8B_INSTANT);
+//!
+//! // Local server without tool calling
+//! let lm_studio = Client::new(Profile::lm_studio(), "");
+//!
+//! // Custom provider
+//! let custom = Client::new(
+//!     Profile::new("https://llm.example.com/v1")
+//!         .name("Example")
+//!         .header("X-Tenant", "yuri")
+//!         .tools(false),
+//!     "YOUR_API_KEY",
+//! );
+//! ```
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::openai;
+use crate::{
+    agent::AgentBuilder,
+    completion::{self, CompletionError, CompletionRequest},
+    embeddings::{self, EmbeddingError, EmbeddingsBuilder},
+    extractor::ExtractorBuilder,
+    json_utils, tool_emulation, Embed,
+};
+
+// ================================================================
+// Provider Profiles
+// ================================================================
+const GROQ_API_BASE_URL: &str = "https://api.groq.com/openai/v1";
+const DEEPSEEK_API_BASE_URL: &str = "https://api.deepseek.com/v1";
+const TOGETHER_API_BASE_URL: &str = "https://api.together.xyz/v1";
+const OPENROUTER_API_BASE_URL: &str = "https://openrouter.ai/api/v1";
+const LM_STUDIO_API_BASE_URL: &str = "http://localhost:1234/v1";
+const OLLAMA_API_BASE_URL: &str = "http://localhost:11434/v1";
+
+/// Description of an OpenAI-compatible provider and of its differences with the OpenAI API.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    name: String,
+    base_url: String,
+    api_key_env: Option<String>,
+    headers: Vec<(String, String)>,
+    tools: bool,
+    tool_choice: bool,
+    images: bool,
+    content_parts: bool,
+    structured_outputs: bool,
+}
+
+impl Profile {
+    /// Profile of a provider at `base_url` supporting everything the OpenAI API supports.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            name: "OpenAI-compatible".to_string(),
+            base_url: base_url.to_string(),
+            api_key_env: None,
+            headers: vec![],
+            tools: true,
+            tool_choice: true,
+            images: true,
+            content_parts: true,
+            structured_outputs: true,
+        }
+    }
+
+    /// [Groq](https://console.groq.com/docs/openai)
+    pub fn groq() -> Self {
+        Self::new(GROQ_API_BASE_URL)
+            .name("Groq")
+            .api_key_env("GROQ_API_KEY")
+            .images(false)
+            .content_parts(false)
+            .structured_outputs(false)
+    }
+
+    /// [DeepSeek](https://api-docs.deepseek.com)
+    pub fn deepseek() -> Self {
+        Self::new(DEEPSEEK_API_BASE_URL)
+            .name("DeepSeek")
+            .api_key_env("DEEPSEEK_API_KEY")
+            .images(false)
+            .content_parts(false)
+            .structured_outputs(false)
+    }
+
+    /// [Together](https://docs.together.ai/docs/openai-api-compatibility)
+    pub fn together() -> Self {
+        Self::new(TOGETHER_API_BASE_URL)
+            .name("Together")
+            .api_key_env("TOGETHER_API_KEY")
+            .structured_outputs(false)
+    }
+
+    /// [OpenRouter](https://openrouter.ai/docs). Apps can identify themselves with the
+    /// `HTTP-Referer` and `X-Title` headers (see [Profile::header]).
+    pub fn openrouter() -> Self {
+        Self::new(OPENROUTER_API_BASE_URL)
+            .name("OpenRouter")
+            .api_key_env("OPENROUTER_API_KEY")
+            .structured_outputs(false)
+    }
+
+    /// [LM Studio](https://lmstudio.ai/docs/api/openai-api) local server
+    pub fn lm_studio() -> Self {
+        Self::new(LM_STUDIO_API_BASE_URL)
+            .name("LM Studio")
+            .tools(false)
+            .images(false)
+            .content_parts(false)
+    }
+
+    /// [vLLM](https://docs.vllm.ai/en/latest/serving/openai_compatible_server.html) server at
+    /// `base_url` (e.g.: `http://localhost:8000/v1`). Tool calling requires server flags, so it
+    /// is emulated by default.
+    pub fn vllm(base_url: &str) -> Self {
+        Self::new(base_url)
+            .name("vLLM")
+            .tools(false)
+            .images(false)
+            .structured_outputs(false)
+    }
+
+    /// [Ollama](https://github.com/ollama/ollama/blob/main/docs/openai.md) local server
+    pub fn ollama() -> Self {
+        Self::new(OLLAMA_API_BASE_URL)
+            .name("Ollama")
+            .tool_choice(false)
+            .structured_outputs(false)
+    }
+
+    /// Set the name of the provider, used in logs
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the environment variable read by [Client::from_env]
+    pub fn api_key_env(mut self, api_key_env: &str) -> Self {
+        self.api_key_env = Some(api_key_env.to_string());
+        self
+    }
+
+    /// Add a header sent with every request
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Whether the provider supports native tool calling. Tool calls are emulated otherwise.
+    pub fn tools(mut self, tools: bool) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Whether the provider accepts the `tool_choice` parameter
+    pub fn tool_choice(mut self, tool_choice: bool) -> Self {
+        self.tool_choice = tool_choice;
+        self
+    }
+
+    /// Whether the provider accepts image inputs. Images are dropped otherwise.
+    pub fn images(mut self, images: bool) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Whether message contents can be arrays of content parts. Contents are sent as plain
+    /// strings otherwise.
+    pub fn content_parts(mut self, content_parts: bool) -> Self {
+        self.content_parts = content_parts;
+        self
+    }
+
+    /// Whether the provider supports `response_format` with a JSON schema
+    pub fn structured_outputs(mut self, structured_outputs: bool) -> Self {
+        self.structured_outputs = structured_outputs;
+        self
+    }
+}
+
+// ================================================================
+// Main OpenAI-compatible Client
+// ================================================================
+#[derive(Clone)]
+pub struct Client {
+    profile: Profile,
+    http_client: reqwest::Client,
+}
+
+impl Client {
+    /// Create a new client for the provider described by `profile` with the given API key
+    /// (which can be empty for local servers).
+    pub fn new(profile: Profile, api_key: &str) -> Self {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if !api_key.is_empty() {
+            headers.insert(
+                "Authorization",
+                format!("Bearer {}", api_key)
+                    .parse()
+                    .expect("Bearer token should parse"),
+            );
+        }
+        for (name, value) in &profile.headers {
+            headers.insert(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                    .expect("Header name should parse"),
+                value.parse().expect("Header value should parse"),
+            );
+        }
+
+        Self {
+            profile,
+            http_client: reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
+                .expect("OpenAI-compatible reqwest client should build"),
+        }
+    }
+
+    /// Create a new client from the API key environment variable of `profile` (e.g.:
+    /// `GROQ_API_KEY`), or without API key if the profile has none.
+    /// Panics if the environment variable is not set.
+    pub fn from_env(profile: Profile) -> Self {
+        let api_key = match &profile.api_key_env {
+            Some(var) => std::env::var(var).unwrap_or_else(|_| panic!("{var} not set")),
+            None => String::new(),
+        };
+        Self::new(profile, &api_key)
+    }
+
+    /// The profile of the provider
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    fn post(&self, path: &str) -> reqwest::RequestBuilder {
+        let url = format!(
+            "{}/{}",
+            self.profile.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        );
+        self.http_client.post(url)
+    }
+
+    /// Create an embedding model with the given name and number of dimensions.
+    pub fn embedding_model(&self, model: &str, ndims: usize) -> EmbeddingModel {
+        EmbeddingModel::new(self.clone(), model, ndims)
+    }
+
+    /// Create an embedding builder with the given embedding model.
+    pub fn embeddings<D: Embed>(
+        &self,
+        model: &str,
+        ndims: usize,
+    ) -> EmbeddingsBuilder<EmbeddingModel, D> {
+        EmbeddingsBuilder::new(self.embedding_model(model, ndims))
+    }
+
+    /// Create a completion model with the given name.
+    pub fn completion_model(&self, model: &str) -> CompletionModel {
+        CompletionModel::new(self.clone(), model)
+    }
+
+    /// Create an agent builder with the given completion model.
+    pub fn agent(&self, model: &str) -> AgentBuilder<CompletionModel> {
+        AgentBuilder::new(self.completion_model(model))
+    }
+
+    /// Create an extractor builder with the given completion model.
+    pub fn extractor<T: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync>(
+        &self,
+        model: &str,
+    ) -> ExtractorBuilder<T, CompletionModel> {
+        ExtractorBuilder::new(self.completion_model(model))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiError {
+    message: String,
+}
+
+/// Some providers report errors with a success status (e.g.: OpenRouter)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ApiResponse<T> {
+    Ok(T),
+    Err { error: ApiError },
+}
+
+/// Token usage, under the OpenAI field names or their `input`/`output` variants
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Usage {
+    #[serde(default, alias = "input_tokens")]
+    pub prompt_tokens: u64,
+    #[serde(default, alias = "output_tokens")]
+    pub completion_tokens: Option<u64>,
+    #[serde(default)]
+    pub total_tokens: Option<u64>,
+}
+
+impl From<&Usage> for completion::Usage {
+    fn from(usage: &Usage) -> Self {
+        let completion_tokens = usage.completion_tokens.unwrap_or_else(|| {
+            usage
+                .total_tokens
+                .unwrap_or_default()
+                .saturating_sub(usage.prompt_tokens)
+        });
+        completion::Usage::new(usage.prompt_tokens, completion_tokens)
+    }
+}
+
+// ================================================================
+// OpenAI-compatible Embedding API
+// ================================================================
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingResponse {
+    pub data: Vec<EmbeddingData>,
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmbeddingData {
+    pub embedding: Vec<f64>,
+    #[serde(default)]
+    pub index: usize,
+}
+
+#[derive(Clone)]
+pub struct EmbeddingModel {
+    client: Client,
+    pub model: String,
+    ndims: usize,
+}
+
+impl EmbeddingModel {
+    pub fn new(client: Client, model: &str, ndims: usize) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+            ndims,
+        }
+    }
+}
+
+impl embeddings::EmbeddingModel for EmbeddingModel {
+    const MAX_DOCUMENTS: usize = 256;
+
+    fn ndims(&self) -> usize {
+        self.ndims
+    }
+
+    async fn embed_texts(
+        &self,
+        documents: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<embeddings::Embedding>, EmbeddingError> {
+        let documents = documents.into_iter().collect::<Vec<_>>();
+
+        let response = self
+            .client
+            .post("/embeddings")
+            .json(&json!({
+                "model": self.model,
+                "input": documents,
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(EmbeddingError::ProviderError(response.text().await?));
+        }
+
+        match response.json::<ApiResponse<EmbeddingResponse>>().await? {
+            ApiResponse::Ok(mut response) => {
+                if response.data.len() != documents.len() {
+                    return Err(EmbeddingError::ResponseError(
+                        "Response data length does not match input length".into(),
+                    ));
+                }
+                response.data.sort_by_key(|data| data.index);
+
+                Ok(response
+                    .data
+                    .into_iter()
+                    .zip(documents)
+                    .map(|(embedding, document)| embeddings::Embedding {
+                        document,
+                        vec: embedding.embedding,
+                    })
+                    .collect())
+            }
+            ApiResponse::Err { error } => Err(EmbeddingError::ProviderError(error.message)),
+        }
+    }
+}
+
+// ================================================================
+// OpenAI-compatible Completion API
+// ================================================================
+/// `llama-3.3-70b-versatile` completion model (Groq)
+pub const GROQ_LLAMA_3_3_70B_VERSATILE: &str = "llama-3.3-70b-versatile";
+/// `llama-3.1-8b-instant` completion model (Groq)
+pub const GROQ_LLAMA_3_1_8B_INSTANT: &str = "llama-3.1-8b-instant";
+/// `gemma2-9b-it` completion model (Groq)
+pub const GROQ_GEMMA2_9B_IT: &str = "gemma2-9b-it";
+/// `deepseek-chat` completion model (DeepSeek)
+pub const DEEPSEEK_CHAT: &str = "deepseek-chat";
+/// `deepseek-reasoner` completion model (DeepSeek)
+pub const DEEPSEEK_REASONER: &str = "deepseek-reasoner";
+/// `meta-llama/Llama-3.3-70B-Instruct-Turbo` completion model (Together)
+pub const TOGETHER_LLAMA_3_3_70B_INSTRUCT_TURBO: &str = "meta-llama/Llama-3.3-70B-Instruct-Turbo";
+/// `Qwen/Qwen2.5-72B-Instruct-Turbo` completion model (Together)
+pub const TOGETHER_QWEN_2_5_72B_INSTRUCT_TURBO: &str = "Qwen/Qwen2.5-72B-Instruct-Turbo";
+/// `openrouter/auto` completion model (OpenRouter), routing each request to a suitable model
+pub const OPENROUTER_AUTO: &str = "openrouter/auto";
+/// `meta-llama/llama-3.1-8b-instruct` completion model (OpenRouter)
+pub const OPENROUTER_LLAMA_3_1_8B_INSTRUCT: &str = "meta-llama/llama-3.1-8b-instruct";
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionResponse {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub model: String,
+    pub choices: Vec<Choice>,
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Choice {
+    pub message: Message,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub role: String,
+    pub content: Option<Content>,
+    pub tool_calls: Option<Vec<ToolCall>>,
+}
+
+/// Content of a message, either a string or an array of content parts
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Content {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl Content {
+    pub fn text(&self) -> String {
+        match self {
+            Content::Text(text) => text.clone(),
+            Content::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| part.text.clone())
+                .collect::<Vec<_>>()
+                .join(""),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContentPart {
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolCall {
+    #[serde(default)]
+    pub id: String,
+    pub function: Function,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Function {
+    pub name: String,
+    /// Arguments of the call, as a JSON string (or, for some providers, a JSON object)
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionResponse> {
+    type Error = CompletionError;
+
+    fn try_from(value: CompletionResponse) -> Result<Self, Self::Error> {
+        let message = &value
+            .choices
+            .first()
+            .ok_or_else(|| CompletionError::ResponseError("Response contained no choices".into()))?
+            .message;
+
+        let choice = match (&message.tool_calls, &message.content) {
+            (Some(calls), _) if !calls.is_empty() => {
+                let call = &calls[0];
+                let args = match &call.function.arguments {
+                    serde_json::Value::String(args) if args.trim().is_empty() => json!({}),
+                    serde_json::Value::String(args) => serde_json::from_str(args)
+                        .ok()
+                        .or_else(|| tool_emulation::repair_json(args))
+                        .ok_or_else(|| {
+                            CompletionError::ResponseError(format!(
+                                "Invalid arguments for tool {}: {args}",
+                                call.function.name
+                            ))
+                        })?,
+                    serde_json::Value::Null => json!({}),
+                    args => args.clone(),
+                };
+                completion::ModelChoice::ToolCall(call.function.name.clone(), args)
+            }
+            (_, Some(content)) => completion::ModelChoice::Message(content.text()),
+            _ => {
+                return Err(CompletionError::ResponseError(
+                    "Response did not contain a message or tool call".into(),
+                ))
+            }
+        };
+
+        Ok(completion::CompletionResponse {
+            choice,
+            usage: value.usage.as_ref().map(completion::Usage::from),
+            citations: vec![],
+            raw_response: value,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct CompletionModel {
+    client: Client,
+    /// Name of the model (e.g.: llama-3.1-8b-instant)
+    pub model: String,
+}
+
+impl CompletionModel {
+    pub fn new(client: Client, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+        }
+    }
+
+    /// Message content for the profile, i.e.: content parts or a plain string
+    fn content(&self, text: String, image_urls: Vec<String>) -> serde_json::Value {
+        if !self.client.profile.content_parts {
+            return json!(text);
+        }
+
+        let mut parts = vec![json!({"type": "text", "text": text})];
+        parts.extend(
+            image_urls
+                .into_iter()
+                .map(|url| json!({"type": "image_url", "image_url": {"url": url}})),
+        );
+        json!(parts)
+    }
+
+    fn create_completion_request(
+        &self,
+        completion_request: CompletionRequest,
+    ) -> serde_json::Value {
+        let profile = &self.client.profile;
+
+        let mut messages = vec![];
+        if let Some(preamble) = &completion_request.preamble {
+            messages.push(json!({
+                "role": "system",
+                "content": self.content(preamble.clone(), vec![]),
+            }));
+        }
+        for message in &completion_request.chat_history {
+            messages.push(json!({
+                "role": message.role,
+                "content": self.content(message.content.clone(), vec![]),
+            }));
+        }
+
+        let image_urls = completion_request.image_urls.clone().unwrap_or_default();
+        let image_urls = match profile.images || image_urls.is_empty() {
+            true => image_urls,
+            false => {
+                tracing::warn!(target: "rig", "{} does not support images, ignoring {} image(s)", profile.name, image_urls.len());
+                vec![]
+            }
+        };
+        let prompt = completion_request.prompt_with_context();
+        messages.push(json!({
+            "role": "user",
+            "content": match (image_urls.is_empty(), profile.content_parts) {
+                // Images require content parts
+                (false, false) => json!([{"type": "text", "text": prompt}])
+                    .as_array_mut()
+                    .map(|parts| {
+                        parts.extend(image_urls.into_iter().map(
+                            |url| json!({"type": "image_url", "image_url": {"url": url}}),
+                        ));
+                        json!(parts)
+                    })
+                    .unwrap_or_default(),
+                _ => self.content(prompt, image_urls),
+            },
+        }));
+
+        let mut request = json!({
+            "model": self.model,
+            "messages": messages,
+        });
+        if let Some(temperature) = completion_request.temperature {
+            request["temperature"] = json!(temperature);
+        }
+        if let Some(max_tokens) = completion_request.max_tokens {
+            request["max_tokens"] = json!(max_tokens);
+        }
+        if !completion_request.tools.is_empty() {
+            request["tools"] = json!(completion_request
+                .tools
+                .into_iter()
+                .map(openai::ToolDefinition::from)
+                .collect::<Vec<_>>());
+            if profile.tool_choice {
+                request["tool_choice"] = json!("auto");
+            }
+        }
+
+        match completion_request.additional_params {
+            Some(params) => json_utils::merge(request, params),
+            None => request,
+        }
+    }
+}
+
+impl completion::CompletionModel for CompletionModel {
+    type Response = CompletionResponse;
+
+    async fn completion(
+        &self,
+        mut completion_request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<CompletionResponse>, CompletionError> {
+        let emulated_tools = match self.client.profile.tools {
+            true => vec![],
+            false => tool_emulation::emulate_tools(&mut completion_request),
+        };
+
+        let response = self
+            .client
+            .post("/chat/completions")
+            .json(&self.create_completion_request(completion_request))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(CompletionError::ProviderError(response.text().await?));
+        }
+
+        match response.json::<ApiResponse<CompletionResponse>>().await? {
+            ApiResponse::Ok(response) => {
+                tracing::info!(target: "rig",
+                    "{} completion token usage: {:?}",
+                    self.client.profile.name,
+                    response.usage.as_ref().map(completion::Usage::from)
+                );
+                let mut response: completion::CompletionResponse<_> = response.try_into()?;
+                if !emulated_tools.is_empty() {
+                    response.choice =
+                        tool_emulation::parse_choice(response.choice, &emulated_tools);
+                }
+                Ok(response)
+            }
+            ApiResponse::Err { error } => Err(CompletionError::ProviderError(error.message)),
+        }
+    }
+
+    fn structured_output_params(
+        &self,
+        name: &str,
+        schema: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        if !self.client.profile.structured_outputs {
+            return None;
+        }
+
+        Some(json!({
+            "response_format": {
+                "type": "json_schema",
+                "json_schema": {
+                    "name": name,
+                    "schema": schema,
+                    "strict": false,
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completion::{CompletionModel as _, Message as ChatMessage, ToolDefinition};
+
+    fn request() -> CompletionRequest {
+        CompletionRequest {
+            prompt: "What is 2 + 2?".to_string(),
+            preamble: Some("You are a calculator.".to_string()),
+            chat_history: vec![ChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+            }],
+            documents: vec![],
+            tools: vec![ToolDefinition {
+                name: "add".to_string(),
+                description: "Add x and y".to_string(),
+                parameters: json!({"type": "object"}),
+            }],
+            temperature: Some(0.0),
+            max_tokens: None,
+            additional_params: None,
+            image_urls: Some(vec!["https://example.com/cat.png".to_string()]),
+            cache_breakpoints: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_create_completion_request() {
+        let model = Client::new(Profile::new("http://localhost"), "").completion_model("m");
+        let body = model.create_completion_request(request());
+
+        assert_eq!(
+            body["messages"][0]["content"][0]["text"],
+            "You are a calculator."
+        );
+        assert_eq!(
+            body["messages"][2]["content"][1]["image_url"]["url"],
+            "https://example.com/cat.png"
+        );
+        assert_eq!(body["tools"][0]["function"]["name"], "add");
+        assert_eq!(body["tool_choice"], "auto");
+        assert_eq!(body["temperature"], 0.0);
+        assert!(body.get("max_tokens").is_none());
+
+        // DeepSeek: plain string contents and no images
+        let model = Client::new(Profile::deepseek(), "key").completion_model(DEEPSEEK_CHAT);
+        let body = model.create_completion_request(request());
+
+        assert_eq!(body["messages"][1]["content"], "Hi");
+        assert_eq!(body["messages"][2]["content"], "What is 2 + 2?");
+
+        // LM Studio: tools are moved to the preamble by the completion
+        let mut request = request();
+        let tools = tool_emulation::emulate_tools(&mut request);
+        let model = Client::new(Profile::lm_studio(), "").completion_model("local");
+        let body = model.create_completion_request(request);
+
+        assert_eq!(tools.len(), 1);
+        assert!(body.get("tools").is_none());
+        assert!(body["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("- add: Add x and y"));
+    }
+
+    #[test]
+    fn test_completion_response() {
+        let response: CompletionResponse = serde_json::from_str(
+            r#"{
+                "choices": [{"message": {"role": "assistant", "content": null, "tool_calls": [
+                    {"function": {"name": "add", "arguments": "{\"x\": 2, \"y\": 2,}"}}
+                ]}, "finish_reason": "tool_calls"}],
+                "usage": {"input_tokens": 10, "total_tokens": 15}
+            }"#,
+        )
+        .unwrap();
+        let response: completion::CompletionResponse<_> = response.try_into().unwrap();
+
+        assert!(matches!(
+            response.choice,
+            completion::ModelChoice::ToolCall(name, args) if name == "add" && args == json!({"x": 2, "y": 2})
+        ));
+        assert_eq!(response.usage, Some(completion::Usage::new(10, 5)));
+
+        let response: CompletionResponse = serde_json::from_str(
+            r#"{"choices": [{"message": {"content": [{"type": "text", "text": "4"}]}}]}"#,
+        )
+        .unwrap();
+        let response: completion::CompletionResponse<_> = response.try_into().unwrap();
+
+        assert!(matches!(response.choice, completion::ModelChoice::Message(text) if text == "4"));
+        assert_eq!(response.usage, None);
+    }
+
+    #[test]
+    fn test_api_error() {
+        let response: ApiResponse<CompletionResponse> =
+            serde_json::from_str(r#"{"error": {"message": "No endpoints found", "code": 404}}"#)
+                .unwrap();
+        assert!(
+            matches!(response, ApiResponse::Err { error } if error.message == "No endpoints found")
+        );
+
+        let model = Client::new(Profile::groq(), "key").completion_model(GROQ_GEMMA2_9B_IT);
+        assert!(model.structured_output_params("x", &json!({})).is_none());
+    }
+}
@@ -11,6 +11,7 @@
 
 use crate::{
     agent::AgentBuilder,
+    classifier::ClassifierBuilder,
     completion::{self, CompletionError},
     extractor::ExtractorBuilder,
     json_utils,
@@ -81,6 +82,15 @@ impl Client {
     ) -> ExtractorBuilder<T, CompletionModel> {
         ExtractorBuilder::new(self.completion_model(model))
     }
+
+    pub fn classifier<
+        L: JsonSchema + for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    >(
+        &self,
+        model: &str,
+    ) -> ClassifierBuilder<L, CompletionModel> {
+        ClassifierBuilder::new(self.completion_model(model))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -122,6 +132,9 @@ pub struct CompletionResponse {
     #[serde(default)]
     pub choices: Vec<Choice>,
     pub usage: Usage,
+    /// URLs of the sources cited in the message as `[1]`, `[2]`, ...
+    #[serde(default)]
+    pub citations: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -171,6 +184,11 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
                 ..
             }, ..] => Ok(completion::CompletionResponse {
                 choice: completion::ModelChoice::Message(content.to_string()),
+                usage: Some(completion::Usage::new(
+                    value.usage.prompt_tokens as u64,
+                    value.usage.completion_tokens as u64,
+                )),
+                citations: citations(content, &value.citations),
                 raw_response: value,
             }),
             _ => Err(CompletionError::ResponseError(
@@ -180,6 +198,38 @@ impl TryFrom<CompletionResponse> for completion::CompletionResponse<CompletionRe
     }
 }
 
+/// Citations of the `urls` marked in `content`, followed by the urls that are never marked
+fn citations(content: &str, urls: &[String]) -> Vec<completion::Citation> {
+    let url = |reference: &str| {
+        reference
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| urls.get(n.checked_sub(1)?))
+    };
+
+    let mut citations = completion::marked_citations(content, |reference| {
+        url(reference).map(|url| completion::Citation {
+            source_id: None,
+            url: Some(url.clone()),
+            span: None,
+        })
+    });
+    for url in urls {
+        if !citations
+            .iter()
+            .any(|citation| citation.url.as_ref() == Some(url))
+        {
+            citations.push(completion::Citation {
+                source_id: None,
+                url: Some(url.clone()),
+                span: None,
+            });
+        }
+    }
+
+    citations
+}
+
 #[derive(Clone)]
 pub struct CompletionModel {
     client: Client,
@@ -259,3 +309,34 @@ impl completion::CompletionModel for CompletionModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citations() {
+        let urls = vec!["https://rig.rs".to_string(), "https://docs.rs".to_string()];
+        let citations = citations("Rig is a Rust library [1][3].", &urls);
+
+        assert_eq!(
+            citations,
+            vec![
+                completion::Citation {
+                    source_id: None,
+                    url: Some("https://rig.rs".into()),
+                    span: Some(completion::Span {
+                        start: 0,
+                        end: 21,
+                        text: "Rig is a Rust library".into()
+                    }),
+                },
+                completion::Citation {
+                    source_id: None,
+                    url: Some("https://docs.rs".into()),
+                    span: None,
+                },
+            ]
+        );
+    }
+}
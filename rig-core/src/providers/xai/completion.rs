@@ -15,6 +15,10 @@ use super::client::{xai_api_types::ApiResponse, Client};
 
 /// `grok-beta` completion model
 pub const GROK_BETA: &str = "grok-beta";
+/// `grok-vision-beta` completion model, with image inputs
+pub const GROK_VISION_BETA: &str = "grok-vision-beta";
+/// `grok-2-vision-1212` completion model, with image inputs
+pub const GROK_2_VISION_1212: &str = "grok-2-vision-1212";
 
 // =================================================================
 // Rig Implementation Types
@@ -58,6 +62,7 @@ impl completion::CompletionModel for CompletionModel {
             role: "user".into(),
             content: prompt_with_context,
         });
+        let messages = vision_messages(messages, completion_request.images());
 
         let mut request = if completion_request.tools.is_empty() {
             json!({
@@ -99,6 +104,29 @@ impl completion::CompletionModel for CompletionModel {
     }
 }
 
+/// Messages of the request, with the images (if any) as content parts of the prompt message
+fn vision_messages(
+    messages: Vec<completion::Message>,
+    images: Vec<completion::Image>,
+) -> serde_json::Value {
+    let mut messages = json!(messages);
+    if images.is_empty() {
+        return messages;
+    }
+
+    if let Some(prompt) = messages
+        .as_array_mut()
+        .and_then(|messages| messages.last_mut())
+    {
+        let mut content = vec![json!({"type": "text", "text": prompt["content"].take()})];
+        content.extend(images.iter().map(|image| {
+            json!({"type": "image_url", "image_url": {"url": image.url(), "detail": "high"}})
+        }));
+        prompt["content"] = json!(content);
+    }
+    messages
+}
+
 pub mod xai_api_types {
     use serde::{Deserialize, Serialize};
 
@@ -118,6 +146,8 @@ pub mod xai_api_types {
                     ..
                 }, ..] => Ok(completion::CompletionResponse {
                     choice: completion::ModelChoice::Message(content.to_string()),
+                    usage: Some((&value.usage).into()),
+                    citations: vec![],
                     raw_response: value,
                 }),
                 [Choice {
@@ -137,6 +167,8 @@ pub mod xai_api_types {
                             call.function.name.clone(),
                             serde_json::from_str(&call.function.arguments)?,
                         ),
+                        usage: Some((&value.usage).into()),
+                        citations: vec![],
                         raw_response: value,
                     })
                 }
@@ -206,4 +238,36 @@ pub mod xai_api_types {
         pub prompt_tokens: i32,
         pub total_tokens: i32,
     }
+
+    impl From<&Usage> for completion::Usage {
+        fn from(usage: &Usage) -> Self {
+            completion::Usage::new(usage.prompt_tokens as u64, usage.completion_tokens as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vision_messages() {
+        let messages = vision_messages(
+            vec![completion::Message {
+                role: "user".into(),
+                content: "What is in this photo?".into(),
+            }],
+            vec![completion::Image::parse(
+                "data:application/octet-stream;base64,/9j/4AAQSkZJRgABAQ",
+            )],
+        );
+
+        assert_eq!(
+            messages,
+            json!([{"role": "user", "content": [
+                {"type": "text", "text": "What is in this photo?"},
+                {"type": "image_url", "image_url": {"url": "data:image/jpeg;base64,/9j/4AAQSkZJRgABAQ", "detail": "high"}},
+            ]}])
+        );
+    }
 }
@@ -14,5 +14,5 @@ pub mod completion;
 pub mod embedding;
 
 pub use client::Client;
-pub use completion::GROK_BETA;
+pub use completion::{GROK_2_VISION_1212, GROK_BETA, GROK_VISION_BETA};
 pub use embedding::EMBEDDING_V1;
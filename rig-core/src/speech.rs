@@ -0,0 +1,235 @@
+//! This module provides functionality for working with speech (i.e.: text-to-speech) models.
+//!
+//! The [SpeechModel] trait is the interface between providers (e.g.: OpenAI TTS) and the
+//! library. Requests are created with [SpeechModel::speech_request] and return the generated
+//! audio as bytes along with its MIME type.
+//!
+//! # Example
+//! ```rust
+//! use rig::{providers::openai, speech::{AudioFormat, SpeechModel}};
+//!
+//! let openai = openai::Client::from_env();
+//! let tts = openai.speech_model(openai::TTS_1);
+//!
+//! let response = tts
+//!     .speech_request("Hello there!")
+//!     .voice("nova")
+//!     .format(AudioFormat::Opus)
+//!     .send()
+//!     .await?;
+//!
+//! std::fs::write("hello.ogg", &response.audio)?;
+//! ```
+use serde::{Deserialize, Serialize};
+
+use crate::json_utils;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SpeechError {
+    /// Http error (e.g.: connection error, timeout, etc.)
+    #[error("HttpError: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    /// Json error (e.g.: serialization, deserialization)
+    #[error("JsonError: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// Error building the speech request
+    #[error("RequestError: {0}")]
+    RequestError(String),
+
+    /// Error parsing the speech response
+    #[error("ResponseError: {0}")]
+    ResponseError(String),
+
+    /// Error returned by the speech model provider
+    #[error("ProviderError: {0}")]
+    ProviderError(String),
+}
+
+/// Format of the generated audio.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    /// Opus in an Ogg container (e.g.: for Telegram voice notes)
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+    /// Raw 16-bit PCM samples
+    Pcm,
+}
+
+impl AudioFormat {
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Pcm => "audio/pcm",
+        }
+    }
+}
+
+/// Struct representing a general speech request that can be sent to a speech model provider.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechRequest {
+    /// The text to speak
+    pub text: String,
+    /// The voice to use, or the default voice of the provider if not set
+    pub voice: Option<String>,
+    /// The format of the generated audio
+    pub format: AudioFormat,
+    /// The speed of the speech (1.0 is the normal speed)
+    pub speed: Option<f64>,
+    /// Additional provider-specific parameters to be sent to the speech model provider
+    pub additional_params: Option<serde_json::Value>,
+}
+
+/// General speech response struct that contains the generated audio and the raw response.
+#[derive(Debug)]
+pub struct SpeechResponse<T> {
+    /// The generated audio
+    pub audio: Vec<u8>,
+    /// The MIME type of the generated audio (e.g.: `audio/mpeg`)
+    pub mime_type: String,
+    /// The raw response returned by the speech model provider
+    pub raw_response: T,
+}
+
+/// Trait defining a speech model, either from a third party provider (e.g.: OpenAI) or a local
+/// model. Test doubles can implement it to return fixed audio.
+pub trait SpeechModel: Clone + Send + Sync {
+    /// The raw response type returned by the underlying speech model.
+    type Response: Send + Sync;
+
+    /// Generates the audio of the given speech request.
+    fn speech(
+        &self,
+        request: SpeechRequest,
+    ) -> impl std::future::Future<Output = Result<SpeechResponse<Self::Response>, SpeechError>> + Send;
+
+    /// Generates a speech request builder for the given `text`.
+    fn speech_request(&self, text: &str) -> SpeechRequestBuilder<Self> {
+        SpeechRequestBuilder::new(self.clone(), text)
+    }
+}
+
+/// Builder struct for constructing a speech request.
+pub struct SpeechRequestBuilder<M: SpeechModel> {
+    model: M,
+    text: String,
+    voice: Option<String>,
+    format: AudioFormat,
+    speed: Option<f64>,
+    additional_params: Option<serde_json::Value>,
+}
+
+impl<M: SpeechModel> SpeechRequestBuilder<M> {
+    pub fn new(model: M, text: &str) -> Self {
+        Self {
+            model,
+            text: text.to_string(),
+            voice: None,
+            format: AudioFormat::default(),
+            speed: None,
+            additional_params: None,
+        }
+    }
+
+    /// Sets the voice of the request
+    pub fn voice(mut self, voice: &str) -> Self {
+        self.voice = Some(voice.to_string());
+        self
+    }
+
+    /// Sets the format of the generated audio (default: MP3)
+    pub fn format(mut self, format: AudioFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the speed of the speech
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = Some(speed);
+        self
+    }
+
+    /// Adds additional parameters to the speech request.
+    pub fn additional_params(mut self, additional_params: serde_json::Value) -> Self {
+        match self.additional_params {
+            Some(params) => {
+                self.additional_params = Some(json_utils::merge(params, additional_params));
+            }
+            None => {
+                self.additional_params = Some(additional_params);
+            }
+        }
+        self
+    }
+
+    /// Builds the speech request.
+    pub fn build(self) -> SpeechRequest {
+        SpeechRequest {
+            text: self.text,
+            voice: self.voice,
+            format: self.format,
+            speed: self.speed,
+            additional_params: self.additional_params,
+        }
+    }
+
+    /// Sends the speech request to the speech model provider and returns the generated audio.
+    pub async fn send(self) -> Result<SpeechResponse<M::Response>, SpeechError> {
+        let model = self.model.clone();
+        model.speech(self.build()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct StubModel;
+
+    impl SpeechModel for StubModel {
+        type Response = SpeechRequest;
+
+        async fn speech(
+            &self,
+            request: SpeechRequest,
+        ) -> Result<SpeechResponse<Self::Response>, SpeechError> {
+            Ok(SpeechResponse {
+                audio: request.text.as_bytes().to_vec(),
+                mime_type: request.format.mime_type().to_string(),
+                raw_response: request,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_speech_request_builder() {
+        let response = StubModel
+            .speech_request("Hello")
+            .voice("nova")
+            .format(AudioFormat::Opus)
+            .speed(1.25)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.audio, b"Hello");
+        assert_eq!(response.mime_type, "audio/ogg");
+        assert_eq!(response.raw_response.voice.as_deref(), Some("nova"));
+        assert_eq!(response.raw_response.speed, Some(1.25));
+        assert_eq!(
+            serde_json::to_value(response.raw_response.format).unwrap(),
+            "opus"
+        );
+    }
+}
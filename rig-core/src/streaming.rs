@@ -0,0 +1,139 @@
+//! This module provides the types for streaming completion responses chunk by chunk, e.g.:
+//! to display the response of a model as it is being generated.
+//!
+//! All completion models can be streamed with [CompletionModel::stream](crate::completion::CompletionModel::stream)
+//! (or [CompletionRequestBuilder::stream](crate::completion::CompletionRequestBuilder::stream)).
+//! Models without native streaming support send the whole response as a single chunk.
+//!
+//! # Example
+//! ```rust
+//! use futures::StreamExt;
+//! use rig::{completion::Completion, providers::openai, streaming::StreamingChoice};
+//!
+//! let openai = openai::Client::from_env();
+//! let agent = openai.agent(openai::GPT_4O).preamble("You are a poet.").build();
+//!
+//! let mut stream = agent.completion("Write a haiku about Rust", vec![]).await?.stream().await?;
+//!
+//! while let Some(chunk) = stream.next().await {
+//!     match chunk? {
+//!         StreamingChoice::Message(text) => print!("{text}"),
+//!         StreamingChoice::ToolCall(name, args) => println!("Tool call: {name}({args})"),
+//!         StreamingChoice::Usage(usage) => println!("\n{usage}"),
+//!     }
+//! }
+//! ```
+use std::{collections::VecDeque, pin::Pin};
+
+use futures::{stream, Stream};
+
+use crate::completion::{CompletionError, ModelChoice, Usage};
+
+/// Chunk of a streaming completion response.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamingChoice {
+    /// A chunk of the message generated by the model
+    Message(String),
+    /// A complete tool call of the form `ToolCall(function_name, function_params)`
+    ToolCall(String, serde_json::Value),
+    /// The token usage of the completion, usually sent at the end of the stream
+    Usage(Usage),
+}
+
+impl From<ModelChoice> for StreamingChoice {
+    fn from(choice: ModelChoice) -> Self {
+        match choice {
+            ModelChoice::Message(message) => StreamingChoice::Message(message),
+            ModelChoice::ToolCall(name, params) => StreamingChoice::ToolCall(name, params),
+        }
+    }
+}
+
+/// Stream of the chunks of a completion response.
+pub type StreamingResult =
+    Pin<Box<dyn Stream<Item = Result<StreamingChoice, CompletionError>> + Send>>;
+
+/// Decoder for server-sent events, yielding the `data` field of each event.
+/// Comments and other fields (`event`, `id`, `retry`) are ignored.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    /// Decode a chunk of the response body, returning the data of the lines completed by it.
+    /// Lines are only decoded once complete, so that multi-byte characters split across
+    /// chunks are preserved.
+    pub(crate) fn decode(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut data = vec![];
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+            data.extend(Self::data(&line));
+        }
+        data
+    }
+
+    /// Decode the last line of the response body, if it was not terminated by a newline
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.buffer);
+        Self::data(&line)
+    }
+
+    fn data(line: &[u8]) -> Option<String> {
+        let line = String::from_utf8_lossy(line);
+        line.trim_end_matches(['\r', '\n'])
+            .strip_prefix("data:")
+            .map(|data| data.strip_prefix(' ').unwrap_or(data).to_string())
+    }
+}
+
+/// Stream of the `data` fields of the server-sent events of a streaming HTTP response.
+pub(crate) fn sse_data(
+    response: reqwest::Response,
+) -> impl Stream<Item = Result<String, CompletionError>> + Send {
+    stream::unfold(
+        (Some(response), SseDecoder::default(), VecDeque::new()),
+        |(mut response, mut decoder, mut pending)| async move {
+            loop {
+                if let Some(data) = pending.pop_front() {
+                    return Some((Ok(data), (response, decoder, pending)));
+                }
+
+                match response.as_mut()?.chunk().await {
+                    Ok(Some(chunk)) => pending.extend(decoder.decode(&chunk)),
+                    Ok(None) => {
+                        response = None;
+                        pending.extend(decoder.finish());
+                        if pending.is_empty() {
+                            return None;
+                        }
+                    }
+                    Err(err) => return Some((Err(err.into()), (None, decoder, pending))),
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_decoder() {
+        let mut decoder = SseDecoder::default();
+
+        assert_eq!(
+            decoder.decode(b": keep-alive\n\ndata: {\"a\": 1}\r\n\ndata: {\"b\": \"\xc3"),
+            vec!["{\"a\": 1}".to_string()]
+        );
+        assert_eq!(
+            decoder.decode(b"\xa9\"}\n\nevent: done\ndata:[DONE]"),
+            vec!["{\"b\": \"é\"}".to_string()]
+        );
+        assert_eq!(decoder.finish(), Some("[DONE]".to_string()));
+        assert_eq!(decoder.finish(), None);
+    }
+}
@@ -285,6 +285,18 @@ impl ToolSet {
         self.tools.get(toolname)
     }
 
+    /// Get the definitions of all the tools in the toolset, sorted by name
+    pub async fn definitions(&self, prompt: &str) -> Vec<ToolDefinition> {
+        let mut definitions = futures::future::join_all(
+            self.tools
+                .values()
+                .map(|tool| tool.definition(prompt.to_string())),
+        )
+        .await;
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
     /// Call a tool with the given name and arguments
     pub async fn call(&self, toolname: &str, args: String) -> Result<String, ToolSetError> {
         if let Some(tool) = self.tools.get(toolname) {
@@ -0,0 +1,548 @@
+//! This module provides function calling for completion models without native tool support
+//! (e.g.: Perplexity, or local models behind an OpenAI-compatible API).
+//!
+//! [ToolEmulation] wraps a completion model: the tool definitions of each request are described
+//! in the preamble in a ReAct-style format (`Thought` / `Action` / `Action Input`) instead of
+//! being sent to the provider, and tool invocations written by the model are parsed back into
+//! [ModelChoice::ToolCall]. Agents built on the wrapped model can therefore use tools as usual.
+//!
+//! Models often produce slightly invalid JSON (trailing commas, single quotes, unquoted keys,
+//! truncated objects, etc.), so arguments are parsed with [repair_json].
+//!
+//! # Example
+//! ```rust
+//! use rig::{agent::AgentBuilder, providers::perplexity, tool_emulation::ToolEmulation};
+//!
+//! let perplexity = perplexity::Client::from_env();
+//! let model = ToolEmulation::new(perplexity.completion_model(perplexity::LLAMA_3_1_70B_INSTRUCT));
+//!
+//! let calculator = AgentBuilder::new(model)
+//!     .preamble("You are a calculator. Use the tools provided to answer.")
+//!     .tool(Adder)
+//!     .build();
+//!
+//! let answer = calculator.prompt("What is 2 + 5?").await?;
+//! ```
+use regex::Regex;
+use serde_json::Value;
+
+use crate::completion::{
+    self, CompletionError, CompletionRequest, CompletionResponse, ModelChoice, ToolDefinition,
+};
+
+const TOOL_PROMPT: &str = "\
+To use a tool, respond with exactly the following format and nothing else:
+Thought: <your reasoning about which tool to use>
+Action: <the name of the tool>
+Action Input: <the arguments of the tool, as a JSON object matching its parameters>
+
+If no tool is needed, respond with the following format instead:
+Thought: <your reasoning>
+Final Answer: <your answer>";
+
+/// Completion model adapter emulating function calling with a ReAct-style prompt.
+///
+/// Requests without tools are sent to the wrapped model unchanged. Tool calls returned natively
+/// by the wrapped model (if any) are passed through.
+#[derive(Clone)]
+pub struct ToolEmulation<M: completion::CompletionModel> {
+    model: M,
+}
+
+impl<M: completion::CompletionModel> ToolEmulation<M> {
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+
+    /// The wrapped completion model
+    pub fn inner(&self) -> &M {
+        &self.model
+    }
+}
+
+/// Description of the tools, appended to the preamble of requests with tools
+fn tools_prompt(tools: &[ToolDefinition]) -> String {
+    let tools = tools
+        .iter()
+        .map(|tool| {
+            format!(
+                "- {}: {}\n  Parameters (JSON Schema): {}",
+                tool.name, tool.description, tool.parameters
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("You have access to the following tools:\n{tools}\n\n{TOOL_PROMPT}")
+}
+
+impl<M: completion::CompletionModel> completion::CompletionModel for ToolEmulation<M> {
+    type Response = M::Response;
+
+    async fn completion(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<CompletionResponse<M::Response>, CompletionError> {
+        if request.tools.is_empty() {
+            return self.model.completion(request).await;
+        }
+
+        let tools = emulate_tools(&mut request);
+        let response = self.model.completion(request).await?;
+
+        Ok(CompletionResponse {
+            choice: parse_choice(response.choice, &tools),
+            ..response
+        })
+    }
+
+    fn structured_output_params(
+        &self,
+        name: &str,
+        schema: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        self.model.structured_output_params(name, schema)
+    }
+}
+
+/// Move the tools of `request` to its preamble, returning them.
+pub(crate) fn emulate_tools(request: &mut CompletionRequest) -> Vec<ToolDefinition> {
+    let tools = std::mem::take(&mut request.tools);
+    request.preamble = Some(match request.preamble.take() {
+        Some(preamble) => format!("{preamble}\n\n{}", tools_prompt(&tools)),
+        None => tools_prompt(&tools),
+    });
+    tools
+}
+
+/// Parse the choice of a model prompted with [emulate_tools] into a tool call or an answer.
+pub(crate) fn parse_choice(choice: ModelChoice, tools: &[ToolDefinition]) -> ModelChoice {
+    match choice {
+        ModelChoice::Message(text) => match parse_tool_call(&text, tools) {
+            Some((name, args)) => ModelChoice::ToolCall(name, args),
+            None => ModelChoice::Message(final_answer(&text)),
+        },
+        choice => choice,
+    }
+}
+
+/// Answer of a ReAct-style message, i.e.: the text after `Final Answer:` if present, or the
+/// message without its `Thought:` line otherwise.
+fn final_answer(text: &str) -> String {
+    let answer = Regex::new(r"(?is)final\s+answer\s*:\s*(.*)")
+        .expect("Final answer regex should be valid")
+        .captures(text)
+        .map(|caps| caps[1].to_string());
+
+    match answer {
+        Some(answer) => answer.trim().to_string(),
+        None => {
+            let text = text.trim();
+            match Regex::new(r"(?i)^thought\s*:[^\n]*\n+")
+                .expect("Thought regex should be valid")
+                .find(text)
+            {
+                Some(thought) if thought.end() < text.len() => text[thought.end()..].trim(),
+                _ => text,
+            }
+            .to_string()
+        }
+    }
+}
+
+/// Name of the tool among `tools` matching `name`, ignoring case, quotes and a `functions.`
+/// prefix (as written by some models).
+fn match_tool(name: &str, tools: &[ToolDefinition]) -> Option<String> {
+    let name = name
+        .trim()
+        .trim_matches(|c: char| c == '`' || c == '"' || c == '\'' || c == '*')
+        .trim_start_matches("functions.");
+
+    tools
+        .iter()
+        .find(|tool| tool.name.eq_ignore_ascii_case(name))
+        .map(|tool| tool.name.clone())
+}
+
+/// Parse a tool invocation from a message, either in the ReAct format (`Action:` and
+/// `Action Input:` lines) or as a JSON object with the name and arguments of the tool (e.g.:
+/// `{"name": "add", "arguments": {"x": 1, "y": 2}}`). Invocations of unknown tools are ignored.
+pub fn parse_tool_call(text: &str, tools: &[ToolDefinition]) -> Option<(String, Value)> {
+    let action = Regex::new(
+        r"(?is)(?:^|\n)[\s*]*action[\s*]*:[\s*]*([^\n]+?)[\s*]*(?:\n[\s*]*action[\s_]*input[\s*]*:(.*?))?(?:\n[\s*]*(?:observation|thought|final\s+answer)\s*:|$)",
+    )
+    .expect("Action regex should be valid");
+
+    if let Some(caps) = action.captures(text) {
+        if let Some(name) = match_tool(&caps[1], tools) {
+            let input = caps.get(2).map_or("", |input| {
+                input
+                    .as_str()
+                    .trim_matches(|c: char| c == '*' || c.is_whitespace())
+            });
+            let args = match input {
+                "" => Value::Object(Default::default()),
+                input => serde_json::from_str(input)
+                    .ok()
+                    .or_else(|| repair_json(input))
+                    .unwrap_or_else(|| Value::String(input.into())),
+            };
+            return Some((name, unwrap_json_string(args)));
+        }
+    }
+
+    let call = repair_json(text)?;
+    let name = ["name", "tool", "function", "action"]
+        .iter()
+        .find_map(|key| call.get(key)?.as_str())
+        .and_then(|name| match_tool(name, tools))?;
+    let args = ["arguments", "args", "parameters", "input", "action_input"]
+        .iter()
+        .find_map(|key| call.get(key).cloned())
+        .unwrap_or_else(|| Value::Object(Default::default()));
+
+    Some((name, unwrap_json_string(args)))
+}
+
+/// Arguments are sometimes written as a JSON string containing the JSON object
+fn unwrap_json_string(args: Value) -> Value {
+    match &args {
+        Value::String(string) => match repair_json(string) {
+            Some(inner @ (Value::Object(_) | Value::Array(_))) => inner,
+            _ => args,
+        },
+        _ => args,
+    }
+}
+
+/// Parse the first JSON object or array in `text`, repairing common mistakes of language
+/// models:
+/// - surrounding text and Markdown code fences
+/// - single-quoted strings, unquoted keys and unquoted string values
+/// - Python literals (`True`, `False`, `None`)
+/// - trailing commas and comments
+/// - raw newlines in strings
+/// - unclosed strings, objects and arrays (e.g.: truncated outputs)
+///
+/// Returns `None` if `text` contains no JSON object or array.
+pub fn repair_json(text: &str) -> Option<Value> {
+    let start = text.find(['{', '['])?;
+    let text = &text[start..];
+
+    if let Some(Ok(value)) = serde_json::Deserializer::from_str(text)
+        .into_iter::<Value>()
+        .next()
+    {
+        return Some(value);
+    }
+
+    serde_json::from_str(&normalize_json(text)).ok()
+}
+
+/// Rewrite almost-JSON `text` (starting with `{` or `[`) into JSON, see [repair_json].
+fn normalize_json(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(text.len());
+    let mut stack = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            quote @ ('"' | '\'') => {
+                let (string, end) = read_string(&chars, i + 1, quote);
+                output.push_str(&string);
+                i = end;
+                continue;
+            }
+            open @ ('{' | '[') => {
+                stack.push(if open == '{' { '}' } else { ']' });
+                output.push(open);
+            }
+            '}' | ']' => {
+                trim_trailing_comma(&mut output);
+                if let Some(close) = stack.pop() {
+                    output.push(close);
+                }
+                if stack.is_empty() {
+                    return output;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let end = (i..chars.len())
+                    .find(|&j| {
+                        !(chars[j].is_ascii_digit()
+                            || matches!(chars[j], '.' | 'e' | 'E' | '+' | '-'))
+                    })
+                    .unwrap_or(chars.len());
+                output.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let end = (i..chars.len())
+                    .find(|&j| !(chars[j].is_alphanumeric() || matches!(chars[j], '_' | '-' | '.')))
+                    .unwrap_or(chars.len());
+                let word = chars[i..end].iter().collect::<String>();
+                let is_key = chars[end..]
+                    .iter()
+                    .find(|c| !c.is_whitespace())
+                    .is_some_and(|&c| c == ':');
+
+                match word.as_str() {
+                    _ if is_key => output.push_str(&Value::String(word).to_string()),
+                    "true" | "True" => output.push_str("true"),
+                    "false" | "False" => output.push_str("false"),
+                    "null" | "None" | "nil" | "undefined" => output.push_str("null"),
+                    _ => {
+                        // Unquoted string value: read until the end of the value
+                        let end = (i..chars.len())
+                            .find(|&j| matches!(chars[j], ',' | '}' | ']' | '\n'))
+                            .unwrap_or(chars.len());
+                        let value = chars[i..end].iter().collect::<String>();
+                        output.push_str(&Value::String(value.trim().to_string()).to_string());
+                        i = end;
+                        continue;
+                    }
+                }
+                i = end;
+                continue;
+            }
+            c => output.push(c),
+        }
+        i += 1;
+    }
+
+    // Truncated input: drop a dangling separator and close what is still open
+    let trimmed = output.trim_end().len();
+    output.truncate(trimmed);
+    if output.ends_with(':') {
+        output.push_str("null");
+    }
+    trim_trailing_comma(&mut output);
+    while let Some(close) = stack.pop() {
+        output.push(close);
+    }
+
+    output
+}
+
+/// Read a string starting after its opening `quote` at `start`, returning it as a JSON string
+/// along with the index after its closing quote (or the end of the input if it is unclosed).
+fn read_string(chars: &[char], start: usize, quote: char) -> (String, usize) {
+    let mut string = String::new();
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                match chars[i + 1] {
+                    // Escapes valid in JSON are kept as they are
+                    c @ ('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' | 'u') => {
+                        string.push('\\');
+                        string.push(c);
+                    }
+                    c => string.push(c),
+                }
+                i += 2;
+                continue;
+            }
+            c if c == quote => return (format!("\"{string}\""), i + 1),
+            '"' => string.push_str("\\\""),
+            '\n' => string.push_str("\\n"),
+            '\r' => string.push_str("\\r"),
+            '\t' => string.push_str("\\t"),
+            c => string.push(c),
+        }
+        i += 1;
+    }
+
+    (format!("\"{string}\""), chars.len())
+}
+
+fn trim_trailing_comma(output: &mut String) {
+    let trimmed = output.trim_end();
+    if trimmed.ends_with(',') {
+        output.truncate(trimmed.len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::completion::{CompletionModel, Usage};
+
+    fn tools() -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            name: "transfer_tokens".to_string(),
+            description: "Transfer SOL to a wallet".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "to": {"type": "string"},
+                    "amount": {"type": "number"}
+                }
+            }),
+        }]
+    }
+
+    #[test]
+    fn test_repair_json() {
+        let cases = [
+            (r#"{"a": 1}"#, json!({"a": 1})),
+            (
+                "Sure! ```json\n{\"a\": [1, 2,],}\n``` Done.",
+                json!({"a": [1, 2]}),
+            ),
+            (
+                "{'a': 'it\\'s', b: True, c: None}",
+                json!({"a": "it's", "b": true, "c": null}),
+            ),
+            (
+                "{\"a\": \"line\nbreak\" // comment\n}",
+                json!({"a": "line\nbreak"}),
+            ),
+            (
+                "{to: wallet_123, amount: 0.5}",
+                json!({"to": "wallet_123", "amount": 0.5}),
+            ),
+            ("[1e3, -2.5, 'x',]", json!([1e3, -2.5, "x"])),
+            ("{\"a\": {\"b\": [1, 2", json!({"a": {"b": [1, 2]}})),
+            ("{\"a\": \"trunc", json!({"a": "trunc"})),
+            ("{\"a\":", json!({"a": null})),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(repair_json(text), Some(expected), "{text}");
+        }
+        assert_eq!(repair_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_parse_tool_call() {
+        let react = "Thought: The seeker solved the riddle.\n\
+            Action: transfer_tokens\n\
+            Action Input: {'to': 'abc', 'amount': 0.1,}\n\
+            Observation:";
+        assert_eq!(
+            parse_tool_call(react, &tools()),
+            Some((
+                "transfer_tokens".to_string(),
+                json!({"to": "abc", "amount": 0.1})
+            ))
+        );
+
+        let markdown =
+            "**Action:** `Transfer_Tokens`\n**Action Input:** \"{\\\"to\\\": \\\"abc\\\"}\"";
+        assert_eq!(
+            parse_tool_call(markdown, &tools()),
+            Some(("transfer_tokens".to_string(), json!({"to": "abc"})))
+        );
+
+        let json_call = r#"<tool_call>{"name": "transfer_tokens", "arguments": {"to": "abc", "amount": 1}}</tool_call>"#;
+        assert_eq!(
+            parse_tool_call(json_call, &tools()),
+            Some((
+                "transfer_tokens".to_string(),
+                json!({"to": "abc", "amount": 1})
+            ))
+        );
+
+        assert_eq!(
+            parse_tool_call("Action: withdraw_all\nAction Input: {}", &tools()),
+            None
+        );
+        assert_eq!(
+            parse_tool_call("Thought: no tool\nFinal Answer: {\"to\": 1}", &tools()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_final_answer() {
+        assert_eq!(
+            final_answer("Thought: easy.\nFinal Answer: The answer is 7."),
+            "The answer is 7."
+        );
+        assert_eq!(final_answer("Thought: hmm\n\nJust text"), "Just text");
+        assert_eq!(final_answer("Just text"), "Just text");
+    }
+
+    #[derive(Clone)]
+    struct EchoModel;
+
+    impl CompletionModel for EchoModel {
+        type Response = Option<String>;
+
+        async fn completion(
+            &self,
+            request: CompletionRequest,
+        ) -> Result<CompletionResponse<Self::Response>, CompletionError> {
+            let text = match request.tools.is_empty() {
+                true => request.prompt.clone(),
+                false => "native tools".to_string(),
+            };
+            Ok(CompletionResponse {
+                choice: ModelChoice::Message(text),
+                usage: Some(Usage::new(1, 1)),
+                citations: vec![],
+                raw_response: request.preamble,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_emulation() {
+        let model = ToolEmulation::new(EchoModel);
+
+        let response = model
+            .completion_request("Action: transfer_tokens\nAction Input: {\"to\": \"abc\"}")
+            .preamble("You are Yuri.".to_string())
+            .tools(tools())
+            .send()
+            .await
+            .unwrap();
+        assert!(matches!(
+            response.choice,
+            ModelChoice::ToolCall(name, args) if name == "transfer_tokens" && args == json!({"to": "abc"})
+        ));
+        let preamble = response.raw_response.unwrap();
+        assert!(preamble.starts_with("You are Yuri.\n\nYou have access to the following tools:"));
+        assert!(preamble.contains("- transfer_tokens: Transfer SOL to a wallet"));
+        assert_eq!(response.usage, Some(Usage::new(1, 1)));
+
+        let response = model
+            .completion_request("Final Answer: no transfer")
+            .tools(tools())
+            .send()
+            .await
+            .unwrap();
+        assert!(matches!(response.choice, ModelChoice::Message(text) if text == "no transfer"));
+
+        // Requests without tools are unchanged
+        let response = model
+            .completion_request("Final Answer: hi")
+            .send()
+            .await
+            .unwrap();
+        assert!(
+            matches!(response.choice, ModelChoice::Message(text) if text == "Final Answer: hi")
+        );
+        assert_eq!(response.raw_response, None);
+    }
+}
@@ -0,0 +1,217 @@
+//! This module provides functionality for working with transcription (i.e.: speech-to-text)
+//! models.
+//!
+//! The [TranscriptionModel] trait is the interface between providers (e.g.: OpenAI Whisper) and
+//! the library. Requests are created with [TranscriptionModel::transcription_request] from the
+//! audio data and the name of the audio file (its extension tells providers the audio format).
+//!
+//! # Example
+//! ```rust
+//! use rig::{providers::openai, transcription::TranscriptionModel};
+//!
+//! let openai = openai::Client::from_env();
+//! let whisper = openai.transcription_model(openai::WHISPER_1);
+//!
+//! let audio = std::fs::read("voice_note.ogg")?;
+//! let response = whisper
+//!     .transcription_request(audio, "voice_note.ogg")
+//!     .language("en")
+//!     .send()
+//!     .await?;
+//!
+//! println!("{}", response.text);
+//! ```
+use crate::json_utils;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranscriptionError {
+    /// Http error (e.g.: connection error, timeout, etc.)
+    #[error("HttpError: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    /// Json error (e.g.: serialization, deserialization)
+    #[error("JsonError: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// Error building the transcription request
+    #[error("RequestError: {0}")]
+    RequestError(String),
+
+    /// Error parsing the transcription response
+    #[error("ResponseError: {0}")]
+    ResponseError(String),
+
+    /// Error returned by the transcription model provider
+    #[error("ProviderError: {0}")]
+    ProviderError(String),
+}
+
+/// Struct representing a general transcription request that can be sent to a transcription
+/// model provider.
+#[derive(Clone, Debug)]
+pub struct TranscriptionRequest {
+    /// The audio data to transcribe
+    pub data: Vec<u8>,
+    /// The name of the audio file (e.g.: `voice.ogg`)
+    pub filename: String,
+    /// The language of the audio as an ISO-639-1 code (e.g.: `en`), detected if not set
+    pub language: Option<String>,
+    /// Text guiding the style of the transcription or spelling of uncommon words
+    pub prompt: Option<String>,
+    /// Temperature of the model
+    pub temperature: Option<f64>,
+    /// Additional provider-specific parameters to be sent to the transcription model provider
+    pub additional_params: Option<serde_json::Value>,
+}
+
+/// General transcription response struct that contains the transcribed text and the raw
+/// response.
+#[derive(Debug)]
+pub struct TranscriptionResponse<T> {
+    /// The transcribed text
+    pub text: String,
+    /// The raw response returned by the transcription model provider
+    pub raw_response: T,
+}
+
+/// Trait defining a transcription model, either from a third party provider (e.g.: OpenAI) or a
+/// local model. Test doubles can implement it to return fixed transcriptions.
+pub trait TranscriptionModel: Clone + Send + Sync {
+    /// The raw response type returned by the underlying transcription model.
+    type Response: Send + Sync;
+
+    /// Transcribes the audio of the given transcription request.
+    fn transcription(
+        &self,
+        request: TranscriptionRequest,
+    ) -> impl std::future::Future<
+        Output = Result<TranscriptionResponse<Self::Response>, TranscriptionError>,
+    > + Send;
+
+    /// Generates a transcription request builder for the given audio `data`. The `filename`
+    /// (e.g.: `voice.ogg`) tells the provider the format of the audio.
+    fn transcription_request(
+        &self,
+        data: Vec<u8>,
+        filename: &str,
+    ) -> TranscriptionRequestBuilder<Self> {
+        TranscriptionRequestBuilder::new(self.clone(), data, filename)
+    }
+}
+
+/// Builder struct for constructing a transcription request.
+pub struct TranscriptionRequestBuilder<M: TranscriptionModel> {
+    model: M,
+    data: Vec<u8>,
+    filename: String,
+    language: Option<String>,
+    prompt: Option<String>,
+    temperature: Option<f64>,
+    additional_params: Option<serde_json::Value>,
+}
+
+impl<M: TranscriptionModel> TranscriptionRequestBuilder<M> {
+    pub fn new(model: M, data: Vec<u8>, filename: &str) -> Self {
+        Self {
+            model,
+            data,
+            filename: filename.to_string(),
+            language: None,
+            prompt: None,
+            temperature: None,
+            additional_params: None,
+        }
+    }
+
+    /// Sets the language of the audio (ISO-639-1 code, e.g.: `en`)
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Sets the prompt guiding the transcription
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Sets the temperature of the model
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Adds additional parameters to the transcription request.
+    pub fn additional_params(mut self, additional_params: serde_json::Value) -> Self {
+        match self.additional_params {
+            Some(params) => {
+                self.additional_params = Some(json_utils::merge(params, additional_params));
+            }
+            None => {
+                self.additional_params = Some(additional_params);
+            }
+        }
+        self
+    }
+
+    /// Builds the transcription request.
+    pub fn build(self) -> TranscriptionRequest {
+        TranscriptionRequest {
+            data: self.data,
+            filename: self.filename,
+            language: self.language,
+            prompt: self.prompt,
+            temperature: self.temperature,
+            additional_params: self.additional_params,
+        }
+    }
+
+    /// Sends the transcription request to the transcription model provider and returns the
+    /// transcribed text.
+    pub async fn send(self) -> Result<TranscriptionResponse<M::Response>, TranscriptionError> {
+        let model = self.model.clone();
+        model.transcription(self.build()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct StubModel;
+
+    impl TranscriptionModel for StubModel {
+        type Response = TranscriptionRequest;
+
+        async fn transcription(
+            &self,
+            request: TranscriptionRequest,
+        ) -> Result<TranscriptionResponse<Self::Response>, TranscriptionError> {
+            Ok(TranscriptionResponse {
+                text: String::from_utf8_lossy(&request.data).to_string(),
+                raw_response: request,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcription_request_builder() {
+        let response = StubModel
+            .transcription_request(b"Hello".to_vec(), "voice.ogg")
+            .language("en")
+            .prompt("Rig, Telegram")
+            .additional_params(serde_json::json!({"timestamp_granularities": ["word"]}))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "Hello");
+        let request = response.raw_response;
+        assert_eq!(request.filename, "voice.ogg");
+        assert_eq!(request.language.as_deref(), Some("en"));
+        assert_eq!(request.prompt.as_deref(), Some("Rig, Telegram"));
+        assert_eq!(request.temperature, None);
+        assert!(request.additional_params.is_some());
+    }
+}
@@ -7,9 +7,9 @@ use std::{
 use ordered_float::OrderedFloat;
 use serde::{Deserialize, Serialize};
 
-use super::{VectorStoreError, VectorStoreIndex};
+use super::{VectorStoreError, VectorStoreIndex, VectorStoreMut};
 use crate::{
-    embeddings::{distance::VectorDistance, Embedding, EmbeddingModel},
+    embeddings::{distance::EmbeddingMatrix, Embedding, EmbeddingModel},
     OneOrMany,
 };
 
@@ -21,34 +21,31 @@ pub struct InMemoryVectorStore<D: Serialize> {
     /// Hashmap key is the document id.
     /// Hashmap value is a tuple of the serializable document and its corresponding embeddings.
     embeddings: HashMap<String, (D, OneOrMany<Embedding>)>,
+    /// The embeddings of all documents as contiguous `f32` rows, for batched similarity search.
+    matrix: EmbeddingMatrix,
+    /// Document id and index in the document embeddings of each row of `matrix`.
+    rows: Vec<(String, usize)>,
 }
 
 impl<D: Serialize + Eq> InMemoryVectorStore<D> {
     /// Create a new [InMemoryVectorStore] from documents and their corresponding embeddings.
     /// Ids are automatically generated have will have the form `"doc{n}"` where `n`
     /// is the index of the document.
-    pub fn from_documents(documents: impl IntoIterator<Item = (D, OneOrMany<Embedding>)>) -> Self {
-        let mut store = HashMap::new();
-        documents
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, (doc, embeddings))| {
-                store.insert(format!("doc{i}"), (doc, embeddings));
-            });
-
-        Self { embeddings: store }
+    pub fn from_documents(
+        documents: impl IntoIterator<Item = (D, OneOrMany<Embedding>)>,
+    ) -> Result<Self, VectorStoreError> {
+        let mut store = Self::empty();
+        store.add_documents(documents)?;
+        Ok(store)
     }
 
     /// Create a new [InMemoryVectorStore] from documents and and their corresponding embeddings with ids.
     pub fn from_documents_with_ids(
         documents: impl IntoIterator<Item = (impl ToString, D, OneOrMany<Embedding>)>,
-    ) -> Self {
-        let mut store = HashMap::new();
-        documents.into_iter().for_each(|(i, doc, embeddings)| {
-            store.insert(i.to_string(), (doc, embeddings));
-        });
-
-        Self { embeddings: store }
+    ) -> Result<Self, VectorStoreError> {
+        let mut store = Self::empty();
+        store.add_documents_with_ids(documents)?;
+        Ok(store)
     }
 
     /// Create a new [InMemoryVectorStore] from documents and their corresponding embeddings.
@@ -56,35 +53,133 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
     pub fn from_documents_with_id_f(
         documents: impl IntoIterator<Item = (D, OneOrMany<Embedding>)>,
         f: fn(&D) -> String,
-    ) -> Self {
-        let mut store = HashMap::new();
-        documents.into_iter().for_each(|(doc, embeddings)| {
-            store.insert(f(&doc), (doc, embeddings));
-        });
+    ) -> Result<Self, VectorStoreError> {
+        let mut store = Self::empty();
+        for (doc, embeddings) in documents {
+            store.insert(f(&doc), doc, embeddings)?;
+        }
+        Ok(store)
+    }
+
+    fn empty() -> Self {
+        Self {
+            embeddings: HashMap::new(),
+            matrix: EmbeddingMatrix::default(),
+            rows: vec![],
+        }
+    }
+
+    /// Insert a document, replacing the document with the same id, and add its embeddings to
+    /// the matrix.
+    ///
+    /// Returns an error, leaving the store unchanged, if the embeddings do not all have the
+    /// dimensions of the embeddings already stored.
+    fn insert(
+        &mut self,
+        id: String,
+        doc: D,
+        embeddings: OneOrMany<Embedding>,
+    ) -> Result<(), VectorStoreError> {
+        // The replaced document is only removed once the embeddings are validated: if its rows
+        // are the only ones, the dimensions are set by the new embeddings
+        let ndims = match self.rows.iter().all(|(row_id, _)| *row_id == id) {
+            true => embeddings
+                .iter()
+                .next()
+                .map_or(0, |embedding| embedding.vec.len()),
+            false => self.matrix.ndims(),
+        };
+        if let Some((index, embedding)) = embeddings
+            .iter()
+            .enumerate()
+            .find(|(_, embedding)| embedding.vec.len() != ndims)
+        {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Embedding {} of document {} has {} dimensions instead of {}",
+                    index,
+                    id,
+                    embedding.vec.len(),
+                    ndims
+                )
+                .into(),
+            ));
+        }
+
+        self.remove(&id);
+
+        for (index, embedding) in embeddings.iter().enumerate() {
+            self.matrix.push(embedding);
+            self.rows.push((id.clone(), index));
+        }
+        self.embeddings.insert(id, (doc, embeddings));
+        Ok(())
+    }
+
+    /// Remove a document and its rows of the matrix.
+    fn remove(&mut self, id: &str) {
+        if self.embeddings.remove(id).is_none() {
+            return;
+        }
 
-        Self { embeddings: store }
+        // Rows after `i` were already checked when they are swapped into `i`
+        for i in (0..self.rows.len()).rev() {
+            if self.rows[i].0 == id {
+                self.matrix.swap_remove(i);
+                self.rows.swap_remove(i);
+            }
+        }
     }
 
     /// Implement vector search on [InMemoryVectorStore].
     /// To be used by implementations of [VectorStoreIndex::top_n] and [VectorStoreIndex::top_n_ids] methods.
-    fn vector_search(&self, prompt_embedding: &Embedding, n: usize) -> EmbeddingRanking<D> {
+    fn vector_search(
+        &self,
+        prompt_embedding: &Embedding,
+        n: usize,
+    ) -> Result<EmbeddingRanking<'_, D>, VectorStoreError> {
+        if !self.matrix.is_empty() && prompt_embedding.vec.len() != self.matrix.ndims() {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Prompt embedding has {} dimensions instead of {}",
+                    prompt_embedding.vec.len(),
+                    self.matrix.ndims()
+                )
+                .into(),
+            ));
+        }
+
         // Sort documents by best embedding distance
         let mut docs = BinaryHeap::new();
 
-        for (id, (doc, embeddings)) in self.embeddings.iter() {
-            // Get the best context for the document given the prompt
-            if let Some((distance, embed_doc)) = embeddings
-                .iter()
-                .map(|embedding| {
-                    (
-                        OrderedFloat(embedding.cosine_similarity(prompt_embedding, false)),
-                        &embedding.document,
-                    )
+        // Get the best context of each document given the prompt
+        let mut best = HashMap::<&str, (OrderedFloat<f64>, usize)>::new();
+        for ((id, index), similarity) in self
+            .rows
+            .iter()
+            .zip(self.matrix.cosine_similarities(prompt_embedding))
+        {
+            let similarity = OrderedFloat(similarity as f64);
+            best.entry(id)
+                .and_modify(|best| {
+                    if similarity > best.0 {
+                        *best = (similarity, *index)
+                    }
                 })
-                .max_by(|a, b| a.0.cmp(&b.0))
-            {
-                docs.push(Reverse(RankingItem(distance, id, doc, embed_doc)));
-            };
+                .or_insert((similarity, *index));
+        }
+
+        for (id, (distance, index)) in best {
+            let (id, (doc, embeddings)) = self
+                .embeddings
+                .get_key_value(id)
+                .expect("Rows reference stored documents");
+            let embed_doc = &embeddings
+                .iter()
+                .nth(index)
+                .expect("Rows reference stored embeddings")
+                .document;
+            docs.push(Reverse(RankingItem(distance, id, doc, embed_doc)));
 
             // If the heap size exceeds n, pop the least old element.
             if docs.len() > n {
@@ -101,7 +196,7 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
                 .join(", ")
         );
 
-        docs
+        Ok(docs)
     }
 
     /// Add documents and their corresponding embeddings to the store.
@@ -110,25 +205,23 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
     pub fn add_documents(
         &mut self,
         documents: impl IntoIterator<Item = (D, OneOrMany<Embedding>)>,
-    ) {
+    ) -> Result<(), VectorStoreError> {
         let current_index = self.embeddings.len();
-        documents
-            .into_iter()
-            .enumerate()
-            .for_each(|(index, (doc, embeddings))| {
-                self.embeddings
-                    .insert(format!("doc{}", index + current_index), (doc, embeddings));
-            });
+        for (index, (doc, embeddings)) in documents.into_iter().enumerate() {
+            self.insert(format!("doc{}", index + current_index), doc, embeddings)?;
+        }
+        Ok(())
     }
 
     /// Add documents and their corresponding embeddings to the store with ids.
     pub fn add_documents_with_ids(
         &mut self,
         documents: impl IntoIterator<Item = (impl ToString, D, OneOrMany<Embedding>)>,
-    ) {
-        documents.into_iter().for_each(|(id, doc, embeddings)| {
-            self.embeddings.insert(id.to_string(), (doc, embeddings));
-        });
+    ) -> Result<(), VectorStoreError> {
+        for (id, doc, embeddings) in documents {
+            self.insert(id.to_string(), doc, embeddings)?;
+        }
+        Ok(())
     }
 
     /// Add documents and their corresponding embeddings to the store.
@@ -137,11 +230,19 @@ impl<D: Serialize + Eq> InMemoryVectorStore<D> {
         &mut self,
         documents: Vec<(D, OneOrMany<Embedding>)>,
         f: fn(&D) -> String,
-    ) {
+    ) -> Result<(), VectorStoreError> {
         for (doc, embeddings) in documents {
             let id = f(&doc);
-            self.embeddings.insert(id, (doc, embeddings));
+            self.insert(id, doc, embeddings)?;
         }
+        Ok(())
+    }
+
+    /// Remove the documents with the given ids from the store.
+    pub fn remove_documents<'a>(&mut self, ids: impl IntoIterator<Item = &'a str>) {
+        ids.into_iter().for_each(|id| {
+            self.remove(id);
+        });
     }
 
     /// Get the document by its id and deserialize it into the given type.
@@ -193,6 +294,20 @@ impl<D: Serialize> InMemoryVectorStore<D> {
     }
 }
 
+impl<D: Serialize + Eq + Send + Sync> VectorStoreMut<D> for InMemoryVectorStore<D> {
+    async fn upsert_documents(
+        &mut self,
+        documents: Vec<(String, D, OneOrMany<Embedding>)>,
+    ) -> Result<(), VectorStoreError> {
+        self.add_documents_with_ids(documents)
+    }
+
+    async fn delete_documents(&mut self, ids: Vec<String>) -> Result<(), VectorStoreError> {
+        self.remove_documents(ids.iter().map(String::as_str));
+        Ok(())
+    }
+}
+
 pub struct InMemoryVectorIndex<M: EmbeddingModel, D: Serialize> {
     model: M,
     pub store: InMemoryVectorStore<D>,
@@ -226,7 +341,7 @@ impl<M: EmbeddingModel + Sync, D: Serialize + Sync + Send + Eq> VectorStoreIndex
     ) -> Result<Vec<(f64, String, T)>, VectorStoreError> {
         let prompt_embedding = &self.model.embed_text(query).await?;
 
-        let docs = self.store.vector_search(prompt_embedding, n);
+        let docs = self.store.vector_search(prompt_embedding, n)?;
 
         // Return n best
         docs.into_iter()
@@ -250,7 +365,7 @@ impl<M: EmbeddingModel + Sync, D: Serialize + Sync + Send + Eq> VectorStoreIndex
     ) -> Result<Vec<(f64, String)>, VectorStoreError> {
         let prompt_embedding = &self.model.embed_text(query).await?;
 
-        let docs = self.store.vector_search(prompt_embedding, n);
+        let docs = self.store.vector_search(prompt_embedding, n)?;
 
         // Return n best
         docs.into_iter()
@@ -263,7 +378,7 @@ impl<M: EmbeddingModel + Sync, D: Serialize + Sync + Send + Eq> VectorStoreIndex
 mod tests {
     use std::cmp::Reverse;
 
-    use crate::{embeddings::embedding::Embedding, OneOrMany};
+    use crate::{embeddings::embedding::Embedding, vector_store::VectorStoreError, OneOrMany};
 
     use super::{InMemoryVectorStore, RankingItem};
 
@@ -291,24 +406,27 @@ mod tests {
                     vec: vec![0.3, 0.7, 0.1],
                 }),
             ),
-        ]);
+        ])
+        .unwrap();
 
-        vector_store.add_documents(vec![
-            (
-                "brotato",
-                OneOrMany::one(Embedding {
-                    document: "brotato".to_string(),
-                    vec: vec![0.3, 0.7, 0.1],
-                }),
-            ),
-            (
-                "ping-pong",
-                OneOrMany::one(Embedding {
-                    document: "ping-pong".to_string(),
-                    vec: vec![0.7, -0.3, 0.0],
-                }),
-            ),
-        ]);
+        vector_store
+            .add_documents(vec![
+                (
+                    "brotato",
+                    OneOrMany::one(Embedding {
+                        document: "brotato".to_string(),
+                        vec: vec![0.3, 0.7, 0.1],
+                    }),
+                ),
+                (
+                    "ping-pong",
+                    OneOrMany::one(Embedding {
+                        document: "ping-pong".to_string(),
+                        vec: vec![0.7, -0.3, 0.0],
+                    }),
+                ),
+            ])
+            .unwrap();
 
         let mut store = vector_store.embeddings.into_iter().collect::<Vec<_>>();
         store.sort_by_key(|(id, _)| id.clone());
@@ -397,15 +515,18 @@ mod tests {
                     vec: vec![0.3, 0.7, 0.1],
                 }),
             ),
-        ]);
-
-        let ranking = vector_store.vector_search(
-            &Embedding {
-                document: "glarby-glarble".to_string(),
-                vec: vec![0.0, 0.1, 0.6],
-            },
-            1,
-        );
+        ])
+        .unwrap();
+
+        let ranking = vector_store
+            .vector_search(
+                &Embedding {
+                    document: "glarby-glarble".to_string(),
+                    vec: vec![0.0, 0.1, 0.6],
+                },
+                1,
+            )
+            .unwrap();
 
         assert_eq!(
             ranking
@@ -419,7 +540,7 @@ mod tests {
                 })
                 .collect::<Vec<(_, _, String)>>(),
             vec![(
-                0.9807965956109156,
+                0.9807966351509094,
                 "doc1".to_string(),
                 "glarb-garb".to_string()
             )]
@@ -474,15 +595,18 @@ mod tests {
                 ])
                 .unwrap(),
             ),
-        ]);
-
-        let ranking = vector_store.vector_search(
-            &Embedding {
-                document: "glarby-glarble".to_string(),
-                vec: vec![0.0, 0.1, 0.6],
-            },
-            1,
-        );
+        ])
+        .unwrap();
+
+        let ranking = vector_store
+            .vector_search(
+                &Embedding {
+                    document: "glarby-glarble".to_string(),
+                    vec: vec![0.0, 0.1, 0.6],
+                },
+                1,
+            )
+            .unwrap();
 
         assert_eq!(
             ranking
@@ -496,10 +620,121 @@ mod tests {
                 })
                 .collect::<Vec<(_, _, String)>>(),
             vec![(
-                0.9807965956109156,
+                0.9807966351509094,
                 "doc1".to_string(),
                 "glarb-garb".to_string()
             )]
         )
     }
+
+    #[test]
+    fn test_replace_and_remove_documents() {
+        let mut vector_store = InMemoryVectorStore::from_documents_with_ids(vec![
+            (
+                "doc1",
+                "glarb-garb",
+                OneOrMany::one(Embedding {
+                    document: "glarb-garb".to_string(),
+                    vec: vec![0.1, 0.1, 0.5],
+                }),
+            ),
+            (
+                "doc2",
+                "marble-marble",
+                OneOrMany::one(Embedding {
+                    document: "marble-marble".to_string(),
+                    vec: vec![0.7, -0.3, 0.0],
+                }),
+            ),
+        ])
+        .unwrap();
+
+        // Replace the embedding of doc1, then remove doc2
+        vector_store
+            .add_documents_with_ids(vec![(
+                "doc1",
+                "flumb-flumb",
+                OneOrMany::one(Embedding {
+                    document: "flumb-flumb".to_string(),
+                    vec: vec![0.3, 0.7, 0.1],
+                }),
+            )])
+            .unwrap();
+        vector_store.remove_documents(["doc2"]);
+
+        assert_eq!(vector_store.len(), 1);
+        assert_eq!(vector_store.matrix.len(), 1);
+
+        let ranking = vector_store
+            .vector_search(
+                &Embedding {
+                    document: "glarby-glarble".to_string(),
+                    vec: vec![0.7, -0.3, 0.0],
+                },
+                2,
+            )
+            .unwrap();
+
+        assert_eq!(
+            ranking
+                .into_iter()
+                .map(|Reverse(RankingItem(_, id, _, embed_doc))| (id.clone(), embed_doc.clone()))
+                .collect::<Vec<_>>(),
+            vec![("doc1".to_string(), "flumb-flumb".to_string())]
+        )
+    }
+
+    #[test]
+    fn test_wrong_dimensions() {
+        let mut vector_store = InMemoryVectorStore::from_documents_with_ids(vec![(
+            "doc1",
+            "glarb-garb",
+            OneOrMany::one(Embedding {
+                document: "glarb-garb".to_string(),
+                vec: vec![0.1, 0.1, 0.5],
+            }),
+        )])
+        .unwrap();
+
+        let result = vector_store.add_documents_with_ids(vec![(
+            "doc2",
+            "marble-marble",
+            OneOrMany::many(vec![
+                Embedding {
+                    document: "marble-marble".to_string(),
+                    vec: vec![0.7, -0.3, 0.0],
+                },
+                Embedding {
+                    document: "sandwich".to_string(),
+                    vec: vec![0.5, 0.5],
+                },
+            ])
+            .unwrap(),
+        )]);
+        assert!(matches!(result, Err(VectorStoreError::DatastoreError(_))));
+        assert_eq!(vector_store.len(), 1);
+        assert_eq!(vector_store.matrix.len(), 1);
+
+        let result = vector_store.vector_search(
+            &Embedding {
+                document: "glarby-glarble".to_string(),
+                vec: vec![0.0, 0.1],
+            },
+            1,
+        );
+        assert!(matches!(result, Err(VectorStoreError::DatastoreError(_))));
+
+        // The only document can be replaced by one with other dimensions
+        vector_store
+            .add_documents_with_ids(vec![(
+                "doc1",
+                "flumb-flumb",
+                OneOrMany::one(Embedding {
+                    document: "flumb-flumb".to_string(),
+                    vec: vec![0.3, 0.7],
+                }),
+            )])
+            .unwrap();
+        assert_eq!(vector_store.matrix.ndims(), 2);
+    }
 }
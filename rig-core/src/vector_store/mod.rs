@@ -2,7 +2,10 @@ use futures::future::BoxFuture;
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::embeddings::EmbeddingError;
+use crate::{
+    embeddings::{Embedding, EmbeddingError},
+    OneOrMany,
+};
 
 pub mod in_memory_store;
 
@@ -40,6 +43,22 @@ pub trait VectorStoreIndex: Send + Sync {
     ) -> impl std::future::Future<Output = Result<Vec<(f64, String)>, VectorStoreError>> + Send;
 }
 
+/// Trait for vector stores whose documents can be added, replaced and removed in place.
+/// Used by [crate::ingest::Ingestor] to keep a store in sync with the files it was built from.
+pub trait VectorStoreMut<D>: Send + Sync {
+    /// Insert the given documents, replacing any existing document with the same id.
+    fn upsert_documents(
+        &mut self,
+        documents: Vec<(String, D, OneOrMany<Embedding>)>,
+    ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + Send;
+
+    /// Delete the documents with the given ids. Unknown ids are ignored.
+    fn delete_documents(
+        &mut self,
+        ids: Vec<String>,
+    ) -> impl std::future::Future<Output = Result<(), VectorStoreError>> + Send;
+}
+
 pub type TopNResults = Result<Vec<(f64, String, Value)>, VectorStoreError>;
 
 pub trait VectorStoreIndexDyn: Send + Sync {
@@ -0,0 +1,225 @@
+//! Batch embedding jobs against a local stand-in of the OpenAI Batch API.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rig::{
+    embeddings::{BatchConfig, EmbeddingsBuilder},
+    providers::openai,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+#[derive(Default)]
+struct Server {
+    /// Content of the uploaded files, by id
+    files: HashMap<String, String>,
+    /// Input file id and number of polls of the batches, by id
+    batches: HashMap<String, (String, usize)>,
+}
+
+impl Server {
+    fn handle(&mut self, method: &str, path: &str, body: &str) -> Value {
+        match (method, path.split('/').collect::<Vec<_>>().as_slice()) {
+            ("POST", ["", "v1", "files"]) => {
+                // Keep the JSONL lines of the multipart body
+                let content = body
+                    .lines()
+                    .filter(|line| line.starts_with('{'))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let id = format!("file-{}", self.files.len());
+                self.files.insert(id.clone(), content);
+                json!({"id": id, "object": "file", "purpose": "batch"})
+            }
+            ("POST", ["", "v1", "batches"]) => {
+                let request: Value = serde_json::from_str(body).unwrap();
+                assert_eq!(request["endpoint"], "/v1/embeddings");
+                let id = format!("batch-{}", self.batches.len());
+                let input_file_id = request["input_file_id"].as_str().unwrap().to_string();
+                self.batches.insert(id.clone(), (input_file_id, 0));
+                json!({"id": id, "status": "validating"})
+            }
+            ("GET", ["", "v1", "batches", id]) => {
+                let (_, polls) = self.batches.get_mut(*id).unwrap();
+                *polls += 1;
+                match polls {
+                    1 => json!({"id": id, "status": "in_progress"}),
+                    _ => {
+                        json!({"id": id, "status": "completed", "output_file_id": format!("output-{id}")})
+                    }
+                }
+            }
+            ("GET", ["", "v1", "files", id, "content"]) => {
+                let batch = id.trim_start_matches("output-");
+                let (input_file_id, _) = &self.batches[batch];
+                let output = self.files[input_file_id]
+                    .lines()
+                    .map(|line| {
+                        let request: Value = serde_json::from_str(line).unwrap();
+                        let texts = request["body"]["input"].as_array().unwrap();
+                        // Embeddings are returned out of order, with their index
+                        let data = texts
+                            .iter()
+                            .enumerate()
+                            .rev()
+                            .map(|(index, text)| {
+                                json!({
+                                    "object": "embedding",
+                                    "index": index,
+                                    "embedding": [text.as_str().unwrap().len() as f64, index as f64],
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        json!({
+                            "id": format!("request-{}", request["custom_id"]),
+                            "custom_id": request["custom_id"],
+                            "response": {"status_code": 200, "body": {
+                                "object": "list",
+                                "data": data,
+                                "model": request["body"]["model"],
+                                "usage": {"prompt_tokens": 1, "total_tokens": 1},
+                            }},
+                            "error": null,
+                        })
+                        .to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Value::String(output)
+            }
+            _ => panic!("Unexpected request {method} {path}"),
+        }
+    }
+}
+
+/// Start the stand-in server, returning its base URL
+async fn serve(server: Arc<Mutex<Server>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/v1", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let server = server.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).await.unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    stream.read_line(&mut header).await.unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                stream.read_exact(&mut body).await.unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+                let response =
+                    server
+                        .lock()
+                        .unwrap()
+                        .handle(method, path, &String::from_utf8_lossy(&body));
+                let body = match response {
+                    Value::String(content) => content,
+                    response => response.to_string(),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+
+    url
+}
+
+#[tokio::test]
+async fn test_build_batch() {
+    let server = Arc::new(Mutex::new(Server::default()));
+    let url = serve(server.clone()).await;
+    let model = openai::Client::from_url("key", &url)
+        .embedding_model_with_ndims(openai::TEXT_EMBEDDING_3_SMALL, 2);
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let state_file = temp.path().join("batch.json");
+    let config = BatchConfig::new(&state_file).poll_interval(Duration::from_millis(10));
+
+    let documents = vec![
+        vec!["flurbo".to_string(), "glarb-glarb".to_string()],
+        vec!["linlingdong".to_string()],
+    ];
+
+    // Interrupt the ingestion while it waits for the job to complete
+    let interrupted = tokio::time::timeout(
+        Duration::from_millis(200),
+        EmbeddingsBuilder::new(model.clone())
+            .documents(documents.clone())
+            .unwrap()
+            .build_batch(config.clone().poll_interval(Duration::from_secs(3600))),
+    )
+    .await;
+
+    assert!(interrupted.is_err());
+    assert!(state_file.exists());
+    assert_eq!(server.lock().unwrap().batches.len(), 1);
+
+    // The state file of the interrupted ingestion cannot be resumed for other texts
+    let result = EmbeddingsBuilder::new(model.clone())
+        .documents(vec![vec!["flurbo".to_string()]])
+        .unwrap()
+        .build_batch(config.clone())
+        .await;
+
+    assert!(result.is_err());
+
+    // The job is resumed from the state file instead of being submitted again
+    let embeddings = EmbeddingsBuilder::new(model.clone())
+        .documents(documents.clone())
+        .unwrap()
+        .build_batch(config.clone())
+        .await
+        .unwrap();
+
+    assert_eq!(embeddings.len(), 2);
+    assert_eq!(embeddings[0].0, documents[0]);
+    assert_eq!(embeddings[0].1.len(), 2);
+    assert_eq!(embeddings[0].1.first().document, "flurbo");
+    assert_eq!(embeddings[0].1.first().vec, vec![6.0, 0.0]);
+    assert_eq!(embeddings[0].1.rest()[0].vec, vec![11.0, 1.0]);
+    assert_eq!(embeddings[1].1.first().document, "linlingdong");
+    assert_eq!(embeddings[1].1.first().vec, vec![11.0, 2.0]);
+    assert_eq!(server.lock().unwrap().files.len(), 1);
+    assert_eq!(server.lock().unwrap().batches.len(), 1);
+    assert!(!state_file.exists());
+
+    // A later ingestion of other texts reuses the same state file
+    let embeddings = EmbeddingsBuilder::new(model)
+        .documents(vec![vec!["flurbo".to_string()]])
+        .unwrap()
+        .build_batch(config)
+        .await
+        .unwrap();
+
+    assert_eq!(embeddings[0].1.first().vec, vec![6.0, 0.0]);
+    assert_eq!(server.lock().unwrap().batches.len(), 2);
+    assert!(!state_file.exists());
+}
@@ -1,12 +1,19 @@
-use rig::embeddings::{Embedding, EmbeddingModel};
-use rig::vector_store::{VectorStoreError, VectorStoreIndex};
-use rig::OneOrMany;
+use rig::embeddings::{Embedding, EmbeddingModel, EmbeddingsBuilder};
+use rig::vector_store::{VectorStoreError, VectorStoreIndex, VectorStoreMut};
+use rig::{Embed, OneOrMany};
+use rusqlite::OptionalExtension;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use tokio_rusqlite::Connection;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use zerocopy::IntoBytes;
 
+pub mod migrations;
+pub mod value;
+pub use migrations::Migration;
+pub use value::SqlValue;
+
 #[derive(Debug)]
 pub enum SqliteError {
     DatabaseError(Box<dyn std::error::Error + Send + Sync>),
@@ -14,8 +21,10 @@ pub enum SqliteError {
     InvalidColumnType(String),
 }
 
+/// Value of a column of a [SqliteVectorStoreTable] row (see [value]).
 pub trait ColumnValue: Send + Sync {
-    fn to_sql_string(&self) -> String;
+    /// Get the value to store in the column
+    fn sql_value(&self) -> SqlValue;
     fn column_type(&self) -> &'static str;
 }
 
@@ -82,84 +91,322 @@ pub trait SqliteVectorStoreTable: Send + Sync + Clone {
     fn schema() -> Vec<Column>;
     fn id(&self) -> String;
     fn column_values(&self) -> Vec<(&'static str, Box<dyn ColumnValue>)>;
+
+    /// Migration steps of the table, applied to existing tables when the store is opened (see
+    /// [migrations]). Columns added to the [Self::schema] do not need a migration step.
+    fn migrations() -> Vec<Migration> {
+        vec![]
+    }
+}
+
+/// Element type of the vectors stored in the `{table}_embeddings` table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VectorType {
+    /// 4 bytes per dimension (sqlite-vec `float[N]`)
+    #[default]
+    Float32,
+    /// 1 byte per dimension (sqlite-vec `int8[N]`), see [Embedding::quantize_int8]
+    Int8,
+    /// 1 bit per dimension (sqlite-vec `bit[N]`), see [Embedding::quantize_binary]
+    Binary,
+}
+
+impl VectorType {
+    fn column_type(&self, dims: usize) -> String {
+        match self {
+            VectorType::Float32 => format!("float[{}]", dims),
+            VectorType::Int8 => format!("int8[{}]", dims),
+            VectorType::Binary => format!("bit[{}]", dims),
+        }
+    }
+
+    /// SQL expression of the vector parameter `param` of this type
+    fn param(&self, param: &str) -> String {
+        match self {
+            VectorType::Float32 => param.to_string(),
+            VectorType::Int8 => format!("vec_int8({})", param),
+            VectorType::Binary => format!("vec_bit({})", param),
+        }
+    }
+
+    /// SQL function computing the distance between two vectors of this type
+    fn distance_function(&self) -> &'static str {
+        match self {
+            VectorType::Float32 | VectorType::Int8 => "vec_distance_l2",
+            VectorType::Binary => "vec_distance_hamming",
+        }
+    }
+
+    fn serialize(&self, embedding: &Embedding) -> Vec<u8> {
+        match self {
+            VectorType::Float32 => serialize_embedding(embedding).as_bytes().to_vec(),
+            VectorType::Int8 => embedding.quantize_int8().vec.as_bytes().to_vec(),
+            VectorType::Binary => embedding.quantize_binary().vec,
+        }
+    }
+}
+
+/// How the embeddings are stored in and searched from the `{table}_embeddings` table.
+///
+/// By default, the embeddings are stored with all their dimensions as `float[N]` vectors. To
+/// reduce disk use, embeddings can be truncated to their first dimensions (for models trained
+/// with Matryoshka representation learning, e.g.: OpenAI's `text-embedding-3` models) and/or
+/// quantized. Quantized vectors can be searched in two stages: a coarse search of the quantized
+/// vectors followed by the rescoring of the candidates with more precise vectors.
+///
+/// # Example
+/// ```rust
+/// use rig_sqlite::{EmbeddingStorage, SqliteVectorStore, VectorType};
+///
+/// // 1024 bit vectors for the coarse search, rescored with 1024 int8 vectors:
+/// // 1.1KB per embedding instead of 12KB for 3072 float vectors
+/// let storage = EmbeddingStorage::default()
+///     .truncate(1024)
+///     .quantize(VectorType::Binary)
+///     .rescore(VectorType::Int8, 8);
+///
+/// let vector_store = SqliteVectorStore::with_storage(conn, &model, storage).await?;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmbeddingStorage {
+    ndims: Option<usize>,
+    vector_type: VectorType,
+    rescore: Option<(VectorType, usize)>,
+}
+
+impl EmbeddingStorage {
+    /// Truncate the embeddings to their first `ndims` dimensions (see [Embedding::truncate])
+    pub fn truncate(mut self, ndims: usize) -> Self {
+        self.ndims = Some(ndims);
+        self
+    }
+
+    /// Store the searched vectors as `vector_type`
+    pub fn quantize(mut self, vector_type: VectorType) -> Self {
+        self.vector_type = vector_type;
+        self
+    }
+
+    /// Also store the vectors as `vector_type` to rescore the candidates of the search. The
+    /// search returns the best of `oversampling` times as many candidates as requested results.
+    pub fn rescore(mut self, vector_type: VectorType, oversampling: usize) -> Self {
+        self.rescore = Some((vector_type, oversampling.max(1)));
+        self
+    }
+
+    fn prepare(&self, embedding: &Embedding) -> Embedding {
+        match self.ndims {
+            Some(ndims) => embedding.truncate(ndims),
+            None => embedding.clone(),
+        }
+    }
+
+    /// Vector columns of the `vec0` embeddings table for embeddings of `model_ndims` dimensions
+    fn vector_columns(&self, model_ndims: usize) -> Result<String, VectorStoreError> {
+        let dims = self
+            .ndims
+            .map_or(model_ndims, |ndims| ndims.min(model_ndims));
+        let vector_types = std::iter::once(self.vector_type)
+            .chain(self.rescore.map(|(vector_type, _)| vector_type));
+        if vector_types
+            .clone()
+            .any(|vector_type| vector_type == VectorType::Binary)
+            && !dims.is_multiple_of(8)
+        {
+            return Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Binary vectors need a multiple of 8 dimensions, got {}",
+                    dims
+                )
+                .into(),
+            ));
+        }
+
+        Ok(["embedding", "embedding_rescore"]
+            .iter()
+            .zip(vector_types)
+            .map(|(column, vector_type)| format!("{} {}", column, vector_type.column_type(dims)))
+            .collect::<Vec<_>>()
+            .join(", "))
+    }
 }
 
 #[derive(Clone)]
 pub struct SqliteVectorStore<E: EmbeddingModel + 'static, T: SqliteVectorStoreTable + 'static> {
     conn: Connection,
+    storage: EmbeddingStorage,
     _phantom: PhantomData<(E, T)>,
 }
 
 impl<E: EmbeddingModel + 'static, T: SqliteVectorStoreTable + 'static> SqliteVectorStore<E, T> {
     pub async fn new(conn: Connection, embedding_model: &E) -> Result<Self, VectorStoreError> {
-        let dims = embedding_model.ndims();
-        let table_name = T::name();
-        let schema = T::schema();
-
-        // Build the table schema
-        let mut create_table = format!("CREATE TABLE IF NOT EXISTS {} (", table_name);
+        Self::with_storage(conn, embedding_model, EmbeddingStorage::default()).await
+    }
 
-        // Add columns
-        let mut first = true;
-        for column in &schema {
-            if !first {
-                create_table.push(',');
-            }
-            create_table.push_str(&format!("\n    {} {}", column.name, column.col_type));
-            first = false;
+    /// Create a vector store storing its embeddings as configured by `storage`.
+    ///
+    /// The table is created or migrated (see [migrations]). If its embeddings table was created
+    /// with other dimensions or storage, e.g.: after changing the embedding model, an error is
+    /// returned: use [Self::with_storage_reembedding] to re-embed the stored documents instead.
+    pub async fn with_storage(
+        conn: Connection,
+        embedding_model: &E,
+        storage: EmbeddingStorage,
+    ) -> Result<Self, VectorStoreError> {
+        let vector_columns = storage.vector_columns(embedding_model.ndims())?;
+
+        match Self::migrate(&conn, vector_columns.clone()).await? {
+            None => Ok(Self {
+                conn,
+                storage,
+                _phantom: PhantomData,
+            }),
+            Some(found) => Err(VectorStoreError::DatastoreError(
+                format!(
+                    "Embeddings table {}_embeddings has columns ({}) instead of ({}), the documents must be re-embedded",
+                    T::name(),
+                    found,
+                    vector_columns
+                )
+                .into(),
+            )),
         }
+    }
 
-        create_table.push_str("\n)");
-
-        // Build index creation statements
-        let mut create_indexes = vec![format!(
-            "CREATE INDEX IF NOT EXISTS idx_{}_id ON {}(id)",
-            table_name, table_name
-        )];
+    /// Create a vector store like [Self::with_storage], but re-embed all the stored documents
+    /// with `embedding_model` if the embeddings table was created with other dimensions or
+    /// storage. The documents are deserialized from the columns of their rows.
+    pub async fn with_storage_reembedding(
+        conn: Connection,
+        embedding_model: &E,
+        storage: EmbeddingStorage,
+    ) -> Result<Self, VectorStoreError>
+    where
+        T: Embed + for<'a> Deserialize<'a>,
+    {
+        let vector_columns = storage.vector_columns(embedding_model.ndims())?;
+        let store = Self {
+            conn,
+            storage,
+            _phantom: PhantomData,
+        };
+
+        let Some(found) = Self::migrate(&store.conn, vector_columns.clone()).await? else {
+            return Ok(store);
+        };
+        warn!(
+            "Embeddings table {}_embeddings has columns ({}) instead of ({}), re-embedding its documents",
+            T::name(),
+            found,
+            vector_columns
+        );
 
-        // Add indexes for marked columns
-        for column in schema {
-            if column.indexed {
-                create_indexes.push(format!(
-                    "CREATE INDEX IF NOT EXISTS idx_{}_{} ON {}({})",
-                    table_name, column.name, table_name, column.name
-                ));
-            }
-        }
+        let table_name = T::name();
+        let columns = T::schema();
+        let select_cols = columns
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let rows = store
+            .conn
+            .call(move |conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT rowid, {} FROM {}",
+                    select_cols, table_name
+                ))?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, i64>(0)?, row_to_json(row, &columns, 1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
 
-        conn.call(move |conn| {
-            conn.execute_batch("BEGIN")?;
+        let mut rowids = HashMap::new();
+        let documents = rows
+            .into_iter()
+            .map(|(rowid, value)| {
+                let document = serde_json::from_value::<T>(value)?;
+                rowids.insert(document.id(), rowid);
+                Ok(document)
+            })
+            .collect::<Result<Vec<_>, VectorStoreError>>()?;
+        info!("Re-embedding {} documents", documents.len());
+
+        let embeddings = if documents.is_empty() {
+            vec![]
+        } else {
+            EmbeddingsBuilder::new(embedding_model.clone())
+                .documents(documents)
+                .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?
+                .build()
+                .await?
+        };
+
+        // Replace the embeddings table only once all the documents are embedded
+        let this = store.clone();
+        store
+            .conn
+            .call(move |conn| {
+                let txn = conn.transaction()?;
+                txn.execute_batch(&format!(
+                    "DROP TABLE {table_name}_embeddings;
+                    CREATE VIRTUAL TABLE {table_name}_embeddings USING vec0({vector_columns})"
+                ))?;
+                for (document, embeddings) in &embeddings {
+                    this.insert_embeddings(&txn, rowids[&document.id()], embeddings)?;
+                }
+                txn.commit()?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
 
-            // Create document table
-            conn.execute_batch(&create_table)?;
+        Ok(store)
+    }
 
-            // Create indexes
-            for index_stmt in create_indexes {
-                conn.execute_batch(&index_stmt)?;
-            }
+    /// Create or migrate the table and create its embeddings table if needed. If the embeddings
+    /// table exists with other `vector_columns`, its columns are returned.
+    async fn migrate(
+        conn: &Connection,
+        vector_columns: String,
+    ) -> Result<Option<String>, VectorStoreError> {
+        let table_name = T::name();
 
-            // Create embeddings table
-            conn.execute_batch(&format!(
-                "CREATE VIRTUAL TABLE IF NOT EXISTS {}_embeddings USING vec0(embedding float[{}])",
-                table_name, dims
-            ))?;
+        conn.call(move |conn| {
+            let txn = conn.transaction()?;
+            migrations::migrate::<T>(&txn)?;
+
+            let embeddings_table = format!("{}_embeddings", table_name);
+            let mismatch = match migrations::vector_columns(&txn, &embeddings_table)? {
+                Some(found) if found != migrations::normalize(&vector_columns) => Some(found),
+                Some(_) => None,
+                None => {
+                    txn.execute_batch(&format!(
+                        "CREATE VIRTUAL TABLE {} USING vec0({})",
+                        embeddings_table, vector_columns
+                    ))?;
+                    None
+                }
+            };
 
-            conn.execute_batch("COMMIT")?;
-            Ok(())
+            txn.commit()?;
+            Ok(mismatch)
         })
         .await
-        .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?;
-
-        Ok(Self {
-            conn,
-            _phantom: PhantomData,
-        })
+        .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
     }
 
     pub fn index(self, model: E) -> SqliteVectorIndex<E, T> {
         SqliteVectorIndex::new(model, self)
     }
 
+    /// Insert the documents and their embeddings in the transaction `txn`, replacing the row and
+    /// the embeddings of the documents with the same [SqliteVectorStoreTable::id]. Returns the
+    /// rowid of the last document.
     pub fn add_rows_with_txn(
         &self,
         txn: &rusqlite::Transaction<'_>,
@@ -175,62 +422,392 @@ impl<E: EmbeddingModel + 'static, T: SqliteVectorStoreTable + 'static> SqliteVec
             let values = doc.column_values();
             let columns = values.iter().map(|(col, _)| *col).collect::<Vec<_>>();
 
-            let placeholders = (1..=values.len())
-                .map(|i| format!("?{}", i))
-                .collect::<Vec<_>>();
+            // Keep the rowid of an existing document, so that its embeddings can be replaced
+            last_id = match Self::rowid(txn, &doc.id())? {
+                Some(rowid) => {
+                    Self::update_row(txn, rowid, doc)?;
+                    txn.execute(
+                        &format!("DELETE FROM {}_embeddings WHERE rowid = ?1", table_name),
+                        [rowid],
+                    )?;
+                    rowid
+                }
+                None => {
+                    let placeholders = (1..=values.len())
+                        .map(|i| format!("?{}", i))
+                        .collect::<Vec<_>>();
+
+                    let insert_sql = format!(
+                        "INSERT INTO {} ({}) VALUES ({})",
+                        table_name,
+                        columns.join(", "),
+                        placeholders.join(", ")
+                    );
+
+                    txn.execute(
+                        &insert_sql,
+                        rusqlite::params_from_iter(values.iter().map(|(_, val)| val.sql_value())),
+                    )?;
+                    txn.last_insert_rowid()
+                }
+            };
+            self.insert_embeddings(txn, last_id, embeddings)?;
+        }
 
-            let insert_sql = format!(
-                "INSERT OR REPLACE INTO {} ({}) VALUES ({})",
-                table_name,
-                columns.join(", "),
-                placeholders.join(", ")
-            );
+        Ok(last_id)
+    }
 
-            txn.execute(
-                &insert_sql,
-                rusqlite::params_from_iter(values.iter().map(|(_, val)| val.to_sql_string())),
-            )?;
-            last_id = txn.last_insert_rowid();
+    /// Update the rows and replace the embeddings of the existing documents in the transaction
+    /// `txn`. Documents that are not stored are ignored. Returns the number of updated documents.
+    pub fn update_rows_with_txn(
+        &self,
+        txn: &rusqlite::Transaction<'_>,
+        documents: Vec<(T, OneOrMany<Embedding>)>,
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let existing = documents
+            .into_iter()
+            .map(|(doc, embeddings)| Ok(Self::rowid(txn, &doc.id())?.map(|_| (doc, embeddings))))
+            .collect::<Result<Vec<_>, tokio_rusqlite::Error>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let updated = existing.len();
+        self.add_rows_with_txn(txn, existing)?;
+        Ok(updated)
+    }
 
-            let embeddings_sql = format!(
-                "INSERT INTO {}_embeddings (rowid, embedding) VALUES (?1, ?2)",
-                table_name
-            );
+    /// Delete the documents with the given ids and their embeddings in the transaction `txn`.
+    /// Unknown ids are ignored. Returns the number of deleted documents.
+    pub fn delete_rows_with_txn(
+        &self,
+        txn: &rusqlite::Transaction<'_>,
+        ids: &[String],
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let rowids = ids
+            .iter()
+            .map(|id| Self::rowid(txn, id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::delete_rowids(txn, rowids.into_iter().flatten().collect())
+    }
+
+    /// Delete the documents matching the SQL condition `filter` on the columns of the table,
+    /// with the parameters `params`, and their embeddings in the transaction `txn`. Returns the
+    /// number of deleted documents.
+    ///
+    /// # Example
+    /// ```rust
+    /// vector_store.delete_rows_where("source_id = ?1", vec![SqlValue::Text(source_id)]).await?;
+    /// ```
+    pub fn delete_rows_where_with_txn(
+        &self,
+        txn: &rusqlite::Transaction<'_>,
+        filter: &str,
+        params: Vec<SqlValue>,
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let mut stmt = txn.prepare(&format!("SELECT rowid FROM {} WHERE {}", T::name(), filter))?;
+        let rowids = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        Self::delete_rowids(txn, rowids)
+    }
+
+    /// Get the rowid of the document `id`, if stored.
+    fn rowid(
+        txn: &rusqlite::Transaction<'_>,
+        id: &str,
+    ) -> Result<Option<i64>, tokio_rusqlite::Error> {
+        Ok(txn
+            .query_row(
+                &format!("SELECT rowid FROM {} WHERE id = ?1", T::name()),
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Update the columns of the row `rowid` with the values of `doc`.
+    fn update_row(
+        txn: &rusqlite::Transaction<'_>,
+        rowid: i64,
+        doc: &T,
+    ) -> Result<(), tokio_rusqlite::Error> {
+        let values = doc.column_values();
+        let assignments = values
+            .iter()
+            .enumerate()
+            .map(|(i, (col, _))| format!("{} = ?{}", col, i + 1))
+            .collect::<Vec<_>>();
+
+        txn.execute(
+            &format!(
+                "UPDATE {} SET {} WHERE rowid = ?{}",
+                T::name(),
+                assignments.join(", "),
+                values.len() + 1
+            ),
+            rusqlite::params_from_iter(
+                values
+                    .iter()
+                    .map(|(_, val)| val.sql_value())
+                    .chain(std::iter::once(SqlValue::Integer(rowid))),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Delete the rows `rowids` of the table and of the embeddings table.
+    fn delete_rowids(
+        txn: &rusqlite::Transaction<'_>,
+        rowids: Vec<i64>,
+    ) -> Result<usize, tokio_rusqlite::Error> {
+        let table_name = T::name();
+        let mut delete_embeddings = txn.prepare(&format!(
+            "DELETE FROM {}_embeddings WHERE rowid = ?1",
+            table_name
+        ))?;
+        let mut delete_row =
+            txn.prepare(&format!("DELETE FROM {} WHERE rowid = ?1", table_name))?;
+
+        for rowid in &rowids {
+            delete_embeddings.execute([rowid])?;
+            delete_row.execute([rowid])?;
+        }
 
-            let mut stmt = txn.prepare(&embeddings_sql)?;
-            for (i, embedding) in embeddings.iter().enumerate() {
-                let vec = serialize_embedding(embedding);
-                debug!(
-                    "Storing embedding {} of {} (size: {} bytes)",
-                    i + 1,
-                    embeddings.len(),
-                    vec.len() * 4
-                );
-                let blob = rusqlite::types::Value::Blob(vec.as_bytes().to_vec());
-                stmt.execute(rusqlite::params![last_id, blob])?;
+        debug!("Deleted {} documents from store", rowids.len());
+        Ok(rowids.len())
+    }
+
+    /// Insert the embeddings of the document of row `rowid` in the embeddings table.
+    fn insert_embeddings(
+        &self,
+        txn: &rusqlite::Transaction<'_>,
+        rowid: i64,
+        embeddings: &OneOrMany<Embedding>,
+    ) -> Result<(), tokio_rusqlite::Error> {
+        let table_name = T::name();
+
+        let embeddings_sql = match self.storage.rescore {
+            None => format!(
+                "INSERT INTO {}_embeddings (rowid, embedding) VALUES (?1, {})",
+                table_name,
+                self.storage.vector_type.param("?2")
+            ),
+            Some((rescore_type, _)) => format!(
+                "INSERT INTO {}_embeddings (rowid, embedding, embedding_rescore) VALUES (?1, {}, {})",
+                table_name,
+                self.storage.vector_type.param("?2"),
+                rescore_type.param("?3")
+            ),
+        };
+
+        let mut stmt = txn.prepare(&embeddings_sql)?;
+        for (i, embedding) in embeddings.iter().enumerate() {
+            let embedding = self.storage.prepare(embedding);
+            let mut values = vec![
+                rusqlite::types::Value::Integer(rowid),
+                rusqlite::types::Value::Blob(self.storage.vector_type.serialize(&embedding)),
+            ];
+            if let Some((rescore_type, _)) = self.storage.rescore {
+                values.push(rusqlite::types::Value::Blob(
+                    rescore_type.serialize(&embedding),
+                ));
             }
+            debug!(
+                "Storing embedding {} of {} (size: {} bytes)",
+                i + 1,
+                embeddings.len(),
+                values
+                    .iter()
+                    .map(|value| match value {
+                        rusqlite::types::Value::Blob(blob) => blob.len(),
+                        _ => 0,
+                    })
+                    .sum::<usize>()
+            );
+            stmt.execute(rusqlite::params_from_iter(values))?;
         }
 
-        Ok(last_id)
+        Ok(())
     }
 
+    /// Insert the documents and their embeddings, replacing the row and the embeddings of the
+    /// documents with the same [SqliteVectorStoreTable::id]. Returns the rowid of the last
+    /// document.
     pub async fn add_rows(
         &self,
         documents: Vec<(T, OneOrMany<Embedding>)>,
     ) -> Result<i64, VectorStoreError> {
-        let documents = documents.clone();
         let this = self.clone();
+        self.with_txn(move |txn| this.add_rows_with_txn(txn, documents))
+            .await
+    }
 
+    /// Update the rows of the existing documents and re-embed them with `embedding_model`.
+    /// Documents that are not stored are ignored. Returns the number of updated documents.
+    pub async fn update_rows(
+        &self,
+        embedding_model: &E,
+        documents: Vec<T>,
+    ) -> Result<usize, VectorStoreError>
+    where
+        T: Embed,
+    {
+        // Only embed the documents that are stored
+        let ids = documents.iter().map(T::id).collect::<Vec<_>>();
+        let stored = self
+            .with_txn(move |txn| {
+                ids.iter()
+                    .map(|id| Ok(Self::rowid(txn, id)?.is_some()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .await?;
+        let documents = documents
+            .into_iter()
+            .zip(stored)
+            .filter_map(|(doc, stored)| stored.then_some(doc))
+            .collect::<Vec<_>>();
+        if documents.is_empty() {
+            return Ok(0);
+        }
+
+        let embeddings = EmbeddingsBuilder::new(embedding_model.clone())
+            .documents(documents)
+            .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))?
+            .build()
+            .await?;
+
+        let this = self.clone();
+        self.with_txn(move |txn| this.update_rows_with_txn(txn, embeddings))
+            .await
+    }
+
+    /// Delete the documents with the given ids and their embeddings. Unknown ids are ignored.
+    /// Returns the number of deleted documents.
+    pub async fn delete_rows(&self, ids: Vec<String>) -> Result<usize, VectorStoreError> {
+        let this = self.clone();
+        self.with_txn(move |txn| this.delete_rows_with_txn(txn, &ids))
+            .await
+    }
+
+    /// Delete the documents matching the SQL condition `filter` with the parameters `params`,
+    /// and their embeddings (see [Self::delete_rows_where_with_txn]). Returns the number of
+    /// deleted documents.
+    pub async fn delete_rows_where(
+        &self,
+        filter: &str,
+        params: Vec<SqlValue>,
+    ) -> Result<usize, VectorStoreError> {
+        let this = self.clone();
+        let filter = filter.to_string();
+        self.with_txn(move |txn| this.delete_rows_where_with_txn(txn, &filter, params))
+            .await
+    }
+
+    /// Run `f` in a transaction, committed if `f` succeeds.
+    async fn with_txn<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&rusqlite::Transaction<'_>) -> Result<R, tokio_rusqlite::Error> + Send + 'static,
+    ) -> Result<R, VectorStoreError> {
         self.conn
             .call(move |conn| {
                 let tx = conn.transaction().map_err(tokio_rusqlite::Error::from)?;
-                let result = this.add_rows_with_txn(&tx, documents)?;
+                let result = f(&tx)?;
                 tx.commit().map_err(tokio_rusqlite::Error::from)?;
                 Ok(result)
             })
             .await
             .map_err(|e| VectorStoreError::DatastoreError(Box::new(e)))
     }
+
+    /// KNN query selecting the `columns` of the document table `d` and the `distance` of the
+    /// rows nearest to the query embedding, with the parameters returned by [Self::knn_params].
+    fn knn_query(&self, columns: &str) -> String {
+        let table_name = T::name();
+        let vector_type = self.storage.vector_type;
+
+        match self.storage.rescore {
+            None => format!(
+                "SELECT {columns}, e.distance
+                FROM {table_name}_embeddings e
+                JOIN {table_name} d ON e.rowid = d.rowid
+                WHERE e.embedding MATCH {} AND k = ?2
+                ORDER BY e.distance",
+                vector_type.param("?1")
+            ),
+            // Coarse search of the quantized vectors, then rescoring of the candidates
+            Some((rescore_type, _)) => format!(
+                "WITH candidates AS (
+                    SELECT rowid, embedding_rescore
+                    FROM {table_name}_embeddings
+                    WHERE embedding MATCH {} AND k = ?3
+                )
+                SELECT {columns}, {}({}, {}) AS distance
+                FROM candidates c
+                JOIN {table_name} d ON c.rowid = d.rowid
+                ORDER BY distance
+                LIMIT ?2",
+                vector_type.param("?1"),
+                rescore_type.distance_function(),
+                // The vector type is lost when the candidates are materialized
+                rescore_type.param("c.embedding_rescore"),
+                rescore_type.param("?4")
+            ),
+        }
+    }
+
+    /// Parameters of [Self::knn_query] for the `n` rows nearest to `embedding`
+    fn knn_params(&self, embedding: &Embedding, n: usize) -> Vec<rusqlite::types::Value> {
+        let embedding = self.storage.prepare(embedding);
+        let mut params = vec![
+            rusqlite::types::Value::Blob(self.storage.vector_type.serialize(&embedding)),
+            rusqlite::types::Value::Integer(n as i64),
+        ];
+        if let Some((rescore_type, oversampling)) = self.storage.rescore {
+            params.push(rusqlite::types::Value::Integer((n * oversampling) as i64));
+            params.push(rusqlite::types::Value::Blob(
+                rescore_type.serialize(&embedding),
+            ));
+        }
+        params
+    }
+}
+
+impl<E: EmbeddingModel + 'static, T: SqliteVectorStoreTable + 'static> VectorStoreMut<T>
+    for SqliteVectorStore<E, T>
+{
+    async fn upsert_documents(
+        &mut self,
+        documents: Vec<(String, T, OneOrMany<Embedding>)>,
+    ) -> Result<(), VectorStoreError> {
+        let documents = documents
+            .into_iter()
+            .map(|(id, doc, embeddings)| {
+                if id == doc.id() {
+                    Ok((doc, embeddings))
+                } else {
+                    Err(VectorStoreError::DatastoreError(
+                        format!(
+                            "Id {} does not match the id {} of the document",
+                            id,
+                            doc.id()
+                        )
+                        .into(),
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.add_rows(documents).await?;
+        Ok(())
+    }
+
+    async fn delete_documents(&mut self, ids: Vec<String>) -> Result<(), VectorStoreError> {
+        self.delete_rows(ids).await?;
+        Ok(())
+    }
 }
 
 /// SQLite vector store implementation for Rig.
@@ -339,40 +916,32 @@ impl<E: EmbeddingModel + std::marker::Sync, T: SqliteVectorStoreTable> VectorSto
     ) -> Result<Vec<(f64, String, D)>, VectorStoreError> {
         debug!("Finding top {} matches for query", n);
         let embedding = self.embedding_model.embed_text(query).await?;
-        let query_vec: Vec<f32> = serialize_embedding(&embedding);
-        let table_name = T::name();
+        let params = self.store.knn_params(&embedding, n);
 
-        // Get all column names from SqliteVectorStoreTable
+        // Get all columns from SqliteVectorStoreTable
         let columns = T::schema();
-        let column_names: Vec<&str> = columns.iter().map(|column| column.name).collect();
+
+        // Build SELECT statement with all columns
+        let select_cols = columns
+            .iter()
+            .map(|column| format!("d.{}", column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = self.store.knn_query(&select_cols);
 
         let rows = self
             .store
             .conn
             .call(move |conn| {
-                // Build SELECT statement with all columns
-                let select_cols = column_names.join(", ");
-                let mut stmt = conn.prepare(&format!(
-                    "SELECT d.{}, e.distance 
-                    FROM {}_embeddings e
-                    JOIN {} d ON e.rowid = d.rowid
-                    WHERE e.embedding MATCH ?1 AND k = ?2
-                    ORDER BY e.distance",
-                    select_cols, table_name, table_name
-                ))?;
+                let mut stmt = conn.prepare(&query)?;
 
                 let rows = stmt
-                    .query_map(rusqlite::params![query_vec.as_bytes().to_vec(), n], |row| {
-                        // Create a map of column names to values
-                        let mut map = serde_json::Map::new();
-                        for (i, col_name) in column_names.iter().enumerate() {
-                            let value: String = row.get(i)?;
-                            map.insert(col_name.to_string(), serde_json::Value::String(value));
-                        }
-                        let distance: f64 = row.get(column_names.len())?;
+                    .query_map(rusqlite::params_from_iter(params), |row| {
+                        let doc = row_to_json(row, &columns, 0)?;
+                        let distance: f64 = row.get(columns.len())?;
                         let id: String = row.get(0)?; // Assuming id is always first column
 
-                        Ok((id, serde_json::Value::Object(map), distance))
+                        Ok((id, doc, distance))
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(rows)
@@ -388,7 +957,7 @@ impl<E: EmbeddingModel + std::marker::Sync, T: SqliteVectorStoreTable> VectorSto
                     top_n.push((distance, id, doc));
                 }
                 Err(e) => {
-                    debug!("Failed to deserialize document {}: {}", id, e);
+                    warn!("Failed to deserialize document {}: {}", id, e);
                     continue;
                 }
             }
@@ -405,33 +974,19 @@ impl<E: EmbeddingModel + std::marker::Sync, T: SqliteVectorStoreTable> VectorSto
     ) -> Result<Vec<(f64, String)>, VectorStoreError> {
         debug!("Finding top {} document IDs for query", n);
         let embedding = self.embedding_model.embed_text(query).await?;
-        let query_vec = serialize_embedding(&embedding);
-        let table_name = T::name();
+        let params = self.store.knn_params(&embedding, n);
+        let query = self.store.knn_query("d.id");
 
         let results = self
             .store
             .conn
             .call(move |conn| {
-                let mut stmt = conn.prepare(&format!(
-                    "SELECT d.id, e.distance 
-                     FROM {0}_embeddings e
-                     JOIN {0} d ON e.rowid = d.rowid
-                     WHERE e.embedding MATCH ?1 AND k = ?2
-                     ORDER BY e.distance",
-                    table_name
-                ))?;
+                let mut stmt = conn.prepare(&query)?;
 
                 let results = stmt
-                    .query_map(
-                        rusqlite::params![
-                            query_vec
-                                .iter()
-                                .flat_map(|x| x.to_le_bytes())
-                                .collect::<Vec<u8>>(),
-                            n
-                        ],
-                        |row| Ok((row.get::<_, f64>(1)?, row.get::<_, String>(0)?)),
-                    )?
+                    .query_map(rusqlite::params_from_iter(params), |row| {
+                        Ok((row.get::<_, f64>(1)?, row.get::<_, String>(0)?))
+                    })?
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(results)
             })
@@ -443,33 +998,90 @@ impl<E: EmbeddingModel + std::marker::Sync, T: SqliteVectorStoreTable> VectorSto
     }
 }
 
-fn serialize_embedding(embedding: &Embedding) -> Vec<f32> {
-    embedding.vec.iter().map(|x| *x as f32).collect()
-}
-
-impl ColumnValue for String {
-    fn to_sql_string(&self) -> String {
-        self.clone()
+/// Create a map of the names of the `columns` of `row`, starting at index `offset`, to their
+/// values (see [value]). NULL values (e.g.: of columns added by a migration) are mapped to `null`.
+fn row_to_json(
+    row: &rusqlite::Row<'_>,
+    columns: &[Column],
+    offset: usize,
+) -> rusqlite::Result<serde_json::Value> {
+    let mut map = serde_json::Map::new();
+    for (i, column) in columns.iter().enumerate() {
+        let value = row
+            .get::<_, SqlValue>(offset + i)?
+            .into_json(column)
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    offset + i,
+                    row.get_ref_unwrap(offset + i).data_type(),
+                    Box::new(e),
+                )
+            })?;
+        map.insert(column.name.to_string(), value);
     }
+    Ok(serde_json::Value::Object(map))
+}
 
-    fn column_type(&self) -> &'static str {
-        "TEXT"
-    }
+fn serialize_embedding(embedding: &Embedding) -> Vec<f32> {
+    embedding.vec.iter().map(|x| *x as f32).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{Column, ColumnValue, SqliteVectorStore, SqliteVectorStoreTable};
+    use rig::embeddings::EmbeddingError;
     use rig::{
         embeddings::EmbeddingsBuilder,
         providers::openai::{Client, TEXT_EMBEDDING_ADA_002},
         Embed,
     };
-    use rusqlite::ffi::sqlite3_auto_extension;
+    use rusqlite::ffi::{sqlite3, sqlite3_api_routines, sqlite3_auto_extension};
     use sqlite_vec::sqlite3_vec_init;
+    use std::os::raw::{c_char, c_int};
     use tokio_rusqlite::Connection;
 
+    type ExtensionInit =
+        unsafe extern "C" fn(*mut sqlite3, *mut *mut c_char, *const sqlite3_api_routines) -> c_int;
+
+    /// Register the sqlite-vec extension for the connections opened afterwards
+    pub(crate) fn init_sqlite_vec() {
+        unsafe {
+            sqlite3_auto_extension(Some(std::mem::transmute::<*const (), ExtensionInit>(
+                sqlite3_vec_init as *const (),
+            )));
+        }
+    }
+
+    /// Embedding model counting the bytes of the texts
+    #[derive(Clone)]
+    pub(crate) struct MockModel(pub(crate) usize);
+
+    impl EmbeddingModel for MockModel {
+        const MAX_DOCUMENTS: usize = 16;
+
+        fn ndims(&self) -> usize {
+            self.0
+        }
+
+        async fn embed_texts(
+            &self,
+            texts: impl IntoIterator<Item = String> + Send,
+        ) -> Result<Vec<Embedding>, EmbeddingError> {
+            Ok(texts
+                .into_iter()
+                .map(|text| {
+                    let mut vec = vec![0.0; self.0];
+                    text.bytes().for_each(|b| vec[b as usize % self.0] += 1.0);
+                    Embedding {
+                        document: text,
+                        vec,
+                    }
+                })
+                .collect())
+        }
+    }
+
     #[derive(Embed, Clone, Debug, Deserialize)]
     struct TestDocument {
         id: String,
@@ -504,9 +1116,7 @@ mod tests {
     #[tokio::test]
     async fn test_vector_search() -> Result<(), anyhow::Error> {
         // Initialize the sqlite-vec extension
-        unsafe {
-            sqlite3_auto_extension(Some(std::mem::transmute(sqlite3_vec_init as *const ())));
-        }
+        init_sqlite_vec();
 
         // Initialize in-memory SQLite connection
         let conn = Connection::open(":memory:").await?;
@@ -554,4 +1164,244 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Embed, Clone, Debug, PartialEq, Deserialize)]
+    struct TypedDocument {
+        id: String,
+        #[embed]
+        content: String,
+        rating: f64,
+        views: i64,
+        published: bool,
+        tags: Vec<String>,
+        note: Option<String>,
+    }
+
+    impl SqliteVectorStoreTable for TypedDocument {
+        fn name() -> &'static str {
+            "typed_documents"
+        }
+
+        fn schema() -> Vec<Column> {
+            vec![
+                Column::new("id", "TEXT PRIMARY KEY"),
+                Column::new("content", "TEXT"),
+                Column::new("rating", "REAL"),
+                Column::new("views", "INTEGER"),
+                Column::new("published", "BOOLEAN"),
+                Column::new("tags", "JSON"),
+                Column::new("note", "TEXT"),
+            ]
+        }
+
+        fn id(&self) -> String {
+            self.id.clone()
+        }
+
+        fn column_values(&self) -> Vec<(&'static str, Box<dyn ColumnValue>)> {
+            vec![
+                ("id", Box::new(self.id.clone())),
+                ("content", Box::new(self.content.clone())),
+                ("rating", Box::new(self.rating)),
+                ("views", Box::new(self.views)),
+                ("published", Box::new(self.published)),
+                ("tags", Box::new(serde_json::json!(self.tags))),
+                ("note", Box::new(self.note.clone())),
+            ]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_columns() -> Result<(), anyhow::Error> {
+        init_sqlite_vec();
+        let conn = Connection::open_in_memory().await?;
+        let model = MockModel(8);
+
+        let documents = vec![
+            TypedDocument {
+                id: "doc0".to_string(),
+                content: "flurbo".to_string(),
+                rating: 4.5,
+                views: 42,
+                published: true,
+                tags: vec!["alien".to_string(), "green".to_string()],
+                note: None,
+            },
+            TypedDocument {
+                id: "doc1".to_string(),
+                content: "glarb-glarb".to_string(),
+                rating: 2.0,
+                views: 0,
+                published: false,
+                tags: vec![],
+                note: Some("ancient tool".to_string()),
+            },
+        ];
+        let embeddings = EmbeddingsBuilder::new(model.clone())
+            .documents(documents.clone())?
+            .build()
+            .await?;
+
+        let vector_store = SqliteVectorStore::new(conn, &model).await?;
+        vector_store.add_rows(embeddings).await?;
+        let index = vector_store.index(model);
+
+        for document in documents {
+            let results = index.top_n::<TypedDocument>(&document.content, 1).await?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].2, document);
+        }
+
+        Ok(())
+    }
+
+    /// Number of rows of the table and of the embeddings table
+    async fn counts(conn: &Connection) -> (i64, i64) {
+        conn.call(|conn| {
+            Ok(conn.query_row(
+                "SELECT (SELECT COUNT(*) FROM test_documents), (SELECT COUNT(*) FROM test_documents_embeddings)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_write_operations() -> Result<(), anyhow::Error> {
+        init_sqlite_vec();
+        let conn = Connection::open_in_memory().await?;
+        let model = MockModel(8);
+        let document = |id: &str, content: &str| TestDocument {
+            id: id.to_string(),
+            content: content.to_string(),
+        };
+
+        let embeddings = EmbeddingsBuilder::new(model.clone())
+            .documents(vec![
+                document("doc0", "flurbo"),
+                document("doc1", "glarb-glarb"),
+                document("doc2", "linlingdong"),
+            ])?
+            .build()
+            .await?;
+        let mut vector_store = SqliteVectorStore::new(conn.clone(), &model).await?;
+        vector_store.add_rows(embeddings.clone()).await?;
+
+        // Adding the documents again replaces them and their embeddings
+        vector_store.add_rows(embeddings.clone()).await?;
+        assert_eq!(counts(&conn).await, (3, 3));
+
+        // Updated documents are re-embedded, unknown documents are ignored
+        let updated = vector_store
+            .update_rows(
+                &model,
+                vec![document("doc0", "zorbo"), document("doc9", "unknown")],
+            )
+            .await?;
+        assert_eq!(updated, 1);
+        assert_eq!(counts(&conn).await, (3, 3));
+        let index = vector_store.clone().index(model.clone());
+        let results = index.top_n::<TestDocument>("zorbo", 1).await?;
+        assert_eq!(results[0].0, 0.0);
+        assert_eq!(results[0].2.content, "zorbo");
+
+        // Deleted documents are removed with their embeddings
+        let deleted = vector_store
+            .delete_rows(vec!["doc1".to_string(), "doc9".to_string()])
+            .await?;
+        assert_eq!(deleted, 1);
+        assert_eq!(counts(&conn).await, (2, 2));
+
+        let deleted = vector_store
+            .delete_rows_where(
+                "content = ?1",
+                vec![SqlValue::Text("linlingdong".to_string())],
+            )
+            .await?;
+        assert_eq!(deleted, 1);
+        assert_eq!(counts(&conn).await, (1, 1));
+
+        // VectorStoreMut
+        let (doc1, embedding1) = embeddings
+            .iter()
+            .find(|(document, _)| document.id == "doc1")
+            .cloned()
+            .unwrap();
+        vector_store
+            .upsert_documents(vec![("doc1".to_string(), doc1, embedding1)])
+            .await?;
+        vector_store
+            .delete_documents(vec!["doc0".to_string()])
+            .await?;
+        assert_eq!(counts(&conn).await, (1, 1));
+        assert_eq!(
+            index.top_n_ids("glarb-glarb", 2).await?,
+            vec![(0.0, "doc1".to_string())]
+        );
+
+        Ok(())
+    }
+
+    /// Check that the nearest document to each query is returned first with `storage`
+    async fn assert_nearest_first(storage: EmbeddingStorage) -> Result<(), anyhow::Error> {
+        init_sqlite_vec();
+        let conn = Connection::open_in_memory().await?;
+        let model = MockModel(8);
+        let documents = ["abc", "efg", "ah"]
+            .iter()
+            .enumerate()
+            .map(|(i, content)| TestDocument {
+                id: format!("doc{}", i),
+                content: content.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        let embeddings = EmbeddingsBuilder::new(model.clone())
+            .documents(documents)?
+            .build()
+            .await?;
+        let vector_store = SqliteVectorStore::with_storage(conn, &model, storage).await?;
+        vector_store.add_rows(embeddings).await?;
+        let index = vector_store.index(model);
+
+        for (query, id) in [("abcc", "doc0"), ("eefg", "doc1"), ("aah", "doc2")] {
+            let results = index.top_n_ids(query, 3).await?;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].1, id, "{:?}: {}", storage, query);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_int8_storage() -> Result<(), anyhow::Error> {
+        assert_nearest_first(EmbeddingStorage::default().quantize(VectorType::Int8)).await
+    }
+
+    #[tokio::test]
+    async fn test_binary_storage() -> Result<(), anyhow::Error> {
+        assert_nearest_first(EmbeddingStorage::default().quantize(VectorType::Binary)).await
+    }
+
+    #[tokio::test]
+    async fn test_binary_storage_int8_rescore() -> Result<(), anyhow::Error> {
+        assert_nearest_first(
+            EmbeddingStorage::default()
+                .quantize(VectorType::Binary)
+                .rescore(VectorType::Int8, 2),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_binary_storage_f32_rescore() -> Result<(), anyhow::Error> {
+        assert_nearest_first(
+            EmbeddingStorage::default()
+                .quantize(VectorType::Binary)
+                .rescore(VectorType::Float32, 2),
+        )
+        .await
+    }
 }
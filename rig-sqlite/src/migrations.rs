@@ -0,0 +1,415 @@
+//! Versioned schema migrations of the tables of [SqliteVectorStore](crate::SqliteVectorStore).
+//!
+//! When a store is opened, its table is migrated in a single transaction:
+//! 1. The columns of [SqliteVectorStoreTable::schema] missing from the table are added, so that
+//!    adding a column to the schema is enough to migrate existing databases. Columns are never
+//!    dropped or altered: use a migration step to do so.
+//! 2. The [Migration] steps of [SqliteVectorStoreTable::migrations] with a version greater than
+//!    the version of the table recorded in the `rig_sqlite_migrations` table are applied in order.
+//!    The steps can therefore fill the columns added to the schema.
+//! 3. The version of the table is set to the greatest version of its migration steps.
+//!
+//! A table created from scratch already has the latest schema, so its migration steps are
+//! skipped. A table created before the migrations were introduced has version 0.
+
+use std::collections::HashSet;
+
+use rusqlite::{OptionalExtension, Transaction};
+use tracing::{info, warn};
+
+use crate::SqliteVectorStoreTable;
+
+/// Name of the table recording the schema version of each table.
+const MIGRATIONS_TABLE: &str = "rig_sqlite_migrations";
+
+/// A step migrating a table to the schema `version`.
+///
+/// # Example
+/// ```rust
+/// impl SqliteVectorStoreTable for Message {
+///     // ...
+///
+///     fn migrations() -> Vec<Migration> {
+///         vec![
+///             Migration::sql(1, "UPDATE messages SET role = lower(role)"),
+///             // `content` replaces the `text` column, and was added when the table was opened
+///             Migration::function(2, |txn| {
+///                 txn.execute("UPDATE messages SET content = text", [])?;
+///                 txn.execute("ALTER TABLE messages DROP COLUMN text", [])?;
+///                 Ok(())
+///             }),
+///         ]
+///     }
+/// }
+/// ```
+pub struct Migration {
+    version: u32,
+    step: MigrationStep,
+}
+
+enum MigrationStep {
+    Sql(String),
+    Function(fn(&Transaction<'_>) -> rusqlite::Result<()>),
+}
+
+impl Migration {
+    /// Create a migration step executing the `sql` statements.
+    pub fn sql(version: u32, sql: impl Into<String>) -> Self {
+        Self {
+            version,
+            step: MigrationStep::Sql(sql.into()),
+        }
+    }
+
+    /// Create a migration step running `f` in the migration transaction.
+    pub fn function(version: u32, f: fn(&Transaction<'_>) -> rusqlite::Result<()>) -> Self {
+        Self {
+            version,
+            step: MigrationStep::Function(f),
+        }
+    }
+
+    /// Version of the schema after this step
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn apply(&self, txn: &Transaction<'_>) -> rusqlite::Result<()> {
+        match &self.step {
+            MigrationStep::Sql(sql) => txn.execute_batch(sql),
+            MigrationStep::Function(f) => f(txn),
+        }
+    }
+}
+
+/// Create or migrate the table of `T` and its indexes, and return its schema version.
+pub(crate) fn migrate<T: SqliteVectorStoreTable>(
+    txn: &Transaction<'_>,
+) -> Result<u32, tokio_rusqlite::Error> {
+    let table_name = T::name();
+    let schema = T::schema();
+
+    let mut migrations = T::migrations();
+    migrations.sort_by_key(Migration::version);
+    if let Some(duplicate) = migrations
+        .windows(2)
+        .find(|pair| pair[0].version == pair[1].version)
+    {
+        return Err(tokio_rusqlite::Error::Other(
+            format!(
+                "Duplicate migrations to version {} of table {}",
+                duplicate[0].version, table_name
+            )
+            .into(),
+        ));
+    }
+    let latest = migrations.last().map_or(0, Migration::version);
+
+    txn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            table_name TEXT PRIMARY KEY,
+            version INTEGER NOT NULL
+        )",
+        MIGRATIONS_TABLE
+    ))?;
+    let version = txn
+        .query_row(
+            &format!(
+                "SELECT version FROM {} WHERE table_name = ?1",
+                MIGRATIONS_TABLE
+            ),
+            [table_name],
+            |row| row.get::<_, u32>(0),
+        )
+        .optional()?;
+
+    if table_exists(txn, table_name)? {
+        let version = version.unwrap_or(0);
+        if version > latest {
+            warn!(
+                "Table {} has schema version {}, newer than the latest migration {}",
+                table_name, version, latest
+            );
+        }
+
+        // Add the columns missing from the table, then apply the migration steps
+        let columns = table_columns(txn, table_name)?;
+        for column in schema.iter().filter(|c| !columns.contains(c.name)) {
+            let col_type = column.col_type.to_uppercase();
+            if col_type.contains("PRIMARY KEY") || col_type.contains("UNIQUE") {
+                return Err(tokio_rusqlite::Error::Other(
+                    format!(
+                        "Column {}.{} ({}) cannot be added to an existing table, add a migration step instead",
+                        table_name, column.name, column.col_type
+                    )
+                    .into(),
+                ));
+            }
+            info!("Adding column {} to table {}", column.name, table_name);
+            txn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table_name, column.name, column.col_type
+            ))?;
+        }
+
+        for migration in migrations.iter().filter(|m| m.version > version) {
+            info!(
+                "Migrating table {} to version {}",
+                table_name, migration.version
+            );
+            migration.apply(txn)?;
+        }
+
+        let columns = table_columns(txn, table_name)?;
+        for column in columns
+            .iter()
+            .filter(|c| !schema.iter().any(|column| column.name == c.as_str()))
+        {
+            warn!(
+                "Column {} of table {} is not in its schema and is left unchanged",
+                column, table_name
+            );
+        }
+    } else {
+        // Build the table schema
+        let columns = schema
+            .iter()
+            .map(|column| format!("\n    {} {}", column.name, column.col_type))
+            .collect::<Vec<_>>()
+            .join(",");
+        txn.execute_batch(&format!("CREATE TABLE {} ({}\n)", table_name, columns))?;
+    }
+
+    // Create the indexes, including those of added columns
+    txn.execute_batch(&format!(
+        "CREATE INDEX IF NOT EXISTS idx_{}_id ON {}(id)",
+        table_name, table_name
+    ))?;
+    for column in schema.iter().filter(|column| column.indexed) {
+        txn.execute_batch(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_{}_{} ON {}({})",
+            table_name, column.name, table_name, column.name
+        ))?;
+    }
+
+    let version = version.unwrap_or(0).max(latest);
+    txn.execute(
+        &format!(
+            "INSERT INTO {} (table_name, version) VALUES (?1, ?2)
+            ON CONFLICT(table_name) DO UPDATE SET version = excluded.version",
+            MIGRATIONS_TABLE
+        ),
+        rusqlite::params![table_name, version],
+    )?;
+
+    Ok(version)
+}
+
+/// Get the vector columns of the embeddings table `name`, as in its `vec0(...)` definition,
+/// or `None` if the table does not exist.
+pub(crate) fn vector_columns(
+    txn: &Transaction<'_>,
+    name: &str,
+) -> rusqlite::Result<Option<String>> {
+    let sql = txn
+        .query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [name],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?;
+
+    Ok(sql.map(|sql| {
+        let start = sql.find('(').map_or(0, |i| i + 1);
+        let end = sql.rfind(')').unwrap_or(sql.len()).max(start);
+        normalize(&sql[start..end])
+    }))
+}
+
+/// Normalize a `vec0(...)` definition for comparison.
+pub(crate) fn normalize(definition: &str) -> String {
+    definition
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn table_exists(txn: &Transaction<'_>, name: &str) -> rusqlite::Result<bool> {
+    txn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        [name],
+        |row| row.get(0),
+    )
+}
+
+fn table_columns(txn: &Transaction<'_>, name: &str) -> rusqlite::Result<HashSet<String>> {
+    let mut stmt = txn.prepare(&format!("PRAGMA table_info({})", name))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>("name"))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use rig::{embeddings::EmbeddingsBuilder, vector_store::VectorStoreIndex, Embed};
+    use serde::Deserialize;
+    use tokio_rusqlite::Connection;
+
+    use super::Migration;
+    use crate::{
+        tests::{init_sqlite_vec, MockModel},
+        Column, ColumnValue, EmbeddingStorage, SqliteVectorStore, SqliteVectorStoreTable,
+    };
+
+    #[derive(Embed, Clone, Debug, Deserialize)]
+    struct DocumentV1 {
+        id: String,
+        #[embed]
+        content: String,
+    }
+
+    impl SqliteVectorStoreTable for DocumentV1 {
+        fn name() -> &'static str {
+            "documents"
+        }
+
+        fn schema() -> Vec<Column> {
+            vec![
+                Column::new("id", "TEXT PRIMARY KEY"),
+                Column::new("content", "TEXT"),
+            ]
+        }
+
+        fn id(&self) -> String {
+            self.id.clone()
+        }
+
+        fn column_values(&self) -> Vec<(&'static str, Box<dyn ColumnValue>)> {
+            vec![
+                ("id", Box::new(self.id.clone())),
+                ("content", Box::new(self.content.clone())),
+            ]
+        }
+    }
+
+    #[derive(Embed, Clone, Debug, Deserialize)]
+    struct DocumentV2 {
+        id: String,
+        #[embed]
+        content: String,
+        title: Option<String>,
+    }
+
+    impl SqliteVectorStoreTable for DocumentV2 {
+        fn name() -> &'static str {
+            "documents"
+        }
+
+        fn schema() -> Vec<Column> {
+            vec![
+                Column::new("id", "TEXT PRIMARY KEY"),
+                Column::new("content", "TEXT"),
+                Column::new("title", "TEXT").indexed(),
+            ]
+        }
+
+        fn id(&self) -> String {
+            self.id.clone()
+        }
+
+        fn column_values(&self) -> Vec<(&'static str, Box<dyn ColumnValue>)> {
+            vec![
+                ("id", Box::new(self.id.clone())),
+                ("content", Box::new(self.content.clone())),
+                ("title", Box::new(self.title.clone().unwrap_or_default())),
+            ]
+        }
+
+        fn migrations() -> Vec<Migration> {
+            vec![Migration::sql(
+                1,
+                "UPDATE documents SET title = content, content = content || '!'",
+            )]
+        }
+    }
+
+    async fn contents(conn: &Connection) -> Vec<String> {
+        conn.call(|conn| {
+            let mut stmt = conn.prepare("SELECT content FROM documents ORDER BY id")?;
+            let contents = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(contents)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrations() -> Result<(), anyhow::Error> {
+        init_sqlite_vec();
+        let conn = Connection::open_in_memory().await?;
+        let model = MockModel(8);
+
+        let documents = vec![
+            DocumentV1 {
+                id: "doc0".to_string(),
+                content: "flurbo".to_string(),
+            },
+            DocumentV1 {
+                id: "doc1".to_string(),
+                content: "glarb-glarb".to_string(),
+            },
+        ];
+        let embeddings = EmbeddingsBuilder::new(model.clone())
+            .documents(documents)?
+            .build()
+            .await?;
+        SqliteVectorStore::<_, DocumentV1>::new(conn.clone(), &model)
+            .await?
+            .add_rows(embeddings)
+            .await?;
+
+        // The new column is added and filled by the migration step, which is applied once
+        for _ in 0..2 {
+            SqliteVectorStore::<_, DocumentV2>::new(conn.clone(), &model).await?;
+            assert_eq!(contents(&conn).await, vec!["flurbo!", "glarb-glarb!"]);
+        }
+        let version = conn
+            .call(|conn| {
+                Ok(conn.query_row(
+                    "SELECT version FROM rig_sqlite_migrations WHERE table_name = 'documents'",
+                    [],
+                    |row| row.get::<_, u32>(0),
+                )?)
+            })
+            .await?;
+        assert_eq!(version, 1);
+
+        // The embeddings of another model cannot be searched without re-embedding
+        let model = MockModel(16);
+        assert!(
+            SqliteVectorStore::<_, DocumentV2>::new(conn.clone(), &model)
+                .await
+                .is_err()
+        );
+        let index = SqliteVectorStore::<_, DocumentV2>::with_storage_reembedding(
+            conn.clone(),
+            &model,
+            EmbeddingStorage::default(),
+        )
+        .await?
+        .index(model.clone());
+
+        let results = index.top_n::<DocumentV2>("glarb-glarb!", 1).await?;
+        assert_eq!(results[0].0, 0.0);
+        assert_eq!(results[0].1, "doc1");
+        assert_eq!(results[0].2.title, Some("glarb-glarb".to_string()));
+
+        SqliteVectorStore::<_, DocumentV2>::new(conn.clone(), &model).await?;
+
+        Ok(())
+    }
+}
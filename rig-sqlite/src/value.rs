@@ -0,0 +1,251 @@
+//! Typed values of the columns of [SqliteVectorStoreTable](crate::SqliteVectorStoreTable) rows.
+//!
+//! Documents are written with the [SqlValue] of each of their [ColumnValue]s, and read back by
+//! converting the values of their columns to JSON before deserializing them. Integers and reals
+//! are read as JSON numbers, and blobs as arrays of bytes. The declared type of the [Column] is
+//! used to read back the values that SQLite has no type for:
+//! - `BOOLEAN` columns are read as JSON booleans,
+//! - `JSON` columns are parsed as JSON.
+
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+
+use crate::{Column, ColumnValue};
+
+/// Value of a column, as written to and read from SQLite.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+    /// JSON value, stored as text
+    Json(serde_json::Value),
+}
+
+impl SqlValue {
+    /// Convert the value of `column` to JSON, for the deserialization of documents.
+    pub(crate) fn into_json(self, column: &Column) -> FromSqlResult<serde_json::Value> {
+        let col_type = column.col_type.to_uppercase();
+
+        Ok(match self {
+            SqlValue::Null => serde_json::Value::Null,
+            SqlValue::Integer(i) if col_type.starts_with("BOOL") => serde_json::Value::Bool(i != 0),
+            SqlValue::Integer(i) => i.into(),
+            SqlValue::Real(f) => f.into(),
+            SqlValue::Text(text) if col_type.starts_with("JSON") => {
+                serde_json::from_str(&text).map_err(|e| FromSqlError::Other(Box::new(e)))?
+            }
+            SqlValue::Text(text) => text.into(),
+            SqlValue::Blob(blob) => blob.into(),
+            SqlValue::Json(json) => json,
+        })
+    }
+}
+
+impl ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            SqlValue::Null => ToSqlOutput::Borrowed(ValueRef::Null),
+            SqlValue::Integer(i) => ToSqlOutput::Borrowed(ValueRef::Integer(*i)),
+            SqlValue::Real(f) => ToSqlOutput::Borrowed(ValueRef::Real(*f)),
+            SqlValue::Text(text) => ToSqlOutput::Borrowed(ValueRef::Text(text.as_bytes())),
+            SqlValue::Blob(blob) => ToSqlOutput::Borrowed(ValueRef::Blob(blob)),
+            SqlValue::Json(json) => ToSqlOutput::from(json.to_string()),
+        })
+    }
+}
+
+impl FromSql for SqlValue {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        Ok(match value {
+            ValueRef::Null => SqlValue::Null,
+            ValueRef::Integer(i) => SqlValue::Integer(i),
+            ValueRef::Real(f) => SqlValue::Real(f),
+            ValueRef::Text(text) => SqlValue::Text(
+                std::str::from_utf8(text)
+                    .map_err(|e| FromSqlError::Other(Box::new(e)))?
+                    .to_string(),
+            ),
+            ValueRef::Blob(blob) => SqlValue::Blob(blob.to_vec()),
+        })
+    }
+}
+
+impl ColumnValue for SqlValue {
+    fn sql_value(&self) -> SqlValue {
+        self.clone()
+    }
+
+    fn column_type(&self) -> &'static str {
+        match self {
+            SqlValue::Null => "NULL",
+            SqlValue::Integer(_) => "INTEGER",
+            SqlValue::Real(_) => "REAL",
+            SqlValue::Text(_) => "TEXT",
+            SqlValue::Blob(_) => "BLOB",
+            SqlValue::Json(_) => "JSON",
+        }
+    }
+}
+
+impl ColumnValue for String {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.clone())
+    }
+
+    fn column_type(&self) -> &'static str {
+        "TEXT"
+    }
+}
+
+impl ColumnValue for &'static str {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.to_string())
+    }
+
+    fn column_type(&self) -> &'static str {
+        "TEXT"
+    }
+}
+
+macro_rules! impl_integer_column_value {
+    ($($ty:ty),*) => {
+        $(
+            impl ColumnValue for $ty {
+                fn sql_value(&self) -> SqlValue {
+                    SqlValue::Integer(*self as i64)
+                }
+
+                fn column_type(&self) -> &'static str {
+                    "INTEGER"
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_column_value!(i8, i16, i32, i64, u8, u16, u32);
+
+impl ColumnValue for f32 {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Real(*self as f64)
+    }
+
+    fn column_type(&self) -> &'static str {
+        "REAL"
+    }
+}
+
+impl ColumnValue for f64 {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Real(*self)
+    }
+
+    fn column_type(&self) -> &'static str {
+        "REAL"
+    }
+}
+
+impl ColumnValue for bool {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Integer(*self as i64)
+    }
+
+    fn column_type(&self) -> &'static str {
+        "BOOLEAN"
+    }
+}
+
+impl ColumnValue for Vec<u8> {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Blob(self.clone())
+    }
+
+    fn column_type(&self) -> &'static str {
+        "BLOB"
+    }
+}
+
+impl ColumnValue for serde_json::Value {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Json(self.clone())
+    }
+
+    fn column_type(&self) -> &'static str {
+        "JSON"
+    }
+}
+
+/// Dates are stored as RFC 3339 text, as expected by their deserialization.
+impl ColumnValue for chrono::DateTime<chrono::Utc> {
+    fn sql_value(&self) -> SqlValue {
+        SqlValue::Text(self.to_rfc3339())
+    }
+
+    fn column_type(&self) -> &'static str {
+        "TEXT"
+    }
+}
+
+impl<T: ColumnValue> ColumnValue for Option<T> {
+    fn sql_value(&self) -> SqlValue {
+        self.as_ref().map_or(SqlValue::Null, ColumnValue::sql_value)
+    }
+
+    fn column_type(&self) -> &'static str {
+        self.as_ref().map_or("NULL", ColumnValue::column_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::SqlValue;
+    use crate::{Column, ColumnValue};
+
+    #[test]
+    fn test_sql_values() {
+        assert_eq!(42u32.sql_value(), SqlValue::Integer(42));
+        assert_eq!(true.sql_value(), SqlValue::Integer(1));
+        assert_eq!(Some(1.5).sql_value(), SqlValue::Real(1.5));
+        assert_eq!(None::<String>.sql_value(), SqlValue::Null);
+        assert_eq!(json!({"a": 1}).sql_value(), SqlValue::Json(json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_into_json() {
+        let column = |col_type| Column::new("column", col_type);
+
+        assert_eq!(
+            SqlValue::Integer(1).into_json(&column("BOOLEAN")).unwrap(),
+            json!(true)
+        );
+        assert_eq!(
+            SqlValue::Integer(1).into_json(&column("INTEGER")).unwrap(),
+            json!(1)
+        );
+        assert_eq!(
+            SqlValue::Text("[1, 2]".to_string())
+                .into_json(&column("JSON"))
+                .unwrap(),
+            json!([1, 2])
+        );
+        assert_eq!(
+            SqlValue::Text("[1, 2]".to_string())
+                .into_json(&column("TEXT"))
+                .unwrap(),
+            json!("[1, 2]")
+        );
+        assert_eq!(
+            SqlValue::Blob(vec![1, 2])
+                .into_json(&column("BLOB"))
+                .unwrap(),
+            json!([1, 2])
+        );
+        assert!(SqlValue::Text("{".to_string())
+            .into_json(&column("JSON"))
+            .is_err());
+    }
+}